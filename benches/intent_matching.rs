@@ -4,10 +4,77 @@
 //! - 精确匹配
 //! - 模糊匹配
 //! - 缓存命中
+//! - 语义匹配
+//!
+//! 并在带标注的测试集上统计三种配置（规则 / 模糊 / 模糊+语义）的准确率。
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use realconsole::dsl::intent::builtin::BuiltinIntents;
 use realconsole::dsl::intent::matcher::{FuzzyConfig, IntentMatcher};
+use realconsole::dsl::intent::SemanticIndex;
+
+/// 带标注的测试集：(输入, 期望意图)，None 表示不应匹配任何意图
+const LABELLED: &[(&str, Option<&str>)] = &[
+    // 规则可直接命中的表述
+    ("统计 Python 代码行数", Some("count_python_lines")),
+    ("查找最大的文件", Some("find_files_by_size")),
+    ("显示最近修改的文件", Some("find_recent_files")),
+    ("检查磁盘使用情况", Some("check_disk_usage")),
+    ("查看内存使用情况", Some("check_memory_usage")),
+    ("列出所有进程", Some("list_processes")),
+    ("查看 CPU 使用率", Some("check_cpu_usage")),
+    ("查看系统运行时间", Some("check_uptime")),
+    // 留出集：改写与英文表述，措辞刻意避开意图的示例句（见 `check_held_out`）
+    ("list the heaviest files by size", Some("find_files_by_size")),
+    ("biggest files in this project", Some("find_files_by_size")),
+    ("files I edited in the last hour", Some("find_recent_files")),
+    ("am I running out of storage", Some("check_disk_usage")),
+    ("is ram almost full", Some("check_memory_usage")),
+    ("show every task the os is executing", Some("list_processes")),
+    ("how many days since the box was restarted", Some("check_uptime")),
+    ("diff a.txt against b.txt", Some("compare_files")),
+    ("硬盘快满了吗", Some("check_disk_usage")),
+    ("体积最大的文件是哪几个", Some("find_files_by_size")),
+    // 不应匹配
+    ("这是一个完全无关的查询xyz123", None),
+    ("tell me a joke", None),
+];
+
+/// 确认标注集不包含意图的示例句（否则语义匹配的准确率只是在查表）
+fn check_held_out() {
+    let examples: Vec<String> = BuiltinIntents::new()
+        .all_intents()
+        .into_iter()
+        .flat_map(|intent| intent.examples)
+        .map(|example| example.to_lowercase())
+        .collect();
+    for (query, _) in LABELLED {
+        assert!(
+            !examples.contains(&query.to_lowercase()),
+            "标注集中的 \"{}\" 与意图示例句重复",
+            query
+        );
+    }
+}
+
+/// 在标注集上统计准确率（top-1 意图与期望一致，或期望不匹配且确实无匹配）
+fn accuracy(matcher: &IntentMatcher) -> (usize, usize) {
+    let correct = LABELLED
+        .iter()
+        .filter(|(query, expected)| {
+            let actual = matcher.best_match(query).map(|m| m.intent.name);
+            actual.as_deref() == *expected
+        })
+        .count();
+    (correct, LABELLED.len())
+}
+
+/// 创建测试用 IntentMatcher（启用模糊匹配 + 内置语义索引）
+fn create_matcher_with_semantic() -> IntentMatcher {
+    let mut matcher = create_matcher_with_fuzzy();
+    matcher.enable_semantic_matching(SemanticIndex::local(), 1.0, 0.5);
+    matcher
+}
 
 /// 创建测试用 IntentMatcher（启用模糊匹配）
 fn create_matcher_with_fuzzy() -> IntentMatcher {
@@ -122,6 +189,52 @@ fn bench_batch_matching(c: &mut Criterion) {
     });
 }
 
+/// 基准测试：语义匹配（每次查询都需计算输入向量，关闭缓存效果）
+fn bench_semantic_match(c: &mut Criterion) {
+    let matcher = create_matcher_with_semantic();
+    let queries = ["which files hog the most space", "how much disk space is free"];
+    let mut i = 0;
+
+    c.bench_function("intent_semantic_match", |b| {
+        b.iter(|| {
+            // 追加序号避免命中查询缓存
+            i += 1;
+            let query = format!("{} {}", queries[i % queries.len()], i);
+            matcher.match_intent(black_box(&query))
+        })
+    });
+}
+
+/// 基准测试：标注集准确率（打印各配置的准确率，并测量整轮评估耗时）
+fn bench_accuracy(c: &mut Criterion) {
+    check_held_out();
+
+    let mut configs = [
+        ("rules", create_matcher()),
+        ("fuzzy", create_matcher_with_fuzzy()),
+        ("fuzzy+semantic", create_matcher_with_semantic()),
+    ];
+
+    for (name, matcher) in &configs {
+        let (correct, total) = accuracy(matcher);
+        println!(
+            "intent accuracy [{}]: {}/{} ({:.1}%)",
+            name,
+            correct,
+            total,
+            correct as f64 * 100.0 / total as f64
+        );
+    }
+
+    let (_, matcher) = &mut configs[2];
+    c.bench_function("intent_accuracy_semantic", |b| {
+        b.iter(|| {
+            matcher.clear_cache();
+            black_box(accuracy(matcher))
+        })
+    });
+}
+
 /// 基准测试：缓存统计
 fn bench_cache_stats(c: &mut Criterion) {
     let matcher = create_matcher();
@@ -147,6 +260,8 @@ criterion_group!(
     bench_no_match,
     bench_long_query,
     bench_batch_matching,
+    bench_semantic_match,
+    bench_accuracy,
     bench_cache_stats
 );
 
@@ -6,7 +6,7 @@
 //! - 文本处理工具组（text_search, text_replace, text_split）
 //! - 系统信息工具组（get_env, get_system_info）
 
-use crate::tool::{Parameter, ParameterType, Tool, ToolRegistry};
+use crate::tool::{OutputRefs, Parameter, ParameterType, Tool, ToolRegistry};
 use serde_json::Value as JsonValue;
 use std::time::Duration;
 
@@ -261,6 +261,7 @@ fn create_json_parse_tool() -> Tool {
             }
         },
     )
+    .with_output_refs(OutputRefs::Content)
 }
 
 /// JSON 查询工具（简化版，使用路径访问）
@@ -310,6 +311,7 @@ fn create_json_query_tool() -> Tool {
                 .map_err(|e| format!("结果序列化失败: {}", e))
         },
     )
+    .with_output_refs(OutputRefs::Content)
 }
 
 /// 简单的 JSON 路径查询（不使用外部库）
@@ -421,6 +423,7 @@ fn create_text_search_tool() -> Tool {
             }
         },
     )
+    .with_output_refs(OutputRefs::Content)
 }
 
 /// 文本替换工具
@@ -489,6 +492,7 @@ fn create_text_replace_tool() -> Tool {
             Ok(result)
         },
     )
+    .with_output_refs(OutputRefs::Content)
 }
 
 /// 文本分割工具
@@ -548,6 +552,7 @@ fn create_text_split_tool() -> Tool {
             Ok(result)
         },
     )
+    .with_output_refs(OutputRefs::Content)
 }
 
 // ============================================================================
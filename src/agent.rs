@@ -9,16 +9,20 @@
 
 use crate::command::CommandRegistry;
 use crate::command_router::{CommandRouter, CommandType as RouterCommandType};
-use crate::config::Config;
+use crate::config::{Config, SemanticMatchingConfig};
 use crate::display::Display;
+use crate::dsl::intent::explain::{self, ChoiceMemory};
 use crate::dsl::intent::{
-    BuiltinIntents, CommandValidator, EntityExtractor, ExecutionPlan, IntentMatcher,
-    IntentToPipeline, LlmToPipeline, TemplateEngine, ValidationResult,
+    BuiltinIntents, CommandValidator, Embedder, EmbeddingCache, EntityExtractor, ExecutionPlan,
+    HashingEmbedder, IntentMatch, IntentMatcher, IntentToPipeline, LlmToPipeline, OllamaEmbedder,
+    SemanticIndex, TemplateEngine, ValidationResult,
 };
 use crate::execution_logger::{CommandType, ExecutionLogger};
 use crate::history::HistoryManager;
 use crate::llm_manager::LlmManager;
-use crate::memory::{EntryType, Memory};
+use crate::memory::long_term::DEFAULT_MAX_ENTRIES;
+use crate::memory::{EntryType, LongTermMemory, Memory, OutputKind, OutputStore};
+use crate::session::{SessionManager, SessionStore};
 use crate::spinner::Spinner;
 use crate::tool::ToolRegistry;
 use crate::tool_executor::ToolExecutor;
@@ -42,17 +46,22 @@ use crate::memory::ContextTracker;
 
 // ✨ Phase 9.2: 错误自动修复支持
 use crate::shell_executor::ShellExecutorWithFixer;
-use crate::error_fixer::{FeedbackLearner, FeedbackRecord, FeedbackType, FixOutcome};
+use crate::error_fixer::{CodeFix, ErrorAnalyzer, FeedbackLearner, FeedbackRecord, FeedbackType, FixOutcome};
 
 // ✨ Phase 8 (Workflow): Workflow Intent 支持
 use crate::dsl::intent::{WorkflowIntent, WorkflowExecutor};
 
+// ✨ Pipeline 原生执行后端
+use crate::dsl::pipeline::{ExecutionPlan as PipelinePlan, NativeExecutor, PipelineBackend};
+
 /// Agent 核心
 pub struct Agent {
     pub config: Config,
     pub registry: CommandRegistry,
     pub llm_manager: Arc<RwLock<LlmManager>>,
     pub memory: Arc<RwLock<Memory>>,
+    // 长期记忆（跨会话全文/语义检索）
+    pub long_term_memory: Arc<RwLock<LongTermMemory>>,
     pub exec_logger: Arc<RwLock<ExecutionLogger>>,
     pub tool_registry: Arc<RwLock<ToolRegistry>>,
     pub tool_executor: Arc<ToolExecutor>,
@@ -71,6 +80,10 @@ pub struct Agent {
     pub stats_collector: Arc<StatsCollector>,
     // ✨ Phase 9.1: 上下文追踪器
     pub context_tracker: Arc<RwLock<ContextTracker>>,
+    // 命名会话管理
+    pub session_manager: Arc<SessionManager>,
+    // 命令输出存储（$1、$2、$last）
+    pub output_store: Arc<RwLock<OutputStore>>,
     // ✨ Phase 9.2: Shell执行器（带错误修复）
     pub shell_executor_with_fixer: Arc<ShellExecutorWithFixer>,
     // 最后失败的命令（用于/fix命令）
@@ -165,11 +178,14 @@ impl Agent {
             memory
         };
 
+        // 初始化长期记忆
+        let long_term_memory = Arc::new(RwLock::new(build_long_term_memory(&config)));
+
         // 初始化执行日志系统
         let exec_logger = ExecutionLogger::new(1000);
 
         // ✨ Phase 8: 初始化命令历史记录管理器
-        let history = HistoryManager::default();
+        let history = Arc::new(RwLock::new(HistoryManager::default()));
 
         // ✨ Phase 8 Week 2: 初始化多轮对话管理器
         let conversation_manager = ConversationManager::new(300); // 5分钟超时
@@ -187,16 +203,35 @@ impl Agent {
         crate::advanced_tools::register_advanced_tools(&mut tool_registry);
         let tool_registry = Arc::new(RwLock::new(tool_registry));
 
+        // 命令输出存储：完整输出以 $1、$2、$last 引用
+        let output_store = Arc::new(RwLock::new(build_output_store(&config)));
+
         // 初始化工具执行引擎（使用配置值）
         let tool_executor = ToolExecutor::new(
             Arc::clone(&tool_registry),
             config.features.max_tool_iterations,
             config.features.max_tools_per_round,
-        );
+        )
+        .with_output_store(Arc::clone(&output_store));
 
         // 初始化 Intent DSL 系统（使用内置意图库）
         let builtin = BuiltinIntents::new();
-        let intent_matcher = builtin.create_matcher();
+        let mut intent_matcher = builtin.create_matcher();
+
+        // 语义意图匹配（可选）：为示例句建立向量索引
+        let semantic = &config.intent.semantic;
+        if semantic.enabled {
+            intent_matcher.enable_semantic_matching(
+                build_semantic_index(semantic),
+                semantic.weight,
+                semantic.threshold,
+            );
+        }
+
+        // 意图消歧（可选）：载入历史选择，相似输入偏向用户曾选的意图
+        if config.intent.disambiguation_enabled {
+            intent_matcher.set_choice_memory(ChoiceMemory::load(ChoiceMemory::default_path()));
+        }
         let template_engine = builtin.create_engine();
 
         // ✨ Phase 6.3: 初始化 Pipeline DSL 转换器
@@ -206,6 +241,26 @@ impl Agent {
         // 这个在 main.rs 中调用 configure_llm() 后会被设置
         let llm_bridge = None;
 
+        // 命名会话：与 Agent 共享记忆、上下文、对话与 LLM 历史
+        let memory = Arc::new(RwLock::new(memory));
+        let llm_manager = Arc::new(RwLock::new(LlmManager::new()));
+        let conversation_manager = Arc::new(RwLock::new(conversation_manager));
+        let context_tracker = Arc::new(RwLock::new(context_tracker));
+        let session_dir = config
+            .memory
+            .as_ref()
+            .and_then(|m| m.session_dir.as_deref())
+            .map(|p| std::path::PathBuf::from(Self::normalize_path(p)))
+            .unwrap_or_else(SessionStore::default_dir);
+        let session_manager = Arc::new(SessionManager::new(
+            SessionStore::new(session_dir),
+            Arc::clone(&memory),
+            Arc::clone(&long_term_memory),
+            Arc::clone(&context_tracker),
+            Arc::clone(&conversation_manager),
+            Arc::clone(&llm_manager),
+        ));
+
         // ✨ Phase 9.2: 初始化错误修复系统
         let feedback_learner = Arc::new(FeedbackLearner::new());
         // 如果配置了持久化路径，设置存储路径
@@ -222,7 +277,9 @@ impl Agent {
 
             let shell_executor_with_fixer = Arc::new(
                 ShellExecutorWithFixer::new()
+                    .with_analyzer(ErrorAnalyzer::with_user_patterns())
                     .with_feedback_learner(feedback_learner)
+                    .with_history(Arc::clone(&history))
             );
 
             let last_failed_command = Arc::new(RwLock::new(None));
@@ -230,8 +287,9 @@ impl Agent {
             return Self {
                 config,
                 registry,
-                llm_manager: Arc::new(RwLock::new(LlmManager::new())),
-                memory: Arc::new(RwLock::new(memory)),
+                llm_manager,
+                memory,
+                long_term_memory,
                 exec_logger: Arc::new(RwLock::new(exec_logger)),
                 tool_registry,
                 tool_executor: Arc::new(tool_executor),
@@ -239,10 +297,12 @@ impl Agent {
                 template_engine,
                 pipeline_converter,
                 llm_bridge,
-                history: Arc::new(RwLock::new(history)),
-                conversation_manager: Arc::new(RwLock::new(conversation_manager)),
+                history,
+                conversation_manager,
                 stats_collector,
-                context_tracker: Arc::new(RwLock::new(context_tracker)),
+                context_tracker,
+                session_manager,
+                output_store,
                 shell_executor_with_fixer,
                 last_failed_command,
                 command_router,
@@ -254,15 +314,18 @@ impl Agent {
         // Fallback: 无持久化
         let shell_executor_with_fixer = Arc::new(
             ShellExecutorWithFixer::new()
+                .with_analyzer(ErrorAnalyzer::with_user_patterns())
                 .with_feedback_learner(feedback_learner)
+                .with_history(Arc::clone(&history))
         );
         let last_failed_command = Arc::new(RwLock::new(None));
 
         Self {
             config,
             registry,
-            llm_manager: Arc::new(RwLock::new(LlmManager::new())),
-            memory: Arc::new(RwLock::new(memory)),
+            llm_manager,
+            memory,
+            long_term_memory,
             exec_logger: Arc::new(RwLock::new(exec_logger)),
             tool_registry,
             tool_executor: Arc::new(tool_executor),
@@ -270,10 +333,12 @@ impl Agent {
             template_engine,
             pipeline_converter,
             llm_bridge,
-            history: Arc::new(RwLock::new(history)),
-            conversation_manager: Arc::new(RwLock::new(conversation_manager)),
+            history,
+            conversation_manager,
             stats_collector,
-            context_tracker: Arc::new(RwLock::new(context_tracker)),
+            context_tracker,
+            session_manager,
+            output_store,
             shell_executor_with_fixer,
             last_failed_command,
             command_router,
@@ -292,6 +357,21 @@ impl Agent {
         Arc::clone(&self.memory)
     }
 
+    /// 获取长期记忆的引用
+    pub fn long_term_memory(&self) -> Arc<RwLock<LongTermMemory>> {
+        Arc::clone(&self.long_term_memory)
+    }
+
+    /// 获取会话管理器的引用
+    pub fn session_manager(&self) -> Arc<SessionManager> {
+        Arc::clone(&self.session_manager)
+    }
+
+    /// 获取命令输出存储的引用
+    pub fn output_store(&self) -> Arc<RwLock<OutputStore>> {
+        Arc::clone(&self.output_store)
+    }
+
     /// 获取执行日志系统的引用
     pub fn exec_logger(&self) -> Arc<RwLock<ExecutionLogger>> {
         Arc::clone(&self.exec_logger)
@@ -374,6 +454,10 @@ impl Agent {
         // 开始计时
         let start = Instant::now();
 
+        // ✨ Phase 10.1: 使用智能命令路由器识别命令类型（自然语言输入随后做指代消解）
+        // 消解结果只用于分派，记忆与历史中保存用户的原始输入
+        let router_result = self.route_and_resolve(line);
+
         // 记录用户输入
         tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
@@ -393,8 +477,12 @@ impl Agent {
             })
         });
 
-        // ✨ Phase 10.1: 使用智能命令路由器识别命令类型
-        let router_result = self.command_router.route(line);
+        // 记录到长期记忆（Shell 命令单独归类，便于按类型检索）
+        let input_type = match router_result {
+            RouterCommandType::CommonShell(_) | RouterCommandType::ForcedShell(_) => EntryType::Shell,
+            _ => EntryType::User,
+        };
+        self.remember_long_term(line, input_type);
 
         let (command_type, response) = match router_result {
             RouterCommandType::CommonShell(cmd) => {
@@ -477,6 +565,7 @@ impl Agent {
                             response.clone()
                         };
                         memory.add(content, EntryType::Assistant);
+                        let _ = self.long_term_memory.write().await.add(response.as_str(), EntryType::Assistant);
 
                         // 如果启用了自动保存，追加到文件
                         if let Some(ref mem_config) = self.config.memory {
@@ -514,6 +603,15 @@ impl Agent {
             });
         }
 
+        // 命名会话：每次输入后自动保存
+        if self.session_manager.current().is_some() {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    let _ = self.session_manager.save().await;
+                })
+            });
+        }
+
         response
     }
 
@@ -524,6 +622,22 @@ impl Agent {
             return format!("{}", "Shell 执行已禁用".red());
         }
 
+        // 展开输出引用：$3、$last 替换为保存该输出的文件路径
+        let expanded = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.output_store.read().await.expand_for_shell(cmd)
+            })
+        });
+        let cmd = match expanded {
+            Ok(expanded) if expanded != cmd => {
+                Display::command_execution(self.config.display.mode, &expanded);
+                expanded
+            }
+            Ok(_) => cmd.to_string(),
+            Err(e) => return format!("{} {}", "展开输出引用失败:".red(), e),
+        };
+        let cmd = cmd.as_str();
+
         // 特殊处理：cd 命令需要在主进程中生效
         let cmd_trimmed = cmd.trim();
         if cmd_trimmed.starts_with("cd ") || cmd_trimmed == "cd" {
@@ -531,14 +645,33 @@ impl Agent {
         }
 
         // ✨ Phase 9.2: 使用 ShellExecutorWithFixer 执行命令（带错误分析）
+        // 启用自动修复时，失败后先尝试经过验证的低风险修复
+        let auto_fix = self.config.features.auto_fix_enabled.unwrap_or(false);
         let execution_result = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
-                self.shell_executor_with_fixer.execute_with_analysis(cmd).await
+                if auto_fix {
+                    self.shell_executor_with_fixer
+                        .execute_with_auto_fix(cmd, self.config.features.auto_fix_max_attempts)
+                        .await
+                } else {
+                    self.shell_executor_with_fixer.execute_with_analysis(cmd).await
+                }
             })
         });
 
+        self.capture_output(cmd, &execution_result.output, execution_result.success);
+
+        // 自动修复记录：展示给用户并写入执行日志
+        let transcript = if execution_result.fix_attempts.is_empty() {
+            String::new()
+        } else {
+            self.log_fix_attempts(&execution_result.fix_attempts);
+            crate::commands::fix_cmd::format_fix_transcript(&execution_result.fix_attempts)
+        };
+
         // 如果执行失败且有修复策略，保存失败的命令并显示交互式修复流程
-        if !execution_result.success && !execution_result.fix_strategies.is_empty() {
+        let has_fixes = !execution_result.fix_strategies.is_empty() || !execution_result.diagnostics.is_empty();
+        if !execution_result.success && has_fixes {
             // 保存失败的命令（用于 /fix 命令）
             tokio::task::block_in_place(|| {
                 tokio::runtime::Handle::current().block_on(async {
@@ -548,13 +681,47 @@ impl Agent {
             });
 
             // 显示交互式修复建议
+            print!("{}", transcript);
             self.display_fix_suggestions(&execution_result)
         } else {
             // 正常输出或没有修复建议的错误
-            execution_result.output
+            format!("{}{}", transcript, execution_result.output)
+        }
+    }
+
+    /// 保存 Shell 命令输出，供后续以 $N 引用
+    fn capture_output(&self, command: &str, output: &str, success: bool) {
+        let id = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.output_store
+                    .write()
+                    .await
+                    .capture(command, OutputKind::Shell, output, success)
+            })
+        });
+        if let Some(id) = id {
+            Display::output_handle(self.config.display.mode, id);
         }
     }
 
+    /// 把自动修复尝试逐条写入执行日志
+    fn log_fix_attempts(&self, attempts: &[crate::shell_executor::FixAttempt]) {
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let mut logger = self.exec_logger.write().await;
+                for attempt in attempts {
+                    logger.log(
+                        format!("[auto-fix] {}", attempt.command),
+                        CommandType::Shell,
+                        attempt.verified,
+                        attempt.duration,
+                        &attempt.summary(),
+                    );
+                }
+            })
+        });
+    }
+
     /// 处理 cd 命令（在主进程中改变目录）
     fn handle_cd_command(&self, cmd: &str) -> String {
         use std::env;
@@ -633,13 +800,36 @@ impl Agent {
             }
         }
 
-        // 3. 显示修复策略列表
-        if result.fix_strategies.is_empty() {
+        // 3. 显示编译器诊断（按文件分组）
+        if !result.diagnostics.is_empty() {
+            output.push_str(&crate::commands::fix_cmd::format_diagnostics(&result.diagnostics));
+        }
+
+        let base_dir = std::env::current_dir().unwrap_or_default();
+        let compiler_fix = CodeFix::from_diagnostics(&result.diagnostics, &base_dir)
+            .filter(|fix| !fix.patches.is_empty());
+        let patch_target = result
+            .diagnostics
+            .iter()
+            .find(|d| d.level == crate::error_fixer::DiagnosticLevel::Error);
+        let llm = patch_target.and_then(|_| {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    let manager = self.llm_manager.read().await;
+                    manager.primary().or(manager.fallback()).cloned()
+                })
+            })
+        });
+
+        // 4. 显示修复策略列表
+        if result.fix_strategies.is_empty() && compiler_fix.is_none() && llm.is_none() {
             output.push_str(&format!("\n{}\n", "暂无自动修复策略".yellow()));
             return output;
         }
 
-        output.push_str(&format!("\n{}\n", "💡 修复策略 (按推荐度排序)".green().bold()));
+        if !result.fix_strategies.is_empty() {
+            output.push_str(&format!("\n{}\n", "💡 修复策略 (按推荐度排序)".green().bold()));
+        }
 
         for (i, strategy) in result.fix_strategies.iter().enumerate() {
             // 风险指示器: 🟢 低 < 5, 🟡 中 5-7, 🔴 高 >= 8
@@ -663,16 +853,34 @@ impl Agent {
             output.push_str(&format!("     {}: {}\n", "预期效果".dimmed(), strategy.expected_outcome.dimmed()));
         }
 
-        // 4. 提示用户选择
+        // 有拼写纠正策略时，回车即可执行纠正后的命令（按策略类型查找，不依赖排序）
+        let correction = result
+            .fix_strategies
+            .iter()
+            .position(|s| s.name == crate::error_fixer::CORRECTION_STRATEGY);
+
+        // 5. 提示用户选择
         output.push_str(&format!("\n{}\n", "请选择:".yellow().bold()));
-        output.push_str(&format!("  • {} - 选择对应编号执行修复\n", "1-N".cyan()));
+        if let Some(index) = correction {
+            let command = &result.fix_strategies[index].command;
+            output.push_str(&format!("  • {} - 执行纠正后的命令: {}\n", "Enter/y".cyan(), command.green()));
+        }
+        if !result.fix_strategies.is_empty() {
+            output.push_str(&format!("  • {} - 选择对应编号执行修复\n", "1-N".cyan()));
+        }
+        if let Some(fix) = &compiler_fix {
+            output.push_str(&format!("  • {} - 应用编译器建议（{}）\n", "a".cyan(), fix.description));
+        }
+        if llm.is_some() {
+            output.push_str(&format!("  • {} - 请 LLM 生成补丁\n", "p".cyan()));
+        }
         output.push_str(&format!("  • {} - 跳过，不执行修复\n", "s/skip".dimmed()));
         output.push_str(&format!("  • {} - 取消\n", "c/cancel".dimmed()));
 
         print!("\n{} ", "您的选择:".yellow());
         let _ = io::stdout().flush();
 
-        // 5. 读取用户输入
+        // 6. 读取用户输入
         let mut user_input = String::new();
         if io::stdin().read_line(&mut user_input).is_err() {
             return format!("{}", "\n读取输入失败".red());
@@ -680,7 +888,7 @@ impl Agent {
 
         let choice = user_input.trim().to_lowercase();
 
-        // 6. 处理用户选择
+        // 7. 处理用户选择
         if choice == "s" || choice == "skip" {
             return format!("{}\n{}", output, "✓ 已跳过修复".yellow());
         }
@@ -689,9 +897,30 @@ impl Agent {
             return format!("{}\n{}", output, "✓ 已取消".yellow());
         }
 
+        if choice == "a" {
+            if let Some(fix) = &compiler_fix {
+                return format!("{}{}", output, self.apply_code_fix(result, fix));
+            }
+        }
+
+        if choice == "p" {
+            if let (Some(llm), Some(diagnostic)) = (&llm, patch_target) {
+                println!("{}", "🤖 正在生成补丁...".dimmed());
+                let proposal = tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current()
+                        .block_on(CodeFix::propose_with_llm(diagnostic, &base_dir, llm.as_ref()))
+                });
+                return match proposal {
+                    Ok(fix) => format!("{}{}", output, self.apply_code_fix(result, &fix)),
+                    Err(e) => format!("{}\n{} {}", output, "❌ 生成补丁失败:".red(), e),
+                };
+            }
+        }
+
         // 解析数字选择
-        let selected_index: usize = match choice.parse::<usize>() {
-            Ok(n) if n > 0 && n <= result.fix_strategies.len() => n - 1,
+        let selected_index: usize = match (choice.parse::<usize>(), correction) {
+            (_, Some(index)) if choice.is_empty() || choice == "y" => index,
+            (Ok(n), _) if n > 0 && n <= result.fix_strategies.len() => n - 1,
             _ => {
                 return format!("{}\n{}", output, format!("❌ 无效选择: {}", choice).red());
             }
@@ -699,7 +928,7 @@ impl Agent {
 
         let selected_strategy = &result.fix_strategies[selected_index];
 
-        // 7. 执行选中的修复策略
+        // 8. 执行选中的修复策略
         output.push_str(&format!("\n{} {}\n", "🔧 执行修复:".cyan().bold(), selected_strategy.command.green()));
 
         let fix_result = tokio::task::block_in_place(|| {
@@ -715,12 +944,50 @@ impl Agent {
 
         output.push_str(&format!("\n{}\n{}\n", if success { "✓ 修复执行成功".green().bold() } else { "✗ 修复执行失败".red().bold() }, fix_output));
 
-        // 8. 记录反馈
+        // 9. 记录反馈
         self.record_fix_feedback(result, selected_index, success);
 
         output
     }
 
+    /// 预览代码修复的 diff，确认后写入文件并记录反馈
+    fn apply_code_fix(&self, result: &crate::shell_executor::ExecutionResult, fix: &CodeFix) -> String {
+        println!("{}", crate::commands::fix_cmd::format_code_fix(fix));
+        print!("{} ", "应用以上修改? [y/N]:".yellow());
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        let confirmed = io::stdin().read_line(&mut answer).is_ok()
+            && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+
+        let (feedback, outcome, message) = if !confirmed {
+            (FeedbackType::Rejected, FixOutcome::Unknown, format!("{}", "✓ 未修改文件".yellow()))
+        } else {
+            match fix.apply() {
+                Ok(files) => (
+                    FeedbackType::Accepted,
+                    FixOutcome::Success,
+                    format!("{} 已修改 {} 个文件", "✓".green().bold(), files),
+                ),
+                Err(e) => (FeedbackType::Accepted, FixOutcome::Failure, format!("{} {}", "✗ 写入失败:".red().bold(), e)),
+            }
+        };
+
+        if let Some(error_analysis) = &result.error_analysis {
+            let record = FeedbackRecord::new(error_analysis, &fix.to_strategy(), feedback, outcome);
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    self.shell_executor_with_fixer
+                        .feedback_learner()
+                        .record_feedback(record)
+                        .await;
+                })
+            });
+        }
+
+        format!("\n{}\n", message)
+    }
+
     /// 记录修复反馈（用于学习）
     fn record_fix_feedback(&self, result: &crate::shell_executor::ExecutionResult, strategy_index: usize, success: bool) {
         if let Some(error_analysis) = &result.error_analysis {
@@ -754,15 +1021,55 @@ impl Agent {
 
         // ✨ Phase 9.2: 特殊处理 /fix 命令
         if cmd_name == "fix" {
+            let arg = arg.trim();
+            let (sub, rest) = arg.split_once(char::is_whitespace).unwrap_or((arg, ""));
+            if sub == "patterns" {
+                return crate::commands::fix_cmd::fix_patterns_command(
+                    self.shell_executor_with_fixer.analyzer(),
+                    rest,
+                );
+            }
+            if arg == "stats" {
+                return self.handle_fix_stats_command();
+            }
             return self.handle_fix_command();
         }
 
+        // 解释意图匹配：/why <输入>
+        if cmd_name == "why" {
+            return self.handle_why_command(arg);
+        }
+
         match self.registry.execute(cmd_name, arg) {
             Ok(output) => output,
             Err(err) => format!("{}", err.red()),
         }
     }
 
+    /// 处理 /why 命令 - 显示每个候选意图的得分构成
+    fn handle_why_command(&self, arg: &str) -> String {
+        let text = arg.trim();
+        if text.is_empty() {
+            return format!(
+                "{} /why <输入>\n{}",
+                "用法:".dimmed(),
+                "显示该输入匹配到各意图时的关键词、模式、相似度与实体".dimmed()
+            );
+        }
+
+        explain::format_explanations(text, &self.intent_matcher.explain(text))
+    }
+
+    /// 处理 /fix stats 命令 - 按上下文查看修复效果
+    fn handle_fix_stats_command(&self) -> String {
+        let learner = self.shell_executor_with_fixer.feedback_learner();
+        let (summary, breakdown) = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(async { (learner.get_summary().await, learner.context_breakdown().await) })
+        });
+        crate::commands::fix_cmd::fix_stats_report(&summary, &breakdown)
+    }
+
     /// ✨ Phase 9.2: 处理 /fix 命令 - 重试上次失败的命令
     fn handle_fix_command(&self) -> String {
         let last_cmd = tokio::task::block_in_place(|| {
@@ -822,8 +1129,155 @@ impl Agent {
         self.handle_text_streaming(text)
     }
 
+    /// 路由输入，并对自然语言输入做指代消解
+    ///
+    /// 先路由再消解：Shell 命令（含 `!` 前缀）与系统命令原样执行，
+    /// 避免 `rm it` 之类的命令被改写成删除最近提到的文件。
+    fn route_and_resolve(&self, line: &str) -> RouterCommandType {
+        match self.command_router.route(line) {
+            RouterCommandType::NaturalLanguage(text) => {
+                RouterCommandType::NaturalLanguage(self.resolve_references(&text))
+            }
+            other => other,
+        }
+    }
+
+    /// 消解自然语言输入中的指代词，并显示每处替换
+    ///
+    /// `memory.resolve_references: false` 时关闭。
+    fn resolve_references(&self, line: &str) -> String {
+        let enabled = self
+            .config
+            .memory
+            .as_ref()
+            .and_then(|m| m.resolve_references)
+            .unwrap_or(true);
+        if !enabled {
+            return line.to_string();
+        }
+
+        let resolved = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.context_tracker.write().await.resolve_input(line)
+            })
+        });
+
+        for reference in &resolved.references {
+            Display::reference_resolution(
+                self.config.display.mode,
+                &reference.pronoun,
+                &reference.resolved_entity.entity_type.display_name(),
+            );
+        }
+        resolved.text
+    }
+
+    /// 记录到长期记忆（持久化失败时忽略，条目仍可在本次会话中检索）
+    fn remember_long_term(&self, content: &str, entry_type: EntryType) {
+        if content.trim().is_empty() {
+            return;
+        }
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let _ = self.long_term_memory.write().await.add(content, entry_type);
+            })
+        });
+    }
+
+    /// 为 LLM 提示词附上相关的长期记忆
+    ///
+    /// 未启用 `memory.auto_recall` 或没有足够相关的条目时原样返回。
+    fn with_recalled_memory(&self, text: &str) -> String {
+        let Some(mem_config) = self.config.memory.as_ref() else {
+            return text.to_string();
+        };
+        if !mem_config.auto_recall.unwrap_or(false) {
+            return text.to_string();
+        }
+
+        let limit = mem_config.recall_limit.unwrap_or(3);
+        let recalled = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.long_term_memory.read().await.recall_context(text, limit)
+            })
+        });
+
+        match recalled {
+            Some(context) => format!(
+                "以下是与当前问题相关的历史记录（仅供参考）:\n{}\n\n当前问题: {}",
+                context, text
+            ),
+            None => text.to_string(),
+        }
+    }
+
+    /// 构建发送给 LLM 的提示词
+    fn build_prompt(&self, text: &str) -> String {
+        let prompt = self.with_recalled_memory(text);
+        let prompt = self.with_resolved_references(&prompt);
+        self.with_referenced_outputs(&prompt, text)
+    }
+
+    /// 为 LLM 提示词附上问题中引用的命令输出（"总结 $3"）
+    ///
+    /// 每条输出最多附上 8000 个字符。
+    fn with_referenced_outputs(&self, prompt: &str, text: &str) -> String {
+        const PROMPT_OUTPUT_CHARS: usize = 8000;
+
+        let sections: Vec<String> = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let store = self.output_store.read().await;
+                store
+                    .referenced(text)
+                    .into_iter()
+                    .map(|output| {
+                        let mut content: String = output.content.chars().take(PROMPT_OUTPUT_CHARS).collect();
+                        if output.truncated || content.len() < output.content.len() {
+                            content.push_str("\n...（已截断）");
+                        }
+                        format!("{}（`{}` 的输出）:\n```\n{}\n```", output.handle(), output.source, content)
+                    })
+                    .collect()
+            })
+        });
+        if sections.is_empty() {
+            return prompt.to_string();
+        }
+        format!("{}\n\n{}", prompt, sections.join("\n\n"))
+    }
+
+    /// 为 LLM 提示词附上本次输入的指代说明
+    ///
+    /// 输入已被改写，这里把原指代词与实体类型告诉模型，避免误解替换后的文本。
+    fn with_resolved_references(&self, prompt: &str) -> String {
+        let references = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.context_tracker.read().await.current_references().to_vec()
+            })
+        });
+        if references.is_empty() {
+            return prompt.to_string();
+        }
+
+        let notes: Vec<String> = references
+            .iter()
+            .map(|r| {
+                format!(
+                    "- \"{}\" 指{} {}",
+                    r.pronoun,
+                    r.resolved_entity.entity_type.type_name(),
+                    r.resolved_entity.entity_type.display_name()
+                )
+            })
+            .collect();
+        format!("{}\n\n指代说明（已在问题中替换）:\n{}", prompt, notes.join("\n"))
+    }
+
     /// 使用工具调用处理文本
     fn handle_text_with_tools(&self, text: &str) -> String {
+        // 附上相关的长期记忆、指代说明与引用的命令输出
+        let prompt = self.build_prompt(text);
+
         // 启动 spinner
         let spinner = Spinner::new();
 
@@ -844,7 +1298,7 @@ impl Agent {
                 // 如果没有工具，回退到普通对话
                 if tool_schemas.is_empty() {
                     let response: Result<String, String> = manager
-                        .chat(text)
+                        .chat(&prompt)
                         .await
                         .map_err(|e| e.to_string());
                     return response;
@@ -852,7 +1306,7 @@ impl Agent {
 
                 // 使用工具执行引擎
                 self.tool_executor
-                    .execute_iterative(llm.as_ref(), text, tool_schemas)
+                    .execute_iterative(llm.as_ref(), &prompt, tool_schemas)
                     .await
             })
         }) {
@@ -884,6 +1338,9 @@ impl Agent {
         // 开始计时
         let start = Instant::now();
 
+        // 附上相关的长期记忆、指代说明与引用的命令输出
+        let prompt = self.build_prompt(text);
+
         // 启动 spinner
         let spinner = Spinner::new();
 
@@ -892,16 +1349,19 @@ impl Agent {
             tokio::runtime::Handle::current().block_on(async {
                 let manager = self.llm_manager.read().await;
                 // 使用流式输出，实时显示每个 token
-                manager.chat_stream(text, |token| {
+                manager.chat_stream(&prompt, |token| {
                     print!("{}", token);
                     let _ = io::stdout().flush();
                 }).await
             })
         }) {
-            Ok(_response) => {
+            Ok(response) => {
                 // 停止 spinner
                 spinner.stop();
 
+                // 流式输出不经过 handle_line 的响应记录，单独记入长期记忆
+                self.remember_long_term(&response, EntryType::Assistant);
+
                 // 计算耗时
                 let elapsed = start.elapsed();
 
@@ -961,15 +1421,14 @@ impl Agent {
                 }) {
                     Ok(pipeline_plan) => {
                         // LLM 成功生成 ExecutionPlan
-                        let command = pipeline_plan.to_shell_command();
-
                         Display::llm_generation(self.config.display.mode);
+                        Display::pipeline_plan(self.config.display.mode, &pipeline_plan.to_dsl());
 
-                        return Some(ExecutionPlan {
-                            command,
-                            template_name: "llm_generated".to_string(),
-                            bindings: std::collections::HashMap::new(),
-                        });
+                        return Some(ExecutionPlan::from_pipeline(
+                            pipeline_plan,
+                            "llm_generated",
+                            std::collections::HashMap::new(),
+                        ));
                     }
                     Err(e) => {
                         // LLM 失败，根据配置决定是否 fallback
@@ -984,8 +1443,11 @@ impl Agent {
             }
         }
 
-        // 1. 使用 IntentMatcher 匹配最佳意图
-        let mut intent_match = self.intent_matcher.best_match(text)?;
+        // 1. 使用 IntentMatcher 匹配最佳意图（得分相近时请用户选择）
+        let mut intent_match = self.match_with_disambiguation(text)?;
+
+        // 指代消解得到的文件/目录优先作为路径参数
+        self.apply_resolved_references(&mut intent_match);
 
         // 2. Phase 2: 使用 LLM 智能补充参数提取（如果启用）
         if self.config.intent.llm_extraction_enabled {
@@ -1002,9 +1464,7 @@ impl Agent {
             &intent_match.extracted_entities,
         ) {
             // Pipeline DSL 成功生成 ExecutionPlan
-            // 将 Pipeline ExecutionPlan 转换为 Template ExecutionPlan
-            let command = pipeline_plan.to_shell_command();
-
+            // 将 Pipeline ExecutionPlan 转换为 Template ExecutionPlan（保留原计划供原生后端使用）
             // 将实体转换为字符串绑定
             let mut bindings = std::collections::HashMap::new();
             for (key, entity) in &intent_match.extracted_entities {
@@ -1019,11 +1479,7 @@ impl Agent {
                 bindings.insert(key.clone(), value);
             }
 
-            ExecutionPlan {
-                command,
-                template_name: intent_match.intent.name.clone(),
-                bindings,
-            }
+            ExecutionPlan::from_pipeline(pipeline_plan, intent_match.intent.name.clone(), bindings)
         } else {
             // 回退到传统模板引擎
             match self.template_engine.generate_from_intent(&intent_match) {
@@ -1141,6 +1597,49 @@ impl Agent {
         }
     }
 
+    /// 用本次输入中消解出的文件/目录填充意图的路径参数
+    ///
+    /// 用户明确指代的对象比正则从改写后文本中猜出的路径更可靠，按参数名顺序依次填入。
+    fn apply_resolved_references(&self, intent_match: &mut crate::dsl::intent::types::IntentMatch) {
+        use crate::dsl::intent::types::EntityType as IntentEntityType;
+        use crate::memory::EntityType as ContextEntityType;
+
+        let paths: Vec<String> = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                self.context_tracker
+                    .read()
+                    .await
+                    .current_references()
+                    .iter()
+                    .filter_map(|r| match &r.resolved_entity.entity_type {
+                        ContextEntityType::File(p) | ContextEntityType::Directory(p) => {
+                            Some(p.display().to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+        });
+        if paths.is_empty() {
+            return;
+        }
+
+        let mut slots: Vec<&String> = intent_match
+            .intent
+            .entities
+            .iter()
+            .filter(|(_, t)| matches!(t, IntentEntityType::Path(_)))
+            .map(|(name, _)| name)
+            .collect();
+        slots.sort();
+
+        for (name, path) in slots.into_iter().zip(paths) {
+            intent_match
+                .extracted_entities
+                .insert(name.clone(), IntentEntityType::Path(path));
+        }
+    }
+
     /// Phase 2: 尝试使用 LLM 补充提取实体
     async fn try_llm_extraction(
         &self,
@@ -1209,6 +1708,41 @@ impl Agent {
         println!();
     }
 
+    /// 匹配最佳意图；启用消歧且前几名得分相近时请用户选择
+    ///
+    /// 用户选择"都不是"时返回 None（交给 LLM 处理），
+    /// 选择结果会被记录，相似输入之后会偏向该意图。
+    fn match_with_disambiguation(&self, text: &str) -> Option<IntentMatch> {
+        if self.config.intent.disambiguation_enabled {
+            let candidates = self
+                .intent_matcher
+                .ambiguous_candidates(text, self.config.intent.disambiguation_margin);
+
+            if !candidates.is_empty() {
+                println!("{}", explain::format_menu(&candidates));
+                let index = self.ask_user_choice(candidates.len())?;
+                let chosen = candidates.into_iter().nth(index)?;
+
+                if let Err(e) = self.intent_matcher.record_choice(text, &chosen.intent.name) {
+                    Display::debug_info(self.config.display.mode, &format!("记录选择失败: {}", e));
+                }
+                return Some(chosen);
+            }
+        }
+
+        self.intent_matcher.best_match(text)
+    }
+
+    /// 询问用户从菜单中选择（返回 0 起始的序号）
+    fn ask_user_choice(&self, count: usize) -> Option<usize> {
+        print!("请选择 [1-{}, 0]: ", count);
+        let _ = io::stdout().flush();
+
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).ok()?;
+        explain::parse_choice(&input, count)
+    }
+
     /// 询问用户确认
     fn ask_user_confirmation(&self) -> bool {
         print!("是否继续执行? [y/N]: ");
@@ -1231,7 +1765,21 @@ impl Agent {
     /// - Intent DSL 生成的命令都是标准 Shell 命令
     /// - 直接复用现有的 shell_executor 基础设施
     /// - 不引入额外的复杂性
+    ///
+    /// 若计划来自 Pipeline DSL，执行前先做类型检查；
+    /// 配置了 `intent.pipeline_backend: native` 时在进程内原生执行并渲染结构化结果表，不经过 Shell。
     fn execute_intent(&self, plan: &ExecutionPlan) -> String {
+        if let Some(pipeline) = &plan.pipeline {
+            // 类型错误的计划不交给 Shell 执行
+            if let Err(e) = pipeline.type_check() {
+                return format!("{} {}", "执行计划类型检查失败:".red(), e);
+            }
+
+            if self.config.intent.pipeline_backend == PipelineBackend::Native {
+                return self.execute_pipeline_native(pipeline);
+            }
+        }
+
         // 显示将要执行的命令
         Display::command_execution(self.config.display.mode, &plan.command);
 
@@ -1241,7 +1789,10 @@ impl Agent {
                 crate::shell_executor::execute_shell(&plan.command).await
             })
         }) {
-            Ok(output) => output,
+            Ok(output) => {
+                self.capture_output(&plan.command, &output, true);
+                output
+            }
             Err(e) => {
                 // 使用用户友好的错误格式
                 e.format_user_friendly()
@@ -1249,6 +1800,19 @@ impl Agent {
         }
     }
 
+    /// 使用原生后端执行 Pipeline 计划
+    fn execute_pipeline_native(&self, pipeline: &PipelinePlan) -> String {
+        Display::command_execution(
+            self.config.display.mode,
+            &format!("[native] {}", pipeline.to_dsl()),
+        );
+
+        match NativeExecutor::new().execute(pipeline) {
+            Ok(table) => table.render(),
+            Err(e) => format!("{} {}", "原生执行失败:".red(), e),
+        }
+    }
+
     // ========== 多轮对话支持方法 (Phase 8 Week 2) ==========
 
     /// 尝试启动多轮对话
@@ -1727,6 +2291,80 @@ impl Agent {
     }
 }
 
+/// 根据配置构建语义索引
+fn build_semantic_index(config: &SemanticMatchingConfig) -> SemanticIndex {
+    let cache_path = config
+        .cache_file
+        .as_deref()
+        .map(|p| std::path::PathBuf::from(Agent::normalize_path(p)))
+        .unwrap_or_else(EmbeddingCache::default_path);
+
+    SemanticIndex::new(build_embedder(config), EmbeddingCache::load(cache_path))
+}
+
+/// 根据配置构建长期记忆
+///
+/// 未启用 `memory.long_term` 时只保存在内存中（仍可在本次会话中检索）。
+/// 会话以启动时间命名。
+fn build_long_term_memory(config: &Config) -> LongTermMemory {
+    let session = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mem_config = config.memory.as_ref();
+
+    let long_term = if mem_config.and_then(|m| m.long_term).unwrap_or(false) {
+        let path = mem_config
+            .and_then(|m| m.long_term_file.as_deref())
+            .map(|p| std::path::PathBuf::from(Agent::normalize_path(p)))
+            .unwrap_or_else(LongTermMemory::default_path);
+        let max_entries = mem_config
+            .and_then(|m| m.long_term_max_entries)
+            .unwrap_or(DEFAULT_MAX_ENTRIES);
+        LongTermMemory::open(&path, session.clone())
+            .map(|memory| memory.with_max_entries(max_entries))
+            .unwrap_or_else(|e| {
+                eprintln!("{} {}", "⚠ 长期记忆加载失败:".yellow(), e);
+                LongTermMemory::new(session)
+            })
+    } else {
+        LongTermMemory::new(session)
+    };
+
+    if mem_config.and_then(|m| m.semantic_recall).unwrap_or(false) {
+        long_term.with_embedder(build_embedder(&config.intent.semantic))
+    } else {
+        long_term
+    }
+}
+
+/// 根据配置构建命令输出存储
+fn build_output_store(config: &Config) -> OutputStore {
+    let mem_config = config.memory.as_ref();
+    OutputStore::new().with_limits(
+        mem_config
+            .and_then(|m| m.output_history)
+            .unwrap_or(crate::memory::output_store::DEFAULT_MAX_OUTPUTS),
+        mem_config
+            .and_then(|m| m.output_max_bytes)
+            .unwrap_or(crate::memory::output_store::DEFAULT_MAX_OUTPUT_BYTES),
+    )
+}
+
+/// 根据配置构建嵌入器
+///
+/// 未知的 provider 回退到内置嵌入器。
+fn build_embedder(config: &SemanticMatchingConfig) -> Arc<dyn Embedder> {
+    match config.provider.as_str() {
+        "ollama" => Arc::new(OllamaEmbedder::new(
+            config.model.as_deref().unwrap_or("nomic-embed-text"),
+            config.endpoint.as_deref().unwrap_or("http://localhost:11434"),
+        )),
+        "local" => Arc::new(HashingEmbedder::default()),
+        other => {
+            eprintln!("{} 未知的嵌入来源 {}，使用内置嵌入器", "⚠".yellow(), other);
+            Arc::new(HashingEmbedder::default())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1825,6 +2463,65 @@ mod tests {
         assert!(!memory_guard.is_empty());
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fix_patterns_subcommand_is_whole_token() {
+        let agent = Agent::new(Config::default(), CommandRegistry::new());
+
+        assert!(agent.handle_command("fix patterns").contains("错误模式库"));
+        assert!(agent.handle_command("fix  patterns list").contains("错误模式库"));
+        let output = agent.handle_command("fix patternsfoo");
+        assert!(!output.contains("错误模式库"));
+        assert!(output.contains("没有可重试的失败命令"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agent_resolves_references() {
+        let agent = Agent::new(Config::default(), CommandRegistry::new());
+        {
+            let mut tracker = agent.context_tracker.write().await;
+            let entities = tracker.extract_entities("cat ./src/main.rs");
+            tracker.record_entities(entities);
+        }
+
+        assert_eq!(agent.resolve_references("delete it"), "delete ./src/main.rs");
+        let prompt = agent.with_resolved_references("delete ./src/main.rs");
+        assert!(prompt.contains("\"it\" 指文件 ./src/main.rs"));
+
+        // Shell 命令与系统命令保持原样，只改写自然语言
+        for input in ["rm it", "grep it x", "!rm it", "/help it"] {
+            let route = agent.route_and_resolve(input);
+            assert!(!matches!(route, RouterCommandType::NaturalLanguage(_)));
+        }
+        assert!(matches!(
+            agent.route_and_resolve("please delete it"),
+            RouterCommandType::NaturalLanguage(text) if text == "please delete ./src/main.rs"
+        ));
+        // 作主语的 it 不改写
+        assert!(matches!(
+            agent.route_and_resolve("is it possible to undo"),
+            RouterCommandType::NaturalLanguage(text) if text == "is it possible to undo"
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agent_output_references() {
+        let mut config = Config::default();
+        config.features.shell_enabled = true;
+        let agent = Agent::new(config, CommandRegistry::new());
+
+        agent.handle("!echo captured-output");
+        assert_eq!(agent.output_store.read().await.len(), 1);
+
+        // Shell 命令中 $last 展开为输出文件
+        let result = agent.handle("!cat $last");
+        assert!(result.contains("captured-output"));
+
+        // 自然语言提示词附上引用的输出
+        let prompt = agent.with_referenced_outputs("summarise $1", "summarise $1");
+        assert!(prompt.contains("`echo captured-output` 的输出"));
+        assert!(agent.with_referenced_outputs("hello", "hello") == "hello");
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_agent_execution_logging() {
         let config = Config::default();
@@ -2151,6 +2848,7 @@ mod tests {
             command: "echo 'test'".to_string(),
             template_name: "test_template".to_string(),
             bindings: std::collections::HashMap::new(),
+            pipeline: None,
         };
 
         // 执行 Intent
@@ -2172,6 +2870,7 @@ mod tests {
             command: "nonexistent_command_xyz_123".to_string(),
             template_name: "test_template".to_string(),
             bindings: std::collections::HashMap::new(),
+            pipeline: None,
         };
 
         // 执行 Intent
@@ -2182,6 +2881,44 @@ mod tests {
         // 可能包含 "not found" 或类似的错误消息
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_intent_native_backend() {
+        use crate::dsl::pipeline::BaseOperation;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("native_probe.rs"), "fn main() {}").unwrap();
+
+        let mut config = Config::default();
+        config.intent.pipeline_backend = PipelineBackend::Native;
+        let agent = Agent::new(config, CommandRegistry::new());
+
+        let pipeline = PipelinePlan::new().with_operation(BaseOperation::FindFiles {
+            path: dir.path().display().to_string(),
+            pattern: "*.rs".to_string(),
+        });
+        let plan = ExecutionPlan::from_pipeline(pipeline, "test_template", std::collections::HashMap::new());
+
+        // 原生后端输出结构化表格，而不是 ls -lh 文本
+        let result = agent.execute_intent(&plan);
+        assert!(result.contains("native_probe.rs"));
+        assert!(result.contains("路径"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_intent_rejects_ill_typed_pipeline() {
+        use crate::dsl::pipeline::BaseOperation;
+
+        let agent = Agent::new(Config::default(), CommandRegistry::new());
+
+        // 没有数据源的计划不会交给 Shell
+        let pipeline = PipelinePlan::new().with_operation(BaseOperation::LimitFiles { count: 5 });
+        let plan = ExecutionPlan::from_pipeline(pipeline, "test_template", std::collections::HashMap::new());
+
+        let result = agent.execute_intent(&plan);
+        assert!(result.contains("类型检查失败"));
+        assert!(result.contains("第 1 步 LimitFiles"));
+    }
+
     // ========== handle_text 路径测试 ==========
 
     #[tokio::test(flavor = "multi_thread")]
@@ -2434,4 +3171,27 @@ features:
         assert!(agent.workflow_intents.is_empty());
         assert!(agent.workflow_executor.is_none());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_why_command_explains_match() {
+        let agent = Agent::new(Config::default(), CommandRegistry::new());
+
+        let output = agent.handle("/why 显示当前目录下最大的文件");
+        assert!(output.contains("find_files_by_size"));
+        assert!(output.contains("关键词"));
+
+        let usage = agent.handle("/why");
+        assert!(usage.contains("/why <输入>"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_disambiguation_disabled_uses_best_match() {
+        let agent = Agent::new(Config::default(), CommandRegistry::new());
+
+        // 未启用消歧时不会读取标准输入，直接返回最佳匹配
+        let text = "显示当前目录下最大的文件";
+        let chosen = agent.match_with_disambiguation(text).map(|m| m.intent.name);
+        let best = agent.intent_matcher.best_match(text).map(|m| m.intent.name);
+        assert_eq!(chosen, best);
+    }
 }
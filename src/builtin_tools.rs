@@ -5,7 +5,7 @@
 //! - FileOps: 文件操作（读/写/列表）
 //! - DateTime: 日期时间查询
 
-use crate::tool::{Parameter, ParameterType, Tool, ToolRegistry};
+use crate::tool::{OutputRefs, Parameter, ParameterType, Tool, ToolRegistry};
 use chrono::Local;
 use serde_json::{json, Value as JsonValue};
 use std::fs;
@@ -463,7 +463,8 @@ fn register_shell_execute(registry: &mut ToolRegistry) {
                 })
             })
         },
-    );
+    )
+    .with_output_refs(OutputRefs::Shell);
 
     registry.register(tool);
 }
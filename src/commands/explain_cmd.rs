@@ -0,0 +1,250 @@
+//! 错误解释命令
+//!
+//! `/explain` 与 `realconsole explain`：分析来自 CI 日志或其他终端的错误输出，
+//! 不需要由 RealConsole 执行原命令。
+//!
+//! 输入来源：
+//! - `/explain <错误输出>`：直接粘贴的文本
+//! - `/explain <文件>`：日志文件
+//! - `/explain`：多行粘贴模式，单独一行 `.` 或 Ctrl-D 结束
+//! - `cmd 2>&1 | realconsole explain`：标准输入
+
+use crate::command::{Command, CommandRegistry};
+use crate::error_fixer::diagnostics::parse_diagnostics;
+use crate::error_fixer::{
+    Diagnostic, ErrorAnalysis, ErrorAnalyzer, ErrorFixer, FeedbackContext, FeedbackLearner, FixStrategy,
+};
+use crate::llm::LlmClient;
+use crate::llm_manager::LlmManager;
+use crate::shell_executor::ShellExecutorWithFixer;
+use colored::Colorize;
+use std::io::{self, BufRead, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 错误解释结果
+#[derive(Debug, Clone)]
+pub struct ErrorExplanation {
+    /// 错误分析（含 LLM 增强分析）
+    pub analysis: ErrorAnalysis,
+    /// 修复策略（按学习到的效果排序）
+    pub strategies: Vec<FixStrategy>,
+    /// 编译器诊断
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ErrorExplanation {
+    /// LLM 给出的根本原因
+    pub fn root_cause(&self) -> Option<String> {
+        let llm_analysis = self.analysis.llm_analysis.as_deref()?;
+        let value: serde_json::Value = serde_json::from_str(llm_analysis).ok()?;
+        value["root_cause"].as_str().map(str::to_string)
+    }
+}
+
+/// 分析一段错误输出
+///
+/// 原命令未知（`command` 为空）时，丢弃基于原命令改写的策略（如 `sudo <原命令>`）。
+pub async fn explain_error_output(
+    output: &str,
+    command: &str,
+    analyzer: &ErrorAnalyzer,
+    learner: Option<&FeedbackLearner>,
+    llm: Option<&dyn LlmClient>,
+) -> ErrorExplanation {
+    let mut analysis = analyzer.analyze(command, output);
+    if let Some(llm) = llm {
+        if let Ok(enhanced) = analyzer.analyze_with_llm(analysis.clone(), llm).await {
+            analysis = enhanced;
+        }
+    }
+
+    let mut strategies = ErrorFixer::generate_strategies(&analysis);
+    if let Some(llm) = llm {
+        if let Ok(llm_strategies) = ErrorFixer::generate_strategies_with_llm(&analysis, llm).await {
+            strategies.extend(llm_strategies);
+        }
+    }
+    // 原命令未知：去掉需要执行原命令的策略
+    if command.trim().is_empty() {
+        strategies.retain(|s| !s.runs_original && !s.command.trim().is_empty());
+    }
+    if let Some(learner) = learner {
+        let context = FeedbackContext::current(&analysis);
+        strategies = learner.rerank_strategies_in_context(strategies, &context).await;
+    }
+
+    ErrorExplanation {
+        diagnostics: parse_diagnostics(output),
+        analysis,
+        strategies,
+    }
+}
+
+/// 格式化解释结果
+pub fn format_explanation(explanation: &ErrorExplanation) -> String {
+    let analysis = &explanation.analysis;
+    let mut output = format!("\n{}\n", "🔍 错误解释".cyan().bold());
+    output.push_str(&format!("  {}: {}\n", "类别".dimmed(), analysis.category.to_string().yellow()));
+    output.push_str(&format!(
+        "  {}: {}\n",
+        "匹配模式".dimmed(),
+        analysis.pattern_name.as_deref().unwrap_or("（未匹配）")
+    ));
+    output.push_str(&format!("  {}: {:?}\n", "严重程度".dimmed(), analysis.severity));
+
+    if let Some(root_cause) = explanation.root_cause() {
+        output.push_str(&format!("  {}: {}\n", "根本原因".dimmed(), root_cause.bold()));
+    }
+
+    if !analysis.possible_causes.is_empty() {
+        output.push_str(&format!("\n  {}:\n", "可能原因".dimmed()));
+        for cause in &analysis.possible_causes {
+            output.push_str(&format!("    • {}\n", cause));
+        }
+    }
+
+    if !analysis.suggested_fixes.is_empty() {
+        output.push_str(&format!("\n  {}:\n", "建议修复".dimmed()));
+        for fix in &analysis.suggested_fixes {
+            output.push_str(&format!("    • {}\n", fix));
+        }
+    }
+
+    if !explanation.diagnostics.is_empty() {
+        output.push_str(&super::fix_cmd::format_diagnostics(&explanation.diagnostics));
+    }
+
+    if explanation.strategies.is_empty() {
+        output.push_str(&format!("\n{}\n", "暂无修复策略".yellow()));
+    } else {
+        output.push_str(&format!("\n{}\n", "💡 修复策略 (按推荐度排序)".green().bold()));
+        for (i, strategy) in explanation.strategies.iter().enumerate() {
+            output.push_str(&format!(
+                "  {}. {} {} {}\n",
+                (i + 1).to_string().cyan().bold(),
+                strategy.name.bold(),
+                format!("(风险: {}/10)", strategy.risk_level).dimmed(),
+                strategy.description.dimmed()
+            ));
+            output.push_str(&format!("     {}\n", format!("$ {}", strategy.command).green()));
+        }
+    }
+    output
+}
+
+/// 读取要解释的输出：已存在的文件路径读文件，`-` 读标准输入，其余视为粘贴的文本
+pub fn read_explain_input(arg: &str) -> Result<String, String> {
+    let arg = arg.trim();
+    if arg == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("读取标准输入失败: {}", e))?;
+        return Ok(input);
+    }
+    if !arg.contains('\n') && Path::new(arg).is_file() {
+        return std::fs::read_to_string(arg).map_err(|e| format!("读取 {} 失败: {}", arg, e));
+    }
+    Ok(arg.to_string())
+}
+
+/// 多行粘贴模式：读到单独一行 `.` 或 EOF 为止
+fn read_pasted_lines() -> Result<String, String> {
+    println!("{}", "粘贴错误输出，单独一行 . 或 Ctrl-D 结束:".dimmed());
+    let _ = io::stdout().flush();
+
+    let mut lines = Vec::new();
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("读取输入失败: {}", e))?;
+        if line.trim() == "." {
+            break;
+        }
+        lines.push(line);
+    }
+    Ok(lines.join("\n"))
+}
+
+/// 注册 /explain 命令
+pub fn register_explain_commands(
+    registry: &mut CommandRegistry,
+    shell_executor: Arc<ShellExecutorWithFixer>,
+    llm_manager: Arc<RwLock<LlmManager>>,
+) {
+    registry.register(Command::from_fn(
+        "explain",
+        "解释任意错误输出（/explain <输出|文件>，不带参数进入多行粘贴模式）",
+        move |arg: &str| {
+            let input = if arg.trim().is_empty() {
+                read_pasted_lines()
+            } else {
+                read_explain_input(arg)
+            };
+            let output = match input {
+                Ok(output) if !output.trim().is_empty() => output,
+                Ok(_) => return format!("{}", "❌ 没有要解释的内容".red()),
+                Err(e) => return format!("{} {}", "❌".red(), e),
+            };
+
+            let shell_executor = Arc::clone(&shell_executor);
+            let llm_manager = Arc::clone(&llm_manager);
+            let explanation = tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    let llm = {
+                        let manager = llm_manager.read().await;
+                        manager.primary().or(manager.fallback()).cloned()
+                    };
+                    let learner = shell_executor.feedback_learner();
+                    explain_error_output(&output, "", shell_executor.analyzer(), Some(&learner), llm.as_deref()).await
+                })
+            });
+            format_explanation(&explanation)
+        },
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_explain_without_command() {
+        let analyzer = ErrorAnalyzer::new();
+        let output = "Traceback (most recent call last):\n  File \"app.py\", line 1, in <module>\n    import numpy\nModuleNotFoundError: No module named 'numpy'";
+
+        let explanation = explain_error_output(output, "", &analyzer, None, None).await;
+        assert_eq!(explanation.analysis.pattern_name.as_deref(), Some("python_module_not_found"));
+        assert!(explanation.strategies.iter().any(|s| s.command == "pip install numpy"));
+        assert_eq!(explanation.diagnostics.len(), 1);
+        assert!(explanation.root_cause().is_none());
+
+        let text = format_explanation(&explanation);
+        assert!(text.contains("python_module_not_found"));
+        assert!(text.contains("pip install numpy"));
+        assert!(text.contains("app.py"));
+    }
+
+    #[tokio::test]
+    async fn test_explain_drops_command_rewrites() {
+        let analyzer = ErrorAnalyzer::new();
+        let output = "bash: ./deploy.sh: Permission denied";
+
+        // 原命令未知：不给出 `sudo <原命令>`
+        let explanation = explain_error_output(output, "", &analyzer, None, None).await;
+        assert!(explanation.strategies.iter().all(|s| !s.command.starts_with("sudo") && !s.runs_original));
+
+        let explanation = explain_error_output(output, "./deploy.sh", &analyzer, None, None).await;
+        assert!(explanation.strategies.iter().any(|s| s.command.starts_with("sudo") && s.runs_original));
+    }
+
+    #[test]
+    fn test_read_explain_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("ci.log");
+        std::fs::write(&log, "error: boom").unwrap();
+
+        assert_eq!(read_explain_input(log.to_str().unwrap()).unwrap(), "error: boom");
+        assert_eq!(read_explain_input("  npm ERR! missing script  ").unwrap(), "npm ERR! missing script");
+    }
+}
@@ -0,0 +1,372 @@
+//! 错误修复命令
+//!
+//! `/fix patterns` 子命令：查看错误模式库、测试模式、查看命中统计。
+//! `/fix stats`：按上下文（项目类型、命令、错误模式 ...）查看修复效果。
+//! （`/fix` 本身由 Agent 处理，用于重试上次失败的命令）
+//!
+//! 另提供编译器诊断的分文件展示与自动修复记录，供交互式修复流程使用。
+
+use crate::error_fixer::diagnostics::group_by_file;
+use crate::error_fixer::patterns::{global_pattern_dir, project_pattern_dir};
+use crate::error_fixer::{
+    CodeFix, ContextBreakdown, ContextDimension, Diagnostic, DiagnosticLevel, ErrorAnalyzer, LearningSummary,
+};
+use crate::shell_executor::FixAttempt;
+use colored::Colorize;
+
+/// 处理 `/fix patterns [list|test <输出>|stats]`
+pub fn fix_patterns_command(analyzer: &ErrorAnalyzer, arg: &str) -> String {
+    let arg = arg.trim();
+    let (sub, rest) = arg.split_once(char::is_whitespace).unwrap_or((arg, ""));
+
+    match sub {
+        "" | "list" => list_patterns(analyzer),
+        "test" => test_patterns(analyzer, rest.trim()),
+        "stats" => pattern_stats(analyzer),
+        other => format!(
+            "{} {}\n{}",
+            "❌ 未知子命令:".red(),
+            other,
+            "用法: /fix patterns [list | test <错误输出> | stats]".dimmed()
+        ),
+    }
+}
+
+/// 列出模式库（按匹配优先级）
+fn list_patterns(analyzer: &ErrorAnalyzer) -> String {
+    let hits: std::collections::HashMap<String, u64> = analyzer.hit_counts().into_iter().collect();
+    let mut output = format!(
+        "\n{} {}\n",
+        "错误模式库".bold(),
+        format!("· {} 个（按匹配优先级）", analyzer.patterns().len()).dimmed()
+    );
+
+    for (idx, pattern) in analyzer.patterns().iter().enumerate() {
+        let hit = hits.get(&pattern.name).copied().unwrap_or(0);
+        output.push_str(&format!(
+            "  {:>2}. {} {} {} {}\n",
+            idx + 1,
+            pattern.name.cyan(),
+            format!("[{} · 严重度 {}]", pattern.category, pattern.severity).dimmed(),
+            if hit > 0 { format!("命中 {}", hit).green() } else { "".normal() },
+            format!("· {}", pattern.source).dimmed()
+        ));
+        for command in &pattern.fix_commands {
+            output.push_str(&format!("      {}\n", format!("$ {}", command).dimmed()));
+        }
+    }
+
+    for error in analyzer.load_errors() {
+        output.push_str(&format!("  {} {}\n", "⚠".yellow(), error));
+    }
+
+    output.push_str(&format!(
+        "{}\n",
+        format!(
+            "自定义模式: {} 或 {}（*.yaml）",
+            project_pattern_dir().display(),
+            global_pattern_dir().display()
+        )
+        .dimmed()
+    ));
+    output
+}
+
+/// 用粘贴的错误输出测试模式（不计入命中统计）
+fn test_patterns(analyzer: &ErrorAnalyzer, error_output: &str) -> String {
+    if error_output.is_empty() {
+        return format!(
+            "{}\n{}",
+            "❌ 请提供要测试的错误输出".red(),
+            "用法: /fix patterns test <错误输出>".dimmed()
+        );
+    }
+
+    let matches = analyzer.matching_patterns(error_output);
+    if matches.is_empty() {
+        return format!("{} 未匹配任何模式（将归为未知错误）", "✗".red());
+    }
+
+    let mut output = String::new();
+    for (idx, pattern) in matches.iter().enumerate() {
+        let marker = if idx == 0 { "✓ 生效".green() } else { "  被遮蔽".dimmed() };
+        output.push_str(&format!(
+            "{} {} {}\n",
+            marker,
+            pattern.name.cyan(),
+            format!("[{}] · {}", pattern.category, pattern.source).dimmed()
+        ));
+
+        if let Some(caps) = pattern.regex.captures(error_output) {
+            for (i, group) in caps.iter().enumerate().skip(1) {
+                if let Some(group) = group {
+                    output.push_str(&format!("    ${} = {}\n", i, group.as_str().yellow()));
+                }
+            }
+        }
+        if !pattern.suggested_fix.is_empty() {
+            output.push_str(&format!("    {}\n", pattern.suggested_fix));
+        }
+        for command in pattern.render_fix_commands(error_output) {
+            output.push_str(&format!("    {}\n", format!("$ {}", command).cyan()));
+        }
+    }
+    output
+}
+
+/// 本次会话的模式命中统计
+fn pattern_stats(analyzer: &ErrorAnalyzer) -> String {
+    let counts = analyzer.hit_counts();
+    let unmatched = analyzer.unmatched_count();
+    let total: u64 = counts.iter().map(|(_, n)| n).sum::<u64>() + unmatched;
+    if total == 0 {
+        return format!("{} 本次会话尚未分析任何错误", "ℹ".dimmed());
+    }
+
+    let percent = |n: u64| n as f64 * 100.0 / total as f64;
+    let mut output = format!("\n{} {}\n", "模式命中统计".bold(), format!("· 共 {} 次错误", total).dimmed());
+    for (name, count) in &counts {
+        output.push_str(&format!(
+            "  {:<28} {:>4} {}\n",
+            name.cyan(),
+            count,
+            format!("{:.0}%", percent(*count)).dimmed()
+        ));
+    }
+    if unmatched > 0 {
+        output.push_str(&format!(
+            "  {:<28} {:>4} {}\n",
+            "（未匹配）".yellow(),
+            unmatched,
+            format!("{:.0}%", percent(unmatched)).dimmed()
+        ));
+    }
+    output
+}
+
+/// `/fix stats`：修复反馈的总体效果与按上下文的细分
+pub fn fix_stats_report(summary: &LearningSummary, breakdown: &[ContextBreakdown]) -> String {
+    if summary.total_feedbacks == 0 {
+        return format!("{} 尚无修复反馈记录", "ℹ".dimmed());
+    }
+
+    let mut output = format!(
+        "\n{} {}\n",
+        "修复效果统计".bold(),
+        format!(
+            "· {} 条反馈 · 成功 {:.0}%",
+            summary.total_feedbacks,
+            summary.overall_success_rate * 100.0
+        )
+        .dimmed()
+    );
+
+    for (dimension, _) in ContextDimension::ALL {
+        let rows: Vec<&ContextBreakdown> = breakdown.iter().filter(|b| b.dimension == dimension).collect();
+        if rows.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n  {}\n", dimension.to_string().cyan()));
+        for row in rows {
+            let best = row
+                .best_strategy
+                .as_ref()
+                .map(|(name, score)| format!("最佳: {} ({:.2})", name, score))
+                .unwrap_or_default();
+            output.push_str(&format!(
+                "    {:<24} {:>4} 条  成功 {:>3.0}%  {}\n",
+                row.value,
+                row.total,
+                row.success_rate * 100.0,
+                best.dimmed()
+            ));
+        }
+    }
+    output.push_str(&format!("{}\n", "排序时按与当前上下文的相似度加权，旧记录每 30 天权重减半".dimmed()));
+    output
+}
+
+/// 自动修复尝试记录（每次尝试的修复命令、验证结果与回滚情况）
+pub fn format_fix_transcript(attempts: &[FixAttempt]) -> String {
+    let mut output = format!("\n{}\n", "🔧 自动修复记录".cyan().bold());
+    for (idx, attempt) in attempts.iter().enumerate() {
+        let status = if attempt.verified {
+            "✓ 通过验证".green()
+        } else if !attempt.fix_succeeded {
+            "✗ 修复命令失败".red()
+        } else {
+            "✗ 未通过验证".red()
+        };
+        output.push_str(&format!(
+            "  {}. {} {} {}\n",
+            idx + 1,
+            attempt.strategy_name.cyan(),
+            status,
+            format!("({}ms)", attempt.duration.as_millis()).dimmed()
+        ));
+        output.push_str(&format!("     {}: {}\n", "修复命令".dimmed(), attempt.command.green()));
+        if attempt.fix_succeeded {
+            output.push_str(&format!("     {}: {}\n", "验证".dimmed(), attempt.verification));
+        }
+        for change in &attempt.rolled_back {
+            output.push_str(&format!("     {} {}\n", "↩ 已回滚".yellow(), change.path().display()));
+        }
+        if let Some(error) = &attempt.rollback_error {
+            output.push_str(&format!("     {} {}\n", "⚠ 回滚失败:".red(), error));
+        } else if !attempt.verified && !attempt.snapshot_complete {
+            output.push_str(&format!("     {}\n", "⚠ 快照不完整，部分文件可能未回滚".yellow()));
+        }
+    }
+    output
+}
+
+/// 按文件分组展示编译器诊断
+pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
+    let mut output = format!(
+        "\n{} {}\n",
+        "📋 编译器诊断".cyan().bold(),
+        format!("· {} 条", diagnostics.len()).dimmed()
+    );
+
+    for (file, items) in group_by_file(diagnostics) {
+        output.push_str(&format!("\n  {}\n", file.bold()));
+        for diagnostic in items {
+            let level = match diagnostic.level {
+                DiagnosticLevel::Error => diagnostic.level.to_string().red(),
+                DiagnosticLevel::Warning => diagnostic.level.to_string().yellow(),
+                DiagnosticLevel::Note => diagnostic.level.to_string().dimmed(),
+            };
+            let code = diagnostic.code.as_deref().map(|c| format!("[{}] ", c)).unwrap_or_default();
+            output.push_str(&format!(
+                "    {} {} {}{}\n",
+                format!("{:>4}:{:<3}", diagnostic.line, diagnostic.column).dimmed(),
+                level,
+                code.dimmed(),
+                diagnostic.message
+            ));
+            for suggestion in &diagnostic.suggestions {
+                let marker = if suggestion.machine_applicable { "✓".green() } else { "?".dimmed() };
+                output.push_str(&format!(
+                    "             {} {} → {}\n",
+                    marker,
+                    suggestion.message.dimmed(),
+                    format!("`{}`", suggestion.replacement).green()
+                ));
+            }
+        }
+    }
+    output
+}
+
+/// 代码修复的 diff 预览（+ 绿色，- 红色）
+pub fn format_code_fix(fix: &CodeFix) -> String {
+    let mut output = format!("\n{} {}\n", fix.strategy_name.cyan().bold(), fix.description.dimmed());
+    for line in fix.diff().lines() {
+        let line = if line.starts_with("+++") || line.starts_with("---") {
+            line.bold()
+        } else if line.starts_with('+') {
+            line.green()
+        } else if line.starts_with('-') {
+            line.red()
+        } else if line.starts_with("@@") {
+            line.cyan()
+        } else {
+            line.normal()
+        };
+        output.push_str(&format!("  {}\n", line));
+    }
+    for skipped in &fix.skipped {
+        output.push_str(&format!("  {} {}\n", "⚠ 跳过".yellow(), skipped));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_and_stats() {
+        let analyzer = ErrorAnalyzer::new();
+        let output = fix_patterns_command(&analyzer, "");
+        assert!(output.contains("command_not_found"));
+        assert!(output.contains("内置"));
+
+        assert!(fix_patterns_command(&analyzer, "stats").contains("尚未分析"));
+        analyzer.analyze("foo", "bash: foo: command not found");
+        analyzer.analyze("bar", "weird failure");
+        let output = fix_patterns_command(&analyzer, "stats");
+        assert!(output.contains("command_not_found"));
+        assert!(output.contains("未匹配"));
+        assert!(output.contains("50%"));
+    }
+
+    #[test]
+    fn test_test_subcommand() {
+        let analyzer = ErrorAnalyzer::new();
+        let output = fix_patterns_command(&analyzer, "test ModuleNotFoundError: No module named 'numpy'");
+        assert!(output.contains("python_module_not_found"));
+        assert!(output.contains("numpy"));
+        assert!(analyzer.hit_counts().is_empty());
+
+        assert!(fix_patterns_command(&analyzer, "test all good").contains("未匹配任何模式"));
+        assert!(fix_patterns_command(&analyzer, "test").contains("请提供"));
+        assert!(fix_patterns_command(&analyzer, "bogus").contains("未知子命令"));
+    }
+
+    #[tokio::test]
+    async fn test_fix_stats_report() {
+        use crate::error_fixer::{ErrorAnalysis, FeedbackLearner, FeedbackRecord, FeedbackType, FixOutcome, FixStrategy};
+
+        let learner = FeedbackLearner::new();
+        let empty = fix_stats_report(&learner.get_summary().await, &learner.context_breakdown().await);
+        assert!(empty.contains("尚无"));
+
+        let analysis = ErrorAnalysis::new("error".to_string(), "cargo build".to_string());
+        let strategy = FixStrategy::new("cargo_clean", "cargo clean", "", 3);
+        learner
+            .record_feedback(FeedbackRecord::new(&analysis, &strategy, FeedbackType::Accepted, FixOutcome::Success))
+            .await;
+
+        let output = fix_stats_report(&learner.get_summary().await, &learner.context_breakdown().await);
+        assert!(output.contains("项目类型"));
+        assert!(output.contains("cargo"));
+        assert!(output.contains("cargo_clean"));
+    }
+
+    #[test]
+    fn test_format_fix_transcript() {
+        use crate::error_fixer::{FileChange, Verification};
+
+        let attempt = FixAttempt {
+            strategy_name: "创建目录".to_string(),
+            command: "mkdir -p out".to_string(),
+            fix_succeeded: true,
+            fix_output: String::new(),
+            verification: Verification::RerunOriginal,
+            verification_output: Some("still failing".to_string()),
+            verified: false,
+            rolled_back: vec![FileChange::Created("out/a.txt".into())],
+            rollback_error: None,
+            snapshot_complete: true,
+            duration: std::time::Duration::from_millis(12),
+        };
+        let output = format_fix_transcript(&[attempt]);
+        assert!(output.contains("mkdir -p out"));
+        assert!(output.contains("未通过验证"));
+        assert!(output.contains("重新执行原命令"));
+        assert!(output.contains("out/a.txt"));
+    }
+
+    #[test]
+    fn test_format_diagnostics() {
+        let diagnostics = crate::error_fixer::diagnostics::parse_diagnostics(
+            "a.c:3:5: error: expected ';'\nfix-it:\"a.c\":{3:5-3:5}:\";\"\nb.c:1:1: warning: unused [-Wunused]",
+        );
+        let output = format_diagnostics(&diagnostics);
+        assert!(output.contains("a.c"));
+        assert!(output.contains("b.c"));
+        assert!(output.contains("[-Wunused]"));
+        assert!(output.contains("`;`"));
+    }
+}
@@ -3,7 +3,7 @@
 //! 提供记忆查看、搜索、清空等功能
 
 use crate::command::{Command, CommandRegistry};
-use crate::memory::{EntryType, Memory};
+use crate::memory::{EntryType, LongTermMemory, Memory, MemoryQuery};
 use colored::Colorize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -12,12 +12,13 @@ use tokio::sync::RwLock;
 pub fn register_memory_commands(
     registry: &mut CommandRegistry,
     memory: Arc<RwLock<Memory>>,
+    long_term: Arc<RwLock<LongTermMemory>>,
 ) {
     // /memory 命令
     let memory_cmd = Command::from_fn(
         "memory",
-        "记忆管理: memory [recent|search|clear|dump|save|type]",
-        move |arg: &str| handle_memory(arg, Arc::clone(&memory)),
+        "记忆管理: memory [recent|search|find|clear|dump|save|type]",
+        move |arg: &str| handle_memory(arg, Arc::clone(&memory), Arc::clone(&long_term)),
     )
     .with_aliases(vec!["mem".to_string(), "m".to_string()])
     .with_group("memory");
@@ -25,11 +26,11 @@ pub fn register_memory_commands(
 }
 
 /// 处理 /memory 命令
-fn handle_memory(arg: &str, memory: Arc<RwLock<Memory>>) -> String {
+fn handle_memory(arg: &str, memory: Arc<RwLock<Memory>>, long_term: Arc<RwLock<LongTermMemory>>) -> String {
     let parts: Vec<&str> = arg.split_whitespace().collect();
 
     if parts.is_empty() {
-        return handle_memory_status(memory);
+        return handle_memory_status(memory, long_term);
     }
 
     let subcommand = parts[0];
@@ -38,6 +39,7 @@ fn handle_memory(arg: &str, memory: Arc<RwLock<Memory>>) -> String {
     match subcommand {
         "recent" | "r" => handle_memory_recent(&rest, memory),
         "search" | "s" => handle_memory_search(&rest, memory),
+        "find" | "f" => handle_memory_find(&rest, long_term),
         "clear" | "c" => handle_memory_clear(memory),
         "dump" | "d" => handle_memory_dump(memory),
         "save" => handle_memory_save(&rest, memory),
@@ -48,15 +50,26 @@ fn handle_memory(arg: &str, memory: Arc<RwLock<Memory>>) -> String {
 }
 
 /// 显示记忆状态
-fn handle_memory_status(memory: Arc<RwLock<Memory>>) -> String {
+fn handle_memory_status(memory: Arc<RwLock<Memory>>, long_term: Arc<RwLock<LongTermMemory>>) -> String {
     tokio::task::block_in_place(|| {
         tokio::runtime::Handle::current().block_on(async {
             let mem = memory.read().await;
+            let long_term = long_term.read().await;
             let mut lines = vec![
                 format!("{}", "记忆系统状态".bold().cyan()),
                 format!("  当前条目: {}", mem.len().to_string().green()),
                 format!("  最大容量: {}", "100".dimmed()),
+                format!(
+                    "  长期记忆: {} 条（{} 个会话，{} 个索引词{}）",
+                    long_term.len().to_string().green(),
+                    long_term.sessions().len(),
+                    long_term.term_count(),
+                    if long_term.has_embedder() { "，语义检索" } else { "" }
+                ),
             ];
+            if let Some(path) = long_term.path() {
+                lines.push(format!("  长期记忆文件: {}", path.display().to_string().dimmed()));
+            }
 
             if !mem.is_empty() {
                 lines.push(String::new());
@@ -129,6 +142,47 @@ fn handle_memory_search(keyword: &str, memory: Arc<RwLock<Memory>>) -> String {
     })
 }
 
+/// 检索长期记忆（BM25 全文 + 可选语义，支持过滤）
+fn handle_memory_find(arg: &str, long_term: Arc<RwLock<LongTermMemory>>) -> String {
+    let query = match MemoryQuery::parse(arg) {
+        Ok(query) => query,
+        Err(e) => return format!("{} {}", "错误:".red(), e),
+    };
+
+    tokio::task::block_in_place(|| {
+        tokio::runtime::Handle::current().block_on(async {
+            let long_term = long_term.read().await;
+            let hits = long_term.search(&query);
+
+            if hits.is_empty() {
+                return format!("{} 长期记忆中没有匹配的条目", "提示:".yellow());
+            }
+
+            let mut lines = vec![format!(
+                "{} {} 条结果{}:",
+                "找到".bold().green(),
+                hits.len().to_string().green(),
+                if query.text.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (查询: {})", query.text.cyan())
+                }
+            )];
+
+            for hit in hits {
+                let score = if query.text.is_empty() {
+                    String::new()
+                } else {
+                    format!(" {}", format!("[{:.2}]", hit.score).dimmed())
+                };
+                lines.push(format!("{}{}", hit.entry.format(), score));
+            }
+
+            lines.join("\n")
+        })
+    })
+}
+
 /// 清空记忆
 fn handle_memory_clear(memory: Arc<RwLock<Memory>>) -> String {
     tokio::task::block_in_place(|| {
@@ -194,13 +248,9 @@ fn handle_memory_save(path: &str, memory: Arc<RwLock<Memory>>) -> String {
 
 /// 按类型过滤记忆
 fn handle_memory_type(type_str: &str, memory: Arc<RwLock<Memory>>) -> String {
-    let entry_type = match type_str.to_lowercase().as_str() {
-        "user" | "u" => EntryType::User,
-        "assistant" | "a" => EntryType::Assistant,
-        "system" | "s" => EntryType::System,
-        "shell" | "sh" => EntryType::Shell,
-        "tool" | "t" => EntryType::Tool,
-        _ => {
+    let entry_type = match EntryType::parse(type_str) {
+        Some(entry_type) => entry_type,
+        None => {
             return format!("{} 未知类型: {}\n支持的类型: user, assistant, system, shell, tool",
                 "错误:".red(),
                 type_str
@@ -243,6 +293,8 @@ fn memory_help() -> String {
   /memory              - 显示记忆状态和最近记忆
   /memory recent <n>   - 查看最近 N 条记忆（默认 10）
   /memory search <关键词> - 搜索包含关键词的记忆
+  /memory find <查询> [过滤] - 检索所有会话的长期记忆（按相关度排序）
+      过滤: type:shell,user  since:7d|24h|2026-10-01  until:<日期>  session:<名称>  limit:<N>
   /memory type <类型>   - 按类型过滤（user/assistant/system/shell/tool）
   /memory clear        - 清空所有记忆
   /memory dump         - 导出所有记忆
@@ -251,11 +303,12 @@ fn memory_help() -> String {
 {examples}
   /memory recent 20
   /memory search "rust"
+  /memory find docker port type:shell since:7d
   /memory type user
   /memory save my_memory.jsonl
 
 {shortcuts}
-  recent → r, search → s, clear → c, dump → d, type → t
+  recent → r, search → s, find → f, clear → c, dump → d, type → t
 "#,
         title = "记忆管理".bold().cyan(),
         subtitle = "用法:".bold(),
@@ -277,11 +330,19 @@ mod tests {
         Arc::new(RwLock::new(mem))
     }
 
+    fn create_test_long_term() -> Arc<RwLock<LongTermMemory>> {
+        let mut long_term = LongTermMemory::new("test");
+        long_term.add("docker compose up fails on port 5432", EntryType::User).unwrap();
+        long_term.add("docker ps", EntryType::Shell).unwrap();
+        Arc::new(RwLock::new(long_term))
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_memory_status() {
         let memory = create_test_memory();
-        let result = handle_memory_status(memory);
+        let result = handle_memory_status(memory, create_test_long_term());
         assert!(result.contains("当前条目: 3"));
+        assert!(result.contains("长期记忆"));
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -350,17 +411,32 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn test_handle_memory_with_empty_args() {
         let memory = create_test_memory();
-        let result = handle_memory("", memory);
+        let result = handle_memory("", memory, create_test_long_term());
         assert!(result.contains("记忆系统状态"));
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_handle_memory_unknown_subcommand() {
         let memory = create_test_memory();
-        let result = handle_memory("unknown", memory);
+        let result = handle_memory("unknown", memory, create_test_long_term());
         assert!(result.contains("未知子命令"));
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_memory_find() {
+        let long_term = create_test_long_term();
+        let result = handle_memory_find("docker port", Arc::clone(&long_term));
+        assert!(result.contains("找到"));
+        assert!(result.contains("5432"));
+
+        let result = handle_memory_find("docker type:shell", Arc::clone(&long_term));
+        assert!(result.contains("docker ps"));
+        assert!(!result.contains("5432"));
+
+        let result = handle_memory_find("since:soon", long_term);
+        assert!(result.contains("错误"));
+    }
+
     #[test]
     fn test_memory_help() {
         let result = memory_help();
@@ -0,0 +1,190 @@
+//! 命令输出管理命令
+//!
+//! `/out [list|show|grep|save|clear]`：查看以 `$1`、`$2`、`$last` 保存的命令输出
+
+use crate::command::{Command, CommandRegistry};
+use crate::memory::{CapturedOutput, OutputStore};
+use colored::Colorize;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// 列表中命令的最大显示宽度
+const SOURCE_PREVIEW_CHARS: usize = 40;
+
+/// 注册命令输出管理命令
+pub fn register_output_commands(registry: &mut CommandRegistry, store: Arc<RwLock<OutputStore>>) {
+    let out_cmd = Command::from_fn(
+        "out",
+        "命令输出: out [list|show|grep|save|clear] ($1、$last)",
+        move |arg: &str| handle_out(arg, &store),
+    )
+    .with_aliases(vec!["output".to_string()])
+    .with_group("memory");
+    registry.register(out_cmd);
+}
+
+/// 处理 /out 命令
+fn handle_out(arg: &str, store: &RwLock<OutputStore>) -> String {
+    let parts: Vec<&str> = arg.split_whitespace().collect();
+
+    tokio::task::block_in_place(|| {
+        tokio::runtime::Handle::current().block_on(async {
+            let result = match parts.as_slice() {
+                [] | ["list" | "ls"] => Ok(handle_out_list(&*store.read().await)),
+                ["show" | "s", handle] => handle_out_show(&*store.read().await, handle),
+                ["grep" | "g", handle, pattern @ ..] if !pattern.is_empty() => {
+                    handle_out_grep(&*store.read().await, handle, &pattern.join(" "))
+                }
+                ["save", handle, path] => store
+                    .read()
+                    .await
+                    .save(handle, Path::new(path))
+                    .map(|_| format!("{} 已保存 {} 到 {}", "✓".green(), handle, path.cyan())),
+                ["clear" | "c"] => {
+                    store.write().await.clear();
+                    Ok(format!("{} 已清空命令输出", "✓".green()))
+                }
+                ["help" | "h"] => Ok(out_help()),
+                [handle] if handle.starts_with('$') => handle_out_show(&*store.read().await, handle),
+                [sub, ..] if matches!(*sub, "show" | "s" | "grep" | "g" | "save") => {
+                    Err("参数不足\n使用 /out help 查看用法".to_string())
+                }
+                [sub, ..] => Err(format!("未知子命令: {}\n使用 /out help 查看帮助", sub)),
+            };
+            result.unwrap_or_else(|e| format!("{} {}", "错误:".red(), e))
+        })
+    })
+}
+
+/// 列出保存的输出
+fn handle_out_list(store: &OutputStore) -> String {
+    if store.is_empty() {
+        return format!("{}", "暂无保存的命令输出".dimmed());
+    }
+
+    let mut lines = vec![format!("{} ({} 条)", "命令输出".bold().cyan(), store.len())];
+    for output in store.list() {
+        let mut source: String = output.source.chars().take(SOURCE_PREVIEW_CHARS).collect();
+        if source.len() < output.source.len() {
+            source.push_str("...");
+        }
+        let status = if output.success { "✓".green() } else { "✗".red() };
+        lines.push(format!(
+            "  {:<5} {} {:<43} {}",
+            output.handle().cyan(),
+            status,
+            source,
+            format_size(output).dimmed()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// 显示完整输出
+fn handle_out_show(store: &OutputStore, handle: &str) -> Result<String, String> {
+    let output = store
+        .resolve(handle)
+        .ok_or_else(|| format!("输出不存在: {}", handle))?;
+
+    let mut text = format!(
+        "{} {} {}\n{}",
+        output.handle().cyan(),
+        output.source.bold(),
+        format_size(output).dimmed(),
+        output.content
+    );
+    if output.truncated {
+        text.push_str(&format!("\n{}", "...（超出上限，已截断）".dimmed()));
+    }
+    Ok(text)
+}
+
+/// 按正则筛选输出行
+fn handle_out_grep(store: &OutputStore, handle: &str, pattern: &str) -> Result<String, String> {
+    let output = store
+        .resolve(handle)
+        .ok_or_else(|| format!("输出不存在: {}", handle))?;
+    let matches = output.grep(pattern)?;
+
+    if matches.is_empty() {
+        return Ok(format!("{} 中没有匹配 {} 的行", output.handle(), pattern.yellow()));
+    }
+    Ok(matches
+        .into_iter()
+        .map(|(line_no, line)| format!("{:>5}: {}", line_no.to_string().dimmed(), line))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// 输出大小描述（行数 + 字节数）
+fn format_size(output: &CapturedOutput) -> String {
+    let bytes = output.content.len();
+    let size = if bytes >= 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{} B", bytes)
+    };
+    format!("{} 行, {}{}", output.line_count(), size, if output.truncated { ", 已截断" } else { "" })
+}
+
+/// /out 命令帮助
+fn out_help() -> String {
+    format!(
+        r#"{title}
+
+{subtitle}
+  /out                       - 列出保存的命令输出
+  /out <$N>                  - 显示完整输出（同 /out show）
+  /out grep <$N> <正则>      - 筛选输出中匹配的行
+  /out save <$N> <文件>      - 保存输出到文件
+  /out clear                 - 清空保存的输出
+
+{refs}
+  Shell 命令中 $N 展开为保存该输出的文件:   grep error $3
+    （引号内的 $N 与 sh -c、set -- 中的位置参数不展开）
+  自然语言与工具参数中展开为输出内容:       总结 $last 中的错误
+
+{examples}
+  /out $last
+  /out grep $2 ^error"#,
+        title = "命令输出".bold().cyan(),
+        subtitle = "用法:".bold(),
+        refs = "引用 ($1、$2、$last):".bold(),
+        examples = "示例:".bold(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::OutputKind;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_out_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RwLock::new(OutputStore::new().with_spool_dir(dir.path()));
+
+        assert!(handle_out("", &store).contains("暂无"));
+
+        store
+            .write()
+            .await
+            .capture("cat app.log", OutputKind::Shell, "error: disk full\ninfo: ok", false);
+
+        assert!(handle_out("list", &store).contains("cat app.log"));
+        assert!(handle_out("$last", &store).contains("info: ok"));
+        assert!(handle_out("grep $1 ^error", &store).contains("1: error: disk full"));
+        assert!(handle_out("grep $1 warn", &store).contains("没有匹配"));
+        assert!(handle_out("show $9", &store).contains("输出不存在"));
+        assert!(handle_out("grep $1", &store).contains("参数不足"));
+
+        let path = dir.path().join("saved.log");
+        assert!(handle_out(&format!("save $1 {}", path.display()), &store).contains("已保存"));
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with("error: disk full"));
+
+        assert!(handle_out("clear", &store).contains("已清空"));
+        assert!(store.read().await.is_empty());
+        assert!(handle_out("bogus", &store).contains("未知子命令"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Pipeline DSL 命令
+//!
+//! 直接书写管道并执行：
+//!
+//! ```text
+//! /pipe files ./src | where ext == "rs" | sort size desc | take 10
+//! /pipe --dry-run files . | take 3
+//! /pipe --format json files ./src | where ext == "rs"
+//! ```
+
+use crate::command::{Command, CommandRegistry};
+use crate::display::{Display, DisplayMode};
+use crate::dsl::pipeline::{self, ExecutionPlan, NativeExecutor, PipelineBackend, TableFormat};
+use colored::Colorize;
+
+/// 注册 Pipeline DSL 命令
+pub fn register_pipe_commands(registry: &mut CommandRegistry, backend: PipelineBackend, mode: DisplayMode) {
+    registry.register(
+        Command::from_fn("pipe", "执行文本形式的 Pipeline DSL", move |arg: &str| {
+            handle_pipe(arg, backend, mode)
+        })
+        .with_group("pipeline"),
+    );
+}
+
+/// 处理 /pipe 命令
+fn handle_pipe(arg: &str, backend: PipelineBackend, mode: DisplayMode) -> String {
+    let arg = arg.trim();
+    if arg.is_empty() {
+        return pipe_help();
+    }
+
+    let options = match parse_options(arg) {
+        Ok(options) => options,
+        Err(e) => return format!("{} {}", "❌".red(), e),
+    };
+    let (dry_run, format, source) = (options.dry_run, options.format, options.source);
+
+    let plan = match pipeline::parse(source) {
+        Ok(plan) => plan,
+        Err(e) => return format!("{}\n{}", "❌ 语法错误".red(), e.render(source)),
+    };
+
+    let output_type = match plan.type_check() {
+        Ok(ty) => ty,
+        Err(e) => return format!("{} {}", "❌ 类型检查失败:".red(), e),
+    };
+
+    if dry_run {
+        return format_dry_run(&plan, &output_type.to_string());
+    }
+
+    // JSON/CSV 需要结构化结果，始终使用原生后端
+    let backend = if format == TableFormat::Table { backend } else { PipelineBackend::Native };
+
+    match backend {
+        PipelineBackend::Native => {
+            Display::command_execution(mode, &format!("[native] {}", plan.to_dsl()));
+            match NativeExecutor::new().execute(&plan) {
+                Ok(table) => table
+                    .format(format)
+                    .unwrap_or_else(|e| format!("{} {}", "导出失败:".red(), e)),
+                Err(e) => format!("{} {}", "原生执行失败:".red(), e),
+            }
+        }
+        PipelineBackend::Shell => {
+            // 与 LLM 生成的计划一致，交给 /bin/sh 前做安全验证
+            if let Err(e) = plan.validate_safety() {
+                return format!("{} {}", "❌ 安全验证失败:".red(), e);
+            }
+            let command = plan.to_shell_command();
+            Display::command_execution(mode, &command);
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(async { crate::shell_executor::execute_shell(&command).await })
+            })
+            .unwrap_or_else(|e| e.format_user_friendly())
+        }
+    }
+}
+
+/// /pipe 的选项
+struct PipeOptions<'a> {
+    dry_run: bool,
+    format: TableFormat,
+    source: &'a str,
+}
+
+/// 解析开头的选项（必须是完整的单词，`--dry-runX` 不算选项）
+fn parse_options(arg: &str) -> Result<PipeOptions<'_>, String> {
+    let mut options = PipeOptions { dry_run: false, format: TableFormat::Table, source: arg.trim() };
+
+    loop {
+        let (token, rest) = options
+            .source
+            .split_once(char::is_whitespace)
+            .unwrap_or((options.source, ""));
+        match token {
+            "--dry-run" | "-n" => options.dry_run = true,
+            "--format" | "-f" => {
+                let (value, after) = rest.trim_start().split_once(char::is_whitespace).unwrap_or((rest.trim(), ""));
+                options.format = parse_format(value)?;
+                options.source = after.trim();
+                continue;
+            }
+            _ => match token.strip_prefix("--format=") {
+                Some(value) => options.format = parse_format(value)?,
+                None => return Ok(options),
+            },
+        }
+        options.source = rest.trim();
+    }
+}
+
+/// 解析输出格式
+fn parse_format(value: &str) -> Result<TableFormat, String> {
+    TableFormat::parse(value).ok_or_else(|| format!("未知的输出格式: {}（可选 table、json、csv）", value))
+}
+
+/// 只解析不执行：显示规范化的管道、输出类型与等价 Shell 命令
+fn format_dry_run(plan: &ExecutionPlan, output_type: &str) -> String {
+    let mut lines = vec![format!("{}", "Pipeline 计划".cyan().bold())];
+    for (index, op) in plan.operations.iter().enumerate() {
+        lines.push(format!(
+            "  {}. {}  {}",
+            index + 1,
+            op.to_dsl(),
+            op.signature().to_string().dimmed()
+        ));
+    }
+    lines.push(format!("  {}: {}", "输出类型".dimmed(), output_type));
+    lines.push(format!("  {}: {}", "Shell".dimmed(), plan.to_shell_command()));
+    lines.join("\n")
+}
+
+fn pipe_help() -> String {
+    [
+        format!("{}", "Pipeline DSL".cyan().bold()),
+        format!("  {} /pipe [--dry-run] [--format table|json|csv] <管道>", "用法:".dimmed()),
+        "        --format json|csv 导出结构化结果（使用原生后端执行）".to_string(),
+        String::new(),
+        format!("  {}", "操作:".dimmed()),
+        "    files [路径] [模式]        查找文件（别名 find）".to_string(),
+        "    ls [路径] / du [路径]      列出文件 / 磁盘使用".to_string(),
+        "    where ext == \"rs\"          按扩展名过滤".to_string(),
+        "    where path ~ \"正则\"        按路径正则过滤（或 contains \"文本\"）".to_string(),
+        "    sort size|time|name [asc|desc]".to_string(),
+        "    take N                     取前 N 条（别名 limit, head）".to_string(),
+        String::new(),
+        format!("  {} /pipe files ./src | where ext == \"rs\" | sort size desc | take 10", "示例:".dimmed()),
+    ]
+    .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipe_help() {
+        let output = handle_pipe("", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("/pipe"));
+    }
+
+    #[test]
+    fn test_pipe_syntax_error_is_rendered() {
+        let output = handle_pipe("files | tak 3", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("语法错误"));
+        assert!(output.contains("files | tak 3"));
+        assert!(output.contains("`take`"));
+    }
+
+    #[test]
+    fn test_pipe_type_error() {
+        let output = handle_pipe("take 3", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("类型检查失败"));
+    }
+
+    #[test]
+    fn test_pipe_dry_run() {
+        let output = handle_pipe(
+            "--dry-run files . \"*.rs\" | sort size desc | take 2",
+            PipelineBackend::Shell,
+            DisplayMode::Minimal,
+        );
+        assert!(output.contains("sort size desc"));
+        assert!(output.contains("List<FilePath>"));
+        assert!(output.contains("find . -name '*.rs'"));
+    }
+
+    #[test]
+    fn test_pipe_dry_run_flag_is_whole_token() {
+        let output = handle_pipe("--dry-runX files .", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("语法错误"));
+
+        let output = handle_pipe("-n files .", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("Pipeline 计划"));
+    }
+
+    #[test]
+    fn test_pipe_shell_arguments_are_quoted() {
+        let output = handle_pipe(
+            "--dry-run files \"my dir\" \"it's*.rs\"",
+            PipelineBackend::Shell,
+            DisplayMode::Minimal,
+        );
+        assert!(output.contains(r"find 'my dir' -name 'it'\''s*.rs'"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_pipe_shell_rejects_unsafe_plan() {
+        let output = handle_pipe("ls ../..", PipelineBackend::Shell, DisplayMode::Minimal);
+        assert!(output.contains("安全验证失败"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_pipe_format_export() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+        let source = format!("files \"{}\" | where ext == \"rs\"", dir.path().display());
+
+        // 即使配置为 Shell 后端，JSON/CSV 也走原生执行
+        let output = handle_pipe(&format!("--format json {}", source), PipelineBackend::Shell, DisplayMode::Minimal);
+        let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(records[0]["name"], "a.rs");
+
+        let output = handle_pipe(&format!("-f csv {}", source), PipelineBackend::Native, DisplayMode::Minimal);
+        assert!(output.starts_with("path,name,size,modified,is_dir\n"));
+        assert!(output.contains("a.rs"));
+
+        let output = handle_pipe(&format!("--format=xml {}", source), PipelineBackend::Native, DisplayMode::Minimal);
+        assert!(output.contains("未知的输出格式"));
+    }
+
+    #[test]
+    fn test_pipe_native_execution() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "text").unwrap();
+
+        let source = format!("files \"{}\" | where ext == \"rs\"", dir.path().display());
+        let output = handle_pipe(&source, PipelineBackend::Native, DisplayMode::Minimal);
+
+        assert!(output.contains("a.rs"));
+        assert!(!output.contains("b.txt"));
+    }
+}
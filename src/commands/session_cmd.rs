@@ -0,0 +1,214 @@
+//! 会话管理命令
+//!
+//! `/session new|list|switch|resume|save|delete|export`
+
+use crate::command::{Command, CommandRegistry};
+use crate::session::{ExportFormat, SessionManager};
+use colored::Colorize;
+use std::sync::Arc;
+
+/// 注册会话管理命令
+pub fn register_session_commands(registry: &mut CommandRegistry, sessions: Arc<SessionManager>) {
+    let session_cmd = Command::from_fn(
+        "session",
+        "会话管理: session [new|list|switch|resume|save|delete|export]",
+        move |arg: &str| handle_session(arg, &sessions),
+    )
+    .with_aliases(vec!["sess".to_string()])
+    .with_group("memory");
+    registry.register(session_cmd);
+}
+
+/// 处理 /session 命令
+fn handle_session(arg: &str, sessions: &SessionManager) -> String {
+    let parts: Vec<&str> = arg.split_whitespace().collect();
+    let Some(&subcommand) = parts.first() else {
+        return handle_session_list(sessions);
+    };
+    let name = parts.get(1).copied();
+
+    let result = tokio::task::block_in_place(|| {
+        tokio::runtime::Handle::current().block_on(async {
+            match subcommand {
+                "list" | "ls" => Ok(handle_session_list(sessions)),
+                "new" => {
+                    let name = name.ok_or("用法: /session new <名称>")?;
+                    sessions.create(name).await?;
+                    Ok(format!("{} 已新建会话 {}", "✓".green(), name.cyan()))
+                }
+                "switch" | "sw" => {
+                    let name = name.ok_or("用法: /session switch <名称>")?;
+                    let snapshot = sessions.switch(name).await?;
+                    Ok(format_resumed(&snapshot))
+                }
+                "resume" => {
+                    let snapshot = sessions.resume(name).await?;
+                    Ok(format_resumed(&snapshot))
+                }
+                "save" => {
+                    let path = match name {
+                        Some(name) => sessions.save_as(name).await?,
+                        None => sessions
+                            .save()
+                            .await?
+                            .ok_or("当前会话未命名，请使用 /session save <名称>")?,
+                    };
+                    Ok(format!("{} 已保存到 {}", "✓".green(), path.display().to_string().dimmed()))
+                }
+                "delete" | "rm" => {
+                    let name = name.ok_or("用法: /session delete <名称>")?;
+                    sessions.delete(name)?;
+                    Ok(format!("{} 已删除会话 {}", "✓".green(), name))
+                }
+                "export" => handle_session_export(&parts[1..], sessions).await,
+                "help" | "h" => Ok(session_help()),
+                _ => Err(format!("未知子命令: {}\n使用 /session help 查看帮助", subcommand)),
+            }
+        })
+    });
+
+    result.unwrap_or_else(|e: String| format!("{} {}", "错误:".red(), e))
+}
+
+/// 列出会话
+fn handle_session_list(sessions: &SessionManager) -> String {
+    let current = sessions.current();
+    let list = sessions.store().list();
+
+    let mut lines = vec![format!(
+        "{} 当前会话: {}",
+        "会话".bold().cyan(),
+        current.as_deref().unwrap_or("（未命名）").green()
+    )];
+
+    if list.is_empty() {
+        lines.push(format!("{}", "暂无已保存的会话（/session new <名称> 新建）".dimmed()));
+        return lines.join("\n");
+    }
+
+    for summary in list {
+        let marker = if current.as_deref() == Some(summary.name.as_str()) { "*" } else { " " };
+        lines.push(format!(
+            " {} {:<20} {}  {} 条记忆  {}",
+            marker.green(),
+            summary.name,
+            summary.updated_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string().dimmed(),
+            summary.entries,
+            summary.cwd.map(|p| p.display().to_string()).unwrap_or_default().dimmed()
+        ));
+    }
+    lines.join("\n")
+}
+
+/// 导出会话：`export [名称] [md|json] [-o 文件]`
+async fn handle_session_export(args: &[&str], sessions: &SessionManager) -> Result<String, String> {
+    let mut name = None;
+    let mut format = ExportFormat::Markdown;
+    let mut output = None;
+
+    let mut iter = args.iter();
+    while let Some(&arg) = iter.next() {
+        match arg {
+            "-o" | "--output" => output = Some(*iter.next().ok_or("-o 需要文件路径")?),
+            "md" | "markdown" | "json" => format = arg.parse()?,
+            _ if name.is_none() => name = Some(arg),
+            _ => return Err(format!("多余的参数: {}", arg)),
+        }
+    }
+
+    let content = sessions.export(name, format).await?;
+    match output {
+        Some(path) => {
+            std::fs::write(path, &content).map_err(|e| format!("写入 {} 失败: {}", path, e))?;
+            Ok(format!("{} 已导出到 {}", "✓".green(), path.cyan()))
+        }
+        None => Ok(content),
+    }
+}
+
+/// 恢复会话后的提示
+fn format_resumed(snapshot: &crate::session::SessionSnapshot) -> String {
+    let mut lines = vec![format!(
+        "{} 已切换到会话 {}（{} 条记忆，{} 条 LLM 消息）",
+        "✓".green(),
+        snapshot.name.cyan(),
+        snapshot.memory.len(),
+        snapshot.llm_history.len()
+    )];
+    if let Some(cwd) = &snapshot.cwd {
+        lines.push(format!("  工作目录: {}", cwd.display().to_string().dimmed()));
+    }
+    if let Some(task) = &snapshot.working_context.current_task {
+        lines.push(format!("  当前任务: {}", task));
+    }
+    if !snapshot.conversations.is_empty() {
+        lines.push(format!("  未完成的对话: {}", snapshot.conversations.len()));
+    }
+    lines.join("\n")
+}
+
+/// 会话命令帮助
+fn session_help() -> String {
+    format!(
+        r#"{title}
+
+{subtitle}
+  /session                      - 显示当前会话与已保存的会话
+  /session new <名称>           - 保存当前会话并新建空会话
+  /session switch <名称>        - 保存当前会话并切换
+  /session resume [名称]        - 恢复会话（默认最近保存的会话）
+  /session save [名称]          - 保存当前会话（指定名称时另存并切换）
+  /session delete <名称>        - 删除已保存的会话
+  /session export [名称] [md|json] [-o 文件] - 导出会话记录
+
+会话保存记忆、工作上下文、未完成的对话、工作目录与 LLM 消息历史，
+命名会话在每次输入后自动保存。
+
+{examples}
+  /session new incident-42
+  /session export incident-42 md -o incident-42.md"#,
+        title = "会话管理".bold().cyan(),
+        subtitle = "用法:".bold(),
+        examples = "示例:".bold(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::ConversationManager;
+    use crate::llm_manager::LlmManager;
+    use crate::memory::{ContextTracker, EntryType, LongTermMemory, Memory};
+    use crate::session::SessionStore;
+    use tokio::sync::RwLock;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_session_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let long_term = Arc::new(RwLock::new(LongTermMemory::new("startup")));
+        let sessions = SessionManager::new(
+            SessionStore::new(dir.path()),
+            Arc::new(RwLock::new(Memory::new(100))),
+            Arc::clone(&long_term),
+            Arc::new(RwLock::new(ContextTracker::new())),
+            Arc::new(RwLock::new(ConversationManager::new(300))),
+            Arc::new(RwLock::new(LlmManager::new())),
+        );
+
+        assert!(handle_session("", &sessions).contains("未命名"));
+        assert!(handle_session("new", &sessions).contains("用法"));
+        assert!(handle_session("new ops", &sessions).contains("已新建会话"));
+        assert!(handle_session("new ops", &sessions).contains("会话已存在"));
+
+        long_term.write().await.add("uptime", EntryType::Shell).unwrap();
+        let output = dir.path().join("ops.md");
+        let result = handle_session(&format!("export md -o {}", output.display()), &sessions);
+        assert!(result.contains("已导出"));
+        assert!(std::fs::read_to_string(&output).unwrap().contains("uptime"));
+
+        assert!(handle_session("list", &sessions).contains("* "));
+        assert!(handle_session("switch missing", &sessions).contains("会话不存在"));
+        assert!(handle_session("delete ops", &sessions).contains("不能删除当前会话"));
+        assert!(handle_session("bogus", &sessions).contains("未知子命令"));
+    }
+}
@@ -5,8 +5,14 @@
 //! 提供任务分解、规划和执行的命令接口
 
 use crate::command::{Command, CommandRegistry};
+use crate::task::cache::StepCache;
+use crate::task::graph::{self, StatusMap};
+use crate::task::handlers::stdin_input_provider;
+use crate::task::history::DecompositionHistory;
+use crate::task::plan_file;
 use crate::task::{
-    ExecutionContext, ExecutionPlan, ExecutionResult, TaskDecomposer, TaskExecutor, TaskPlanner,
+    ExecutionContext, ExecutionPlan, ExecutionResult, FailurePolicy, InputProvider, PlanCheckpoint,
+    PlanStore, Replanner, TaskDecomposer, TaskExecutor, TaskPlanner, TaskStatus,
 };
 use colored::Colorize;
 use std::sync::Arc;
@@ -24,6 +30,18 @@ pub struct TaskManager {
 
     /// 最近的执行结果
     last_result: Option<ExecutionResult>,
+
+    /// 计划存储（None 表示仅保存在内存中）
+    store: Option<Arc<PlanStore>>,
+
+    /// 工具注册表（FileOperation / Network 任务通过它调用工具）
+    tools: Option<Arc<tokio::sync::RwLock<crate::tool::ToolRegistry>>>,
+
+    /// LLM 管理器（自适应执行时用于重新规划）
+    llm_manager: Option<Arc<tokio::sync::RwLock<crate::llm_manager::LlmManager>>>,
+
+    /// 分解历史（相似目标的成功计划用作分解示例）
+    decomposition_history: Option<Arc<RwLock<DecompositionHistory>>>,
 }
 
 impl TaskManager {
@@ -32,9 +50,64 @@ impl TaskManager {
             current_plan: None,
             history: Vec::new(),
             last_result: None,
+            store: None,
+            tools: None,
+            llm_manager: None,
+            decomposition_history: None,
         }
     }
 
+    /// 设置计划存储（计划与任务结果会被持久化）
+    pub fn with_store(mut self, store: Arc<PlanStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// 获取计划存储
+    pub fn store(&self) -> Option<&Arc<PlanStore>> {
+        self.store.as_ref()
+    }
+
+    /// 设置工具注册表
+    pub fn with_tool_registry(
+        mut self,
+        tools: Arc<tokio::sync::RwLock<crate::tool::ToolRegistry>>,
+    ) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// 获取工具注册表
+    pub fn tool_registry(&self) -> Option<&Arc<tokio::sync::RwLock<crate::tool::ToolRegistry>>> {
+        self.tools.as_ref()
+    }
+
+    /// 设置 LLM 管理器
+    pub fn with_llm_manager(
+        mut self,
+        llm_manager: Arc<tokio::sync::RwLock<crate::llm_manager::LlmManager>>,
+    ) -> Self {
+        self.llm_manager = Some(llm_manager);
+        self
+    }
+
+    /// 设置分解历史
+    pub fn with_decomposition_history(mut self, history: Arc<RwLock<DecompositionHistory>>) -> Self {
+        self.decomposition_history = Some(history);
+        self
+    }
+
+    /// 获取分解历史
+    pub fn decomposition_history(&self) -> Option<&Arc<RwLock<DecompositionHistory>>> {
+        self.decomposition_history.as_ref()
+    }
+
+    /// 获取可用的 LLM 客户端（主 LLM 优先）
+    pub async fn llm(&self) -> Option<Arc<dyn crate::llm::LlmClient>> {
+        let mgr = self.llm_manager.as_ref()?.read().await;
+        mgr.primary().or(mgr.fallback()).cloned()
+    }
+
     /// 保存计划
     pub fn save_plan(&mut self, plan: ExecutionPlan) {
         // 如果有当前计划，移到历史
@@ -75,9 +148,18 @@ pub fn register_task_commands(
     registry: &mut CommandRegistry,
     llm_manager: Arc<tokio::sync::RwLock<crate::llm_manager::LlmManager>>,
     shell_executor: Arc<crate::shell_executor::ShellExecutorWithFixer>,
+    tool_registry: Arc<tokio::sync::RwLock<crate::tool::ToolRegistry>>,
 ) {
-    // 创建共享的任务管理器
-    let task_manager = Arc::new(RwLock::new(TaskManager::new()));
+    // 创建共享的任务管理器（计划持久化到 ~/.realconsole/plans）
+    let store = Arc::new(PlanStore::new(PlanStore::default_dir()));
+    let history = DecompositionHistory::load(DecompositionHistory::default_path());
+    let task_manager = Arc::new(RwLock::new(
+        TaskManager::new()
+            .with_store(store)
+            .with_decomposition_history(Arc::new(RwLock::new(history)))
+            .with_tool_registry(tool_registry)
+            .with_llm_manager(Arc::clone(&llm_manager)),
+    ));
 
     // /plan 命令 - 分解和规划任务
     {
@@ -86,15 +168,29 @@ pub fn register_task_commands(
 
         registry.register(Command::from_fn(
             "plan",
-            "分解和规划任务",
+            "分解和规划任务（--file <文件> 加载计划文件，--save <文件> 保存当前计划，--similar [目标] 查看相似目标的历史计划）",
             move |goal: &str| {
                 if goal.trim().is_empty() {
                     return format!(
-                        "{}\n使用方式: /plan <目标描述>",
+                        "{}\n使用方式: /plan <目标描述> | --file <文件> | --save <文件> | --similar [目标]",
                         "❌ 请提供任务目标".red()
                     );
                 }
 
+                // 计划文件不需要 LLM
+                if let Some((flag, value)) = split_plan_flag(goal) {
+                    let manager = Arc::clone(&manager);
+                    return tokio::task::block_in_place(|| {
+                        tokio::runtime::Handle::current().block_on(async {
+                            match flag {
+                                "--file" => load_plan_file_command(&manager, value).await,
+                                "--similar" => similar_plans_command(&manager, value).await,
+                                _ => save_plan_file_command(&manager, value).await,
+                            }
+                        })
+                    });
+                }
+
                 let llm_manager = Arc::clone(&llm_manager);
                 let manager = Arc::clone(&manager);
                 let goal = goal.to_string();
@@ -116,14 +212,14 @@ pub fn register_task_commands(
 
         registry.register(Command::from_fn(
             "execute",
-            "执行任务计划",
-            move |_arg: &str| {
+            "执行任务计划（--fail-fast 首个失败即停止，--adaptive 失败后由 LLM 修订计划，--resume [计划ID] 从中断处继续，--force 忽略步骤缓存）",
+            move |arg: &str| {
                 let shell_executor = Arc::clone(&shell_executor);
                 let manager = Arc::clone(&manager);
 
                 tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async {
-                        execute_tasks_command(&shell_executor, &manager).await
+                        execute_tasks_command(&shell_executor, &manager, arg).await
                     })
                 })
             },
@@ -136,13 +232,17 @@ pub fn register_task_commands(
 
         registry.register(Command::from_fn(
             "tasks",
-            "查看当前任务计划",
-            move |_arg: &str| {
+            "查看当前任务计划（--graph 依赖图，--dot/--mermaid [文件] 导出）",
+            move |arg: &str| {
                 let manager = Arc::clone(&manager);
 
                 tokio::task::block_in_place(|| {
                     tokio::runtime::Handle::current().block_on(async {
-                        view_tasks_command(&manager).await
+                        if arg.trim().is_empty() {
+                            view_tasks_command(&manager).await
+                        } else {
+                            view_graph_command(&manager, arg).await
+                        }
                     })
                 })
             },
@@ -175,8 +275,6 @@ async fn execute_plan_command(
     manager: &Arc<RwLock<TaskManager>>,
     goal: &str,
 ) -> String {
-    let mut output = String::new();
-
     // 1. 获取执行上下文
     let context = ExecutionContext::current();
 
@@ -191,8 +289,11 @@ async fn execute_plan_command(
         }
     };
 
-    // 3. 分解任务
-    let decomposer = TaskDecomposer::new(llm);
+    // 3. 分解任务（参考相似目标过去成功的计划）
+    let mut decomposer = TaskDecomposer::new(llm);
+    if let Some(history) = manager.read().await.decomposition_history() {
+        decomposer = decomposer.with_history(Arc::clone(history));
+    }
     let subtasks = match decomposer.decompose(goal, &context).await {
         Ok(tasks) => tasks,
         Err(e) => {
@@ -209,11 +310,118 @@ async fn execute_plan_command(
         }
     };
 
+    present_plan(manager, &planner, plan).await
+}
+
+/// 拆分 /plan 的选项：第一个词是 `--file`、`--similar` 或 `--save` 时返回（选项, 其余参数）
+///
+/// 按整词匹配，`--filesystem cleanup` 之类的目标不会被当作选项。
+fn split_plan_flag(arg: &str) -> Option<(&str, &str)> {
+    let arg = arg.trim();
+    let (flag, value) = arg.split_once(char::is_whitespace).unwrap_or((arg, ""));
+    matches!(flag, "--file" | "--similar" | "--save").then(|| (flag, value.trim()))
+}
+
+/// 从 YAML 计划文件加载计划（/plan --file <路径>）
+async fn load_plan_file_command(manager: &Arc<RwLock<TaskManager>>, path: &str) -> String {
+    if path.is_empty() {
+        return format!("{}\n使用方式: /plan --file <计划文件.yaml>", "❌ 请提供计划文件路径".red());
+    }
+
+    let planner = TaskPlanner::new();
+    match plan_file::load(std::path::Path::new(path), &planner) {
+        Ok(plan) => present_plan(manager, &planner, plan).await,
+        Err(e) => format!("{} {}", "❌ 计划文件无效:".red(), e),
+    }
+}
+
+/// 查看相似目标的历史计划（/plan --similar [目标]，默认使用当前计划的目标）
+async fn similar_plans_command(manager: &Arc<RwLock<TaskManager>>, query: &str) -> String {
+    let mgr = manager.read().await;
+    let goal = match (query.is_empty(), mgr.get_current_plan()) {
+        (false, _) => query.to_string(),
+        (true, Some(plan)) => plan.goal.clone(),
+        (true, None) => {
+            return format!("{}\n使用方式: /plan --similar <目标>", "❌ 请提供目标".red());
+        }
+    };
+    let Some(history) = mgr.decomposition_history() else {
+        return format!("{} 未启用分解历史", "ℹ".dimmed());
+    };
+
+    let history = history.read().await;
+    let similar = history.similar(&goal, 5);
+    if similar.is_empty() {
+        return format!("{} 没有与 \"{}\" 相似的历史计划", "ℹ".dimmed(), goal);
+    }
+
+    let mut output = format!("\n{} {}\n", "相似目标的历史计划:".bold(), goal.dimmed());
+    for (record, similarity) in similar {
+        let outcome = match &record.outcome {
+            Some(o) if o.success => format!("✓ 成功 · {}秒", o.total_time).green(),
+            Some(o) => format!("✗ 失败 {}/{}", o.failed, record.subtasks.len()).red(),
+            None => "· 未执行".dimmed(),
+        };
+        output.push_str(&format!(
+            "\n{} {} {} {}\n",
+            format!("{:.0}%", similarity * 100.0).cyan(),
+            record.goal.bold(),
+            outcome,
+            record
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format("%m-%d %H:%M")
+                .to_string()
+                .dimmed()
+        ));
+        for task in &record.subtasks {
+            output.push_str(&format!(
+                "  {} {} {}\n",
+                "·".dimmed(),
+                task.name,
+                format!("$ {}", task.command).dimmed()
+            ));
+        }
+    }
+
+    output
+}
+
+/// 将当前计划保存为 YAML 计划文件（/plan --save <路径>）
+async fn save_plan_file_command(manager: &Arc<RwLock<TaskManager>>, path: &str) -> String {
+    if path.is_empty() {
+        return format!("{}\n使用方式: /plan --save <计划文件.yaml>", "❌ 请提供保存路径".red());
+    }
+
+    let mgr = manager.read().await;
+    let Some(plan) = mgr.get_current_plan() else {
+        return format!("❌ 无当前计划\n{}", "提示: /plan <目标>".dimmed());
+    };
+
+    match plan_file::save(plan, std::path::Path::new(path)) {
+        Ok(()) => format!(
+            "{} 计划已保存到 {}\n{}",
+            "✓".green(),
+            path,
+            format!("使用 {} 重新加载", format!("/plan --file {}", path).cyan()).dimmed()
+        ),
+        Err(e) => format!("{} 保存失败: {}", "❌".red(), e),
+    }
+}
+
+/// 显示并保存新计划
+async fn present_plan(
+    manager: &Arc<RwLock<TaskManager>>,
+    planner: &TaskPlanner,
+    plan: ExecutionPlan,
+) -> String {
+    let mut output = String::new();
+
     // 5. 分析计划
     let analysis = planner.analyze_plan(&plan);
 
     // 6. 紧凑的输出格式
-    output.push_str(&format!("\n{}\n", goal.bold()));
+    output.push_str(&format!("\n{}\n", plan.goal.bold()));
 
     // 摘要行（单行显示核心信息）
     let summary = format!(
@@ -235,6 +443,15 @@ async fn execute_plan_command(
         output.push_str(&format!("{}\n", summary.dimmed()));
     }
 
+    if analysis.critical_path.len() > 1 {
+        output.push_str(&format!(
+            "{} {} {}\n",
+            "▸ 关键路径".dimmed(),
+            analysis.critical_path.join(" → ").yellow(),
+            format!("· {}秒", analysis.critical_path_time).dimmed()
+        ));
+    }
+
     // 7. 树状结构显示任务
     for (idx, stage) in plan.stages.iter().enumerate() {
         let is_last_stage = idx == plan.stages.len() - 1;
@@ -268,51 +485,176 @@ async fn execute_plan_command(
         }
     }
 
-    // 8. 保存计划
+    // 8. 保存计划（同时持久化，以便退出后仍可恢复）
     {
         let mut mgr = manager.write().await;
+        if let Some(store) = mgr.store() {
+            if let Err(e) = store.save(&PlanCheckpoint::new(plan.clone())) {
+                output.push_str(&format!("{}\n", format!("⚠ 计划未能保存到磁盘: {}", e).yellow()));
+            }
+        }
         mgr.save_plan(plan);
     }
 
-    output.push_str(&format!("\n{}\n", format!("使用 {} 执行", "/execute".cyan()).dimmed()));
+    output.push_str(&format!(
+        "\n{}\n",
+        format!("使用 {} 执行 · {} 保存为计划文件", "/execute".cyan(), "/plan --save <文件>".cyan()).dimmed()
+    ));
 
     output
 }
 
 /// 执行 /execute 命令
+///
+/// - `/execute` 执行当前计划
+/// - `/execute --resume [计划ID]` 从已保存的检查点继续，跳过已成功的任务
+///   （省略 ID 时选择最近一个未完成的计划）
+/// - `--fail-fast` 任一任务失败后不再启动新任务（默认只阻塞失败任务的下游）
+/// - `--adaptive` 任务失败后由 LLM 修订剩余计划，批准后继续执行
+/// - `--graph` 执行中实时显示按状态着色的依赖图
 async fn execute_tasks_command(
     shell_executor: &Arc<crate::shell_executor::ShellExecutorWithFixer>,
     manager: &Arc<RwLock<TaskManager>>,
+    arg: &str,
 ) -> String {
-    // 1. 获取当前计划
-    let plan = {
-        let mgr = manager.read().await;
-        match mgr.get_current_plan() {
-            Some(p) => p.clone(),
-            None => {
-                return format!("❌ 无待执行计划\n{}", "提示: /plan <目标>".dimmed());
+    let mut output = String::new();
+
+    let mut fail_fast = false;
+    let mut adaptive = false;
+    let mut show_graph = false;
+    let mut force = false;
+    let mut resume = false;
+    let mut rest = Vec::new();
+    for token in arg.split_whitespace() {
+        match token {
+            "--fail-fast" => fail_fast = true,
+            "--adaptive" => adaptive = true,
+            "--graph" => show_graph = true,
+            "--force" => force = true,
+            "--resume" => resume = true,
+            other => rest.push(other),
+        }
+    }
+    let id = rest.join(" ");
+
+    // 1. 获取要执行的计划（以及恢复时已成功的任务结果）
+    let (plan, previous) = match resume.then_some(id.as_str()) {
+        Some(id) => {
+            let checkpoint = {
+                let mgr = manager.read().await;
+                match find_checkpoint(&mgr, id) {
+                    Ok(c) => c,
+                    Err(e) => return e,
+                }
+            };
+
+            let previous = checkpoint.succeeded();
+            match checkpoint.next_task() {
+                Some(task) => output.push_str(&format!(
+                    "\n{} {} {}\n",
+                    "↻".cyan(),
+                    checkpoint.plan.goal.bold(),
+                    format!(
+                        "· 跳过 {} 个已成功任务，从 {} 继续",
+                        previous.len(),
+                        task.name
+                    )
+                    .dimmed()
+                )),
+                None => {
+                    return format!(
+                        "{} 计划 {} 已全部完成",
+                        "✓".green(),
+                        short_id(&checkpoint.plan.id)
+                    );
+                }
+            }
+
+            manager.write().await.save_plan(checkpoint.plan.clone());
+            (checkpoint.plan, previous)
+        }
+        None => {
+            let mgr = manager.read().await;
+            match mgr.get_current_plan() {
+                Some(p) => (p.clone(), Vec::new()),
+                None => {
+                    return format!("❌ 无待执行计划\n{}", "提示: /plan <目标>".dimmed());
+                }
             }
         }
     };
 
-    let mut output = String::new();
+    let plan = if fail_fast {
+        plan.with_failure_policy(FailurePolicy::FailFast)
+    } else {
+        plan
+    };
 
-    // 2. 创建执行器
-    let executor = TaskExecutor::new(Arc::clone(shell_executor))
-        .with_timeout(300);
+    // 2. 创建执行器（配置了存储时每个任务完成后写入检查点，并启用步骤缓存）
+    let mut executor = TaskExecutor::new(Arc::clone(shell_executor))
+        .with_timeout(300)
+        .with_force(force);
+    if let Some(store) = manager.read().await.store() {
+        executor = executor.with_store(Arc::clone(store));
+        if let Ok(path) = store.cache_path(&plan.id) {
+            executor = executor.with_step_cache(Arc::new(StepCache::load(path)));
+        }
+    }
+    if let Some(tools) = manager.read().await.tool_registry() {
+        executor = executor.with_tool_registry(Arc::clone(tools));
+    }
+    let live_graph = show_graph.then(|| LiveGraph::new(&plan, &previous));
+    if let Some(live) = &live_graph {
+        executor = executor
+            .with_status_callback(live.status_callback())
+            .with_input_provider(live.pause_during(stdin_input_provider()));
+    }
 
-    // 3. 执行计划
-    let result = match executor.execute(plan.clone()).await {
+    // 3. 执行计划（自适应模式下失败后请求修订）
+    let revisions_before = plan.revisions.len();
+    let run = if adaptive {
+        let Some(llm) = manager.read().await.llm().await else {
+            return format!("❌ 未配置 LLM 客户端\n{}", "提示: --adaptive 需要 LLM 来修订计划".dimmed());
+        };
+        let mut replanner = Replanner::new(TaskDecomposer::new(llm));
+        if let Some(live) = &live_graph {
+            replanner = replanner.with_approval(live.pause_during(stdin_input_provider()));
+        }
+        replanner
+            .run(&executor, plan, previous)
+            .await
+    } else {
+        executor
+            .resume(plan.clone(), previous)
+            .await
+            .map(|result| (plan, result))
+    };
+    let (plan, result) = match run {
         Ok(r) => r,
         Err(e) => {
             return format!("❌ 执行失败: {}", e);
         }
     };
 
-    // 4. 保存结果
+    let revised = plan.revisions.len() - revisions_before;
+    if revised > 0 {
+        output.push_str(&format!(
+            "{} {}\n",
+            "↻".cyan(),
+            format!("计划已修订 {} 次", revised).dimmed()
+        ));
+        manager.write().await.save_plan(plan.clone());
+    }
+
+    // 4. 保存结果，并把执行结果记入分解历史
     {
         let mut mgr = manager.write().await;
         mgr.save_result(result.clone());
+        if let Some(history) = mgr.decomposition_history() {
+            if let Err(e) = history.write().await.record_outcome(&plan, &result) {
+                output.push_str(&format!("{} {}\n", "⚠".yellow(), e));
+            }
+        }
     }
 
     // 5. 紧凑的结果显示
@@ -330,10 +672,23 @@ async fn execute_tasks_command(
         result.total_time
     ));
 
+    let cached = result.task_results.iter().filter(|r| r.cached).count();
+    if cached > 0 {
+        output.push_str(&format!(
+            "{} {}\n",
+            "≡".cyan(),
+            format!("{} 个任务输入未变，复用缓存结果（--force 强制执行）", cached).dimmed()
+        ));
+    }
+
+    if show_graph {
+        output.push_str(&graph::render_ascii(&plan, &graph::statuses(&result)));
+    }
+
     // 6. 仅在有失败时显示详情
     if result.failed_tasks > 0 {
         for task_result in &result.task_results {
-            if matches!(task_result.status, crate::task::TaskStatus::Failed) {
+            if matches!(task_result.status, TaskStatus::Failed) {
                 output.push_str(&format!(
                     "  {} {} {}\n",
                     "✗".red(),
@@ -345,16 +700,227 @@ async fn execute_tasks_command(
                 }
             }
         }
+
+        // 因依赖失败被阻塞或被 fail-fast 取消的任务
+        for task_result in &result.task_results {
+            if matches!(task_result.status, TaskStatus::Blocked | TaskStatus::Cancelled) {
+                output.push_str(&format!(
+                    "  {} {} {}\n",
+                    "⊗".yellow(),
+                    task_result.task.name,
+                    task_result.error.as_deref().unwrap_or_default().dimmed()
+                ));
+            }
+        }
+
+        if manager.read().await.store().is_some() {
+            output.push_str(&format!(
+                "{}\n",
+                format!(
+                    "修复后使用 {} 继续",
+                    format!("/execute --resume {}", short_id(&plan.id)).cyan()
+                )
+                .dimmed()
+            ));
+        }
+    }
+
+    output
+}
+
+/// 执行中实时重绘的依赖图（输出到 stderr，非终端时不绘制）
+///
+/// 等待用户输入期间暂停重绘，避免覆盖提示；输入结束后在提示下方重新绘制。
+struct LiveGraph {
+    term: console::Term,
+    plan: ExecutionPlan,
+    state: std::sync::Mutex<LiveGraphState>,
+}
+
+struct LiveGraphState {
+    /// 当前状态
+    statuses: StatusMap,
+    /// 上次绘制的行数（0 表示不清除之前的输出）
+    drawn_lines: usize,
+    /// 是否正在等待用户输入
+    paused: bool,
+}
+
+impl LiveGraph {
+    fn new(plan: &ExecutionPlan, previous: &[crate::task::TaskExecutionResult]) -> Arc<Self> {
+        let statuses = previous
+            .iter()
+            .map(|r| (r.task.id.clone(), r.status.clone()))
+            .collect();
+        Arc::new(Self {
+            term: console::Term::stderr(),
+            plan: plan.clone(),
+            state: std::sync::Mutex::new(LiveGraphState {
+                statuses,
+                drawn_lines: 0,
+                paused: false,
+            }),
+        })
+    }
+
+    /// 状态回调：记录状态并重绘（暂停期间只记录）
+    fn status_callback(self: &Arc<Self>) -> crate::task::StatusCallback {
+        let graph = Arc::clone(self);
+        Arc::new(move |task_id: &str, status: &TaskStatus| {
+            let Ok(mut state) = graph.state.lock() else {
+                return;
+            };
+            state.statuses.insert(task_id.to_string(), status.clone());
+            if !state.paused {
+                graph.redraw(&mut state);
+            }
+        })
+    }
+
+    /// 包装输入来源：提示期间暂停重绘
+    fn pause_during(self: &Arc<Self>, provider: InputProvider) -> InputProvider {
+        let graph = Arc::clone(self);
+        Arc::new(move |prompt: &str| {
+            if let Ok(mut state) = graph.state.lock() {
+                state.paused = true;
+                // 保留已绘制的图，恢复后从提示下方重新绘制
+                state.drawn_lines = 0;
+            }
+            let answer = provider(prompt);
+            if let Ok(mut state) = graph.state.lock() {
+                state.paused = false;
+                graph.redraw(&mut state);
+            }
+            answer
+        })
+    }
+
+    fn redraw(&self, state: &mut LiveGraphState) {
+        if !self.term.is_term() {
+            return;
+        }
+        let drawing = graph::render_ascii(&self.plan, &state.statuses);
+        if state.drawn_lines > 0 {
+            let _ = self.term.clear_last_lines(state.drawn_lines);
+        }
+        let _ = self.term.write_str(&drawing);
+        state.drawn_lines = drawing.lines().count();
+    }
+}
+
+/// 执行 /tasks --graph / --dot / --mermaid
+///
+/// 有当前计划的执行结果时按状态着色；导出格式可附带文件路径直接写入文件。
+async fn view_graph_command(manager: &Arc<RwLock<TaskManager>>, arg: &str) -> String {
+    let mgr = manager.read().await;
+    let Some(plan) = mgr.get_current_plan() else {
+        return format!("无当前计划\n{}", "提示: /plan <目标>".dimmed());
+    };
+    let statuses = mgr
+        .get_last_result()
+        .filter(|r| r.plan_id == plan.id)
+        .map(graph::statuses)
+        .unwrap_or_default();
+
+    let mut parts = arg.split_whitespace();
+    let format = parts.next().unwrap_or_default();
+    let path = parts.next();
+
+    let content = match format {
+        "--graph" => return graph::render_ascii(plan, &statuses),
+        "--dot" => graph::to_dot(plan, &statuses),
+        "--mermaid" => graph::to_mermaid(plan, &statuses),
+        other => {
+            return format!(
+                "{} 未知选项: {}\n{}",
+                "❌".red(),
+                other,
+                "用法: /tasks [--graph | --dot [文件] | --mermaid [文件]]".dimmed()
+            );
+        }
+    };
+
+    match path {
+        Some(path) => match std::fs::write(path, &content) {
+            Ok(()) => format!("{} 已导出到 {}", "✓".green(), path),
+            Err(e) => format!("{} 导出失败: {}", "❌".red(), e),
+        },
+        None => content,
+    }
+}
+
+/// 查找要恢复的检查点：指定 ID（或前缀）时精确查找，否则取最近一个未完成的计划
+fn find_checkpoint(manager: &TaskManager, id: &str) -> Result<PlanCheckpoint, String> {
+    let store = manager
+        .store()
+        .ok_or_else(|| format!("{}", "❌ 未启用计划持久化".red()))?;
+
+    if !id.is_empty() {
+        return store
+            .load(id)
+            .map_err(|e| format!("{} {}\n{}", "❌".red(), e, "提示: /tasks 查看已保存的计划".dimmed()));
     }
 
+    store
+        .list()
+        .map_err(|e| format!("{} {}", "❌".red(), e))?
+        .into_iter()
+        .find(|c| !c.is_finished())
+        .ok_or_else(|| format!("无未完成的计划\n{}", "提示: /plan <目标>".dimmed()))
+}
+
+/// 计划 ID 的简短形式（前 8 位，可用于 --resume）
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
+/// 渲染已保存的计划列表（最多 10 个）
+fn format_saved_plans(store: &PlanStore) -> String {
+    let checkpoints = match store.list() {
+        Ok(list) if !list.is_empty() => list,
+        _ => return String::new(),
+    };
+
+    let mut output = format!("\n{}\n", "已保存的计划".dimmed());
+    for checkpoint in checkpoints.iter().take(10) {
+        let icon = if checkpoint.is_finished() {
+            "✓".green()
+        } else if checkpoint.failed_count() > 0 {
+            "✗".red()
+        } else {
+            "•".dimmed()
+        };
+
+        output.push_str(&format!(
+            "{} {} {} {} {}\n",
+            icon,
+            short_id(&checkpoint.plan.id).cyan(),
+            checkpoint.plan.goal,
+            format!("{}/{}", checkpoint.succeeded_count(), checkpoint.plan.total_tasks()).dimmed(),
+            checkpoint
+                .updated_at
+                .with_timezone(&chrono::Local)
+                .format("%m-%d %H:%M")
+                .to_string()
+                .dimmed()
+        ));
+    }
+
+    if checkpoints.iter().any(|c| !c.is_finished()) {
+        output.push_str(&format!(
+            "{}\n",
+            format!("使用 {} 继续未完成的计划", "/execute --resume <ID>".cyan()).dimmed()
+        ));
+    }
     output
 }
 
 /// 执行 /tasks 命令
 async fn view_tasks_command(manager: &Arc<RwLock<TaskManager>>) -> String {
     let mgr = manager.read().await;
+    let saved = mgr.store().map(|store| format_saved_plans(store)).unwrap_or_default();
 
-    match mgr.get_current_plan() {
+    let current = match mgr.get_current_plan() {
         Some(plan) => {
             let mut output = String::new();
 
@@ -367,6 +933,22 @@ async fn view_tasks_command(manager: &Arc<RwLock<TaskManager>>) -> String {
                 plan.total_estimated_time
             ));
 
+            // 重新规划历史
+            for revision in &plan.revisions {
+                output.push_str(&format!(
+                    "{} {}\n",
+                    "↻".cyan(),
+                    format!(
+                        "{} 任务 {} 失败后修订: {} 个任务替换为 {} 个",
+                        revision.revised_at.with_timezone(&chrono::Local).format("%m-%d %H:%M"),
+                        revision.failed_task,
+                        revision.replaced.len(),
+                        revision.tasks.len()
+                    )
+                    .dimmed()
+                ));
+            }
+
             // 树状任务列表
             for (idx, stage) in plan.stages.iter().enumerate() {
                 let is_last_stage = idx == plan.stages.len() - 1;
@@ -402,10 +984,13 @@ async fn view_tasks_command(manager: &Arc<RwLock<TaskManager>>) -> String {
 
             output
         }
+        None if !saved.is_empty() => String::new(),
         None => {
             format!("无当前计划\n{}", "提示: /plan <目标>".dimmed())
         }
-    }
+    };
+
+    format!("{}{}", current, saved)
 }
 
 /// 执行 /task_status 命令
@@ -429,9 +1014,10 @@ async fn view_task_status_command(manager: &Arc<RwLock<TaskManager>>) -> String
             // 紧凑的任务列表
             for task_result in &result.task_results {
                 let icon = match task_result.status {
-                    crate::task::TaskStatus::Success => "✓".green(),
-                    crate::task::TaskStatus::Failed => "✗".red(),
-                    crate::task::TaskStatus::Skipped => "⊘".yellow(),
+                    TaskStatus::Success => "✓".green(),
+                    TaskStatus::Failed => "✗".red(),
+                    TaskStatus::Skipped => "⊘".yellow(),
+                    TaskStatus::Blocked | TaskStatus::Cancelled => "⊗".yellow(),
                     _ => "•".dimmed(),
                 };
 
@@ -443,7 +1029,7 @@ async fn view_task_status_command(manager: &Arc<RwLock<TaskManager>>) -> String
                 ));
 
                 // 仅显示失败任务的错误信息
-                if matches!(task_result.status, crate::task::TaskStatus::Failed) {
+                if matches!(task_result.status, TaskStatus::Failed) {
                     if let Some(error) = &task_result.error {
                         output.push_str(&format!("  {}\n", error.red()));
                     }
@@ -462,6 +1048,16 @@ async fn view_task_status_command(manager: &Arc<RwLock<TaskManager>>) -> String
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_plan_flag() {
+        assert_eq!(split_plan_flag(" --file plans/deploy.yaml "), Some(("--file", "plans/deploy.yaml")));
+        assert_eq!(split_plan_flag("--similar"), Some(("--similar", "")));
+        assert_eq!(split_plan_flag("--save my plan.yaml"), Some(("--save", "my plan.yaml")));
+        assert_eq!(split_plan_flag("--filesystem cleanup"), None);
+        assert_eq!(split_plan_flag("--saved-searches audit"), None);
+        assert_eq!(split_plan_flag("部署 --file x"), None);
+    }
+
     #[test]
     fn test_task_manager_new() {
         let manager = TaskManager::new();
@@ -482,4 +1078,244 @@ mod tests {
         manager.save_plan(plan);
         assert!(manager.current_plan.is_some());
     }
+
+    fn manager_with_store(dir: &std::path::Path) -> Arc<RwLock<TaskManager>> {
+        let store = Arc::new(PlanStore::new(dir));
+        Arc::new(RwLock::new(TaskManager::new().with_store(store)))
+    }
+
+    #[tokio::test]
+    async fn test_tasks_lists_saved_plans() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = manager_with_store(dir.path());
+
+        let output = view_tasks_command(&manager).await;
+        assert!(output.contains("无当前计划"));
+
+        let plan = ExecutionPlan::new("saved goal", vec![]);
+        let id = plan.id.clone();
+        PlanStore::new(dir.path()).save(&PlanCheckpoint::new(plan)).unwrap();
+
+        let output = view_tasks_command(&manager).await;
+        assert!(output.contains("已保存的计划"));
+        assert!(output.contains("saved goal"));
+        assert!(output.contains(short_id(&id)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_resume_skips_succeeded_tasks() {
+        use crate::task::{ExecutionMode, ExecutionStage, RetryPolicy, SubTask};
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = manager_with_store(&dir.path().join("plans"));
+        let shell = Arc::new(crate::shell_executor::ShellExecutorWithFixer::new());
+        let marker = dir.path().join("ran");
+        let gate = dir.path().join("gate");
+
+        let tasks = vec![
+            SubTask::new("t1", "Mark", format!("echo x >> {}", marker.display())),
+            SubTask::new("t2", "Gate", format!("test -f {}", gate.display()))
+                .with_retry_policy(RetryPolicy::simple(0)),
+        ];
+        let plan = ExecutionPlan::new(
+            "resume goal",
+            vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)],
+        );
+        let id = plan.id.clone();
+        manager.write().await.save_plan(plan);
+
+        let output = execute_tasks_command(&shell, &manager, "").await;
+        assert!(output.contains("--resume"));
+
+        // 模拟重启：新的管理器只能从磁盘恢复
+        let manager = manager_with_store(&dir.path().join("plans"));
+        std::fs::write(&gate, "").unwrap();
+
+        let output = execute_tasks_command(&shell, &manager, &format!("--resume {}", short_id(&id))).await;
+        assert!(output.contains("跳过 1 个已成功任务"));
+        assert!(output.contains("2/2"));
+        assert_eq!(std::fs::read_to_string(&marker).unwrap().lines().count(), 1);
+
+        // 已完成的计划无需再恢复
+        let output = execute_tasks_command(&shell, &manager, "--resume").await;
+        assert!(output.contains("无未完成的计划"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_resume_unknown_plan() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = manager_with_store(dir.path());
+        let shell = Arc::new(crate::shell_executor::ShellExecutorWithFixer::new());
+
+        let output = execute_tasks_command(&shell, &manager, "--resume deadbeef").await;
+        assert!(output.contains("执行计划不存在"));
+
+        // --resume 必须是完整的参数
+        let output = execute_tasks_command(&shell, &manager, "--resumedeadbeef").await;
+        assert!(output.contains("无待执行计划"));
+        let output = execute_tasks_command(&shell, &manager, "--resume ../plans").await;
+        assert!(output.contains("无效的计划 ID"));
+
+        let manager = Arc::new(RwLock::new(TaskManager::new()));
+        let output = execute_tasks_command(&shell, &manager, "--resume").await;
+        assert!(output.contains("未启用计划持久化"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_blocked_tasks() {
+        use crate::task::{ExecutionMode, ExecutionStage, RetryPolicy, SubTask};
+
+        let manager = Arc::new(RwLock::new(TaskManager::new()));
+        let shell = Arc::new(crate::shell_executor::ShellExecutorWithFixer::new());
+
+        let tasks = vec![
+            SubTask::new("build", "Build", "false").with_retry_policy(RetryPolicy::simple(0)),
+            SubTask::new("deploy", "Deploy", "echo deploy").with_dependency("build"),
+        ];
+        let plan = ExecutionPlan::new(
+            "blocked goal",
+            vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)],
+        );
+        manager.write().await.save_plan(plan);
+
+        let output = execute_tasks_command(&shell, &manager, "--fail-fast").await;
+        assert!(output.contains("Deploy"));
+        assert!(output.contains("依赖的任务 build 失败"));
+
+        let status = view_task_status_command(&manager).await;
+        assert!(status.contains("⊗"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_adaptive_requires_llm() {
+        use crate::task::{ExecutionMode, ExecutionStage, SubTask};
+
+        let manager = Arc::new(RwLock::new(TaskManager::new()));
+        let shell = Arc::new(crate::shell_executor::ShellExecutorWithFixer::new());
+        let plan = ExecutionPlan::new(
+            "adaptive goal",
+            vec![ExecutionStage::new(0, vec![SubTask::new("t1", "T1", "true")], ExecutionMode::Sequential)],
+        );
+        manager.write().await.save_plan(plan);
+
+        let output = execute_tasks_command(&shell, &manager, "--adaptive").await;
+        assert!(output.contains("未配置 LLM"));
+    }
+
+    #[test]
+    fn test_live_graph_pauses_during_input() {
+        let plan = TaskPlanner::new()
+            .plan(
+                "graph goal",
+                vec![crate::task::SubTask::new("fetch", "Fetch", "echo fetch")],
+            )
+            .unwrap();
+        let live = LiveGraph::new(&plan, &[]);
+        let callback = live.status_callback();
+
+        let during = Arc::clone(&live);
+        let inner: InputProvider = Arc::new(move |_: &str| {
+            // 提示期间的状态变化只记录，不重绘
+            callback("fetch", &TaskStatus::Running);
+            assert!(during.state.lock().unwrap().paused);
+            Some("y".to_string())
+        });
+        let provider = live.pause_during(inner);
+
+        assert_eq!(provider("继续?").as_deref(), Some("y"));
+        let state = live.state.lock().unwrap();
+        assert!(!state.paused);
+        assert_eq!(state.statuses.get("fetch"), Some(&TaskStatus::Running));
+    }
+
+    #[tokio::test]
+    async fn test_tasks_graph_exports() {
+        use crate::task::{ExecutionMode, ExecutionStage, SubTask};
+
+        let manager = Arc::new(RwLock::new(TaskManager::new()));
+        assert!(view_graph_command(&manager, "--dot").await.contains("无当前计划"));
+
+        let tasks = vec![
+            SubTask::new("fetch", "Fetch", "true"),
+            SubTask::new("build", "Build", "true").with_dependency("fetch"),
+        ];
+        let plan = ExecutionPlan::new(
+            "graph goal",
+            vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)],
+        );
+        manager.write().await.save_plan(plan);
+
+        assert!(view_graph_command(&manager, "--graph").await.contains("层 2"));
+        assert!(view_graph_command(&manager, "--dot").await.contains("\"fetch\" -> \"build\""));
+        assert!(view_graph_command(&manager, "--mermaid").await.contains("fetch --> build"));
+        assert!(view_graph_command(&manager, "--svg").await.contains("未知选项"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("plan.mmd");
+        let output = view_graph_command(&manager, &format!("--mermaid {}", file.display())).await;
+        assert!(output.contains("已导出"));
+        assert!(std::fs::read_to_string(&file).unwrap().starts_with("flowchart LR"));
+    }
+
+    #[tokio::test]
+    async fn test_plan_file_load_and_save() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manager = manager_with_store(&dir.path().join("plans"));
+
+        let source = dir.path().join("runbook.yaml");
+        std::fs::write(
+            &source,
+            "goal: bootstrap\ntasks:\n  - id: a\n    command: echo a\n  - id: b\n    command: echo b\n    depends_on: [a]\n",
+        )
+        .unwrap();
+
+        let output = load_plan_file_command(&manager, source.to_str().unwrap()).await;
+        assert!(output.contains("bootstrap"));
+        assert_eq!(manager.read().await.get_current_plan().unwrap().total_tasks(), 2);
+
+        let saved = dir.path().join("saved.yaml");
+        let output = save_plan_file_command(&manager, saved.to_str().unwrap()).await;
+        assert!(output.contains("计划已保存"));
+        assert!(std::fs::read_to_string(&saved).unwrap().contains("goal: bootstrap"));
+
+        std::fs::write(&source, "goal: broken\ntasks:\n  - id: a\n    command: x\n    depends_on: [zz]\n").unwrap();
+        let output = load_plan_file_command(&manager, source.to_str().unwrap()).await;
+        assert!(output.contains("runbook.yaml:5:"));
+        assert!(output.contains("zz"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_records_outcome_for_similar_plans() {
+        use crate::task::history::DecompositionRecord;
+        use crate::task::SubTask;
+
+        let history = Arc::new(RwLock::new(DecompositionHistory::new()));
+        let manager = Arc::new(RwLock::new(
+            TaskManager::new().with_decomposition_history(Arc::clone(&history)),
+        ));
+        let shell = Arc::new(crate::shell_executor::ShellExecutorWithFixer::new());
+
+        let output = similar_plans_command(&manager, "count source lines").await;
+        assert!(output.contains("没有与"));
+
+        let tasks = vec![SubTask::new("t1", "Count", "echo 42")];
+        history
+            .write()
+            .await
+            .record(DecompositionRecord::new("count source lines", &ExecutionContext::current(), &tasks))
+            .unwrap();
+        let plan = TaskPlanner::new().plan("count source lines", tasks).unwrap();
+        manager.write().await.save_plan(plan);
+
+        let output = similar_plans_command(&manager, "").await;
+        assert!(output.contains("未执行"));
+
+        execute_tasks_command(&shell, &manager, "").await;
+        assert!(history.read().await.records()[0].succeeded());
+
+        let output = similar_plans_command(&manager, "count the source lines").await;
+        assert!(output.contains("count source lines"));
+        assert!(output.contains("成功"));
+        assert!(output.contains("$ echo 42"));
+    }
 }
@@ -6,6 +6,7 @@
 //! - 默认配置
 
 use crate::display::DisplayMode;
+use crate::dsl::pipeline::PipelineBackend;
 use crate::error::{ErrorCode, FixSuggestion, RealError};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
@@ -69,6 +70,46 @@ pub struct MemoryConfig {
 
     /// 是否自动保存到文件（默认 false）
     pub auto_save: Option<bool>,
+
+    /// 是否持久化长期记忆（跨会话保存全部条目，默认 false）
+    #[serde(default)]
+    pub long_term: Option<bool>,
+
+    /// 长期记忆文件（默认 ~/.realconsole/memory/long_term.jsonl）
+    #[serde(default)]
+    pub long_term_file: Option<String>,
+
+    /// 长期记忆最多保留的条目数（超出时丢弃最早的条目，默认 10000）
+    #[serde(default)]
+    pub long_term_max_entries: Option<usize>,
+
+    /// 是否启用语义检索（嵌入来源沿用 intent.semantic 的配置，默认 false）
+    #[serde(default)]
+    pub semantic_recall: Option<bool>,
+
+    /// 是否自动把相关的历史条目附到 LLM 提示词（默认 false）
+    #[serde(default)]
+    pub auto_recall: Option<bool>,
+
+    /// 自动召回的最多条数（默认 3）
+    #[serde(default)]
+    pub recall_limit: Option<usize>,
+
+    /// 是否消解输入中的指代（"它"、"this file"、"上一个命令" 等，默认 true）
+    #[serde(default)]
+    pub resolve_references: Option<bool>,
+
+    /// 保留的命令输出条数（$1、$2……，默认 50）
+    #[serde(default)]
+    pub output_history: Option<usize>,
+
+    /// 单条命令输出保存的最大字节数（默认 65536）
+    #[serde(default)]
+    pub output_max_bytes: Option<usize>,
+
+    /// 命名会话的保存目录（默认 ~/.realconsole/sessions）
+    #[serde(default)]
+    pub session_dir: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -103,6 +144,15 @@ pub struct FeaturesConfig {
     /// Workflow 缓存默认 TTL（秒，默认 300）
     #[serde(default = "default_workflow_cache_ttl")]
     pub workflow_cache_ttl_default: Option<u64>,
+
+    /// Shell 命令失败时是否自动尝试低风险修复（默认 false）
+    /// 修复后会验证，未通过验证则回滚文件变化
+    #[serde(default)]
+    pub auto_fix_enabled: Option<bool>,
+
+    /// 每条命令最多尝试的自动修复次数（默认 3）
+    #[serde(default = "default_auto_fix_max_attempts")]
+    pub auto_fix_max_attempts: usize,
 }
 
 fn default_true() -> bool {
@@ -133,6 +183,10 @@ fn default_workflow_cache_ttl() -> Option<u64> {
     Some(300)
 }
 
+fn default_auto_fix_max_attempts() -> usize {
+    3
+}
+
 /// Intent DSL 配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IntentConfig {
@@ -159,6 +213,82 @@ pub struct IntentConfig {
     /// LLM 生成失败时是否降级到规则匹配（默认 true）
     #[serde(default)]
     pub llm_generation_fallback: Option<bool>,
+
+    /// Pipeline 执行后端：shell（默认）或 native（进程内执行，结构化输出）
+    #[serde(default)]
+    pub pipeline_backend: PipelineBackend,
+
+    /// 语义意图匹配（默认关闭）
+    #[serde(default)]
+    pub semantic: SemanticMatchingConfig,
+
+    /// 前几名意图得分相近时是否请用户选择（默认 false）
+    #[serde(default = "default_false")]
+    pub disambiguation_enabled: bool,
+
+    /// 与最高分相差不超过此值的候选视为"相近"（默认 0.1）
+    #[serde(default = "default_disambiguation_margin")]
+    pub disambiguation_margin: f64,
+}
+
+/// 语义意图匹配配置
+///
+/// 为意图示例句计算向量，将输入与示例的余弦相似度计入匹配置信度。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SemanticMatchingConfig {
+    /// 是否启用（默认 false）
+    #[serde(default = "default_false")]
+    pub enabled: bool,
+
+    /// 嵌入来源：local（内置特征哈希，默认）或 ollama
+    #[serde(default = "default_embedding_provider")]
+    pub provider: String,
+
+    /// Ollama 嵌入模型（默认 nomic-embed-text）
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Ollama 服务端点（默认 http://localhost:11434）
+    #[serde(default)]
+    pub endpoint: Option<String>,
+
+    /// 语义相似度的分数权重（默认 1.0）
+    #[serde(default = "default_semantic_weight")]
+    pub weight: f64,
+
+    /// 语义相似度阈值（默认 0.5）
+    #[serde(default = "default_semantic_threshold")]
+    pub threshold: f64,
+
+    /// 嵌入缓存文件（默认 ~/.realconsole/embeddings.json）
+    #[serde(default)]
+    pub cache_file: Option<String>,
+}
+
+fn default_embedding_provider() -> String {
+    "local".to_string()
+}
+
+fn default_semantic_weight() -> f64 {
+    1.0
+}
+
+fn default_semantic_threshold() -> f64 {
+    0.5
+}
+
+impl Default for SemanticMatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: default_embedding_provider(),
+            model: None,
+            endpoint: None,
+            weight: default_semantic_weight(),
+            threshold: default_semantic_threshold(),
+            cache_file: None,
+        }
+    }
 }
 
 fn default_false() -> bool {
@@ -169,6 +299,10 @@ fn default_validation_threshold() -> f64 {
     0.7
 }
 
+fn default_disambiguation_margin() -> f64 {
+    0.1
+}
+
 impl Default for IntentConfig {
     fn default() -> Self {
         Self {
@@ -178,6 +312,10 @@ impl Default for IntentConfig {
             require_confirmation: true,
             llm_generation_enabled: Some(false),  // Phase 7: 默认关闭
             llm_generation_fallback: Some(true),  // 默认开启降级
+            pipeline_backend: PipelineBackend::Shell,  // 默认 Shell，保持向后兼容
+            semantic: SemanticMatchingConfig::default(),  // 默认关闭
+            disambiguation_enabled: false,
+            disambiguation_margin: 0.1,
         }
     }
 }
@@ -214,6 +352,8 @@ impl Default for FeaturesConfig {
             workflow_enabled: Some(false), // Phase 8: 默认关闭，保持向后兼容
             workflow_cache_enabled: Some(true), // 启用 Workflow 时默认开启缓存
             workflow_cache_ttl_default: Some(300), // 默认缓存 5 分钟
+            auto_fix_enabled: Some(false),
+            auto_fix_max_attempts: 3,
         }
     }
 }
@@ -399,4 +539,57 @@ features:
         assert_eq!(config.features.workflow_cache_enabled, Some(true));
         assert_eq!(config.features.workflow_cache_ttl_default, Some(600));
     }
+
+    #[test]
+    fn test_pipeline_backend_config() {
+        // 未配置时默认 Shell 后端
+        let config: Config = serde_yaml::from_str("prefix: \"/\"\n").unwrap();
+        assert_eq!(config.intent.pipeline_backend, PipelineBackend::Shell);
+
+        let yaml = r#"
+intent:
+  pipeline_backend: native
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.intent.pipeline_backend, PipelineBackend::Native);
+    }
+
+    #[test]
+    fn test_disambiguation_config() {
+        let config: Config = serde_yaml::from_str("prefix: \"/\"\n").unwrap();
+        assert!(!config.intent.disambiguation_enabled);
+        assert_eq!(config.intent.disambiguation_margin, 0.1);
+
+        let yaml = r#"
+prefix: "/"
+intent:
+  disambiguation_enabled: true
+  disambiguation_margin: 0.05
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.intent.disambiguation_enabled);
+        assert_eq!(config.intent.disambiguation_margin, 0.05);
+    }
+
+    #[test]
+    fn test_semantic_matching_config() {
+        // 未配置时关闭，使用内置嵌入器
+        let config: Config = serde_yaml::from_str("prefix: \"/\"\n").unwrap();
+        assert!(!config.intent.semantic.enabled);
+        assert_eq!(config.intent.semantic.provider, "local");
+
+        let yaml = r#"
+intent:
+  semantic:
+    enabled: true
+    provider: ollama
+    model: nomic-embed-text
+    weight: 0.8
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.intent.semantic.enabled);
+        assert_eq!(config.intent.semantic.provider, "ollama");
+        assert_eq!(config.intent.semantic.weight, 0.8);
+        assert_eq!(config.intent.semantic.threshold, 0.5);
+    }
 }
@@ -4,6 +4,7 @@
 
 use super::state::ConversationState;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
@@ -11,6 +12,7 @@ use uuid::Uuid;
 pub use super::parameter::{ParameterType, ParameterValue};
 
 /// 对话上下文
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationContext {
     /// 对话 ID
     pub id: String,
@@ -73,7 +75,6 @@ impl ConversationContext {
     }
 
     /// 更新活跃时间
-    #[allow(dead_code)]
     pub fn touch(&mut self) {
         self.last_active = Utc::now();
     }
@@ -82,7 +83,7 @@ impl ConversationContext {
 /// 参数规格
 ///
 /// 定义对话需要收集的参数
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParameterSpec {
     /// 参数名称
     pub name: String,
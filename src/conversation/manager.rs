@@ -192,6 +192,26 @@ impl ConversationManager {
             .count()
     }
 
+    /// 导出未结束的对话（用于保存会话）
+    pub fn export_active(&self) -> Vec<ConversationContext> {
+        self.active_conversations
+            .values()
+            .filter(|ctx| !ctx.state.is_terminal())
+            .cloned()
+            .collect()
+    }
+
+    /// 用导出的对话替换当前对话（用于恢复会话）
+    ///
+    /// 恢复的对话重新开始计时，避免因会话搁置而立即超时。
+    pub fn import(&mut self, conversations: Vec<ConversationContext>) {
+        self.active_conversations.clear();
+        for mut context in conversations {
+            context.touch();
+            self.active_conversations.insert(context.id.clone(), context);
+        }
+    }
+
     /// 使用 LLM 智能提取参数
     ///
     /// 从用户输入中自动提取参数值
@@ -2,12 +2,13 @@
 //!
 //! 定义对话的各种状态和状态转换规则
 
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 /// 对话状态
 ///
 /// 遵循"一分为三"原则：初始 - 执行 - 完成，中间穿插验证和确认
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ConversationState {
     /// 初始化：理解用户意图
     Initializing,
@@ -154,6 +154,13 @@ impl Display {
         }
     }
 
+    /// Pipeline 计划（以文本语法显示，可直接复制给 /pipe）
+    pub fn pipeline_plan(mode: DisplayMode, dsl: &str) {
+        if mode.show_command() {
+            println!("{} {}", "⚙".dimmed(), dsl.dimmed());
+        }
+    }
+
     /// Workflow 匹配信息 ✨ Phase 8
     pub fn workflow_match(mode: DisplayMode, workflow_name: &str, confidence: f64) {
         if mode.show_intent() {
@@ -229,6 +236,22 @@ impl Display {
         }
     }
 
+    /// 指代消解提示（改写了用户输入，总是显示）
+    pub fn reference_resolution(mode: DisplayMode, pronoun: &str, resolved: &str) {
+        if mode.show_debug() {
+            println!("{} {} → {}", "↪ 指代:".dimmed(), pronoun.dimmed(), resolved.cyan());
+        } else {
+            println!("{} {} → {}", "↪".dimmed(), pronoun.dimmed(), resolved.dimmed());
+        }
+    }
+
+    /// 命令输出的引用名
+    pub fn output_handle(mode: DisplayMode, id: usize) {
+        if mode.show_command() {
+            println!("{} ${}", "ⓘ 输出已保存为".dimmed(), id.to_string().dimmed());
+        }
+    }
+
     /// 执行耗时
     pub fn execution_timing(mode: DisplayMode, seconds: f64) {
         if mode.show_timing() {
@@ -14,7 +14,7 @@
 //! ## 使用示例
 //!
 //! ```rust
-//! use simpleconsole::dsl::intent::builtin::BuiltinIntents;
+//! use realconsole::dsl::intent::builtin::BuiltinIntents;
 //!
 //! let builtin = BuiltinIntents::new();
 //!
@@ -40,7 +40,7 @@ use crate::dsl::intent::{EntityType, Intent, IntentDomain, IntentMatcher, Templa
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::builtin::BuiltinIntents;
+/// use realconsole::dsl::intent::builtin::BuiltinIntents;
 ///
 /// let builtin = BuiltinIntents::new();
 ///
@@ -188,6 +188,11 @@ impl BuiltinIntents {
             0.5,
         )
         .with_entity("path", EntityType::Path(".".to_string()))
+        .with_examples(examples(&[
+            "how many lines of python code are there",
+            "count lines in python files",
+            "统计 Python 代码有多少行",
+        ]))
     }
 
     /// 模板: 统计 Python 代码行数
@@ -220,6 +225,11 @@ impl BuiltinIntents {
         )
         .with_entity("path", EntityType::Path(".".to_string()))
         .with_entity("ext", EntityType::FileType("*".to_string()))
+        .with_examples(examples(&[
+            "how many files are in this folder",
+            "count the files in the directory",
+            "这个目录下有多少个文件",
+        ]))
     }
 
     /// 模板: 统计文件数量
@@ -270,6 +280,14 @@ impl BuiltinIntents {
         .with_entity("limit", EntityType::Number(10.0))
         // sort_order 将由实体提取器动态确定："-hr" (降序/最大) 或 "-h" (升序/最小)
         .with_entity("sort_order", EntityType::Custom("sort".to_string(), "-hr".to_string()))
+        .with_examples(examples(&[
+            "which files take up the most space",
+            "show the largest files",
+            "find the smallest files",
+            "显示最大的几个文件",
+            "哪些文件占用空间最多",
+            "最占空间的文件",
+        ]))
     }
 
     /// 模板: 按体积查找文件
@@ -318,6 +336,11 @@ impl BuiltinIntents {
         .with_entity("path", EntityType::Path(".".to_string()))
         .with_entity("ext", EntityType::FileType("*".to_string()))
         .with_entity("limit", EntityType::Number(10.0))
+        .with_examples(examples(&[
+            "which files were changed recently",
+            "show the most recently modified files",
+            "最近改过哪些文件",
+        ]))
     }
 
     /// 模板: 查找最近修改的文件
@@ -349,6 +372,11 @@ impl BuiltinIntents {
             vec![r"(?i)(搜索|查找).*模式".to_string()],
             0.5,
         )
+        .with_examples(examples(&[
+            "search for a word in the files",
+            "find lines containing error",
+            "在文件里搜索关键字",
+        ]))
     }
 
     /// 模板: 搜索文本模式
@@ -377,6 +405,11 @@ impl BuiltinIntents {
             vec![r"(?i)排序.*文本".to_string()],
             0.5,
         )
+        .with_examples(examples(&[
+            "sort the lines of a file",
+            "order the file contents alphabetically",
+            "把文件内容按行排序",
+        ]))
     }
 
     /// 模板: 排序文本行
@@ -406,6 +439,11 @@ impl BuiltinIntents {
             vec![r"(?i)统计.*(次数|出现)".to_string()],
             0.5,
         )
+        .with_examples(examples(&[
+            "how many times does a word appear",
+            "count occurrences of a pattern",
+            "统计某个词出现了几次",
+        ]))
     }
 
     /// 模板: 统计模式出现次数
@@ -437,6 +475,11 @@ impl BuiltinIntents {
             vec![r"(?i)分析.*错误".to_string()],
             0.5,
         )
+        .with_examples(examples(&[
+            "what errors are in the log",
+            "summarize the errors in the log file",
+            "分析日志里的错误",
+        ]))
     }
 
     /// 模板: 分析错误日志
@@ -469,6 +512,11 @@ impl BuiltinIntents {
         )
         .with_entity("path", EntityType::Path(".".to_string()))
         .with_entity("limit", EntityType::Number(10.0))
+        .with_examples(examples(&[
+            "how much disk space is left",
+            "check disk usage",
+            "磁盘空间还剩多少",
+        ]))
     }
 
     /// 模板: 检查磁盘使用情况
@@ -500,6 +548,11 @@ impl BuiltinIntents {
             vec![r"(?i)列出.*进程".to_string()],
             0.5,
         )
+        .with_examples(examples(&[
+            "what processes are running",
+            "show running programs",
+            "列出正在运行的进程",
+        ]))
     }
 
     /// 模板: 列出进程
@@ -534,6 +587,11 @@ impl BuiltinIntents {
             ],
             0.6,  // 稍高的置信度阈值，避免误匹配
         )
+        .with_examples(examples(&[
+            "how much memory is being used",
+            "check ram usage",
+            "内存占用多少",
+        ]))
     }
 
     /// 模板: 检查内存使用情况
@@ -575,6 +633,11 @@ impl BuiltinIntents {
             0.50,  // 降低置信度阈值，让过滤型Intent优先
         )
         .with_entity("path", EntityType::Path(".".to_string()))
+        .with_examples(examples(&[
+            "what is in this folder",
+            "show the directory contents",
+            "看看当前目录有什么",
+        ]))
     }
 
     /// 模板: 查看目录内容
@@ -610,6 +673,11 @@ impl BuiltinIntents {
             ],
             0.6,
         )
+        .with_examples(examples(&[
+            "how busy is the cpu",
+            "check processor load",
+            "CPU 占用高不高",
+        ]))
     }
 
     /// 模板: 检查CPU使用率
@@ -644,6 +712,11 @@ impl BuiltinIntents {
             ],
             0.6,
         )
+        .with_examples(examples(&[
+            "what network connections are open",
+            "show listening ports",
+            "查看当前的网络连接",
+        ]))
     }
 
     /// 模板: 检查网络连接
@@ -679,6 +752,11 @@ impl BuiltinIntents {
             0.6,
         )
         .with_entity("lines", EntityType::Number(50.0))
+        .with_examples(examples(&[
+            "show the system log",
+            "what happened in the system log recently",
+            "查看系统日志",
+        ]))
     }
 
     /// 模板: 查看系统日志
@@ -713,6 +791,11 @@ impl BuiltinIntents {
             ],
             0.55,
         )
+        .with_examples(examples(&[
+            "how long has the system been running",
+            "when did the machine last reboot",
+            "系统运行多久了",
+        ]))
     }
 
     /// 模板: 查看系统运行时长
@@ -753,6 +836,11 @@ impl BuiltinIntents {
             0.6,
         )
         .with_entity("path", EntityType::Path(".".to_string()))
+        .with_examples(examples(&[
+            "find a file called config",
+            "where is the file named readme",
+            "按名字查找文件",
+        ]))
     }
 
     /// 模板: 按名称查找文件
@@ -790,6 +878,11 @@ impl BuiltinIntents {
             0.6,
         )
         .with_entity("file", EntityType::Path("".to_string()))
+        .with_examples(examples(&[
+            "how many words are in this file",
+            "count lines and words of a file",
+            "统计文件的行数和字数",
+        ]))
     }
 
     /// 模板: 统计文件行数/字数
@@ -827,6 +920,11 @@ impl BuiltinIntents {
         )
         .with_entity("file1", EntityType::Path("".to_string()))
         .with_entity("file2", EntityType::Path("".to_string()))
+        .with_examples(examples(&[
+            "what is the difference between two files",
+            "compare these two files",
+            "比较两个文件有什么不同",
+        ]))
     }
 
     /// 模板: 比较文件差异
@@ -864,6 +962,11 @@ impl BuiltinIntents {
             0.65,
         )
         .with_entity("count", EntityType::Number(4.0))
+        .with_examples(examples(&[
+            "is the server reachable",
+            "check if a host is up",
+            "测试能不能连通某台主机",
+        ]))
     }
 
     /// 模板: 测试网络连通性
@@ -900,6 +1003,11 @@ impl BuiltinIntents {
             ],
             0.6,
         )
+        .with_examples(examples(&[
+            "what is the value of an environment variable",
+            "show my PATH",
+            "查看环境变量",
+        ]))
     }
 
     /// 模板: 查看环境变量
@@ -936,6 +1044,11 @@ impl BuiltinIntents {
             ],
             0.6,
         )
+        .with_examples(examples(&[
+            "is the service running",
+            "check the status of a service",
+            "查看服务状态",
+        ]))
     }
 
     /// 模板: 查看服务状态
@@ -979,6 +1092,11 @@ impl BuiltinIntents {
             0.65,
         )
         .with_entity("path", EntityType::Path("".to_string()))
+        .with_examples(examples(&[
+            "make a new folder",
+            "create a directory",
+            "新建一个目录",
+        ]))
     }
 
     /// 模板: 创建目录
@@ -1016,6 +1134,11 @@ impl BuiltinIntents {
         )
         .with_entity("source", EntityType::Path("".to_string()))
         .with_entity("target", EntityType::Path("".to_string()))
+        .with_examples(examples(&[
+            "make a shortcut link to a file",
+            "create a symbolic link",
+            "创建软链接",
+        ]))
     }
 
     /// 模板: 创建符号链接
@@ -1035,6 +1158,11 @@ impl Default for BuiltinIntents {
     }
 }
 
+/// 示例句列表（用于语义匹配）
+fn examples(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
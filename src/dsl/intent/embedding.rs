@@ -0,0 +1,702 @@
+//! 语义匹配 - 基于向量嵌入的意图相似度
+//!
+//! **哲学**：言不同而意同
+//!
+//! 关键词与正则只认"字面"，"which files hog the most space" 与
+//! "查找最大的文件" 字面毫无交集，意思却相同。语义匹配为每个意图的
+//! 示例句（`Intent::examples`）计算向量，用户输入与示例的余弦相似度
+//! 按 `FuzzyConfig::semantic_weight` 计入置信度。
+//!
+//! ## 嵌入来源
+//!
+//! - **HashingEmbedder**（内置）：字符 n-gram 特征哈希，无需网络与模型文件
+//! - **OllamaEmbedder**：调用本地 Ollama 的 `/api/embeddings` 接口
+//!
+//! 示例句的向量缓存在磁盘（`EmbeddingCache`），重启后无需重新计算。
+
+use super::types::Intent;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 文本嵌入器
+///
+/// 实现者负责将文本映射为固定维度的向量。接口是同步的：
+/// 意图匹配发生在同步路径上，异步实现需自行桥接运行时。
+pub trait Embedder: Send + Sync + fmt::Debug {
+    /// 模型标识（作为缓存键的一部分，换模型后缓存自动失效）
+    fn model_id(&self) -> String;
+
+    /// 计算文本的嵌入向量
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String>;
+}
+
+/// 嵌入器失败后暂停调用的时长（秒）
+const EMBEDDER_COOLDOWN_SECS: u64 = 60;
+
+/// 失败即停的嵌入器
+///
+/// 包装另一个嵌入器：调用失败后在冷却期内不再调用（如 Ollama 不可用），
+/// 直接返回错误，调用方退化为缓存或全文检索，不必每次等待超时；
+/// 冷却期过后再试一次，偶发错误不会让嵌入器永久停用。
+#[derive(Debug)]
+pub struct FailFastEmbedder {
+    inner: Arc<dyn Embedder>,
+    cooldown: Duration,
+    /// 最近一次失败的时间
+    failed_at: Mutex<Option<Instant>>,
+}
+
+impl FailFastEmbedder {
+    /// 包装嵌入器（冷却期 [`EMBEDDER_COOLDOWN_SECS`] 秒）
+    pub fn new(inner: Arc<dyn Embedder>) -> Self {
+        Self {
+            inner,
+            cooldown: Duration::from_secs(EMBEDDER_COOLDOWN_SECS),
+            failed_at: Mutex::new(None),
+        }
+    }
+
+    /// 设置失败后的冷却期
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// 嵌入器是否处于失败后的冷却期
+    pub fn failed(&self) -> bool {
+        self.failed_at
+            .lock()
+            .map(|failed_at| failed_at.is_some_and(|at| at.elapsed() < self.cooldown))
+            .unwrap_or(false)
+    }
+}
+
+impl Embedder for FailFastEmbedder {
+    fn model_id(&self) -> String {
+        self.inner.model_id()
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        if self.failed() {
+            return Err(format!("嵌入器 {} 不可用", self.inner.model_id()));
+        }
+        let result = self.inner.embed(text);
+        if let Ok(mut failed_at) = self.failed_at.lock() {
+            *failed_at = result.is_err().then(Instant::now);
+        }
+        result
+    }
+}
+
+/// 余弦相似度（任一向量为零或维度不同时返回 0.0）
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (*x as f64, *y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+// ========== 内置嵌入器 ==========
+
+/// 英文停用词（不参与特征）
+const STOPWORDS: &[&str] = &[
+    "a", "an", "the", "is", "are", "was", "be", "of", "in", "on", "at", "to", "for", "me", "my",
+    "i", "you", "please", "can", "could", "which", "what", "that", "this", "it", "do", "does",
+    "and", "or", "with", "up", "all",
+];
+
+/// 中文虚字（不作为单字特征，也不参与二元组）
+const CJK_STOP_CHARS: &str = "的了吗呢吧啊是我你他哪些个一下把这那有";
+
+/// 特征哈希嵌入器
+///
+/// **特征**：
+/// - 英文：词（去掉复数 s）+ 带边界的字符三元组，容忍词形变化与拼写错误
+/// - 中文：实字单字 + 相邻二元组（跳过虚字），无需分词
+///
+/// 特征经 FNV-1a 哈希到固定维度，带符号累加后做 L2 归一化。
+/// 它不理解"近义词"，但能让改写、语序变化、拼写错误的句子互相靠近。
+#[derive(Debug, Clone)]
+pub struct HashingEmbedder {
+    dimensions: usize,
+}
+
+impl HashingEmbedder {
+    /// 创建指定维度的嵌入器
+    pub fn new(dimensions: usize) -> Self {
+        Self {
+            dimensions: dimensions.max(16),
+        }
+    }
+
+    fn features(text: &str) -> Vec<(String, f32)> {
+        let mut features = Vec::new();
+        let lower = text.to_lowercase();
+        let mut cjk_run: Vec<char> = Vec::new();
+
+        let flush_cjk = |run: &mut Vec<char>, features: &mut Vec<(String, f32)>| {
+            run.retain(|c| !CJK_STOP_CHARS.contains(*c));
+            for c in run.iter() {
+                features.push((c.to_string(), 1.0));
+            }
+            for pair in run.windows(2) {
+                features.push((pair.iter().collect(), 1.5));
+            }
+            run.clear();
+        };
+
+        for token in lower.split(|c: char| !c.is_alphanumeric() && !is_cjk(c)) {
+            let mut word = String::new();
+            for c in token.chars() {
+                if is_cjk(c) {
+                    cjk_run.push(c);
+                } else {
+                    flush_cjk(&mut cjk_run, &mut features);
+                    word.push(c);
+                }
+            }
+            flush_cjk(&mut cjk_run, &mut features);
+
+            if word.is_empty() || STOPWORDS.contains(&word.as_str()) {
+                continue;
+            }
+
+            let stem = if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                &word[..word.len() - 1]
+            } else {
+                word.as_str()
+            };
+            features.push((format!("w:{}", stem), 2.0));
+
+            let padded: Vec<char> = format!("#{}#", stem).chars().collect();
+            for gram in padded.windows(3) {
+                features.push((gram.iter().collect(), 0.5));
+            }
+        }
+
+        features
+    }
+}
+
+impl Default for HashingEmbedder {
+    fn default() -> Self {
+        Self::new(512)
+    }
+}
+
+impl Embedder for HashingEmbedder {
+    fn model_id(&self) -> String {
+        format!("hashing-{}", self.dimensions)
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let mut vector = vec![0.0f32; self.dimensions];
+
+        for (feature, weight) in Self::features(text) {
+            let hash = fnv1a(feature.as_bytes());
+            let index = (hash % self.dimensions as u64) as usize;
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[index] += sign * weight;
+        }
+
+        let norm = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+        Ok(vector)
+    }
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}')
+}
+
+/// FNV-1a 64 位哈希（稳定，跨进程一致，适合作为缓存键的特征索引）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// ========== Ollama 嵌入器 ==========
+
+/// 连接 Ollama 的超时（秒）
+const OLLAMA_CONNECT_TIMEOUT_SECS: u64 = 2;
+
+/// Ollama 嵌入器
+///
+/// 调用 `POST {endpoint}/api/embeddings`，需要多线程 tokio 运行时
+/// （与 Agent 中其他同步桥接一致，使用 `block_in_place`）。
+/// 连接超时很短，服务不可用时尽快失败。
+#[derive(Debug, Clone)]
+pub struct OllamaEmbedder {
+    endpoint: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl OllamaEmbedder {
+    /// 创建 Ollama 嵌入器
+    ///
+    /// # 参数
+    /// - `model`: 嵌入模型名称（如 "nomic-embed-text"）
+    /// - `endpoint`: Ollama 服务端点（如 "http://localhost:11434"）
+    pub fn new(model: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+            model: model.into(),
+            client: reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(OLLAMA_CONNECT_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    async fn embed_async(&self, text: &str) -> Result<Vec<f32>, String> {
+        let response = self
+            .client
+            .post(format!("{}/api/embeddings", self.endpoint))
+            .json(&json!({ "model": self.model, "prompt": text }))
+            .timeout(Duration::from_secs(30))
+            .send()
+            .await
+            .map_err(|e| format!("嵌入请求失败: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("嵌入服务返回错误: {}", response.status()));
+        }
+
+        #[derive(Deserialize)]
+        struct EmbeddingResponse {
+            embedding: Vec<f32>,
+        }
+
+        let body: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("嵌入响应解析失败: {}", e))?;
+
+        if body.embedding.is_empty() {
+            return Err(format!("模型 {} 返回了空向量", self.model));
+        }
+        Ok(body.embedding)
+    }
+}
+
+impl Embedder for OllamaEmbedder {
+    fn model_id(&self) -> String {
+        format!("ollama:{}", self.model)
+    }
+
+    fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let handle = tokio::runtime::Handle::try_current()
+            .map_err(|_| "Ollama 嵌入需要在 tokio 运行时中调用".to_string())?;
+        if handle.runtime_flavor() != tokio::runtime::RuntimeFlavor::MultiThread {
+            return Err("Ollama 嵌入需要多线程 tokio 运行时".to_string());
+        }
+
+        tokio::task::block_in_place(|| handle.block_on(self.embed_async(text)))
+    }
+}
+
+// ========== 磁盘缓存 ==========
+
+/// 嵌入向量缓存
+///
+/// 以 `模型 → 文本 → 向量` 的结构保存为 JSON。
+#[derive(Debug, Default)]
+pub struct EmbeddingCache {
+    path: Option<PathBuf>,
+    entries: HashMap<String, HashMap<String, Vec<f32>>>,
+    dirty: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    models: HashMap<String, HashMap<String, Vec<f32>>>,
+}
+
+impl EmbeddingCache {
+    /// 仅内存缓存
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载（文件不存在或损坏时从空缓存开始）
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .map(|file| file.models)
+            .unwrap_or_default();
+
+        Self {
+            path: Some(path),
+            entries,
+            dirty: false,
+        }
+    }
+
+    /// 默认缓存路径：`~/.realconsole/embeddings.json`
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("embeddings.json")
+    }
+
+    /// 查询缓存
+    pub fn get(&self, model: &str, text: &str) -> Option<&Vec<f32>> {
+        self.entries.get(model)?.get(text)
+    }
+
+    /// 写入缓存
+    pub fn insert(&mut self, model: &str, text: &str, vector: Vec<f32>) {
+        self.entries
+            .entry(model.to_string())
+            .or_default()
+            .insert(text.to_string(), vector);
+        self.dirty = true;
+    }
+
+    /// 缓存条目数（所有模型）
+    pub fn len(&self) -> usize {
+        self.entries.values().map(|m| m.len()).sum()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 有新条目时写回磁盘
+    pub fn save(&mut self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建缓存目录失败: {}", e))?;
+        }
+        let file = CacheFile {
+            models: self.entries.clone(),
+        };
+        let content = serde_json::to_string(&file).map_err(|e| format!("序列化缓存失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入缓存失败: {}", e))?;
+
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+// ========== 语义索引 ==========
+
+/// 语义索引：意图示例句的向量
+///
+/// 注册意图时计算（或从缓存读取）示例向量；匹配时只需为用户输入计算一次向量。
+/// 嵌入器经 [`FailFastEmbedder`] 包装，失败后只使用缓存中的向量。
+pub struct SemanticIndex {
+    embedder: FailFastEmbedder,
+    cache: Mutex<EmbeddingCache>,
+    /// 意图名 → (示例句, 向量)
+    vectors: HashMap<String, Vec<(String, Vec<f32>)>>,
+}
+
+impl fmt::Debug for SemanticIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SemanticIndex")
+            .field("model", &self.embedder.model_id())
+            .field("intents", &self.vectors.len())
+            .finish()
+    }
+}
+
+impl SemanticIndex {
+    /// 创建语义索引
+    pub fn new(embedder: Arc<dyn Embedder>, cache: EmbeddingCache) -> Self {
+        Self {
+            embedder: FailFastEmbedder::new(embedder),
+            cache: Mutex::new(cache),
+            vectors: HashMap::new(),
+        }
+    }
+
+    /// 使用内置嵌入器、仅内存缓存
+    pub fn local() -> Self {
+        Self::new(Arc::new(HashingEmbedder::default()), EmbeddingCache::in_memory())
+    }
+
+    /// 模型标识
+    pub fn model_id(&self) -> String {
+        self.embedder.model_id()
+    }
+
+    /// 为意图的示例句建立索引
+    ///
+    /// 没有示例的意图不参与语义匹配。单个示例嵌入失败时跳过该示例。
+    pub fn index_intent(&mut self, intent: &Intent) {
+        self.index_examples(intent);
+        self.save_cache();
+    }
+
+    /// 为多个意图建立索引（全部完成后只写一次缓存）
+    pub fn index_intents<'a>(&mut self, intents: impl IntoIterator<Item = &'a Intent>) {
+        for intent in intents {
+            self.index_examples(intent);
+        }
+        self.save_cache();
+    }
+
+    /// 嵌入器是否已失败
+    pub fn embedder_failed(&self) -> bool {
+        self.embedder.failed()
+    }
+
+    /// 计算（或从缓存读取）意图示例句的向量，不写回缓存
+    fn index_examples(&mut self, intent: &Intent) {
+        if intent.examples.is_empty() {
+            return;
+        }
+
+        let model = self.embedder.model_id();
+        let mut vectors = Vec::with_capacity(intent.examples.len());
+
+        if let Ok(mut cache) = self.cache.lock() {
+            for example in &intent.examples {
+                if let Some(vector) = cache.get(&model, example) {
+                    vectors.push((example.clone(), vector.clone()));
+                } else if let Ok(vector) = self.embedder.embed(example) {
+                    cache.insert(&model, example, vector.clone());
+                    vectors.push((example.clone(), vector));
+                }
+            }
+        }
+
+        if !vectors.is_empty() {
+            self.vectors.insert(intent.name.clone(), vectors);
+        }
+    }
+
+    /// 有新向量时写回缓存
+    fn save_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            if let Err(e) = cache.save() {
+                eprintln!("警告: 嵌入缓存保存失败: {}", e);
+            }
+        }
+    }
+
+    /// 移除所有索引
+    pub fn clear(&mut self) {
+        self.vectors.clear();
+    }
+
+    /// 是否有可用的示例向量
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// 计算用户输入的向量
+    pub fn embed_query(&self, input: &str) -> Result<Vec<f32>, String> {
+        self.embedder.embed(input)
+    }
+
+    /// 与输入向量最相似的示例句及相似度（未索引的意图返回 `None`）
+    pub fn best_example(&self, intent_name: &str, query: &[f32]) -> Option<(&str, f64)> {
+        self.vectors
+            .get(intent_name)?
+            .iter()
+            .map(|(example, v)| (example.as_str(), cosine_similarity(v, query)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    /// 输入向量与意图示例的最高相似度（未索引的意图返回 `None`）
+    pub fn similarity(&self, intent_name: &str, query: &[f32]) -> Option<f64> {
+        self.best_example(intent_name, query).map(|(_, similarity)| similarity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::intent::types::IntentDomain;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tempfile::TempDir;
+
+    fn embed(text: &str) -> Vec<f32> {
+        HashingEmbedder::default().embed(text).unwrap()
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[1.0, 0.0]) - 1.0).abs() < 1e-9);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine_similarity(&[1.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[test]
+    fn test_hashing_embedder_is_normalized_and_stable() {
+        let a = embed("Show the largest files");
+        let b = embed("Show the largest files");
+        let norm: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+        assert_eq!(a, b);
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert_eq!(a.len(), 512);
+    }
+
+    #[test]
+    fn test_hashing_embedder_paraphrase_is_closer() {
+        let example = embed("which files take up the most space");
+        let paraphrase = embed("which files hog the most space");
+        let unrelated = embed("how long has the system been running");
+
+        assert!(cosine_similarity(&example, &paraphrase) > cosine_similarity(&example, &unrelated));
+    }
+
+    #[test]
+    fn test_hashing_embedder_chinese() {
+        let example = embed("显示最大的文件");
+        let paraphrase = embed("最大的几个文件是哪些");
+        let unrelated = embed("查看网络连接");
+
+        assert!(cosine_similarity(&example, &paraphrase) > cosine_similarity(&example, &unrelated));
+    }
+
+    #[test]
+    fn test_embedding_cache_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache").join("embeddings.json");
+
+        let mut cache = EmbeddingCache::load(&path);
+        assert!(cache.is_empty());
+        cache.insert("m", "hello", vec![1.0, 2.0]);
+        cache.save().unwrap();
+
+        let reloaded = EmbeddingCache::load(&path);
+        assert_eq!(reloaded.get("m", "hello"), Some(&vec![1.0, 2.0]));
+        assert_eq!(reloaded.get("other", "hello"), None);
+    }
+
+    #[test]
+    fn test_semantic_index_uses_cache() {
+        #[derive(Debug, Default)]
+        struct FailingEmbedder {
+            calls: AtomicUsize,
+        }
+        impl Embedder for FailingEmbedder {
+            fn model_id(&self) -> String {
+                "failing".to_string()
+            }
+            fn embed(&self, _text: &str) -> Result<Vec<f32>, String> {
+                self.calls.fetch_add(1, Ordering::Relaxed);
+                Err("offline".to_string())
+            }
+        }
+
+        let mut cache = EmbeddingCache::in_memory();
+        cache.insert("failing", "big files", vec![1.0, 0.0]);
+
+        let intent = Intent::new("find_big", IntentDomain::FileOps, vec![], vec![], 0.5)
+            .with_examples(vec!["big files".to_string(), "uncached example".to_string()]);
+
+        let other = Intent::new("disk_usage", IntentDomain::FileOps, vec![], vec![], 0.5)
+            .with_examples(vec!["disk usage".to_string(), "free space".to_string()]);
+
+        let embedder = Arc::new(FailingEmbedder::default());
+        let mut index = SemanticIndex::new(embedder.clone(), cache);
+        index.index_intents([&intent, &other]);
+
+        // 嵌入器失败后冷却期内不再调用
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 1);
+        assert!(index.embedder_failed());
+
+        // 缓存命中的示例可用，嵌入失败的示例被跳过
+        assert_eq!(index.similarity("find_big", &[1.0, 0.0]), Some(1.0));
+        assert_eq!(index.best_example("find_big", &[1.0, 0.0]), Some(("big files", 1.0)));
+        assert_eq!(index.similarity("unknown", &[1.0, 0.0]), None);
+        assert!(index.embed_query("anything").is_err());
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_fail_fast_embedder_retries_after_cooldown() {
+        #[derive(Debug, Default)]
+        struct FlakyEmbedder {
+            calls: AtomicUsize,
+        }
+        impl Embedder for FlakyEmbedder {
+            fn model_id(&self) -> String {
+                "flaky".to_string()
+            }
+            fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+                // 只有第一次调用失败
+                if self.calls.fetch_add(1, Ordering::Relaxed) == 0 {
+                    return Err("timeout".to_string());
+                }
+                HashingEmbedder::default().embed(text)
+            }
+        }
+
+        let inner = Arc::new(FlakyEmbedder::default());
+        let embedder = FailFastEmbedder::new(inner.clone());
+        assert!(embedder.embed("a").is_err());
+        assert!(embedder.failed());
+        // 冷却期内不调用
+        assert!(embedder.embed("a").is_err());
+        assert_eq!(inner.calls.load(Ordering::Relaxed), 1);
+
+        let inner = Arc::new(FlakyEmbedder::default());
+        let embedder = FailFastEmbedder::new(inner.clone()).with_cooldown(Duration::ZERO);
+        assert!(embedder.embed("a").is_err());
+        assert!(!embedder.failed());
+        assert!(embedder.embed("a").is_ok());
+        assert_eq!(inner.calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_semantic_index_saves_cache_once_indexed() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("embeddings.json");
+
+        let intents: Vec<Intent> = ["a", "b"]
+            .iter()
+            .map(|name| {
+                Intent::new(*name, IntentDomain::FileOps, vec![], vec![], 0.5)
+                    .with_examples(vec![format!("example {}", name)])
+            })
+            .collect();
+
+        let mut index = SemanticIndex::new(Arc::new(HashingEmbedder::default()), EmbeddingCache::load(&path));
+        index.index_intents(&intents);
+        assert!(!index.is_empty());
+
+        let reloaded = EmbeddingCache::load(&path);
+        assert_eq!(reloaded.len(), 2);
+    }
+}
@@ -0,0 +1,443 @@
+//! 匹配解释与消歧 - 让"为什么是它"看得见
+//!
+//! **哲学**：知其然，亦知其所以然
+//!
+//! - **解释**：每个候选意图的得分由哪些关键词、模式、模糊相似度、
+//!   语义相似度与历史选择构成，提取到了哪些实体
+//! - **消歧**：前几名得分相近时，不再默默取第一，而是请用户选择
+//! - **记忆**：用户的选择被记录下来，之后相似的输入会偏向该意图
+
+use super::matcher::string_similarity;
+use super::types::{EntityType, IntentMatch};
+use chrono::{DateTime, Local};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 历史选择的最大加分（乘以输入相似度）
+pub const CHOICE_BOOST: f64 = 0.2;
+
+/// 输入与历史输入的最低相似度（低于此值不加分）
+pub const CHOICE_SIMILARITY_THRESHOLD: f64 = 0.75;
+
+/// 最多保留的历史选择条数
+const MAX_CHOICES: usize = 500;
+
+// ========== 解释 ==========
+
+/// 得分来源
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoreSource {
+    /// 关键词精确命中
+    Keyword(String),
+    /// 关键词模糊命中（关键词, 输入中的词, 相似度）
+    FuzzyKeyword {
+        keyword: String,
+        word: String,
+        similarity: f64,
+    },
+    /// 正则模式命中
+    Pattern(String),
+    /// 与示例句语义相似
+    Semantic { example: String, similarity: f64 },
+    /// 与用户曾经选择过该意图的输入相似
+    Choice { input: String, similarity: f64 },
+}
+
+/// 单项得分贡献
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreContribution {
+    pub source: ScoreSource,
+    pub score: f64,
+}
+
+impl ScoreContribution {
+    pub fn new(source: ScoreSource, score: f64) -> Self {
+        Self { source, score }
+    }
+}
+
+impl fmt::Display for ScoreContribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{:.2}  ", self.score)?;
+        match &self.source {
+            ScoreSource::Keyword(keyword) => write!(f, "关键词 \"{}\"", keyword),
+            ScoreSource::FuzzyKeyword {
+                keyword,
+                word,
+                similarity,
+            } => write!(f, "模糊关键词 \"{}\" ≈ \"{}\" ({:.2})", keyword, word, similarity),
+            ScoreSource::Pattern(pattern) => write!(f, "模式 {}", pattern),
+            ScoreSource::Semantic {
+                example,
+                similarity,
+            } => write!(f, "语义 \"{}\" ({:.2})", example, similarity),
+            ScoreSource::Choice { input, similarity } => {
+                write!(f, "历史选择 \"{}\" ({:.2})", input, similarity)
+            }
+        }
+    }
+}
+
+/// 单个候选意图的匹配解释
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchExplanation {
+    /// 意图名称
+    pub intent_name: String,
+    /// 最终置信度（已归一化）
+    pub confidence: f64,
+    /// 意图的置信度阈值
+    pub threshold: f64,
+    /// 得分构成
+    pub contributions: Vec<ScoreContribution>,
+    /// 提取到的实体（仅对达到阈值的候选提取）
+    pub entities: HashMap<String, EntityType>,
+}
+
+impl MatchExplanation {
+    /// 是否达到阈值（会出现在匹配结果中）
+    pub fn accepted(&self) -> bool {
+        self.confidence >= self.threshold
+    }
+}
+
+impl fmt::Display for MatchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = if self.accepted() {
+            "✓".green()
+        } else {
+            "✗".dimmed()
+        };
+        writeln!(
+            f,
+            "{} {} {:.2} {}",
+            status,
+            self.intent_name.bold(),
+            self.confidence,
+            format!("(阈值 {:.2})", self.threshold).dimmed()
+        )?;
+
+        for contribution in &self.contributions {
+            writeln!(f, "    {}", contribution)?;
+        }
+
+        if !self.entities.is_empty() {
+            let mut entities: Vec<String> = self
+                .entities
+                .iter()
+                .map(|(name, entity)| format!("{}={}", name, entity_value(entity)))
+                .collect();
+            entities.sort();
+            writeln!(f, "    {} {}", "实体:".dimmed(), entities.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+fn entity_value(entity: &EntityType) -> String {
+    match entity {
+        EntityType::FileType(v)
+        | EntityType::Operation(v)
+        | EntityType::Path(v)
+        | EntityType::Date(v)
+        | EntityType::Custom(_, v) => v.clone(),
+        EntityType::Number(n) => n.to_string(),
+    }
+}
+
+/// 渲染全部候选的解释
+pub fn format_explanations(input: &str, explanations: &[MatchExplanation]) -> String {
+    if explanations.is_empty() {
+        return format!("{} \"{}\" 没有任何意图得分", "ⓘ".dimmed(), input);
+    }
+
+    let mut output = format!("{} \"{}\"\n", "意图匹配解释:".cyan().bold(), input);
+    for explanation in explanations {
+        output.push_str(&explanation.to_string());
+    }
+    output.trim_end().to_string()
+}
+
+// ========== 消歧 ==========
+
+/// 从匹配结果中挑出需要消歧的候选
+///
+/// 第二名与第一名的差距不超过 `margin` 时，返回所有与第一名差距在 `margin`
+/// 以内的候选（最多 `max` 个）；否则返回空列表，表示无需消歧。
+pub fn ambiguous_candidates(matches: &[IntentMatch], margin: f64, max: usize) -> Vec<IntentMatch> {
+    let Some(top) = matches.first() else {
+        return Vec::new();
+    };
+
+    let close: Vec<IntentMatch> = matches
+        .iter()
+        .take_while(|m| top.confidence - m.confidence <= margin)
+        .take(max)
+        .cloned()
+        .collect();
+
+    if close.len() < 2 {
+        Vec::new()
+    } else {
+        close
+    }
+}
+
+/// 渲染消歧菜单
+pub fn format_menu(candidates: &[IntentMatch]) -> String {
+    let mut output = format!("{}\n", "🤔 有几个意图得分相近，请选择:".yellow());
+    for (index, candidate) in candidates.iter().enumerate() {
+        output.push_str(&format!(
+            "  {}. {} {}\n",
+            index + 1,
+            candidate.intent.name,
+            format!("({:.2})", candidate.confidence).dimmed()
+        ));
+    }
+    output.push_str(&format!("  {}. {}", 0, "都不是（交给 LLM 处理）".dimmed()));
+    output
+}
+
+/// 解析菜单选择（1 起始；0、空输入或无效输入返回 None）
+pub fn parse_choice(answer: &str, count: usize) -> Option<usize> {
+    match answer.trim().parse::<usize>() {
+        Ok(n) if (1..=count).contains(&n) => Some(n - 1),
+        _ => None,
+    }
+}
+
+// ========== 选择记忆 ==========
+
+/// 一条历史选择
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChoiceRecord {
+    /// 用户输入（小写、去首尾空白）
+    pub input: String,
+    /// 用户选择的意图
+    pub intent: String,
+    /// 选择次数
+    pub count: u32,
+    /// 最近一次选择时间
+    pub last_chosen: DateTime<Local>,
+}
+
+/// 消歧选择记忆
+///
+/// 记录"某类输入 → 用户选择的意图"，之后相似的输入会给该意图加分。
+/// 指定了文件路径时，每次记录后写回磁盘（JSON）。
+#[derive(Debug, Default)]
+pub struct ChoiceMemory {
+    records: Vec<ChoiceRecord>,
+    path: Option<PathBuf>,
+}
+
+impl ChoiceMemory {
+    /// 仅内存记忆
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载（文件不存在或损坏时从空记忆开始）
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let records = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            records,
+            path: Some(path),
+        }
+    }
+
+    /// 默认存储路径：`~/.realconsole/intent_choices.json`
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("intent_choices.json")
+    }
+
+    /// 记录一次选择
+    pub fn record(&mut self, input: &str, intent: &str) -> Result<(), String> {
+        let input = normalize(input);
+        let now = Local::now();
+
+        if let Some(record) = self
+            .records
+            .iter_mut()
+            .find(|r| r.input == input && r.intent == intent)
+        {
+            record.count += 1;
+            record.last_chosen = now;
+        } else {
+            self.records.push(ChoiceRecord {
+                input,
+                intent: intent.to_string(),
+                count: 1,
+                last_chosen: now,
+            });
+        }
+
+        // 超出上限时淘汰最久未使用的记录
+        if self.records.len() > MAX_CHOICES {
+            self.records.sort_by_key(|r| std::cmp::Reverse(r.last_chosen));
+            self.records.truncate(MAX_CHOICES);
+        }
+
+        self.save()
+    }
+
+    /// 查找与输入最相似、且选择了该意图的历史记录
+    ///
+    /// 返回 `(历史输入, 相似度)`；相似度低于阈值时返回 None。
+    pub fn find_similar(&self, input: &str, intent: &str) -> Option<(&str, f64)> {
+        let input = normalize(input);
+        self.records
+            .iter()
+            .filter(|r| r.intent == intent)
+            .map(|r| (r.input.as_str(), string_similarity(&input, &r.input)))
+            .filter(|(_, similarity)| *similarity >= CHOICE_SIMILARITY_THRESHOLD)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    /// 记录数量
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&self.records)
+            .map_err(|e| format!("序列化选择记录失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入选择记录失败: {}", e))
+    }
+}
+
+fn normalize(input: &str) -> String {
+    input.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::intent::types::{Intent, IntentDomain};
+    use tempfile::TempDir;
+
+    fn candidate(name: &str, confidence: f64) -> IntentMatch {
+        IntentMatch::new(
+            Intent::new(name, IntentDomain::FileOps, vec![], vec![], 0.5),
+            confidence,
+        )
+    }
+
+    #[test]
+    fn test_ambiguous_candidates() {
+        let matches = vec![
+            candidate("a", 0.90),
+            candidate("b", 0.85),
+            candidate("c", 0.82),
+            candidate("d", 0.50),
+        ];
+
+        let close = ambiguous_candidates(&matches, 0.1, 5);
+        let names: Vec<&str> = close.iter().map(|m| m.intent.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c"]);
+
+        // 限制数量
+        assert_eq!(ambiguous_candidates(&matches, 0.1, 2).len(), 2);
+
+        // 差距明显时无需消歧
+        assert!(ambiguous_candidates(&matches, 0.01, 5).is_empty());
+        assert!(ambiguous_candidates(&matches[3..], 0.1, 5).is_empty());
+        assert!(ambiguous_candidates(&[], 0.1, 5).is_empty());
+    }
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("1", 3), Some(0));
+        assert_eq!(parse_choice(" 3 \n", 3), Some(2));
+        assert_eq!(parse_choice("0", 3), None);
+        assert_eq!(parse_choice("4", 3), None);
+        assert_eq!(parse_choice("", 3), None);
+        assert_eq!(parse_choice("abc", 3), None);
+    }
+
+    #[test]
+    fn test_format_menu() {
+        let menu = format_menu(&[candidate("a", 0.9), candidate("b", 0.85)]);
+        assert!(menu.contains("1. a"));
+        assert!(menu.contains("2. b"));
+        assert!(menu.contains("0."));
+    }
+
+    #[test]
+    fn test_explanation_display() {
+        let explanation = MatchExplanation {
+            intent_name: "find_files_by_size".to_string(),
+            confidence: 0.72,
+            threshold: 0.7,
+            contributions: vec![
+                ScoreContribution::new(ScoreSource::Keyword("最大".to_string()), 0.3),
+                ScoreContribution::new(
+                    ScoreSource::Semantic {
+                        example: "show the largest files".to_string(),
+                        similarity: 0.42,
+                    },
+                    0.42,
+                ),
+            ],
+            entities: HashMap::from([("limit".to_string(), EntityType::Number(10.0))]),
+        };
+
+        assert!(explanation.accepted());
+        let text = explanation.to_string();
+        assert!(text.contains("find_files_by_size"));
+        assert!(text.contains("+0.30  关键词 \"最大\""));
+        assert!(text.contains("语义 \"show the largest files\" (0.42)"));
+        assert!(text.contains("limit=10"));
+    }
+
+    #[test]
+    fn test_choice_memory_similarity() {
+        let mut memory = ChoiceMemory::new();
+        memory.record("显示最大的文件", "find_files_by_size").unwrap();
+        memory.record("显示最大的文件", "find_files_by_size").unwrap();
+
+        assert_eq!(memory.len(), 1);
+        assert_eq!(memory.records[0].count, 2);
+
+        // 相似输入命中，不同意图或差异大的输入不命中
+        assert!(memory.find_similar("显示最大的文件吧", "find_files_by_size").is_some());
+        assert!(memory.find_similar("显示最大的文件", "list_directory").is_none());
+        assert!(memory.find_similar("查看网络连接", "find_files_by_size").is_none());
+    }
+
+    #[test]
+    fn test_choice_memory_persistence() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("choices.json");
+
+        let mut memory = ChoiceMemory::load(&path);
+        assert!(memory.is_empty());
+        memory.record("Show Big Files", "find_files_by_size").unwrap();
+
+        let reloaded = ChoiceMemory::load(&path);
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.records[0].input, "show big files");
+    }
+}
@@ -52,7 +52,8 @@ impl LlmToPipeline {
     /// 2. 解析 JSON 响应
     /// 3. 检查是否适用（applicable）
     /// 4. 转换为 ExecutionPlan
-    /// 5. 安全验证
+    /// 5. 类型检查（类型错误时尝试修复，无法修复则拒绝）
+    /// 6. 安全验证
     pub async fn understand_and_generate(
         &self,
         user_input: &str,
@@ -74,7 +75,10 @@ impl LlmToPipeline {
         // 4. 转换为 ExecutionPlan
         let plan = self.to_execution_plan(llm_intent)?;
 
-        // 5. 安全验证
+        // 5. 类型检查（失败时尝试修复）
+        let plan = check_or_repair(plan)?;
+
+        // 6. 安全验证
         plan.validate_safety()?;
 
         Ok(plan)
@@ -259,6 +263,19 @@ pub struct ModifierJson {
 
 // ========== 辅助函数 ==========
 
+/// 类型检查 LLM 生成的计划，类型错误时尝试修复
+///
+/// LLM 偶尔会把数据源放在修饰操作之后、或给出 `limit: 0` 之类的参数，
+/// 能修复的直接修复，修复不了的拒绝执行。
+fn check_or_repair(plan: ExecutionPlan) -> Result<ExecutionPlan, String> {
+    match plan.type_check() {
+        Ok(_) => Ok(plan),
+        Err(e) => plan
+            .repaired()
+            .ok_or_else(|| format!("LLM 生成的计划类型检查失败: {}", e)),
+    }
+}
+
 /// 解析 Field 枚举
 fn parse_field(s: &str) -> Field {
     match s.to_lowercase().as_str() {
@@ -659,4 +676,27 @@ mod tests {
 
         assert!(bad_plan.validate_safety().is_err());
     }
+
+    #[test]
+    fn test_check_or_repair() {
+        let find = BaseOperation::FindFiles {
+            path: ".".to_string(),
+            pattern: "*.rs".to_string(),
+        };
+
+        // 合法计划原样返回
+        let plan = ExecutionPlan::new().with_operation(find.clone());
+        assert_eq!(check_or_repair(plan.clone()).unwrap(), plan);
+
+        // limit: 0 被修复（丢弃）
+        let plan = ExecutionPlan::new()
+            .with_operation(find.clone())
+            .with_operation(BaseOperation::LimitFiles { count: 0 });
+        assert_eq!(check_or_repair(plan).unwrap().operations, vec![find]);
+
+        // 没有数据源，无法修复
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::LimitFiles { count: 5 });
+        let err = check_or_repair(plan).unwrap_err();
+        assert!(err.contains("类型检查失败"));
+    }
 }
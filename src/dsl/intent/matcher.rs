@@ -2,6 +2,10 @@
 //!
 //! 负责将用户的自然语言输入匹配到预定义的意图。
 
+use crate::dsl::intent::embedding::SemanticIndex;
+use crate::dsl::intent::explain::{
+    self, ChoiceMemory, MatchExplanation, ScoreContribution, ScoreSource, CHOICE_BOOST,
+};
 use crate::dsl::intent::extractor::EntityExtractor;
 use crate::dsl::intent::types::{Intent, IntentMatch};
 use lru::LruCache;
@@ -28,7 +32,7 @@ use std::sync::{Arc, RwLock};
 /// # 示例
 ///
 /// ```
-/// use simpleconsole::dsl::intent::matcher::levenshtein_distance;
+/// use realconsole::dsl::intent::matcher::levenshtein_distance;
 ///
 /// assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
 /// assert_eq!(levenshtein_distance("统计", "统计"), 0);
@@ -104,10 +108,10 @@ pub fn levenshtein_distance(s1: &str, s2: &str) -> usize {
 /// # 示例
 ///
 /// ```
-/// use simpleconsole::dsl::intent::matcher::string_similarity;
+/// use realconsole::dsl::intent::matcher::string_similarity;
 ///
 /// assert_eq!(string_similarity("统计", "统计"), 1.0);
-/// assert!(string_similarity("统计", "统记") > 0.5);
+/// assert_eq!(string_similarity("统计", "统记"), 0.5);
 /// assert!(string_similarity("hello", "world") < 0.5);
 /// ```
 pub fn string_similarity(s1: &str, s2: &str) -> f64 {
@@ -129,7 +133,7 @@ pub fn string_similarity(s1: &str, s2: &str) -> f64 {
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+/// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
 ///
 /// let mut matcher = IntentMatcher::new();
 ///
@@ -170,11 +174,17 @@ pub struct IntentMatcher {
 
     /// 模糊匹配配置 (Fuzzy Matching)
     fuzzy_config: FuzzyConfig,
+
+    /// 语义索引（None 表示不启用语义匹配）
+    semantic: Option<SemanticIndex>,
+
+    /// 消歧选择记忆（用户选择过的意图会为相似输入加分）
+    choices: Arc<RwLock<ChoiceMemory>>,
 }
 
 /// 模糊匹配配置
 ///
-/// 控制关键词模糊匹配的行为。
+/// 控制关键词模糊匹配与语义匹配的行为。
 #[derive(Debug, Clone)]
 pub struct FuzzyConfig {
     /// 是否启用模糊匹配
@@ -189,6 +199,16 @@ pub struct FuzzyConfig {
     /// 精确匹配贡献 0.3 分，模糊匹配贡献 0.3 * fuzzy_weight 分
     /// 默认值：0.7（即模糊匹配贡献 0.21 分）
     pub fuzzy_weight: f64,
+
+    /// 语义匹配的分数权重（仅在启用语义索引时生效）
+    /// 语义匹配贡献 semantic_weight * 余弦相似度 分
+    /// 默认值：1.0
+    pub semantic_weight: f64,
+
+    /// 语义相似度阈值（0.0 到 1.0）
+    /// 只有与某个示例句的相似度 >= 该阈值时才计分
+    /// 默认值：0.5
+    pub semantic_threshold: f64,
 }
 
 impl Default for FuzzyConfig {
@@ -197,6 +217,8 @@ impl Default for FuzzyConfig {
             enabled: false,
             similarity_threshold: 0.8,
             fuzzy_weight: 0.7,
+            semantic_weight: 1.0,
+            semantic_threshold: 0.5,
         }
     }
 }
@@ -213,9 +235,17 @@ impl FuzzyConfig {
             enabled: true,
             similarity_threshold,
             fuzzy_weight,
+            ..Default::default()
         }
     }
 
+    /// 设置语义匹配的权重与阈值
+    pub fn with_semantic(mut self, semantic_weight: f64, semantic_threshold: f64) -> Self {
+        self.semantic_weight = semantic_weight;
+        self.semantic_threshold = semantic_threshold;
+        self
+    }
+
     /// 创建一个禁用模糊匹配的配置
     pub fn disabled() -> Self {
         Self {
@@ -233,7 +263,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::IntentMatcher;
+    /// use realconsole::dsl::intent::IntentMatcher;
     ///
     /// let matcher = IntentMatcher::new();
     /// ```
@@ -250,7 +280,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::IntentMatcher;
+    /// use realconsole::dsl::intent::IntentMatcher;
     ///
     /// // 创建一个缓存容量为 50 的匹配器
     /// let matcher = IntentMatcher::with_cache_capacity(50);
@@ -269,7 +299,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{IntentMatcher, FuzzyConfig};
+    /// use realconsole::dsl::intent::{IntentMatcher, FuzzyConfig};
     ///
     /// // 创建一个启用模糊匹配的匹配器
     /// let fuzzy_config = FuzzyConfig::enabled(0.8, 0.7);
@@ -286,6 +316,8 @@ impl IntentMatcher {
             cache_hits: Arc::new(RwLock::new(0)),
             cache_misses: Arc::new(RwLock::new(0)),
             fuzzy_config,
+            semantic: None,
+            choices: Arc::new(RwLock::new(ChoiceMemory::new())),
         }
     }
 
@@ -308,6 +340,41 @@ impl IntentMatcher {
         self.clear_cache();
     }
 
+    /// 启用语义匹配
+    ///
+    /// 为已注册意图的示例句建立索引，之后注册的意图也会自动索引。
+    ///
+    /// # 参数
+    ///
+    /// * `index` - 语义索引（决定使用哪个嵌入器与缓存）
+    /// * `semantic_weight` - 语义相似度的分数权重
+    /// * `semantic_threshold` - 语义相似度阈值（0.0 到 1.0）
+    pub fn enable_semantic_matching(
+        &mut self,
+        mut index: SemanticIndex,
+        semantic_weight: f64,
+        semantic_threshold: f64,
+    ) {
+        self.fuzzy_config.semantic_weight = semantic_weight;
+        self.fuzzy_config.semantic_threshold = semantic_threshold;
+
+        index.clear();
+        index.index_intents(&self.intents);
+        self.semantic = Some(index);
+        self.clear_cache();
+    }
+
+    /// 禁用语义匹配
+    pub fn disable_semantic_matching(&mut self) {
+        self.semantic = None;
+        self.clear_cache();
+    }
+
+    /// 语义匹配使用的模型（未启用时返回 None）
+    pub fn semantic_model(&self) -> Option<String> {
+        self.semantic.as_ref().map(|index| index.model_id())
+    }
+
     /// 注册一个意图
     ///
     /// 注册时会预编译所有正则表达式模式以提高匹配性能。
@@ -316,7 +383,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -342,6 +409,10 @@ impl IntentMatcher {
             }
         }
 
+        if let Some(index) = self.semantic.as_mut() {
+            index.index_intent(&intent);
+        }
+
         self.intents.push(intent);
 
         // 清空查询缓存，因为新意图会影响匹配结果
@@ -359,13 +430,15 @@ impl IntentMatcher {
     ///
     /// 1. **关键词匹配** - 每个匹配的关键词贡献 0.3 分
     /// 2. **正则模式匹配** - 每个匹配的模式贡献 0.7 分
-    /// 3. **置信度归一化** - 总分不超过 1.0
-    /// 4. **阈值过滤** - 只返回满足意图阈值的匹配
+    /// 3. **语义匹配**（启用时）- 与示例句的最高相似度 × semantic_weight
+    /// 4. **历史选择**（有其他依据时）- 与消歧时选过该意图的输入相似，最多加 0.2 分
+    /// 5. **置信度归一化** - 总分不超过 1.0
+    /// 6. **阈值过滤** - 只返回满足意图阈值的匹配
     ///
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -401,76 +474,16 @@ impl IntentMatcher {
 
         let mut matches = Vec::new();
 
-        // 将输入转换为小写以进行不区分大小写的匹配
-        let input_lower = input.to_lowercase();
+        // 语义匹配：每次查询只计算一次输入向量（失败时仅跳过语义计分）
+        let query_vector = self.query_vector(input);
 
         for intent in &self.intents {
-            let mut score: f64 = 0.0;
-            let mut matched_keywords = Vec::new();
-
-            // 1. 关键词匹配（每个关键词 0.3 分，模糊匹配权重更低）
-            for keyword in &intent.keywords {
-                let keyword_lower = keyword.to_lowercase();
-                let mut matched = false;
-
-                // 精确匹配：检查输入中的每个词是否包含关键词
-                for input_word in input_lower.split_whitespace() {
-                    if input_word.contains(&keyword_lower) {
-                        score += 0.3;
-                        matched_keywords.push(keyword.clone());
-                        matched = true;
-                        break;
-                    }
-                }
-
-                // 模糊匹配：如果精确匹配失败且启用模糊匹配
-                if !matched && self.fuzzy_config.enabled {
-                    let mut best_similarity = 0.0;
-                    let keyword_len = keyword_lower.chars().count();
-
-                    for input_word in input_lower.split_whitespace() {
-                        let input_len = input_word.chars().count();
-
-                        // ✨ 长度预筛选优化：如果长度差异太大，跳过计算
-                        // 例如：threshold=0.8 时，长度比率必须 >= 0.8
-                        let len_ratio = if input_len < keyword_len {
-                            input_len as f64 / keyword_len as f64
-                        } else {
-                            keyword_len as f64 / input_len as f64
-                        };
-
-                        // 长度比率低于阈值，相似度不可能达标，跳过
-                        if len_ratio < self.fuzzy_config.similarity_threshold {
-                            continue;
-                        }
-
-                        let similarity = string_similarity(input_word, &keyword_lower);
-                        if similarity > best_similarity {
-                            best_similarity = similarity;
-                        }
-                    }
-
-                    // 如果相似度超过阈值，按权重计分
-                    if best_similarity >= self.fuzzy_config.similarity_threshold {
-                        score += 0.3 * self.fuzzy_config.fuzzy_weight * best_similarity;
-                        matched_keywords.push(format!("{}~", keyword)); // 使用 ~ 标记模糊匹配
-                    }
-                }
-            }
-
-            // 2. 正则模式匹配（每个模式 0.7 分）
-            for pattern in &intent.patterns {
-                if let Some(regex) = self.regex_cache.get(pattern) {
-                    if regex.is_match(input) {
-                        score += 0.7;
-                    }
-                }
-            }
+            let contributions = self.score_intent(intent, input, query_vector.as_deref());
 
-            // 3. 归一化置信度（不超过 1.0）
-            let confidence = score.min(1.0);
+            // 5. 归一化置信度（不超过 1.0）
+            let confidence = total_score(&contributions);
 
-            // 4. 只保留满足阈值的匹配
+            // 6. 只保留满足阈值的匹配
             if confidence >= intent.confidence_threshold {
                 // ✨ Phase 3 Week 3: 实体提取
                 let extracted_entities = self.extractor.extract(input, &intent.entities);
@@ -478,7 +491,7 @@ impl IntentMatcher {
                 let intent_match = IntentMatch {
                     intent: intent.clone(),
                     confidence,
-                    matched_keywords,
+                    matched_keywords: matched_keywords(&contributions),
                     extracted_entities,
                 };
                 matches.push(intent_match);
@@ -507,7 +520,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -528,12 +541,226 @@ impl IntentMatcher {
         self.match_intent(input).into_iter().next()
     }
 
+    /// 解释每个候选意图的得分构成
+    ///
+    /// 返回所有得分大于 0 的意图（按置信度降序），包括未达到阈值的候选，
+    /// 便于回答"为什么匹配到它 / 为什么没匹配到它"。
+    ///
+    /// # 示例
+    ///
+    /// ```rust
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher, ScoreSource};
+    ///
+    /// let mut matcher = IntentMatcher::new();
+    /// matcher.register(Intent::new(
+    ///     "count_lines",
+    ///     IntentDomain::FileOps,
+    ///     vec!["统计".to_string(), "行数".to_string()],
+    ///     vec![],
+    ///     0.5,
+    /// ));
+    ///
+    /// let explanations = matcher.explain("统计 行数");
+    /// assert_eq!(explanations[0].intent_name, "count_lines");
+    /// assert_eq!(explanations[0].contributions[0].source, ScoreSource::Keyword("统计".to_string()));
+    /// ```
+    pub fn explain(&self, input: &str) -> Vec<MatchExplanation> {
+        let query_vector = self.query_vector(input);
+
+        let mut explanations: Vec<MatchExplanation> = self
+            .intents
+            .iter()
+            .filter_map(|intent| {
+                let contributions = self.score_intent(intent, input, query_vector.as_deref());
+                if contributions.is_empty() {
+                    return None;
+                }
+
+                let confidence = total_score(&contributions);
+                let entities = if confidence >= intent.confidence_threshold {
+                    self.extractor.extract(input, &intent.entities)
+                } else {
+                    HashMap::new()
+                };
+
+                Some(MatchExplanation {
+                    intent_name: intent.name.clone(),
+                    confidence,
+                    threshold: intent.confidence_threshold,
+                    contributions,
+                    entities,
+                })
+            })
+            .collect();
+
+        explanations.sort_by(|a, b| {
+            b.confidence
+                .partial_cmp(&a.confidence)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        explanations
+    }
+
+    /// 得分相近、需要用户消歧的候选
+    ///
+    /// 前两名置信度之差不超过 `margin` 时返回所有相近候选（最多 5 个），
+    /// 否则返回空列表。
+    pub fn ambiguous_candidates(&self, input: &str, margin: f64) -> Vec<IntentMatch> {
+        explain::ambiguous_candidates(&self.match_intent(input), margin, 5)
+    }
+
+    /// 使用指定的选择记忆（如从磁盘加载的历史选择）
+    pub fn set_choice_memory(&mut self, memory: ChoiceMemory) {
+        if let Ok(mut choices) = self.choices.write() {
+            *choices = memory;
+        }
+        self.clear_cache();
+    }
+
+    /// 记录用户在消歧时的选择，之后相似输入会偏向该意图
+    pub fn record_choice(&self, input: &str, intent_name: &str) -> Result<(), String> {
+        let result = match self.choices.write() {
+            Ok(mut choices) => choices.record(input, intent_name),
+            Err(_) => Err("选择记忆不可用".to_string()),
+        };
+
+        // 选择会影响匹配结果，缓存需要失效
+        if let Ok(mut cache) = self.query_cache.write() {
+            cache.clear();
+        }
+        result
+    }
+
+    /// 计算输入的语义向量（未启用语义匹配或嵌入失败时返回 None）
+    fn query_vector(&self, input: &str) -> Option<Vec<f32>> {
+        self.semantic
+            .as_ref()
+            .filter(|index| !index.is_empty())
+            .and_then(|index| index.embed_query(input).ok())
+    }
+
+    /// 计算单个意图的得分构成
+    fn score_intent(
+        &self,
+        intent: &Intent,
+        input: &str,
+        query_vector: Option<&[f32]>,
+    ) -> Vec<ScoreContribution> {
+        let mut contributions = Vec::new();
+
+        // 将输入转换为小写以进行不区分大小写的匹配
+        let input_lower = input.to_lowercase();
+
+        // 1. 关键词匹配（每个关键词 0.3 分，模糊匹配权重更低）
+        for keyword in &intent.keywords {
+            let keyword_lower = keyword.to_lowercase();
+
+            // 精确匹配：检查输入中的每个词是否包含关键词
+            if input_lower
+                .split_whitespace()
+                .any(|input_word| input_word.contains(&keyword_lower))
+            {
+                contributions.push(ScoreContribution::new(
+                    ScoreSource::Keyword(keyword.clone()),
+                    0.3,
+                ));
+                continue;
+            }
+
+            // 模糊匹配：如果精确匹配失败且启用模糊匹配
+            if self.fuzzy_config.enabled {
+                let mut best: Option<(&str, f64)> = None;
+                let keyword_len = keyword_lower.chars().count();
+
+                for input_word in input_lower.split_whitespace() {
+                    let input_len = input_word.chars().count();
+
+                    // ✨ 长度预筛选优化：如果长度差异太大，跳过计算
+                    // 例如：threshold=0.8 时，长度比率必须 >= 0.8
+                    let len_ratio = if input_len < keyword_len {
+                        input_len as f64 / keyword_len as f64
+                    } else {
+                        keyword_len as f64 / input_len as f64
+                    };
+
+                    // 长度比率低于阈值，相似度不可能达标，跳过
+                    if len_ratio < self.fuzzy_config.similarity_threshold {
+                        continue;
+                    }
+
+                    let similarity = string_similarity(input_word, &keyword_lower);
+                    if best.is_none_or(|(_, s)| similarity > s) {
+                        best = Some((input_word, similarity));
+                    }
+                }
+
+                // 如果相似度超过阈值，按权重计分
+                if let Some((word, similarity)) = best {
+                    if similarity >= self.fuzzy_config.similarity_threshold {
+                        contributions.push(ScoreContribution::new(
+                            ScoreSource::FuzzyKeyword {
+                                keyword: keyword.clone(),
+                                word: word.to_string(),
+                                similarity,
+                            },
+                            0.3 * self.fuzzy_config.fuzzy_weight * similarity,
+                        ));
+                    }
+                }
+            }
+        }
+
+        // 2. 正则模式匹配（每个模式 0.7 分）
+        for pattern in &intent.patterns {
+            if let Some(regex) = self.regex_cache.get(pattern) {
+                if regex.is_match(input) {
+                    contributions.push(ScoreContribution::new(
+                        ScoreSource::Pattern(pattern.clone()),
+                        0.7,
+                    ));
+                }
+            }
+        }
+
+        // 3. 语义匹配（与示例句的最高余弦相似度）
+        if let (Some(index), Some(query)) = (&self.semantic, query_vector) {
+            if let Some((example, similarity)) = index.best_example(&intent.name, query) {
+                if similarity >= self.fuzzy_config.semantic_threshold {
+                    contributions.push(ScoreContribution::new(
+                        ScoreSource::Semantic {
+                            example: example.to_string(),
+                            similarity,
+                        },
+                        self.fuzzy_config.semantic_weight * similarity,
+                    ));
+                }
+            }
+        }
+
+        // 4. 历史选择加分（仅在已有其他依据时生效，避免凭空匹配）
+        if !contributions.is_empty() {
+            if let Ok(choices) = self.choices.read() {
+                if let Some((past_input, similarity)) = choices.find_similar(input, &intent.name) {
+                    contributions.push(ScoreContribution::new(
+                        ScoreSource::Choice {
+                            input: past_input.to_string(),
+                            similarity,
+                        },
+                        CHOICE_BOOST * similarity,
+                    ));
+                }
+            }
+        }
+
+        contributions
+    }
+
     /// 获取已注册的意图数量
     ///
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     /// assert_eq!(matcher.len(), 0);
@@ -557,7 +784,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::IntentMatcher;
+    /// use realconsole::dsl::intent::IntentMatcher;
     ///
     /// let matcher = IntentMatcher::new();
     /// assert!(matcher.is_empty());
@@ -573,7 +800,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -593,6 +820,9 @@ impl IntentMatcher {
     pub fn clear(&mut self) {
         self.intents.clear();
         self.regex_cache.clear();
+        if let Some(index) = self.semantic.as_mut() {
+            index.clear();
+        }
         self.clear_cache();
     }
 
@@ -604,7 +834,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -648,7 +878,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -682,7 +912,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -716,7 +946,7 @@ impl IntentMatcher {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain, IntentMatcher};
     ///
     /// let mut matcher = IntentMatcher::new();
     ///
@@ -765,6 +995,23 @@ impl Default for IntentMatcher {
     }
 }
 
+/// 得分构成的总分（归一化到不超过 1.0）
+fn total_score(contributions: &[ScoreContribution]) -> f64 {
+    contributions.iter().map(|c| c.score).sum::<f64>().min(1.0)
+}
+
+/// 从得分构成中提取匹配到的关键词（模糊匹配以 ~ 标记）
+fn matched_keywords(contributions: &[ScoreContribution]) -> Vec<String> {
+    contributions
+        .iter()
+        .filter_map(|c| match &c.source {
+            ScoreSource::Keyword(keyword) => Some(keyword.clone()),
+            ScoreSource::FuzzyKeyword { keyword, .. } => Some(format!("{}~", keyword)),
+            _ => None,
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1556,4 +1803,170 @@ mod tests {
         assert!(!matches.is_empty(), "Should fuzzy match after enabling");
         assert!(matches[0].matched_keywords.iter().any(|k| k.contains('~')));
     }
+
+    fn size_intent() -> Intent {
+        Intent::new(
+            "find_files_by_size",
+            IntentDomain::FileOps,
+            vec!["大文件".to_string()],
+            Vec::new(),
+            0.5,
+        )
+        .with_examples(vec![
+            "which files take up the most space".to_string(),
+            "show the largest files".to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_semantic_matching_paraphrase() {
+        let mut matcher = IntentMatcher::new();
+        matcher.register(size_intent());
+
+        // 关键词与正则都无法命中的改写
+        assert!(matcher.match_intent("which files hog the most space").is_empty());
+
+        matcher.enable_semantic_matching(SemanticIndex::local(), 1.0, 0.5);
+        let matches = matcher.match_intent("which files hog the most space");
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].intent.name, "find_files_by_size");
+        assert!(matches[0].confidence > 0.5);
+        assert_eq!(matcher.semantic_model().as_deref(), Some("hashing-512"));
+    }
+
+    #[test]
+    fn test_semantic_matching_threshold_and_disable() {
+        let mut matcher = IntentMatcher::new();
+        matcher.enable_semantic_matching(SemanticIndex::local(), 1.0, 0.5);
+
+        // 启用后注册的意图也会被索引
+        matcher.register(size_intent());
+        assert!(!matcher.match_intent("which files hog the most space").is_empty());
+
+        // 无关输入低于阈值
+        assert!(matcher.match_intent("how long has the system been running").is_empty());
+
+        matcher.disable_semantic_matching();
+        assert!(matcher.match_intent("which files hog the most space").is_empty());
+        assert_eq!(matcher.semantic_model(), None);
+    }
+
+    #[test]
+    fn test_semantic_matching_blends_with_keywords() {
+        let mut matcher = IntentMatcher::new();
+        matcher.register(size_intent());
+        matcher.enable_semantic_matching(SemanticIndex::local(), 0.5, 0.5);
+
+        // 关键词 0.3 + 语义 0.5 × 相似度
+        let matches = matcher.match_intent("show the largest files 大文件");
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].confidence > 0.6);
+        assert!(matches[0].matched_keywords.contains(&"大文件".to_string()));
+    }
+
+    fn keyword_intent(name: &str, keywords: &[&str]) -> Intent {
+        Intent::new(
+            name,
+            IntentDomain::FileOps,
+            keywords.iter().map(|k| k.to_string()).collect(),
+            vec![],
+            0.3,
+        )
+    }
+
+    #[test]
+    fn test_explain_contributions() {
+        let mut matcher = IntentMatcher::new();
+        matcher.enable_fuzzy_matching(0.8, 0.8);
+        matcher.register(Intent::new(
+            "count_lines",
+            IntentDomain::FileOps,
+            vec!["统计".to_string(), "python".to_string()],
+            vec![r"统计.*行数".to_string()],
+            0.5,
+        ));
+        matcher.register(keyword_intent("unrelated", &["网络"]));
+
+        let explanations = matcher.explain("统计 pythn 代码行数");
+
+        // 无任何得分的意图不出现
+        assert_eq!(explanations.len(), 1);
+        let explanation = &explanations[0];
+        assert_eq!(explanation.intent_name, "count_lines");
+        assert!(explanation.accepted());
+
+        let sources: Vec<&ScoreSource> = explanation.contributions.iter().map(|c| &c.source).collect();
+        assert_eq!(sources[0], &ScoreSource::Keyword("统计".to_string()));
+        assert!(matches!(
+            sources[1],
+            ScoreSource::FuzzyKeyword { keyword, word, .. } if keyword == "python" && word == "pythn"
+        ));
+        assert_eq!(sources[2], &ScoreSource::Pattern(r"统计.*行数".to_string()));
+
+        // 解释与实际匹配结果一致
+        let matches = matcher.match_intent("统计 pythn 代码行数");
+        assert_eq!(matches[0].confidence, explanation.confidence);
+        assert_eq!(matches[0].matched_keywords, vec!["统计".to_string(), "python~".to_string()]);
+    }
+
+    #[test]
+    fn test_explain_includes_rejected_candidates() {
+        let mut matcher = IntentMatcher::new();
+        matcher.register(Intent::new(
+            "strict",
+            IntentDomain::FileOps,
+            vec!["文件".to_string()],
+            vec![],
+            0.9,
+        ));
+
+        assert!(matcher.match_intent("查看 文件").is_empty());
+
+        let explanations = matcher.explain("查看 文件");
+        assert_eq!(explanations.len(), 1);
+        assert!(!explanations[0].accepted());
+        assert!(explanations[0].entities.is_empty());
+    }
+
+    #[test]
+    fn test_ambiguous_candidates_and_choice_boost() {
+        let mut matcher = IntentMatcher::new();
+        matcher.register(keyword_intent("list_files", &["文件"]));
+        matcher.register(keyword_intent("find_files", &["文件"]));
+
+        let candidates = matcher.ambiguous_candidates("列出 文件", 0.1);
+        assert_eq!(candidates.len(), 2);
+
+        // 记录选择后，相似输入偏向被选中的意图，不再需要消歧
+        matcher.record_choice("列出 文件", "find_files").unwrap();
+        let matches = matcher.match_intent("列出 文件");
+        assert_eq!(matches[0].intent.name, "find_files");
+        assert!(matches[0].confidence > matches[1].confidence);
+        assert!(matcher.ambiguous_candidates("列出 文件", 0.1).is_empty());
+
+        let explanation = &matcher.explain("列出 文件")[0];
+        assert!(explanation
+            .contributions
+            .iter()
+            .any(|c| matches!(c.source, ScoreSource::Choice { .. })));
+
+        // 历史选择不会让毫无依据的意图凭空匹配
+        matcher.register(keyword_intent("network", &["网络"]));
+        matcher.record_choice("列出 端口", "network").unwrap();
+        assert!(matcher.match_intent("列出 端口").is_empty());
+    }
+
+    #[test]
+    fn test_set_choice_memory() {
+        let mut memory = ChoiceMemory::new();
+        memory.record("列出 文件", "list_files").unwrap();
+
+        let mut matcher = IntentMatcher::new();
+        matcher.register(keyword_intent("list_files", &["文件"]));
+        matcher.register(keyword_intent("find_files", &["文件"]));
+        matcher.set_choice_memory(memory);
+
+        assert_eq!(matcher.best_match("列出 文件").unwrap().intent.name, "list_files");
+    }
 }
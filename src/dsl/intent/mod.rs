@@ -14,6 +14,7 @@
 //! intent/
 //! ├── types.rs          - 核心数据结构定义 ✅
 //! ├── matcher.rs        - 意图匹配引擎 ✅
+//! ├── embedding.rs      - 语义匹配（示例句嵌入）✅
 //! ├── template.rs       - 模板系统 ✅
 //! ├── builtin.rs        - 内置意图和模板库 ✅
 //! ├── extractor.rs      - 实体提取引擎 ✅ (Phase 3 Week 3 + Phase 2 LLM)
@@ -25,7 +26,7 @@
 //! ## 使用示例
 //!
 //! ```rust
-//! use simpleconsole::dsl::intent::{Intent, IntentMatcher, IntentDomain};
+//! use realconsole::dsl::intent::{Intent, IntentMatcher, IntentDomain};
 //!
 //! let mut matcher = IntentMatcher::new();
 //!
@@ -37,6 +38,7 @@
 //!     patterns: vec![r"统计.*行数".to_string()],
 //!     entities: std::collections::HashMap::new(),
 //!     confidence_threshold: 0.5,
+//!     examples: vec![],
 //! });
 //!
 //! // 匹配用户输入
@@ -45,6 +47,8 @@
 
 pub mod types;
 pub mod matcher;
+pub mod embedding;  // 语义匹配：示例句向量与相似度
+pub mod explain;  // 匹配解释与消歧
 pub mod template;
 pub mod builtin;
 pub mod extractor;
@@ -58,7 +62,9 @@ pub mod workflow_templates;  // ✨ Phase 8: Builtin Workflow Templates
 pub use types::{
     EntityType, Intent, IntentDomain, IntentMatch,
 };
-pub use matcher::IntentMatcher;
+pub use matcher::{FuzzyConfig, IntentMatcher};
+pub use embedding::{Embedder, EmbeddingCache, FailFastEmbedder, HashingEmbedder, OllamaEmbedder, SemanticIndex};
+pub use explain::{ChoiceMemory, MatchExplanation, ScoreContribution, ScoreSource};
 pub use template::{Template, TemplateEngine, ExecutionPlan};
 pub use builtin::BuiltinIntents;
 pub use extractor::EntityExtractor;
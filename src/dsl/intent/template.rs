@@ -8,6 +8,7 @@
 //! - 返璞归真：使用最简单的 {variable} 语法
 
 use crate::dsl::intent::types::{EntityType, IntentMatch};
+use crate::dsl::pipeline::ExecutionPlan as PipelinePlan;
 use std::collections::HashMap;
 
 /// 命令模板
@@ -23,7 +24,7 @@ use std::collections::HashMap;
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::Template;
+/// use realconsole::dsl::intent::Template;
 ///
 /// let template = Template::new(
 ///     "count_files",
@@ -60,7 +61,7 @@ pub struct Template {
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::ExecutionPlan;
+/// use realconsole::dsl::intent::ExecutionPlan;
 /// use std::collections::HashMap;
 ///
 /// // ExecutionPlan 通常由 TemplateEngine 生成
@@ -68,6 +69,7 @@ pub struct Template {
 ///     command: "find . -name '*.py' | wc -l".to_string(),
 ///     template_name: "count_files".to_string(),
 ///     bindings: HashMap::new(),
+///     pipeline: None,
 /// };
 /// ```
 #[derive(Debug, Clone)]
@@ -80,6 +82,9 @@ pub struct ExecutionPlan {
 
     /// 变量绑定（变量名 -> 值）
     pub bindings: HashMap<String, String>,
+
+    /// 来源 Pipeline 计划（由 Pipeline DSL 生成时存在，供原生后端执行）
+    pub pipeline: Option<PipelinePlan>,
 }
 
 /// 模板引擎
@@ -95,7 +100,7 @@ pub struct ExecutionPlan {
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::{Template, TemplateEngine};
+/// use realconsole::dsl::intent::{Template, TemplateEngine};
 /// use std::collections::HashMap;
 ///
 /// let mut engine = TemplateEngine::new();
@@ -134,7 +139,7 @@ impl Template {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::Template;
+    /// use realconsole::dsl::intent::Template;
     ///
     /// let template = Template::new(
     ///     "grep_files",
@@ -200,7 +205,7 @@ impl TemplateEngine {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::TemplateEngine;
+    /// use realconsole::dsl::intent::TemplateEngine;
     ///
     /// let engine = TemplateEngine::new();
     /// ```
@@ -215,7 +220,7 @@ impl TemplateEngine {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Template, TemplateEngine};
+    /// use realconsole::dsl::intent::{Template, TemplateEngine};
     ///
     /// let mut engine = TemplateEngine::new();
     ///
@@ -250,7 +255,7 @@ impl TemplateEngine {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Template, TemplateEngine};
+    /// use realconsole::dsl::intent::{Template, TemplateEngine};
     /// use std::collections::HashMap;
     ///
     /// let mut engine = TemplateEngine::new();
@@ -294,6 +299,7 @@ impl TemplateEngine {
             command,
             template_name: template_name.to_string(),
             bindings,
+            pipeline: None,
         })
     }
 
@@ -304,7 +310,7 @@ impl TemplateEngine {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentMatch, IntentDomain, Template, TemplateEngine};
+    /// use realconsole::dsl::intent::{Intent, IntentMatch, IntentDomain, Template, TemplateEngine};
     /// use std::collections::HashMap;
     ///
     /// let mut engine = TemplateEngine::new();
@@ -376,7 +382,7 @@ impl TemplateEngine {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::TemplateEngine;
+    /// use realconsole::dsl::intent::TemplateEngine;
     /// use std::collections::HashMap;
     ///
     /// let mut bindings = HashMap::new();
@@ -439,6 +445,21 @@ impl ExecutionPlan {
             command: command.into(),
             template_name: template_name.into(),
             bindings,
+            pipeline: None,
+        }
+    }
+
+    /// 从 Pipeline 计划创建（命令即计划的 Shell 形式）
+    pub fn from_pipeline(
+        pipeline: PipelinePlan,
+        template_name: impl Into<String>,
+        bindings: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            command: pipeline.to_shell_command(),
+            template_name: template_name.into(),
+            bindings,
+            pipeline: Some(pipeline),
         }
     }
 
@@ -12,7 +12,7 @@ use std::collections::HashMap;
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::{Intent, IntentDomain};
+/// use realconsole::dsl::intent::{Intent, IntentDomain};
 /// use std::collections::HashMap;
 ///
 /// let intent = Intent {
@@ -22,6 +22,7 @@ use std::collections::HashMap;
 ///     patterns: vec![r"统计.*python.*行数".to_string()],
 ///     entities: HashMap::new(),
 ///     confidence_threshold: 0.5,
+///     examples: vec![],
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +44,10 @@ pub struct Intent {
 
     /// 置信度阈值（0.0 - 1.0）
     pub confidence_threshold: f64,
+
+    /// 示例句（用于语义匹配，可为空）
+    #[serde(default)]
+    pub examples: Vec<String>,
 }
 
 /// 意图领域分类
@@ -97,7 +102,7 @@ pub enum EntityType {
 /// # 示例
 ///
 /// ```rust
-/// use simpleconsole::dsl::intent::{IntentMatch, Intent, IntentDomain};
+/// use realconsole::dsl::intent::{IntentMatch, Intent, IntentDomain};
 /// use std::collections::HashMap;
 ///
 /// let intent = Intent {
@@ -107,6 +112,7 @@ pub enum EntityType {
 ///     patterns: vec![],
 ///     entities: HashMap::new(),
 ///     confidence_threshold: 0.5,
+///     examples: vec![],
 /// };
 ///
 /// let intent_match = IntentMatch {
@@ -139,7 +145,7 @@ impl Intent {
     /// # 示例
     ///
     /// ```rust
-    /// use simpleconsole::dsl::intent::{Intent, IntentDomain};
+    /// use realconsole::dsl::intent::{Intent, IntentDomain};
     ///
     /// let intent = Intent::new(
     ///     "count_lines",
@@ -163,6 +169,7 @@ impl Intent {
             patterns,
             entities: HashMap::new(),
             confidence_threshold,
+            examples: Vec::new(),
         }
     }
 
@@ -172,6 +179,12 @@ impl Intent {
         self
     }
 
+    /// 添加示例句（语义匹配时与用户输入比较）
+    pub fn with_examples(mut self, examples: Vec<String>) -> Self {
+        self.examples = examples;
+        self
+    }
+
     /// 检查置信度是否满足阈值
     pub fn meets_threshold(&self, confidence: f64) -> bool {
         confidence >= self.confidence_threshold
@@ -226,6 +239,7 @@ mod tests {
             patterns: vec![r"统计.*python.*行数".to_string()],
             entities: HashMap::new(),
             confidence_threshold: 0.5,
+            examples: vec![],
         };
 
         assert_eq!(intent.name, "count_python_lines");
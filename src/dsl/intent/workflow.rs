@@ -16,13 +16,22 @@
 //! - **ExecutionContext**: 工作流执行上下文（参数、中间结果等）
 
 use crate::dsl::intent::types::{EntityType, Intent, IntentMatch};
+use crate::dsl::type_system::{
+    CompositeType, PlanTypeError, Signature, Type, TypeChecker, TypeError,
+};
 use crate::llm::LlmClient;
 use crate::tool::ToolRegistry;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// 模板占位符（`{variable}`）
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap());
+
 /// 工作流步骤类型
 ///
 /// 定义了工作流中可以执行的操作类型
@@ -187,6 +196,47 @@ impl WorkflowIntent {
         self
     }
 
+    /// 执行前类型检查
+    ///
+    /// 以意图实体为初始环境（实体名 → 类型），按顺序检查每个步骤：
+    /// - 模板占位符与输入键必须引用已定义的参数或前序步骤结果
+    /// - Transform 的输入类型必须与其签名兼容（如 ExtractJson 需要文本）
+    ///
+    /// 每个步骤的结果以其输出类型加入环境，供后续步骤引用。
+    pub fn type_check(&self) -> Result<(), PlanTypeError> {
+        let checker = TypeChecker::new();
+        let mut env: HashMap<String, Type> = self
+            .base_intent
+            .entities
+            .iter()
+            .map(|(name, entity)| (name.clone(), entity_type(entity)))
+            .collect();
+
+        for (index, step) in self.workflow_steps.iter().enumerate() {
+            let step_name = step.name();
+
+            for (name, context) in step.referenced_variables() {
+                let Some(actual) = env.get(&name) else {
+                    return Err(PlanTypeError::new(
+                        index + 1,
+                        step_name,
+                        TypeError::UndefinedVariable { name, context },
+                    ));
+                };
+
+                if let WorkflowStep::Transform { .. } = step {
+                    checker
+                        .check_assignable(&step.signature().input, actual, &context)
+                        .map_err(|e| PlanTypeError::new(index + 1, step_name.clone(), e))?;
+                }
+            }
+
+            env.insert(step.result_key().to_string(), step.signature().output);
+        }
+
+        Ok(())
+    }
+
     /// 从 IntentMatch 提取参数
     pub fn extract_parameters(&self, intent_match: &IntentMatch) -> HashMap<String, String> {
         let mut parameters = HashMap::new();
@@ -207,6 +257,99 @@ impl WorkflowIntent {
     }
 }
 
+impl WorkflowStep {
+    /// 步骤名称（用于错误报告）
+    pub fn name(&self) -> String {
+        match self {
+            WorkflowStep::ToolCall { tool_name, .. } => format!("ToolCall({})", tool_name),
+            WorkflowStep::LlmAnalyze { .. } => "LlmAnalyze".to_string(),
+            WorkflowStep::Transform { operation, .. } => format!("Transform({})", operation.name()),
+        }
+    }
+
+    /// 结果存储的键名
+    pub fn result_key(&self) -> &str {
+        match self {
+            WorkflowStep::ToolCall { result_key, .. }
+            | WorkflowStep::LlmAnalyze { result_key, .. }
+            | WorkflowStep::Transform { result_key, .. } => result_key,
+        }
+    }
+
+    /// 输入/输出签名
+    ///
+    /// - ToolCall: `Dict<String, String> → String`（参数表 → 工具输出）
+    /// - LlmAnalyze: `String → String`（提示词 → 分析结果）
+    /// - Transform: 见 [`TransformOperation::signature`]
+    pub fn signature(&self) -> Signature {
+        match self {
+            WorkflowStep::ToolCall { .. } => Signature::new(
+                Type::Composite(CompositeType::Dict(
+                    Box::new(Type::string()),
+                    Box::new(Type::string()),
+                )),
+                Type::string(),
+            ),
+            WorkflowStep::LlmAnalyze { .. } => Signature::new(Type::string(), Type::string()),
+            WorkflowStep::Transform { operation, .. } => operation.signature(),
+        }
+    }
+
+    /// 步骤引用的变量（模板占位符与输入键），附带引用位置
+    pub fn referenced_variables(&self) -> Vec<(String, String)> {
+        let mut refs = Vec::new();
+
+        match self {
+            WorkflowStep::ToolCall { args_template, .. } => {
+                let mut args: Vec<_> = args_template.iter().collect();
+                args.sort_by(|a, b| a.0.cmp(b.0));
+                for (arg, template) in args {
+                    for name in placeholders(template) {
+                        refs.push((name, format!("args.{}", arg)));
+                    }
+                }
+            }
+            WorkflowStep::LlmAnalyze { prompt_template, .. } => {
+                for name in placeholders(prompt_template) {
+                    refs.push((name, "prompt_template".to_string()));
+                }
+            }
+            WorkflowStep::Transform { input_key, .. } => {
+                refs.push((input_key.clone(), "input_key".to_string()));
+            }
+        }
+
+        refs
+    }
+}
+
+impl TransformOperation {
+    /// 操作名称
+    pub fn name(&self) -> &str {
+        match self {
+            TransformOperation::ExtractJson { .. } => "extract_json",
+            TransformOperation::FormatMarkdown => "format_markdown",
+            TransformOperation::Truncate { .. } => "truncate",
+            TransformOperation::Custom { function_name } => function_name,
+        }
+    }
+
+    /// 输入/输出签名
+    ///
+    /// - ExtractJson: `String → String`（输入必须是 JSON 文本）
+    /// - FormatMarkdown / Truncate: `Any → String`
+    /// - Custom: `Any → Any`
+    pub fn signature(&self) -> Signature {
+        match self {
+            TransformOperation::ExtractJson { .. } => Signature::new(Type::string(), Type::string()),
+            TransformOperation::FormatMarkdown | TransformOperation::Truncate { .. } => {
+                Signature::new(Type::Any, Type::string())
+            }
+            TransformOperation::Custom { .. } => Signature::new(Type::Any, Type::Any),
+        }
+    }
+}
+
 impl ExecutionContext {
     /// 创建新的执行上下文
     pub fn new(parameters: HashMap<String, String>) -> Self {
@@ -283,6 +426,11 @@ impl WorkflowExecutor {
         workflow_intent: &WorkflowIntent,
         intent_match: &IntentMatch,
     ) -> Result<WorkflowResult, String> {
+        // 0. 类型检查（引用错误的工作流不执行）
+        workflow_intent
+            .type_check()
+            .map_err(|e| format!("工作流类型检查失败: {}", e))?;
+
         // 1. 提取参数
         let parameters = workflow_intent.extract_parameters(intent_match);
 
@@ -525,6 +673,27 @@ fn entity_to_string(entity: &EntityType) -> String {
     }
 }
 
+/// 实体对应的类型
+fn entity_type(entity: &EntityType) -> Type {
+    match entity {
+        EntityType::Path(_) => Type::file_path(),
+        EntityType::Number(_) => Type::float(),
+        _ => Type::string(),
+    }
+}
+
+/// 提取模板中的占位符名称（按出现顺序，去重）
+fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for caps in PLACEHOLDER_REGEX.captures_iter(template) {
+        let name = caps[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
 /// 生成缓存键
 fn generate_cache_key(intent_name: &str, parameters: &HashMap<String, String>) -> String {
     let mut keys: Vec<_> = parameters.keys().collect();
@@ -595,4 +764,107 @@ mod tests {
         assert!(key.contains("a=1"));
         assert!(key.contains("b=2"));
     }
+
+    fn intent_with_entity() -> Intent {
+        Intent::new(
+            "test_workflow",
+            IntentDomain::Custom("Test".to_string()),
+            vec![],
+            vec![],
+            0.5,
+        )
+        .with_entity("url", EntityType::Custom("url".to_string(), "https://example.com".to_string()))
+        .with_entity("count", EntityType::Number(3.0))
+    }
+
+    fn fetch_step() -> WorkflowStep {
+        let mut args = HashMap::new();
+        args.insert("url".to_string(), "{url}".to_string());
+        WorkflowStep::ToolCall {
+            tool_name: "http_get".to_string(),
+            args_template: args,
+            result_key: "page".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_builtin_workflows_type_check() {
+        for workflow in crate::dsl::intent::register_builtin_workflows() {
+            assert!(
+                workflow.type_check().is_ok(),
+                "{}: {:?}",
+                workflow.base_intent.name,
+                workflow.type_check()
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_check_chained_results() {
+        let workflow = WorkflowIntent::new(
+            intent_with_entity(),
+            vec![
+                fetch_step(),
+                WorkflowStep::Transform {
+                    operation: TransformOperation::ExtractJson { path: "data".to_string() },
+                    input_key: "page".to_string(),
+                    result_key: "data".to_string(),
+                },
+                WorkflowStep::LlmAnalyze {
+                    prompt_template: "总结 {data}（来源 {url}）".to_string(),
+                    result_key: "summary".to_string(),
+                },
+            ],
+        );
+
+        assert!(workflow.type_check().is_ok());
+    }
+
+    #[test]
+    fn test_type_check_undefined_placeholder() {
+        let workflow = WorkflowIntent::new(
+            intent_with_entity(),
+            vec![
+                fetch_step(),
+                WorkflowStep::LlmAnalyze {
+                    prompt_template: "分析 {page_data}".to_string(),
+                    result_key: "summary".to_string(),
+                },
+            ],
+        );
+
+        let err = workflow.type_check().unwrap_err();
+        assert_eq!(err.step, 2);
+        assert_eq!(err.operation, "LlmAnalyze");
+        assert!(matches!(
+            *err.error,
+            TypeError::UndefinedVariable { ref name, .. } if name == "page_data"
+        ));
+    }
+
+    #[test]
+    fn test_type_check_transform_input_type() {
+        // 对数字参数做 JSON 提取：类型不兼容
+        let workflow = WorkflowIntent::new(
+            intent_with_entity(),
+            vec![WorkflowStep::Transform {
+                operation: TransformOperation::ExtractJson { path: "x".to_string() },
+                input_key: "count".to_string(),
+                result_key: "x".to_string(),
+            }],
+        );
+
+        let err = workflow.type_check().unwrap_err();
+        assert_eq!(err.step, 1);
+        assert_eq!(err.operation, "Transform(extract_json)");
+        assert!(matches!(*err.error, TypeError::IncompatibleTypes { .. }));
+    }
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(
+            placeholders("{a}/x/{b} {a} {\"json\": 1}"),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
 }
@@ -38,9 +38,37 @@
 //! let command = plan.to_shell_command();
 //! // → "find . -name '*.rs' -type f -exec ls -lh {} + | sort -k5 -h | head -n 1"
 //! ```
+//!
+//! ## 执行后端
+//!
+//! - **Shell**（默认）：`to_shell_command()` 生成命令，交给 `/bin/sh`
+//! - **Native**：`NativeExecutor` 在进程内求值，返回可导出的 `RecordTable`
+//!
+//! 两种后端执行前都会调用 `ExecutionPlan::type_check` 做整链类型检查。
+//!
+//! ## 文本语法
+//!
+//! 执行计划也可以直接书写（见 `syntax` 模块），`to_dsl()` 可将其打印回来：
+//!
+//! ```rust
+//! use realconsole::dsl::pipeline::ExecutionPlan;
+//!
+//! let plan: ExecutionPlan = "files ./src | where ext == \"rs\" | sort size desc | take 10"
+//!     .parse()
+//!     .unwrap();
+//! assert_eq!(plan.len(), 4);
+//! assert_eq!(plan.to_dsl(), "files ./src | where ext == \"rs\" | sort size desc | take 10");
+//! ```
 
+pub mod native;
 pub mod operations;
 pub mod plan;
+pub mod syntax;
+pub mod table;
+pub mod typecheck;
 
+pub use native::{NativeExecutor, PipelineBackend};
 pub use operations::{BaseOperation, Direction, Field};
 pub use plan::ExecutionPlan;
+pub use syntax::{parse, Span, SyntaxError};
+pub use table::{FileRecord, RecordTable, TableFormat};
@@ -0,0 +1,480 @@
+//! 原生执行器 - 不经过 Shell 的执行计划求值
+//!
+//! **哲学**：同一个"卦"（执行计划），可以有不同的"用"（执行方式）
+//!
+//! - Shell 后端：把计划翻译成 `find | sort | head`，交给 `/bin/sh`
+//! - 原生后端：在 Rust 中直接遍历文件系统，对类型化的记录流逐个施加操作
+//!
+//! 原生后端的结果与平台无关，可解析，可导出为 JSON/CSV，也可继续作为下一个操作的输入。
+//!
+//! ## 示例
+//!
+//! ```rust,no_run
+//! use realconsole::dsl::pipeline::{BaseOperation, Direction, ExecutionPlan, Field, NativeExecutor};
+//!
+//! let plan = ExecutionPlan::new()
+//!     .with_operation(BaseOperation::FindFiles {
+//!         path: "./src".to_string(),
+//!         pattern: "*.rs".to_string(),
+//!     })
+//!     .with_operation(BaseOperation::SortFiles {
+//!         field: Field::Size,
+//!         direction: Direction::Descending,
+//!     })
+//!     .with_operation(BaseOperation::LimitFiles { count: 5 });
+//!
+//! let table = NativeExecutor::new().execute(&plan).unwrap();
+//! println!("{}", table.render());
+//! ```
+
+use super::operations::{BaseOperation, Direction, Field};
+use super::plan::ExecutionPlan;
+use super::table::{FileRecord, RecordTable};
+use chrono::{DateTime, Local};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs::{self, Metadata};
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// 执行后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineBackend {
+    /// 生成 Shell 命令并通过 `/bin/sh` 执行（默认，保持向后兼容）
+    #[default]
+    Shell,
+    /// 在进程内原生执行，返回结构化结果表
+    Native,
+}
+
+impl PipelineBackend {
+    /// 从字符串解析
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "shell" | "sh" => Some(Self::Shell),
+            "native" | "rust" => Some(Self::Native),
+            _ => None,
+        }
+    }
+}
+
+/// 原生执行器
+///
+/// **设计**：
+/// - 数据源操作（FindFiles / ListFiles / DiskUsage）产生初始记录表
+/// - 修饰操作（SortFiles / LimitFiles / FilterFiles）消费并产出记录表
+/// - 与 Shell 后端一致：ListFiles 与 DiskUsage 不含隐藏文件，FindFiles 包含
+#[derive(Debug, Clone)]
+pub struct NativeExecutor {
+    /// FindFiles 的最大递归深度（None = 不限制）
+    max_depth: Option<usize>,
+
+    /// 是否跟随符号链接
+    follow_links: bool,
+}
+
+impl NativeExecutor {
+    /// 创建默认执行器（不限深度，不跟随符号链接）
+    pub fn new() -> Self {
+        Self {
+            max_depth: None,
+            follow_links: false,
+        }
+    }
+
+    /// 限制 FindFiles 的递归深度
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// 设置是否跟随符号链接
+    pub fn with_follow_links(mut self, follow: bool) -> Self {
+        self.follow_links = follow;
+        self
+    }
+
+    /// 执行整个计划（执行前先做类型检查）
+    pub fn execute(&self, plan: &ExecutionPlan) -> Result<RecordTable, String> {
+        plan.type_check().map_err(|e| format!("类型检查失败: {}", e))?;
+
+        let mut table = None;
+        for operation in &plan.operations {
+            table = Some(self.apply(table, operation)?);
+        }
+
+        Ok(table.unwrap_or_default())
+    }
+
+    /// 对输入表施加单个操作
+    ///
+    /// `input` 为 `None` 表示管道开头，此时只接受数据源操作。
+    pub fn apply(
+        &self,
+        input: Option<RecordTable>,
+        operation: &BaseOperation,
+    ) -> Result<RecordTable, String> {
+        match (input, operation) {
+            (None, BaseOperation::FindFiles { path, pattern }) => self.find_files(path, pattern),
+            (None, BaseOperation::ListFiles { path }) => list_files(path),
+            (None, BaseOperation::DiskUsage { path }) => disk_usage(path),
+            (Some(_), BaseOperation::FindFiles { .. })
+            | (Some(_), BaseOperation::ListFiles { .. })
+            | (Some(_), BaseOperation::DiskUsage { .. }) => {
+                Err("数据源操作只能位于管道开头".to_string())
+            }
+            (None, _) => Err("管道必须以数据源操作开头（FindFiles / ListFiles / DiskUsage）".to_string()),
+            (Some(table), BaseOperation::SortFiles { field, direction }) => {
+                Ok(sort_records(table, field, direction))
+            }
+            (Some(mut table), BaseOperation::LimitFiles { count }) => {
+                table.records.truncate(*count);
+                Ok(table)
+            }
+            (Some(table), BaseOperation::FilterFiles { condition }) => {
+                Ok(filter_records(table, condition))
+            }
+        }
+    }
+
+    /// 递归查找匹配模式的文件
+    fn find_files(&self, path: &str, pattern: &str) -> Result<RecordTable, String> {
+        ensure_exists(path)?;
+        let matcher = glob::Pattern::new(pattern)
+            .map_err(|e| format!("无效的文件模式 '{}': {}", pattern, e))?;
+
+        let mut walker = WalkDir::new(path).follow_links(self.follow_links);
+        if let Some(depth) = self.max_depth {
+            walker = walker.max_depth(depth);
+        }
+
+        let mut records = Vec::new();
+        // 与 find 一致：无权限访问的目录跳过而不是中断
+        for entry in walker.into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy();
+            if !matcher.matches(&name) {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata() {
+                records.push(make_record(entry.path(), &metadata, metadata.len()));
+            }
+        }
+
+        Ok(RecordTable::from_records(records))
+    }
+}
+
+impl Default for NativeExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 列出目录下的直接子项（不含隐藏文件，按名称排序，同 `ls`）
+fn list_files(path: &str) -> Result<RecordTable, String> {
+    let mut records: Vec<FileRecord> = visible_entries(path)?
+        .into_iter()
+        .map(|(entry_path, metadata)| {
+            let size = if metadata.is_dir() { 0 } else { metadata.len() };
+            make_record(&entry_path, &metadata, size)
+        })
+        .collect();
+
+    records.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(RecordTable::from_records(records))
+}
+
+/// 统计目录下每个子项的总大小（同 `du -s path/*`）
+fn disk_usage(path: &str) -> Result<RecordTable, String> {
+    let mut records: Vec<FileRecord> = visible_entries(path)?
+        .into_iter()
+        .map(|(entry_path, metadata)| {
+            let size = if metadata.is_dir() {
+                dir_size(&entry_path)
+            } else {
+                metadata.len()
+            };
+            make_record(&entry_path, &metadata, size)
+        })
+        .collect();
+
+    records.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(RecordTable::from_records(records))
+}
+
+/// 读取目录的非隐藏子项
+fn visible_entries(path: &str) -> Result<Vec<(std::path::PathBuf, Metadata)>, String> {
+    ensure_exists(path)?;
+
+    let entries = fs::read_dir(path).map_err(|e| format!("无法读取目录 '{}': {}", path, e))?;
+
+    Ok(entries
+        .filter_map(|e| e.ok())
+        .filter(|e| !e.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|e| e.metadata().ok().map(|m| (e.path(), m)))
+        .collect())
+}
+
+/// 递归计算目录总大小
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// 路径不存在时返回错误
+fn ensure_exists(path: &str) -> Result<(), String> {
+    if Path::new(path).exists() {
+        Ok(())
+    } else {
+        Err(format!("路径不存在: {}", path))
+    }
+}
+
+/// 由路径和元数据构造记录
+fn make_record(path: &Path, metadata: &Metadata, size: u64) -> FileRecord {
+    FileRecord {
+        path: path.display().to_string(),
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string()),
+        size,
+        modified: metadata.modified().ok().map(DateTime::<Local>::from),
+        is_dir: metadata.is_dir(),
+    }
+}
+
+/// 排序记录
+///
+/// `Field::Default` 在 Shell 后端中表示"第一列"（du 输出的大小列），
+/// 原生后端中统一按大小排序。
+fn sort_records(mut table: RecordTable, field: &Field, direction: &Direction) -> RecordTable {
+    let compare = |a: &FileRecord, b: &FileRecord| -> Ordering {
+        match field {
+            Field::Size | Field::Default => a.size.cmp(&b.size),
+            Field::Time => a.modified.cmp(&b.modified),
+            Field::Name => a.name.cmp(&b.name),
+        }
+    };
+
+    match direction {
+        Direction::Ascending => table.records.sort_by(compare),
+        Direction::Descending => table.records.sort_by(|a, b| compare(b, a)),
+    }
+
+    table
+}
+
+/// 过滤记录（同 `grep`：条件按正则匹配路径，非法正则时按子串匹配）
+fn filter_records(mut table: RecordTable, condition: &str) -> RecordTable {
+    match Regex::new(condition) {
+        Ok(re) => table.records.retain(|r| re.is_match(&r.path)),
+        Err(_) => table.records.retain(|r| r.path.contains(condition)),
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn write_file(dir: &Path, name: &str, size: usize) {
+        let path = dir.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        let mut file = File::create(path).unwrap();
+        file.write_all(&vec![b'x'; size]).unwrap();
+    }
+
+    fn fixture() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        write_file(dir.path(), "small.rs", 10);
+        write_file(dir.path(), "medium.rs", 100);
+        write_file(dir.path(), "notes.txt", 1000);
+        write_file(dir.path(), "nested/large.rs", 5000);
+        write_file(dir.path(), ".hidden.rs", 1);
+        dir
+    }
+
+    fn root(dir: &TempDir) -> String {
+        dir.path().display().to_string()
+    }
+
+    #[test]
+    fn test_backend_parse() {
+        assert_eq!(PipelineBackend::parse("native"), Some(PipelineBackend::Native));
+        assert_eq!(PipelineBackend::parse("Shell"), Some(PipelineBackend::Shell));
+        assert_eq!(PipelineBackend::parse("python"), None);
+        assert_eq!(PipelineBackend::default(), PipelineBackend::Shell);
+    }
+
+    #[test]
+    fn test_find_largest_files() {
+        let dir = fixture();
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::FindFiles {
+                path: root(&dir),
+                pattern: "*.rs".to_string(),
+            })
+            .with_operation(BaseOperation::SortFiles {
+                field: Field::Size,
+                direction: Direction::Descending,
+            })
+            .with_operation(BaseOperation::LimitFiles { count: 2 });
+
+        let table = NativeExecutor::new().execute(&plan).unwrap();
+        let names: Vec<&str> = table.records.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["large.rs", "medium.rs"]);
+    }
+
+    #[test]
+    fn test_find_smallest_includes_hidden() {
+        // find 会包含隐藏文件，原生后端保持一致
+        let dir = fixture();
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::FindFiles {
+                path: root(&dir),
+                pattern: "*.rs".to_string(),
+            })
+            .with_operation(BaseOperation::SortFiles {
+                field: Field::Size,
+                direction: Direction::Ascending,
+            })
+            .with_operation(BaseOperation::LimitFiles { count: 1 });
+
+        let table = NativeExecutor::new().execute(&plan).unwrap();
+        assert_eq!(table.records[0].name, ".hidden.rs");
+    }
+
+    #[test]
+    fn test_find_with_max_depth() {
+        let dir = fixture();
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::FindFiles {
+            path: root(&dir),
+            pattern: "*.rs".to_string(),
+        });
+
+        let table = NativeExecutor::new().with_max_depth(1).execute(&plan).unwrap();
+        assert!(table.records.iter().all(|r| r.name != "large.rs"));
+        assert_eq!(table.len(), 3);
+    }
+
+    #[test]
+    fn test_list_files_skips_hidden() {
+        let dir = fixture();
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::ListFiles { path: root(&dir) });
+
+        let table = NativeExecutor::new().execute(&plan).unwrap();
+        let names: Vec<&str> = table.records.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["medium.rs", "nested", "notes.txt", "small.rs"]);
+        assert!(table.records.iter().find(|r| r.name == "nested").unwrap().is_dir);
+    }
+
+    #[test]
+    fn test_disk_usage_sums_directories() {
+        let dir = fixture();
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::DiskUsage { path: root(&dir) })
+            .with_operation(BaseOperation::SortFiles {
+                field: Field::Default,
+                direction: Direction::Descending,
+            })
+            .with_operation(BaseOperation::LimitFiles { count: 1 });
+
+        let table = NativeExecutor::new().execute(&plan).unwrap();
+        assert_eq!(table.records[0].name, "nested");
+        assert_eq!(table.records[0].size, 5000);
+    }
+
+    #[test]
+    fn test_filter_files() {
+        let dir = fixture();
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::FindFiles {
+                path: root(&dir),
+                pattern: "*".to_string(),
+            })
+            .with_operation(BaseOperation::FilterFiles {
+                condition: r"\.txt$".to_string(),
+            });
+
+        let table = NativeExecutor::new().execute(&plan).unwrap();
+        assert_eq!(table.len(), 1);
+        assert_eq!(table.records[0].name, "notes.txt");
+    }
+
+    #[test]
+    fn test_apply_chains_tables() {
+        // 记录表可以逐步流入下一个操作
+        let dir = fixture();
+        let executor = NativeExecutor::new();
+
+        let found = executor
+            .apply(
+                None,
+                &BaseOperation::FindFiles {
+                    path: root(&dir),
+                    pattern: "*.rs".to_string(),
+                },
+            )
+            .unwrap();
+        let sorted = executor
+            .apply(
+                Some(found),
+                &BaseOperation::SortFiles {
+                    field: Field::Name,
+                    direction: Direction::Ascending,
+                },
+            )
+            .unwrap();
+
+        assert_eq!(sorted.records[0].name, ".hidden.rs");
+        assert_eq!(sorted.records.last().unwrap().name, "small.rs");
+    }
+
+    #[test]
+    fn test_modifier_without_source_fails() {
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::LimitFiles { count: 1 });
+        assert!(NativeExecutor::new().execute(&plan).is_err());
+    }
+
+    #[test]
+    fn test_source_after_source_fails() {
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::ListFiles { path: ".".to_string() })
+            .with_operation(BaseOperation::DiskUsage { path: ".".to_string() });
+
+        assert!(NativeExecutor::new().execute(&plan).is_err());
+    }
+
+    #[test]
+    fn test_missing_path_fails() {
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::ListFiles {
+            path: "/nonexistent/realconsole/path".to_string(),
+        });
+
+        let err = NativeExecutor::new().execute(&plan).unwrap_err();
+        assert!(err.contains("路径不存在"));
+    }
+
+    #[test]
+    fn test_empty_plan_fails() {
+        assert!(NativeExecutor::new().execute(&ExecutionPlan::new()).is_err());
+    }
+}
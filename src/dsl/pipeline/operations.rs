@@ -6,6 +6,7 @@
 //! - 组合产生无穷变化（卦）
 
 use serde::{Deserialize, Serialize};
+use crate::shell_util::shell_quote;
 
 /// 排序字段（爻之一）
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -148,15 +149,19 @@ impl BaseOperation {
     pub fn to_shell_fragment(&self) -> String {
         match self {
             BaseOperation::FindFiles { path, pattern } => {
-                format!("find {} -name '{}' -type f -exec ls -lh {{}} +", path, pattern)
+                format!(
+                    "find {} -name {} -type f -exec ls -lh {{}} +",
+                    quote_path(path),
+                    quote_pattern(pattern)
+                )
             }
 
             BaseOperation::ListFiles { path } => {
-                format!("ls -lh {}", path)
+                format!("ls -lh {}", quote_path(path))
             }
 
             BaseOperation::DiskUsage { path } => {
-                format!("du -sh {}/*", path)
+                format!("du -sh {}/*", quote_path(path))
             }
 
             BaseOperation::SortFiles { field, direction } => {
@@ -173,7 +178,7 @@ impl BaseOperation {
             }
 
             BaseOperation::FilterFiles { condition } => {
-                format!("grep '{}'", condition)
+                format!("grep -e {}", quote_pattern(condition))
             }
         }
     }
@@ -184,6 +189,20 @@ impl BaseOperation {
     }
 }
 
+/// 把路径转义为单个 shell 单词，保留开头的 `~` 以便展开到 HOME
+fn quote_path(path: &str) -> String {
+    match path.strip_prefix('~') {
+        Some("") => "~".to_string(),
+        Some(rest) if rest.starts_with('/') => format!("~/{}", shell_quote(&rest[1..])),
+        _ => shell_quote(path),
+    }
+}
+
+/// 把模式转义为单引号包裹的 shell 单词（通配符交给命令处理，不让 shell 展开）
+fn quote_pattern(pattern: &str) -> String {
+    format!("'{}'", pattern.replace('\'', r"'\''"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,6 +269,18 @@ mod tests {
         assert_eq!(op.to_shell_fragment(), "du -sh /var/log/*");
     }
 
+    #[test]
+    fn test_fragments_quote_arguments() {
+        let op = BaseOperation::ListFiles { path: "my dir; rm -rf x".to_string() };
+        assert_eq!(op.to_shell_fragment(), "ls -lh 'my dir; rm -rf x'");
+
+        let op = BaseOperation::DiskUsage { path: "~/my logs".to_string() };
+        assert_eq!(op.to_shell_fragment(), "du -sh ~/'my logs'/*");
+
+        let op = BaseOperation::FilterFiles { condition: "it's".to_string() };
+        assert_eq!(op.to_shell_fragment(), r"grep -e 'it'\''s'");
+    }
+
     #[test]
     fn test_sort_files_with_default_field() {
         // Field::Default 用于不指定列的排序（如 du 输出）
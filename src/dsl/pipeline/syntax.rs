@@ -0,0 +1,755 @@
+//! 文本语法 - 直接书写的 Pipeline DSL
+//!
+//! **哲学**：象可以被"说出来"，也可以被"写出来"
+//!
+//! 意图匹配与 LLM 生成的是结构化的执行计划；熟练用户则希望直接书写：
+//!
+//! ```text
+//! files ./src | where ext == "rs" | sort size desc | take 10
+//! ```
+//!
+//! ## 语法
+//!
+//! ```text
+//! pipeline := stage ("|" stage)*
+//! stage    := "files" [path] [pattern]          → FindFiles（别名 find）
+//!           | "ls" [path]                       → ListFiles
+//!           | "du" [path]                       → DiskUsage
+//!           | "where" field op value            → FilterFiles
+//!           | "sort" field ["asc" | "desc"]     → SortFiles
+//!           | "take" count                      → LimitFiles（别名 limit, head）
+//! ```
+//!
+//! `where` 支持 `ext == "rs"`、`path ~ "正则"`、`path contains "文本"`。
+//!
+//! 解析错误带有精确的字节区间，可渲染为带 `^^^` 标记的提示；
+//! `to_dsl()` 则把任意执行计划（包括 LLM 生成的）打印回同一种语法。
+
+use super::operations::{BaseOperation, Direction, Field};
+use super::plan::ExecutionPlan;
+use crate::dsl::intent::matcher::levenshtein_distance;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::str::FromStr;
+use unicode_width::UnicodeWidthStr;
+
+/// 操作名称（用于补全与拼写提示）
+pub const OPERATIONS: &[&str] = &["files", "find", "ls", "du", "where", "sort", "take", "limit", "head"];
+
+/// 排序字段关键字
+const SORT_FIELDS: &[&str] = &["size", "time", "name", "default"];
+
+/// 排序方向关键字
+const DIRECTIONS: &[&str] = &["asc", "desc"];
+
+/// 过滤字段关键字
+const FILTER_FIELDS: &[&str] = &["ext", "path"];
+
+/// 过滤运算符
+const FILTER_OPS: &[&str] = &["==", "~", "contains"];
+
+/// 扩展名过滤生成的正则（用于打印时还原为 `where ext == "..."`）
+static EXT_CONDITION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\\\.([A-Za-z0-9_]+)\$$").unwrap());
+
+/// 源码区间（字节偏移，左闭右开）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    /// 指向某个位置的零宽区间（渲染时显示为单个 `^`）
+    pub fn point(at: usize) -> Self {
+        Self::new(at, at)
+    }
+}
+
+/// 语法错误
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    /// 错误信息
+    pub message: String,
+    /// 出错位置
+    pub span: Span,
+    /// 修正建议（如拼写提示）
+    pub hint: Option<String>,
+}
+
+impl SyntaxError {
+    fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    /// 渲染为带位置标记的多行提示
+    ///
+    /// ```text
+    /// files ./src | wher ext == "rs"
+    ///               ^^^^ 未知操作: wher
+    ///               提示: 是否想输入 `where`？
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let end = self.span.end.clamp(start, source.len());
+
+        // 按显示宽度对齐（中文路径等宽字符占两列）
+        let indent = " ".repeat(source[..start].width());
+        let marker = "^".repeat(source[start..end].width().max(1));
+
+        let mut output = format!("{}\n{}{} {}", source, indent, marker, self.message);
+        if let Some(hint) = &self.hint {
+            output.push_str(&format!("\n{}提示: {}", indent, hint));
+        }
+        output
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}（位置 {}）", self.message, self.span.start + 1)?;
+        if let Some(hint) = &self.hint {
+            write!(f, "，{}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+// ========== 词法分析 ==========
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    /// 裸词（操作名、路径、关键字、数字）
+    Word(String),
+    /// 引号字符串
+    Str(String),
+    /// 运算符 `==` / `~`
+    Op(&'static str),
+    /// 管道 `|`
+    Pipe,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    span: Span,
+}
+
+impl Token {
+    /// 作为参数值的文本（裸词或字符串）
+    fn text(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Word(s) | TokenKind::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// 裸词中不允许出现的字符
+fn is_word_char(c: char) -> bool {
+    !c.is_whitespace() && !matches!(c, '|' | '"' | '\'' | '=')
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, SyntaxError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        match c {
+            '|' => {
+                chars.next();
+                tokens.push(Token {
+                    kind: TokenKind::Pipe,
+                    span: Span::new(start, start + 1),
+                });
+            }
+            '=' => {
+                chars.next();
+                if chars.next_if(|&(_, c)| c == '=').is_none() {
+                    return Err(SyntaxError::new("无效的运算符 `=`", Span::new(start, start + 1))
+                        .with_hint("比较请使用 `==`"));
+                }
+                tokens.push(Token {
+                    kind: TokenKind::Op("=="),
+                    span: Span::new(start, start + 2),
+                });
+            }
+            '"' | '\'' => {
+                chars.next();
+                let mut value = String::new();
+                let mut closed = false;
+                while let Some((_, ch)) = chars.next() {
+                    if ch == c {
+                        closed = true;
+                        break;
+                    }
+                    // 双引号内支持 \" 与 \\ 转义
+                    if ch == '\\' && c == '"' {
+                        if let Some((_, escaped)) = chars.next_if(|&(_, n)| n == '"' || n == '\\') {
+                            value.push(escaped);
+                            continue;
+                        }
+                    }
+                    value.push(ch);
+                }
+                if !closed {
+                    return Err(SyntaxError::new("字符串缺少结束引号", Span::new(start, source.len())));
+                }
+                let end = chars.peek().map(|&(i, _)| i).unwrap_or(source.len());
+                tokens.push(Token {
+                    kind: TokenKind::Str(value),
+                    span: Span::new(start, end),
+                });
+            }
+            _ => {
+                let mut end = start;
+                while let Some((i, ch)) = chars.next_if(|&(_, ch)| is_word_char(ch)) {
+                    end = i + ch.len_utf8();
+                }
+                let word = &source[start..end];
+                // 单独的 ~ 是匹配运算符；~/path 仍是路径
+                let kind = if word == "~" {
+                    TokenKind::Op("~")
+                } else {
+                    TokenKind::Word(word.to_string())
+                };
+                tokens.push(Token {
+                    kind,
+                    span: Span::new(start, end),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+// ========== 语法分析 ==========
+
+/// 解析文本管道为执行计划
+///
+/// 只做语法层面的检查；操作能否相接由 `ExecutionPlan::type_check` 负责。
+pub fn parse(source: &str) -> Result<ExecutionPlan, SyntaxError> {
+    let tokens = tokenize(source)?;
+    if tokens.is_empty() {
+        return Err(SyntaxError::new("管道为空", Span::point(0))
+            .with_hint(format!("可用操作: {}", OPERATIONS.join(", "))));
+    }
+
+    let mut plan = ExecutionPlan::new();
+    for stage in split_stages(&tokens, source.len())? {
+        plan = plan.with_operation(StageParser::new(stage).parse()?);
+    }
+    Ok(plan)
+}
+
+/// 一个管道阶段的词法单元，以及阶段末尾的位置（用于报告"缺少参数"）
+struct Stage<'a> {
+    tokens: &'a [Token],
+    end: usize,
+}
+
+fn split_stages(tokens: &[Token], source_len: usize) -> Result<Vec<Stage<'_>>, SyntaxError> {
+    let mut stages = Vec::new();
+    let mut begin = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind == TokenKind::Pipe {
+            if index == begin {
+                return Err(SyntaxError::new("管道阶段为空", token.span));
+            }
+            stages.push(Stage {
+                tokens: &tokens[begin..index],
+                end: token.span.start,
+            });
+            begin = index + 1;
+        }
+    }
+
+    if begin == tokens.len() {
+        return Err(SyntaxError::new("管道末尾缺少操作", Span::point(source_len)));
+    }
+    stages.push(Stage {
+        tokens: &tokens[begin..],
+        end: source_len,
+    });
+
+    Ok(stages)
+}
+
+struct StageParser<'a> {
+    stage: Stage<'a>,
+    pos: usize,
+}
+
+impl<'a> StageParser<'a> {
+    fn new(stage: Stage<'a>) -> Self {
+        Self { stage, pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<BaseOperation, SyntaxError> {
+        let head = self.next().expect("阶段至少包含一个词法单元");
+        let name = match &head.kind {
+            TokenKind::Word(w) => w.to_lowercase(),
+            _ => return Err(SyntaxError::new("阶段应以操作名开头", head.span)),
+        };
+
+        let operation = match name.as_str() {
+            "files" | "find" => {
+                let path = self.optional_text().unwrap_or_else(|| ".".to_string());
+                let pattern = self.optional_text().unwrap_or_else(|| "*".to_string());
+                BaseOperation::FindFiles { path, pattern }
+            }
+            "ls" => BaseOperation::ListFiles {
+                path: self.optional_text().unwrap_or_else(|| ".".to_string()),
+            },
+            "du" => BaseOperation::DiskUsage {
+                path: self.optional_text().unwrap_or_else(|| ".".to_string()),
+            },
+            "where" => self.parse_where()?,
+            "sort" => self.parse_sort()?,
+            "take" | "limit" | "head" => {
+                let token = self.expect_text("数量")?;
+                let count = token
+                    .text()
+                    .and_then(|t| t.parse::<usize>().ok())
+                    .ok_or_else(|| SyntaxError::new("数量必须是非负整数", token.span))?;
+                BaseOperation::LimitFiles { count }
+            }
+            _ => {
+                let mut error = SyntaxError::new(format!("未知操作: {}", name), head.span);
+                if let Some(suggestion) = suggest(&name, OPERATIONS) {
+                    error = error.with_hint(format!("是否想输入 `{}`？", suggestion));
+                }
+                return Err(error);
+            }
+        };
+
+        if let Some(extra) = self.next() {
+            let last = self.stage.tokens.last().unwrap_or(extra);
+            return Err(SyntaxError::new(
+                format!("{} 的参数过多", name),
+                Span::new(extra.span.start, last.span.end),
+            ));
+        }
+
+        Ok(operation)
+    }
+
+    fn parse_where(&mut self) -> Result<BaseOperation, SyntaxError> {
+        let field_token = self.expect_text("过滤字段")?;
+        let field = field_token.text().unwrap_or_default().to_lowercase();
+        if !FILTER_FIELDS.contains(&field.as_str()) {
+            return Err(unknown_keyword("过滤字段", &field, field_token.span, FILTER_FIELDS));
+        }
+
+        let op_token = self.next().ok_or_else(|| self.missing("运算符"))?;
+        let op = match &op_token.kind {
+            TokenKind::Op(op) => *op,
+            TokenKind::Word(w) if w.eq_ignore_ascii_case("contains") => "contains",
+            _ => return Err(unknown_keyword("运算符", "", op_token.span, FILTER_OPS)),
+        };
+
+        let value_token = self.expect_text("过滤值")?;
+        let value = value_token.text().unwrap_or_default();
+
+        let condition = match (field.as_str(), op) {
+            ("ext", "==") => format!(r"\.{}$", regex::escape(value.trim_start_matches('.'))),
+            ("path", "~") => {
+                Regex::new(value).map_err(|e| {
+                    SyntaxError::new(format!("无效的正则表达式: {}", e), value_token.span)
+                })?;
+                value.to_string()
+            }
+            ("path", "contains") => regex::escape(value),
+            _ => {
+                let supported = if field == "ext" { "==" } else { "~ 或 contains" };
+                return Err(SyntaxError::new(
+                    format!("{} 不支持运算符 {}", field, op),
+                    op_token.span,
+                )
+                .with_hint(format!("{} 可用运算符: {}", field, supported)));
+            }
+        };
+
+        Ok(BaseOperation::FilterFiles { condition })
+    }
+
+    fn parse_sort(&mut self) -> Result<BaseOperation, SyntaxError> {
+        let field_token = self.expect_text("排序字段")?;
+        let field_name = field_token.text().unwrap_or_default().to_lowercase();
+        let field = match field_name.as_str() {
+            "size" => Field::Size,
+            "time" => Field::Time,
+            "name" => Field::Name,
+            "default" => Field::Default,
+            _ => return Err(unknown_keyword("排序字段", &field_name, field_token.span, SORT_FIELDS)),
+        };
+
+        let direction = match self.next() {
+            None => Direction::Ascending,
+            Some(token) => match token.text().map(|t| t.to_lowercase()).as_deref() {
+                Some("asc") => Direction::Ascending,
+                Some("desc") => Direction::Descending,
+                other => {
+                    return Err(unknown_keyword(
+                        "排序方向",
+                        other.unwrap_or_default(),
+                        token.span,
+                        DIRECTIONS,
+                    ))
+                }
+            },
+        };
+
+        Ok(BaseOperation::SortFiles { field, direction })
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.stage.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    /// 可选的文本参数（遇到运算符不消费）
+    fn optional_text(&mut self) -> Option<String> {
+        let text = self.stage.tokens.get(self.pos)?.text()?.to_string();
+        self.pos += 1;
+        Some(text)
+    }
+
+    fn expect_text(&mut self, what: &str) -> Result<&'a Token, SyntaxError> {
+        let token = self.next().ok_or_else(|| self.missing(what))?;
+        if token.text().is_none() {
+            return Err(SyntaxError::new(format!("此处应为{}", what), token.span));
+        }
+        Ok(token)
+    }
+
+    fn missing(&self, what: &str) -> SyntaxError {
+        SyntaxError::new(format!("缺少{}", what), Span::point(self.stage.end))
+    }
+}
+
+fn unknown_keyword(what: &str, value: &str, span: Span, candidates: &[&str]) -> SyntaxError {
+    let error = SyntaxError::new(format!("未知的{}: {}", what, value), span);
+    match suggest(value, candidates) {
+        Some(s) if !value.is_empty() => error.with_hint(format!("是否想输入 `{}`？", s)),
+        _ => error.with_hint(format!("可选: {}", candidates.join(", "))),
+    }
+}
+
+/// 拼写建议（编辑距离不超过 2 的最近候选）
+fn suggest<'c>(word: &str, candidates: &[&'c str]) -> Option<&'c str> {
+    candidates
+        .iter()
+        .map(|c| (*c, levenshtein_distance(word, c)))
+        .filter(|(_, d)| *d <= 2)
+        .min_by_key(|(_, d)| *d)
+        .map(|(c, _)| c)
+}
+
+impl FromStr for ExecutionPlan {
+    type Err = SyntaxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+// ========== 打印 ==========
+
+impl BaseOperation {
+    /// 打印为文本语法
+    pub fn to_dsl(&self) -> String {
+        match self {
+            BaseOperation::FindFiles { path, pattern } if pattern == "*" => {
+                format!("files {}", quote_word(path))
+            }
+            BaseOperation::FindFiles { path, pattern } => {
+                format!("files {} {}", quote_word(path), quote(pattern))
+            }
+            BaseOperation::ListFiles { path } => format!("ls {}", quote_word(path)),
+            BaseOperation::DiskUsage { path } => format!("du {}", quote_word(path)),
+            BaseOperation::SortFiles { field, direction } => {
+                let field = match field {
+                    Field::Size => "size",
+                    Field::Time => "time",
+                    Field::Name => "name",
+                    Field::Default => "default",
+                };
+                let direction = match direction {
+                    Direction::Ascending => "asc",
+                    Direction::Descending => "desc",
+                };
+                format!("sort {} {}", field, direction)
+            }
+            BaseOperation::LimitFiles { count } => format!("take {}", count),
+            BaseOperation::FilterFiles { condition } => {
+                if let Some(ext) = EXT_CONDITION.captures(condition) {
+                    format!("where ext == {}", quote(&ext[1]))
+                } else if regex::escape(condition) == *condition {
+                    format!("where path contains {}", quote(condition))
+                } else {
+                    format!("where path ~ {}", quote(condition))
+                }
+            }
+        }
+    }
+}
+
+impl ExecutionPlan {
+    /// 打印为文本语法（`parse(plan.to_dsl())` 得到相同的计划）
+    pub fn to_dsl(&self) -> String {
+        self.operations
+            .iter()
+            .map(|op| op.to_dsl())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// 能作为裸词时原样输出，否则加引号
+fn quote_word(value: &str) -> String {
+    if !value.is_empty() && value != "~" && value.chars().all(is_word_char) {
+        value.to_string()
+    } else {
+        quote(value)
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// ========== 补全 ==========
+
+/// 计算光标处的补全候选
+///
+/// 返回 `(替换起点, 候选列表)`：阶段开头补全操作名，
+/// `sort` / `where` 之后补全字段、方向与运算符。
+pub fn complete(line: &str, pos: usize) -> (usize, Vec<String>) {
+    let before = &line[..pos.min(line.len())];
+    let stage_start = before.rfind('|').map(|i| i + 1).unwrap_or(0);
+    let stage = &before[stage_start..];
+
+    let word_start = stage
+        .rfind(char::is_whitespace)
+        .map(|i| i + stage[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let prefix = &stage[word_start..];
+    let previous: Vec<&str> = stage[..word_start].split_whitespace().collect();
+
+    let candidates: &[&str] = match previous.as_slice() {
+        [] => OPERATIONS,
+        ["sort"] => SORT_FIELDS,
+        ["sort", _] => DIRECTIONS,
+        ["where"] => FILTER_FIELDS,
+        ["where", _] => FILTER_OPS,
+        _ => &[],
+    };
+
+    let matches = candidates
+        .iter()
+        .filter(|c| c.starts_with(prefix))
+        .map(|c| c.to_string())
+        .collect();
+
+    (stage_start + word_start, matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_full_pipeline() {
+        let plan = parse(r#"files ./src | where ext == "rs" | sort size desc | take 10"#).unwrap();
+
+        assert_eq!(
+            plan.operations,
+            vec![
+                BaseOperation::FindFiles {
+                    path: "./src".to_string(),
+                    pattern: "*".to_string(),
+                },
+                BaseOperation::FilterFiles {
+                    condition: r"\.rs$".to_string(),
+                },
+                BaseOperation::SortFiles {
+                    field: Field::Size,
+                    direction: Direction::Descending,
+                },
+                BaseOperation::LimitFiles { count: 10 },
+            ]
+        );
+        assert!(plan.type_check().is_ok());
+    }
+
+    #[test]
+    fn test_parse_defaults_and_aliases() {
+        let plan: ExecutionPlan = "find | sort name | head 3".parse().unwrap();
+
+        assert_eq!(
+            plan.operations[0],
+            BaseOperation::FindFiles {
+                path: ".".to_string(),
+                pattern: "*".to_string(),
+            }
+        );
+        assert_eq!(
+            plan.operations[1],
+            BaseOperation::SortFiles {
+                field: Field::Name,
+                direction: Direction::Ascending,
+            }
+        );
+        assert_eq!(plan.operations[2], BaseOperation::LimitFiles { count: 3 });
+    }
+
+    #[test]
+    fn test_parse_where_variants() {
+        let plan = parse(r#"ls ~/logs | where path ~ "err(or)?" | where path contains "a.b""#).unwrap();
+
+        assert_eq!(plan.operations[0], BaseOperation::ListFiles { path: "~/logs".to_string() });
+        assert_eq!(
+            plan.operations[1],
+            BaseOperation::FilterFiles { condition: "err(or)?".to_string() }
+        );
+        assert_eq!(
+            plan.operations[2],
+            BaseOperation::FilterFiles { condition: r"a\.b".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_unknown_operation_span_and_hint() {
+        let source = r#"files ./src | wher ext == "rs""#;
+        let err = parse(source).unwrap_err();
+
+        assert_eq!(err.span, Span::new(14, 18));
+        assert_eq!(err.hint.as_deref(), Some("是否想输入 `where`？"));
+
+        let rendered = err.render(source);
+        let marker_line = rendered.lines().nth(1).unwrap();
+        assert!(marker_line.starts_with(&format!("{}^^^^ ", " ".repeat(14))));
+    }
+
+    #[test]
+    fn test_error_spans() {
+        // 数量不是数字
+        let err = parse("files | take ten").unwrap_err();
+        assert_eq!(err.span, Span::new(13, 16));
+
+        // 缺少排序字段：指向阶段末尾
+        let err = parse("files | sort | take 1").unwrap_err();
+        assert_eq!(err.message, "缺少排序字段");
+        assert_eq!(err.span, Span::point(13));
+
+        // 空阶段
+        let err = parse("files || take 1").unwrap_err();
+        assert_eq!(err.span, Span::new(7, 8));
+
+        // 末尾悬空的管道
+        let err = parse("files |").unwrap_err();
+        assert_eq!(err.span, Span::point(7));
+
+        // 未闭合的字符串
+        let err = parse(r#"files . "*.rs"#).unwrap_err();
+        assert_eq!(err.span, Span::new(8, 13));
+
+        // 多余参数
+        let err = parse("take 1 2 3").unwrap_err();
+        assert_eq!(err.span, Span::new(7, 10));
+    }
+
+    #[test]
+    fn test_where_operator_mismatch() {
+        let err = parse(r#"files | where ext ~ "rs""#).unwrap_err();
+        assert!(err.message.contains("ext 不支持运算符 ~"));
+
+        let err = parse(r#"files | where path ~ "(""#).unwrap_err();
+        assert!(err.message.contains("无效的正则表达式"));
+
+        let err = parse(r#"files | where path = "a""#).unwrap_err();
+        assert_eq!(err.hint.as_deref(), Some("比较请使用 `==`"));
+    }
+
+    #[test]
+    fn test_parse_does_not_type_check() {
+        // 语法正确但类型错误：由 type_check 报告
+        let plan = parse("sort size | files").unwrap();
+        assert!(plan.type_check().is_err());
+    }
+
+    #[test]
+    fn test_to_dsl_roundtrip() {
+        let plan = ExecutionPlan::new()
+            .with_operation(BaseOperation::FindFiles {
+                path: "my docs".to_string(),
+                pattern: "*.md".to_string(),
+            })
+            .with_operation(BaseOperation::FilterFiles { condition: r"\.md$".to_string() })
+            .with_operation(BaseOperation::FilterFiles { condition: "draft".to_string() })
+            .with_operation(BaseOperation::FilterFiles { condition: "^a.*\"b".to_string() })
+            .with_operation(BaseOperation::SortFiles {
+                field: Field::Time,
+                direction: Direction::Ascending,
+            })
+            .with_operation(BaseOperation::LimitFiles { count: 5 });
+
+        let text = plan.to_dsl();
+        assert_eq!(
+            text,
+            r#"files "my docs" "*.md" | where ext == "md" | where path contains "draft" | where path ~ "^a.*\"b" | sort time asc | take 5"#
+        );
+        assert_eq!(parse(&text).unwrap(), plan);
+    }
+
+    #[test]
+    fn test_complete_operations() {
+        let (start, candidates) = complete("files . | so", 12);
+        assert_eq!(start, 10);
+        assert_eq!(candidates, vec!["sort"]);
+
+        let (start, candidates) = complete("", 0);
+        assert_eq!(start, 0);
+        assert_eq!(candidates.len(), OPERATIONS.len());
+    }
+
+    #[test]
+    fn test_complete_arguments() {
+        let (_, candidates) = complete("files | sort s", 14);
+        assert_eq!(candidates, vec!["size"]);
+
+        let (_, candidates) = complete("files | sort size ", 18);
+        assert_eq!(candidates, vec!["asc", "desc"]);
+
+        let (_, candidates) = complete("files | where ", 14);
+        assert_eq!(candidates, vec!["ext", "path"]);
+
+        let (_, candidates) = complete("files ./s", 9);
+        assert!(candidates.is_empty());
+    }
+}
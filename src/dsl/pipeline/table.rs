@@ -0,0 +1,263 @@
+//! 结构化结果表 - Pipeline DSL 原生执行的数据载体
+//!
+//! **哲学**：
+//! - Shell 后端的输出是"文本"，只能看，难以再加工
+//! - 原生后端的输出是"记录"，可渲染、可导出、可继续流入下一个操作
+//!
+//! 每个操作消费一张表、产出一张表，操作之间传递的是类型明确的文件记录流。
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// 文件记录（表中的一行）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileRecord {
+    /// 完整路径（相对于搜索起点的原样拼接）
+    pub path: String,
+
+    /// 文件名（路径最后一段）
+    pub name: String,
+
+    /// 大小（字节）；目录为其下所有文件的总大小（仅 DiskUsage 计算）
+    pub size: u64,
+
+    /// 最后修改时间
+    pub modified: Option<DateTime<Local>>,
+
+    /// 是否为目录
+    pub is_dir: bool,
+}
+
+impl FileRecord {
+    /// 人类可读的大小（与 `ls -lh` 相同的 K/M/G 风格）
+    pub fn human_size(&self) -> String {
+        format_size(self.size)
+    }
+}
+
+/// 输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TableFormat {
+    /// 终端表格（默认）
+    #[default]
+    Table,
+    /// JSON 数组
+    Json,
+    /// CSV（带表头）
+    Csv,
+}
+
+impl TableFormat {
+    /// 从字符串解析
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "table" | "text" => Some(Self::Table),
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// 结构化结果表
+///
+/// **设计**：
+/// - 行顺序即操作结果顺序（排序、截取都直接作用于行）
+/// - 可渲染为终端表格，或导出为 JSON / CSV
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RecordTable {
+    /// 记录列表
+    pub records: Vec<FileRecord>,
+}
+
+impl RecordTable {
+    /// 创建空表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从记录列表创建
+    pub fn from_records(records: Vec<FileRecord>) -> Self {
+        Self { records }
+    }
+
+    /// 记录数量
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// 按指定格式输出
+    pub fn format(&self, format: TableFormat) -> Result<String, String> {
+        match format {
+            TableFormat::Table => Ok(self.render()),
+            TableFormat::Json => self.to_json(),
+            TableFormat::Csv => Ok(self.to_csv()),
+        }
+    }
+
+    /// 渲染为终端表格
+    ///
+    /// ```text
+    /// 大小    修改时间          路径
+    /// 47K     2025-10-15 21:41  ./src/agent.rs
+    /// ```
+    pub fn render(&self) -> String {
+        if self.records.is_empty() {
+            return "(无结果)".to_string();
+        }
+
+        let sizes: Vec<String> = self.records.iter().map(|r| r.human_size()).collect();
+        let size_width = sizes.iter().map(|s| s.len()).max().unwrap_or(0).max(4);
+
+        // 中文表头每字占两列显示宽度，填充宽度需按字符数折算
+        let mut output = format!("{:<width$}  {:<12}  路径\n", "大小", "修改时间", width = size_width - 2);
+
+        for (record, size) in self.records.iter().zip(&sizes) {
+            let modified = record
+                .modified
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let path = if record.is_dir {
+                format!("{}/", record.path.trim_end_matches('/'))
+            } else {
+                record.path.clone()
+            };
+            output.push_str(&format!(
+                "{:<width$}  {:<16}  {}\n",
+                size,
+                modified,
+                path,
+                width = size_width
+            ));
+        }
+
+        output.trim_end().to_string()
+    }
+
+    /// 导出为 JSON 数组
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(&self.records).map_err(|e| format!("JSON 序列化失败: {}", e))
+    }
+
+    /// 导出为 CSV（带表头）
+    pub fn to_csv(&self) -> String {
+        let mut output = String::from("path,name,size,modified,is_dir\n");
+
+        for record in &self.records {
+            let modified = record.modified.map(|t| t.to_rfc3339()).unwrap_or_default();
+            output.push_str(&format!(
+                "{},{},{},{},{}\n",
+                csv_escape(&record.path),
+                csv_escape(&record.name),
+                record.size,
+                modified,
+                record.is_dir
+            ));
+        }
+
+        output
+    }
+}
+
+/// 格式化字节数（1024 进制，保留一位小数，与 `ls -lh` 一致）
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value < 10.0 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+/// CSV 字段转义（含逗号、引号或换行时加引号）
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(path: &str, size: u64) -> FileRecord {
+        FileRecord {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            size,
+            modified: None,
+            is_dir: false,
+        }
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0B");
+        assert_eq!(format_size(1023), "1023B");
+        assert_eq!(format_size(1024), "1.0K");
+        assert_eq!(format_size(47 * 1024), "47K");
+        assert_eq!(format_size(5 * 1024 * 1024 + 512 * 1024), "5.5M");
+    }
+
+    #[test]
+    fn test_table_format_parse() {
+        assert_eq!(TableFormat::parse("JSON"), Some(TableFormat::Json));
+        assert_eq!(TableFormat::parse("csv"), Some(TableFormat::Csv));
+        assert_eq!(TableFormat::parse("table"), Some(TableFormat::Table));
+        assert_eq!(TableFormat::parse("xml"), None);
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = RecordTable::from_records(vec![record("./a.rs", 2048), record("./b.rs", 10)]);
+        let rendered = table.render();
+
+        assert!(rendered.contains("./a.rs"));
+        assert!(rendered.contains("2.0K"));
+        assert!(rendered.contains("10B"));
+        assert_eq!(rendered.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_render_empty_table() {
+        assert_eq!(RecordTable::new().render(), "(无结果)");
+    }
+
+    #[test]
+    fn test_to_csv_escapes_fields() {
+        let table = RecordTable::from_records(vec![record("./a,b.rs", 1)]);
+        let csv = table.to_csv();
+
+        assert!(csv.starts_with("path,name,size,modified,is_dir\n"));
+        assert!(csv.contains("\"./a,b.rs\",\"a,b.rs\",1,,false"));
+    }
+
+    #[test]
+    fn test_to_json_roundtrip() {
+        let table = RecordTable::from_records(vec![record("./a.rs", 42)]);
+        let json = table.to_json().unwrap();
+        let parsed: Vec<FileRecord> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed, table.records);
+    }
+}
@@ -0,0 +1,284 @@
+//! 类型检查 - 执行前的"卦象"校验
+//!
+//! **哲学**：组合之前先看"象"能否相接
+//!
+//! 每个基础操作都有输入/输出签名：
+//!
+//! ```text
+//! FindFiles / ListFiles / DiskUsage : ()            → List<FilePath>
+//! SortFiles / LimitFiles / FilterFiles : List<$T>   → List<$T>
+//! ```
+//!
+//! 执行计划在执行前做整链类型推导，错误会精确到步骤与参数，
+//! 而不是等 Shell 管道跑出一堆看不懂的输出。
+
+use super::operations::BaseOperation;
+use super::plan::ExecutionPlan;
+use crate::dsl::type_system::checker::ConstraintValidator;
+use crate::dsl::type_system::{
+    infer_chain, Constraint, ConstraintValue, PlanTypeError, Signature, Type, TypeChecker,
+    TypeError,
+};
+
+impl BaseOperation {
+    /// 操作名称（用于错误报告）
+    pub fn name(&self) -> &'static str {
+        match self {
+            BaseOperation::FindFiles { .. } => "FindFiles",
+            BaseOperation::ListFiles { .. } => "ListFiles",
+            BaseOperation::DiskUsage { .. } => "DiskUsage",
+            BaseOperation::SortFiles { .. } => "SortFiles",
+            BaseOperation::LimitFiles { .. } => "LimitFiles",
+            BaseOperation::FilterFiles { .. } => "FilterFiles",
+        }
+    }
+
+    /// 是否为数据源操作
+    pub fn is_source(&self) -> bool {
+        matches!(
+            self,
+            BaseOperation::FindFiles { .. }
+                | BaseOperation::ListFiles { .. }
+                | BaseOperation::DiskUsage { .. }
+        )
+    }
+
+    /// 输入/输出签名
+    pub fn signature(&self) -> Signature {
+        if self.is_source() {
+            Signature::source(Type::list(Type::file_path()))
+        } else {
+            let element = Type::TypeVar("T".to_string());
+            Signature::new(Type::list(element.clone()), Type::list(element))
+        }
+    }
+
+    /// 检查参数约束
+    pub fn check_parameters(&self) -> Result<(), TypeError> {
+        let checker = TypeChecker::new();
+
+        match self {
+            BaseOperation::FindFiles { path, pattern } => {
+                checker.validate_constraint(&Constraint::NonEmpty, &Param::Text("path", path))?;
+                checker.validate_constraint(&Constraint::NonEmpty, &Param::Text("pattern", pattern))
+            }
+            BaseOperation::ListFiles { path } | BaseOperation::DiskUsage { path } => {
+                checker.validate_constraint(&Constraint::NonEmpty, &Param::Text("path", path))
+            }
+            BaseOperation::LimitFiles { count } => checker.validate_constraint(
+                &Constraint::Range(ConstraintValue::Int(1), ConstraintValue::Unbounded),
+                &Param::Int("count", *count as i64),
+            ),
+            BaseOperation::FilterFiles { condition } => checker
+                .validate_constraint(&Constraint::NonEmpty, &Param::Text("condition", condition)),
+            BaseOperation::SortFiles { .. } => Ok(()),
+        }
+    }
+}
+
+impl ExecutionPlan {
+    /// 执行前类型检查
+    ///
+    /// 依次检查每个操作的参数约束，再对整条操作链做类型推导。
+    /// 成功时返回计划的输出类型。
+    pub fn type_check(&self) -> Result<Type, PlanTypeError> {
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation
+                .check_parameters()
+                .map_err(|e| PlanTypeError::new(index + 1, operation.name(), e))?;
+        }
+
+        let output = infer_chain(
+            self.operations
+                .iter()
+                .map(|op| (op.name(), op.signature())),
+        )?;
+
+        if output == Type::unit() {
+            return Err(PlanTypeError::new(
+                0,
+                "ExecutionPlan",
+                TypeError::IncompatibleTypes {
+                    expected: Type::list(Type::file_path()),
+                    actual: output,
+                    context: "计划输出（计划为空）".to_string(),
+                },
+            ));
+        }
+
+        Ok(output)
+    }
+
+    /// 尝试修复类型错误的计划
+    ///
+    /// **修复规则**：
+    /// - 第一个数据源移到开头，其余数据源丢弃
+    /// - 丢弃参数不合法的修饰操作（如 `LimitFiles { count: 0 }`）
+    ///
+    /// 修复后仍无法通过类型检查（如根本没有数据源）时返回 `None`。
+    pub fn repaired(&self) -> Option<ExecutionPlan> {
+        let source = self.operations.iter().find(|op| op.is_source())?.clone();
+
+        let mut operations = vec![source];
+        operations.extend(
+            self.operations
+                .iter()
+                .filter(|op| !op.is_source() && op.check_parameters().is_ok())
+                .cloned(),
+        );
+
+        let plan = ExecutionPlan { operations };
+        plan.type_check().ok().map(|_| plan)
+    }
+}
+
+/// 操作参数值（用于约束验证）
+enum Param<'a> {
+    Int(&'a str, i64),
+    Text(&'a str, &'a str),
+}
+
+impl ConstraintValidator for Param<'_> {
+    fn validate_range(&self, min: &ConstraintValue, max: &ConstraintValue) -> Result<(), TypeError> {
+        let Param::Int(name, value) = self else {
+            return Err(TypeError::ConstraintViolation {
+                constraint: "Range".to_string(),
+                value: "<text>".to_string(),
+                reason: "文本参数不支持范围约束".to_string(),
+            });
+        };
+
+        let below = matches!(min, ConstraintValue::Int(m) if value < m);
+        let above = matches!(max, ConstraintValue::Int(m) if value > m);
+        if below || above {
+            return Err(TypeError::ConstraintViolation {
+                constraint: format!("Range({:?}, {:?})", min, max),
+                value: value.to_string(),
+                reason: format!("参数 {} 超出允许范围", name),
+            });
+        }
+        Ok(())
+    }
+
+    fn validate_non_empty(&self) -> Result<(), TypeError> {
+        if let Param::Text(name, value) = self {
+            if value.trim().is_empty() {
+                return Err(TypeError::ConstraintViolation {
+                    constraint: "NonEmpty".to_string(),
+                    value: format!("{:?}", value),
+                    reason: format!("参数 {} 不能为空", name),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::pipeline::{Direction, Field};
+
+    fn find() -> BaseOperation {
+        BaseOperation::FindFiles {
+            path: ".".to_string(),
+            pattern: "*.rs".to_string(),
+        }
+    }
+
+    fn sort() -> BaseOperation {
+        BaseOperation::SortFiles {
+            field: Field::Size,
+            direction: Direction::Descending,
+        }
+    }
+
+    #[test]
+    fn test_signatures() {
+        assert_eq!(find().signature().to_string(), "() → List<FilePath>");
+        assert_eq!(sort().signature().to_string(), "List<$T> → List<$T>");
+        assert!(find().is_source());
+        assert!(!sort().is_source());
+    }
+
+    #[test]
+    fn test_valid_plan_type_checks() {
+        let plan = ExecutionPlan::new()
+            .with_operation(find())
+            .with_operation(sort())
+            .with_operation(BaseOperation::LimitFiles { count: 10 });
+
+        assert_eq!(plan.type_check().unwrap(), Type::list(Type::file_path()));
+    }
+
+    #[test]
+    fn test_modifier_without_source() {
+        // 对 () 排序：第 1 步报错
+        let plan = ExecutionPlan::new().with_operation(sort());
+        let err = plan.type_check().unwrap_err();
+
+        assert_eq!(err.step, 1);
+        assert_eq!(err.operation, "SortFiles");
+        assert!(err.to_string().contains("期望 List<$T0>"));
+    }
+
+    #[test]
+    fn test_source_in_middle() {
+        let plan = ExecutionPlan::new()
+            .with_operation(find())
+            .with_operation(BaseOperation::DiskUsage { path: ".".to_string() });
+        let err = plan.type_check().unwrap_err();
+
+        assert_eq!(err.step, 2);
+        assert_eq!(err.operation, "DiskUsage");
+    }
+
+    #[test]
+    fn test_limit_zero_violates_constraint() {
+        let plan = ExecutionPlan::new()
+            .with_operation(find())
+            .with_operation(BaseOperation::LimitFiles { count: 0 });
+        let err = plan.type_check().unwrap_err();
+
+        assert_eq!(err.step, 2);
+        assert!(matches!(*err.error, TypeError::ConstraintViolation { .. }));
+    }
+
+    #[test]
+    fn test_empty_pattern_violates_constraint() {
+        let plan = ExecutionPlan::new().with_operation(BaseOperation::FindFiles {
+            path: ".".to_string(),
+            pattern: "  ".to_string(),
+        });
+
+        assert!(plan.type_check().is_err());
+    }
+
+    #[test]
+    fn test_empty_plan() {
+        let err = ExecutionPlan::new().type_check().unwrap_err();
+        assert_eq!(err.step, 0);
+    }
+
+    #[test]
+    fn test_repair_moves_source_first() {
+        let plan = ExecutionPlan::new()
+            .with_operation(sort())
+            .with_operation(find())
+            .with_operation(BaseOperation::LimitFiles { count: 0 })
+            .with_operation(BaseOperation::ListFiles { path: ".".to_string() });
+
+        let repaired = plan.repaired().unwrap();
+        assert_eq!(repaired.operations, vec![find(), sort()]);
+        assert!(repaired.type_check().is_ok());
+    }
+
+    #[test]
+    fn test_repair_without_source_fails() {
+        let plan = ExecutionPlan::new()
+            .with_operation(sort())
+            .with_operation(BaseOperation::LimitFiles { count: 3 });
+
+        assert!(plan.repaired().is_none());
+    }
+}
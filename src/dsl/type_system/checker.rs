@@ -24,6 +24,8 @@ pub enum TypeError {
     },
     /// 未定义的类型变量
     UndefinedTypeVar { name: String },
+    /// 引用了未定义的变量（如模板占位符、步骤输入）
+    UndefinedVariable { name: String, context: String },
     /// 内层类型不匹配（用于复合类型）
     InnerTypeMismatch {
         expected: Type,
@@ -56,6 +58,9 @@ impl std::fmt::Display for TypeError {
             TypeError::UndefinedTypeVar { name } => {
                 write!(f, "未定义的类型变量: ${}", name)
             }
+            TypeError::UndefinedVariable { name, context } => {
+                write!(f, "未定义的变量 [{}]: {{{}}}", context, name)
+            }
             TypeError::InnerTypeMismatch {
                 expected,
                 actual,
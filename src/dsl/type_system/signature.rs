@@ -0,0 +1,209 @@
+//! 类型系统 - 操作签名
+//!
+//! 为可组合的执行单元（管道操作、工作流步骤）描述输入/输出类型，
+//! 并在执行前对整条计划做类型推导，报告出错的具体步骤。
+
+use super::checker::TypeError;
+use super::inference::TypeInference;
+use super::types::{CompositeType, Type};
+use std::collections::HashMap;
+use std::fmt;
+
+/// 操作签名：输入类型 → 输出类型
+///
+/// 签名中可以使用类型变量（如 `List<$T> → List<$T>`），
+/// 每次推导时会替换为新的类型变量，避免不同步骤之间互相污染。
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    /// 输入类型（数据源的输入为 `()`）
+    pub input: Type,
+    /// 输出类型
+    pub output: Type,
+}
+
+impl Signature {
+    /// 创建签名
+    pub fn new(input: Type, output: Type) -> Self {
+        Self { input, output }
+    }
+
+    /// 数据源签名：`() → output`
+    pub fn source(output: Type) -> Self {
+        Self::new(Type::unit(), output)
+    }
+}
+
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} → {}", self.input, self.output)
+    }
+}
+
+/// 计划类型错误（带步骤定位）
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanTypeError {
+    /// 出错步骤（从 1 开始）
+    pub step: usize,
+    /// 步骤名称
+    pub operation: String,
+    /// 具体类型错误
+    pub error: Box<TypeError>,
+}
+
+impl PlanTypeError {
+    /// 创建计划类型错误
+    pub fn new(step: usize, operation: impl Into<String>, error: TypeError) -> Self {
+        Self {
+            step,
+            operation: operation.into(),
+            error: Box::new(error),
+        }
+    }
+}
+
+impl fmt::Display for PlanTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "第 {} 步 {}: {}", self.step, self.operation, self.error)
+    }
+}
+
+impl std::error::Error for PlanTypeError {}
+
+/// 对线性步骤链做类型推导
+///
+/// 从 `()` 开始，依次将上一步的输出与下一步的输入统一，
+/// 返回整条链的最终输出类型。
+pub fn infer_chain<I, S>(steps: I) -> Result<Type, PlanTypeError>
+where
+    I: IntoIterator<Item = (S, Signature)>,
+    S: Into<String>,
+{
+    let mut inference = TypeInference::new();
+    let mut current = Type::unit();
+
+    for (index, (name, signature)) in steps.into_iter().enumerate() {
+        let name = name.into();
+        let signature = freshen(&mut inference, &signature);
+
+        if inference.unify(&signature.input, &current).is_err() {
+            return Err(PlanTypeError::new(
+                index + 1,
+                name.clone(),
+                TypeError::IncompatibleTypes {
+                    expected: inference.resolve_type(&signature.input),
+                    actual: inference.resolve_type(&current),
+                    context: format!("{} 的输入", name),
+                },
+            ));
+        }
+
+        current = inference.resolve_type(&signature.output);
+    }
+
+    Ok(current)
+}
+
+/// 将签名中的类型变量替换为新的类型变量
+fn freshen(inference: &mut TypeInference, signature: &Signature) -> Signature {
+    let mut names = Vec::new();
+    collect_type_vars(&signature.input, &mut names);
+    collect_type_vars(&signature.output, &mut names);
+
+    if names.is_empty() {
+        return signature.clone();
+    }
+
+    let substitutions: HashMap<String, Type> = names
+        .into_iter()
+        .map(|name| (name, inference.fresh_type_var()))
+        .collect();
+
+    Signature::new(
+        inference.instantiate(&signature.input, &substitutions),
+        inference.instantiate(&signature.output, &substitutions),
+    )
+}
+
+/// 收集类型中出现的类型变量名
+fn collect_type_vars(ty: &Type, names: &mut Vec<String>) {
+    match ty {
+        Type::TypeVar(name) if !names.contains(name) => names.push(name.clone()),
+        Type::Composite(composite) => match composite {
+            CompositeType::List(t) | CompositeType::Optional(t) => collect_type_vars(t, names),
+            CompositeType::Dict(a, b) | CompositeType::Result(a, b) => {
+                collect_type_vars(a, names);
+                collect_type_vars(b, names);
+            }
+            CompositeType::Tuple(types) => {
+                for t in types {
+                    collect_type_vars(t, names);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generic_list() -> Signature {
+        let t = Type::TypeVar("T".to_string());
+        Signature::new(Type::list(t.clone()), Type::list(t))
+    }
+
+    #[test]
+    fn test_signature_display() {
+        let sig = Signature::source(Type::list(Type::file_path()));
+        assert_eq!(sig.to_string(), "() → List<FilePath>");
+    }
+
+    #[test]
+    fn test_infer_chain_propagates_element_type() {
+        let result = infer_chain(vec![
+            ("source", Signature::source(Type::list(Type::file_path()))),
+            ("sort", generic_list()),
+            ("limit", generic_list()),
+        ])
+        .unwrap();
+
+        assert_eq!(result, Type::list(Type::file_path()));
+    }
+
+    #[test]
+    fn test_infer_chain_reports_step() {
+        // 对标量排序：第 2 步出错
+        let err = infer_chain(vec![
+            ("count", Signature::source(Type::integer())),
+            ("sort", generic_list()),
+        ])
+        .unwrap_err();
+
+        assert_eq!(err.step, 2);
+        assert_eq!(err.operation, "sort");
+        match *err.error {
+            TypeError::IncompatibleTypes { actual, .. } => assert_eq!(actual, Type::integer()),
+            other => panic!("期望 IncompatibleTypes，实际 {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_infer_chain_empty_is_unit() {
+        let steps: Vec<(String, Signature)> = Vec::new();
+        assert_eq!(infer_chain(steps).unwrap(), Type::unit());
+    }
+
+    #[test]
+    fn test_plan_type_error_display() {
+        let err = PlanTypeError::new(
+            3,
+            "LimitFiles",
+            TypeError::UndefinedVariable {
+                name: "path".to_string(),
+                context: "args".to_string(),
+            },
+        );
+        assert_eq!(err.to_string(), "第 3 步 LimitFiles: 未定义的变量 [args]: {path}");
+    }
+}
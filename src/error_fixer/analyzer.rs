@@ -2,9 +2,11 @@
 //!
 //! 分析命令执行错误，识别错误类型和原因
 
-use super::patterns::{BuiltinPatterns, ErrorDetails, ErrorPattern};
+use super::patterns::{self, BuiltinPatterns, ErrorDetails, ErrorPattern};
 use crate::llm::{LlmClient, LlmError, Message};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 /// 错误类别
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -28,6 +30,8 @@ pub enum ErrorCategory {
     Language(String),
     /// Git 相关错误
     Git,
+    /// 工具特定错误（用户模式定义的类别，如 Terraform、kubectl、Docker）
+    Tool(String),
     /// 未知错误
     Unknown,
 }
@@ -44,6 +48,7 @@ impl std::fmt::Display for ErrorCategory {
             ErrorCategory::Disk => write!(f, "磁盘错误"),
             ErrorCategory::Language(lang) => write!(f, "{} 错误", lang),
             ErrorCategory::Git => write!(f, "Git 错误"),
+            ErrorCategory::Tool(tool) => write!(f, "{} 错误", tool),
             ErrorCategory::Unknown => write!(f, "未知错误"),
         }
     }
@@ -105,6 +110,10 @@ pub struct ErrorAnalysis {
     /// 是否可以自动修复
     pub auto_fixable: bool,
 
+    /// 匹配模式给出的修复命令（已替换捕获组）
+    #[serde(default)]
+    pub fix_commands: Vec<String>,
+
     /// LLM 增强分析（可选）
     pub llm_analysis: Option<String>,
 }
@@ -122,6 +131,7 @@ impl ErrorAnalysis {
             possible_causes: Vec::new(),
             suggested_fixes: Vec::new(),
             auto_fixable: false,
+            fix_commands: Vec::new(),
             llm_analysis: None,
         }
     }
@@ -134,15 +144,105 @@ impl ErrorAnalysis {
 
 /// 错误分析器
 pub struct ErrorAnalyzer {
-    /// 错误模式库
+    /// 错误模式库（按优先级排列）
     patterns: Vec<ErrorPattern>,
+
+    /// 加载用户模式时的错误
+    load_errors: Vec<String>,
+
+    /// 模式命中统计
+    hits: Mutex<HitStats>,
+}
+
+/// 模式命中统计
+#[derive(Debug, Default)]
+struct HitStats {
+    by_pattern: HashMap<String, u64>,
+    unmatched: u64,
 }
 
 impl ErrorAnalyzer {
-    /// 创建新的分析器
+    /// 创建新的分析器（仅内置模式）
     pub fn new() -> Self {
+        Self::with_patterns(BuiltinPatterns::all())
+    }
+
+    /// 使用指定的模式库
+    pub fn with_patterns(patterns: Vec<ErrorPattern>) -> Self {
         Self {
-            patterns: BuiltinPatterns::all(),
+            patterns,
+            load_errors: Vec::new(),
+            hits: Mutex::new(HitStats::default()),
+        }
+    }
+
+    /// 内置模式加上用户模式
+    ///
+    /// 优先级：项目 `.realconsole/patterns` > 全局 `~/.realconsole/patterns` > 内置；
+    /// 同名模式只保留优先级最高的一个。
+    pub fn with_user_patterns() -> Self {
+        Self::with_pattern_dirs(&[patterns::project_pattern_dir(), patterns::global_pattern_dir()])
+    }
+
+    /// 从指定目录（按优先级）加载用户模式，再加上内置模式
+    pub fn with_pattern_dirs(dirs: &[std::path::PathBuf]) -> Self {
+        let mut all = Vec::new();
+        let mut load_errors = Vec::new();
+        for dir in dirs {
+            let (loaded, errors) = patterns::load_pattern_dir(dir);
+            all.extend(loaded);
+            load_errors.extend(errors);
+        }
+        all.extend(BuiltinPatterns::all());
+
+        let mut seen = HashSet::new();
+        all.retain(|p| seen.insert(p.name.clone()));
+
+        let mut analyzer = Self::with_patterns(all);
+        analyzer.load_errors = load_errors;
+        analyzer
+    }
+
+    /// 当前模式库（按优先级）
+    pub fn patterns(&self) -> &[ErrorPattern] {
+        &self.patterns
+    }
+
+    /// 加载用户模式时的错误
+    pub fn load_errors(&self) -> &[String] {
+        &self.load_errors
+    }
+
+    /// 所有匹配该输出的模式（不计入统计，用于测试模式）
+    pub fn matching_patterns(&self, error_output: &str) -> Vec<&ErrorPattern> {
+        self.patterns
+            .iter()
+            .filter(|p| p.matches(error_output))
+            .collect()
+    }
+
+    /// 各模式命中次数（按次数降序）
+    pub fn hit_counts(&self) -> Vec<(String, u64)> {
+        let Ok(hits) = self.hits.lock() else {
+            return Vec::new();
+        };
+        let mut counts: Vec<(String, u64)> =
+            hits.by_pattern.iter().map(|(k, v)| (k.clone(), *v)).collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        counts
+    }
+
+    /// 未匹配任何模式的错误数
+    pub fn unmatched_count(&self) -> u64 {
+        self.hits.lock().map(|h| h.unmatched).unwrap_or(0)
+    }
+
+    fn record_hit(&self, pattern_name: Option<&str>) {
+        if let Ok(mut hits) = self.hits.lock() {
+            match pattern_name {
+                Some(name) => *hits.by_pattern.entry(name.to_string()).or_insert(0) += 1,
+                None => hits.unmatched += 1,
+            }
         }
     }
 
@@ -165,7 +265,9 @@ impl ErrorAnalyzer {
                 analysis.category = self.map_category(&pattern.category);
                 analysis.severity = ErrorSeverity::from_score(pattern.severity);
                 analysis.suggested_fixes.push(pattern.suggested_fix.clone());
-                analysis.auto_fixable = pattern.auto_fixable;
+                // 用户与项目模式的修复命令总是需要确认
+                analysis.auto_fixable = pattern.auto_fixable && pattern.is_builtin();
+                analysis.fix_commands = pattern.render_fix_commands(error_output);
 
                 // 提取详情
                 if let Some(details) = pattern.extract_details(error_output) {
@@ -177,6 +279,8 @@ impl ErrorAnalyzer {
             }
         }
 
+        self.record_hit(analysis.pattern_name.as_deref());
+
         // 推断可能的原因
         analysis.possible_causes = self.infer_causes(&analysis);
 
@@ -265,7 +369,8 @@ impl ErrorAnalyzer {
             "nodejs" => ErrorCategory::Language("Node.js".to_string()),
             "rust" => ErrorCategory::Language("Rust".to_string()),
             "git" => ErrorCategory::Git,
-            _ => ErrorCategory::Unknown,
+            "" | "unknown" => ErrorCategory::Unknown,
+            tool => ErrorCategory::Tool(tool.to_string()),
         }
     }
 
@@ -299,6 +404,9 @@ impl ErrorAnalyzer {
                 causes.push(format!("{} 依赖未安装", lang));
                 causes.push(format!("{} 版本不兼容", lang));
             }
+            ErrorCategory::Tool(ref tool) => {
+                causes.push(format!("{} 配置或状态异常", tool));
+            }
             _ => {
                 causes.push("需要进一步调查".to_string());
             }
@@ -391,4 +499,54 @@ mod tests {
         assert!(!causes.is_empty());
         assert!(causes.iter().any(|c| c.contains("拼写")));
     }
+
+    #[test]
+    fn test_user_patterns_take_precedence() {
+        let project = tempfile::TempDir::new().unwrap();
+        let global = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            project.path().join("k8s.yaml"),
+            r#"
+patterns:
+  - name: kubectl_context
+    regex: 'error: context "(\S+)" does not exist'
+    category: kubectl
+    severity: 4
+    suggested_fix: 切换到已存在的 context
+    fix_commands: ["kubectl config get-contexts", "kubectl config use-context $1"]
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            global.path().join("k8s.yaml"),
+            "patterns:\n  - name: kubectl_context\n    regex: 'context'\n",
+        )
+        .unwrap();
+
+        let analyzer = ErrorAnalyzer::with_pattern_dirs(&[
+            project.path().to_path_buf(),
+            global.path().to_path_buf(),
+        ]);
+        assert!(analyzer.load_errors().is_empty());
+        // 同名模式只保留项目中的定义（覆盖全局与内置）
+        assert_eq!(analyzer.patterns().len(), BuiltinPatterns::all().len() + 1);
+        assert_eq!(analyzer.patterns()[0].name, "kubectl_context");
+
+        let analysis = analyzer.analyze("kubectl get pods", r#"error: context "prod" does not exist"#);
+        assert_eq!(analysis.category, ErrorCategory::Tool("kubectl".to_string()));
+        assert_eq!(analysis.category.to_string(), "kubectl 错误");
+        assert_eq!(analysis.fix_commands[1], "kubectl config use-context prod");
+        // 用户模式的修复命令总是需要确认
+        assert!(!analysis.auto_fixable);
+        let strategies = crate::error_fixer::ErrorFixer::generate_strategies(&analysis);
+        assert!(strategies[0].requires_confirmation);
+
+        analyzer.analyze("rm x", "rm: cannot remove 'x': Permission denied");
+        analyzer.analyze("true", "something odd happened");
+        assert_eq!(analyzer.hit_counts()[0], ("kubectl_context".to_string(), 1));
+        assert_eq!(analyzer.unmatched_count(), 1);
+        // 测试模式不计入统计
+        assert_eq!(analyzer.matching_patterns(r#"error: context "x" does not exist"#).len(), 1);
+        assert_eq!(analyzer.hit_counts().len(), 2);
+    }
 }
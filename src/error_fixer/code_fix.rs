@@ -0,0 +1,591 @@
+//! 代码修复应用
+//!
+//! 把诊断中的建议替换（或 LLM 给出的补丁）转换为文件修改：
+//! 先生成补丁和 diff 预览，用户确认后再写入文件。
+
+use super::diagnostics::{Diagnostic, EditRange, Suggestion};
+use super::fixer::FixStrategy;
+use crate::llm::{LlmClient, LlmError, Message};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// 应用编译器建议的策略名称
+pub const COMPILER_FIX_STRATEGY: &str = "应用编译器建议";
+
+/// 应用 LLM 补丁的策略名称
+pub const LLM_PATCH_STRATEGY: &str = "应用 LLM 补丁";
+
+/// diff 预览的上下文行数
+const DIFF_CONTEXT: usize = 2;
+
+/// 发给 LLM 的代码片段半径（行）
+const LLM_EXCERPT_RADIUS: usize = 15;
+
+/// LCS 表的最大单元数，超出时 diff 退化为整段删除 + 插入
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// 单个文件的补丁
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    /// 文件路径
+    pub path: PathBuf,
+    /// 修改前内容
+    pub original: String,
+    /// 修改后内容
+    pub patched: String,
+    /// 应用的修改数
+    pub edits: usize,
+}
+
+impl FilePatch {
+    /// unified diff 格式的预览
+    pub fn diff(&self) -> String {
+        unified_diff(&self.path.display().to_string(), &self.original, &self.patched)
+    }
+
+    /// 写入文件
+    ///
+    /// 文件在生成补丁后被修改过时拒绝写入，避免覆盖用户的改动。
+    pub fn apply(&self) -> Result<(), String> {
+        let current = std::fs::read_to_string(&self.path)
+            .map_err(|e| format!("读取 {} 失败: {}", self.path.display(), e))?;
+        if current != self.original {
+            return Err(format!("{} 在预览后已被修改，请重新生成补丁", self.path.display()));
+        }
+        std::fs::write(&self.path, &self.patched).map_err(|e| format!("写入 {} 失败: {}", self.path.display(), e))
+    }
+}
+
+/// 一组待应用的代码修改
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeFix {
+    /// 策略名称（[`COMPILER_FIX_STRATEGY`] 或 [`LLM_PATCH_STRATEGY`]）
+    pub strategy_name: &'static str,
+    /// 修改说明
+    pub description: String,
+    /// 各文件的补丁
+    pub patches: Vec<FilePatch>,
+    /// 无法应用的建议（范围越界、与其他建议重叠、文件不可读）
+    pub skipped: Vec<String>,
+}
+
+impl CodeFix {
+    /// 收集诊断中机器可应用的建议
+    ///
+    /// 相对路径基于 `base_dir` 解析，位于 `base_dir` 之外的文件被跳过；
+    /// 同一文件中重叠的建议只应用第一条。没有可应用的建议时返回 None。
+    pub fn from_diagnostics(diagnostics: &[Diagnostic], base_dir: &Path) -> Option<Self> {
+        let mut by_file: BTreeMap<&str, Vec<&Suggestion>> = BTreeMap::new();
+        for suggestion in diagnostics.iter().flat_map(Diagnostic::applicable_suggestions) {
+            let entry = by_file.entry(suggestion.file.as_str()).or_default();
+            if !entry.contains(&suggestion) {
+                entry.push(suggestion);
+            }
+        }
+        if by_file.is_empty() {
+            return None;
+        }
+
+        let mut patches = Vec::new();
+        let mut skipped = Vec::new();
+        for (file, suggestions) in by_file {
+            let path = match project_path(base_dir, file) {
+                Ok(path) => path,
+                Err(e) => {
+                    skipped.push(e);
+                    continue;
+                }
+            };
+            let original = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) => {
+                    skipped.push(format!("{}: {}", path.display(), e));
+                    continue;
+                }
+            };
+
+            let mut edits = Vec::new();
+            for suggestion in suggestions {
+                match resolve_range(&original, &suggestion.range) {
+                    Some((start, end)) => edits.push((start, end, suggestion.replacement.as_str())),
+                    None => skipped.push(format!("{}: 范围无效（{}）", path.display(), suggestion.message)),
+                }
+            }
+
+            let (patched, applied, overlapping) = apply_edits(&original, edits);
+            if overlapping > 0 {
+                skipped.push(format!("{}: {} 条建议与其他建议重叠", path.display(), overlapping));
+            }
+            if applied > 0 && patched != original {
+                patches.push(FilePatch { path, original, patched, edits: applied });
+            }
+        }
+
+        let edits: usize = patches.iter().map(|p| p.edits).sum();
+        Some(Self {
+            strategy_name: COMPILER_FIX_STRATEGY,
+            description: format!("{} 处修改，涉及 {} 个文件", edits, patches.len()),
+            patches,
+            skipped,
+        })
+    }
+
+    /// 请 LLM 针对一条诊断生成补丁
+    ///
+    /// LLM 只看到诊断位置附近的代码，返回要替换的行范围和新内容。
+    pub async fn propose_with_llm(
+        diagnostic: &Diagnostic,
+        base_dir: &Path,
+        llm: &dyn LlmClient,
+    ) -> Result<Self, LlmError> {
+        let path = project_path(base_dir, &diagnostic.file).map_err(LlmError::Other)?;
+        let original = std::fs::read_to_string(&path)
+            .map_err(|e| LlmError::Other(format!("读取 {} 失败: {}", path.display(), e)))?;
+
+        let lines: Vec<&str> = original.lines().collect();
+        let first = diagnostic.line.saturating_sub(LLM_EXCERPT_RADIUS).max(1);
+        let last = (diagnostic.line + LLM_EXCERPT_RADIUS).min(lines.len());
+        let excerpt: String = (first..=last)
+            .map(|n| format!("{:>5} | {}\n", n, lines[n - 1]))
+            .collect();
+
+        let prompt = format!(
+            r#"修复以下代码中的编译/运行错误。
+
+文件: {}
+位置: 第 {} 行第 {} 列
+错误: {}
+
+代码（带行号）:
+{}
+请返回 JSON：
+{{
+  "start_line": 起始行号,
+  "end_line": 结束行号（包含）,
+  "replacement": "替换这些行的新代码（不带行号）",
+  "description": "修改说明"
+}}
+
+只修改必要的行，只返回 JSON，不要其他解释。"#,
+            diagnostic.file, diagnostic.line, diagnostic.column, diagnostic.message, excerpt
+        );
+
+        let response = llm.chat(vec![Message::user(prompt)]).await?;
+        let (start_line, end_line, replacement, description) = parse_patch_response(&response)?;
+        if start_line == 0 || start_line > end_line || end_line > lines.len() {
+            return Err(LlmError::Parse(format!("补丁行范围无效: {}-{}", start_line, end_line)));
+        }
+
+        let mut patched_lines: Vec<&str> = lines[..start_line - 1].to_vec();
+        patched_lines.extend(replacement.lines());
+        patched_lines.extend(&lines[end_line..]);
+        let mut patched = patched_lines.join("\n");
+        if original.ends_with('\n') {
+            patched.push('\n');
+        }
+
+        Ok(Self {
+            strategy_name: LLM_PATCH_STRATEGY,
+            description,
+            patches: vec![FilePatch { path, original, patched, edits: 1 }],
+            skipped: Vec::new(),
+        })
+    }
+
+    /// 所有文件的 diff 预览
+    pub fn diff(&self) -> String {
+        self.patches.iter().map(FilePatch::diff).collect::<Vec<_>>().join("\n")
+    }
+
+    /// 写入所有补丁（遇到第一个失败即停止）
+    pub fn apply(&self) -> Result<usize, String> {
+        for patch in &self.patches {
+            patch.apply()?;
+        }
+        Ok(self.patches.len())
+    }
+
+    /// 作为修复策略（用于反馈学习）
+    pub fn to_strategy(&self) -> FixStrategy {
+        let files: Vec<String> = self.patches.iter().map(|p| p.path.display().to_string()).collect();
+        let risk = if self.strategy_name == COMPILER_FIX_STRATEGY { 3 } else { 5 };
+        FixStrategy::new(self.strategy_name, format!("edit {}", files.join(" ")), &self.description, risk)
+            .with_confirmation(true)
+            .with_outcome("修改源文件")
+    }
+}
+
+/// 解析诊断中的文件路径，拒绝位于项目目录之外的文件
+///
+/// 相对路径基于 `base_dir`；解析符号链接与 `..` 后必须仍在 `base_dir` 之内。
+fn project_path(base_dir: &Path, file: &str) -> Result<PathBuf, String> {
+    let root = base_dir
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", base_dir.display(), e))?;
+    let path = base_dir
+        .join(file)
+        .canonicalize()
+        .map_err(|e| format!("{}: {}", file, e))?;
+    if path.starts_with(&root) {
+        Ok(path)
+    } else {
+        Err(format!("{}: 不在项目目录 {} 内，拒绝修改", file, root.display()))
+    }
+}
+
+/// 把替换范围换算为字节偏移（越界或不在字符边界上时为 None）
+fn resolve_range(content: &str, range: &EditRange) -> Option<(usize, usize)> {
+    let (start, end) = match *range {
+        EditRange::Bytes { start, end } => (start, end),
+        EditRange::Utf16 { start, end } => (utf16_offset(content, start)?, utf16_offset(content, end)?),
+        EditRange::LineColumn { start_line, start_column, end_line, end_column } => (
+            line_column_offset(content, start_line, start_column)?,
+            line_column_offset(content, end_line, end_column)?,
+        ),
+    };
+    (start <= end && end <= content.len() && content.is_char_boundary(start) && content.is_char_boundary(end))
+        .then_some((start, end))
+}
+
+/// UTF-16 码元偏移对应的字节偏移（落在代理对中间或越界时为 None）
+fn utf16_offset(content: &str, units: usize) -> Option<usize> {
+    let mut counted = 0;
+    for (offset, c) in content.char_indices() {
+        if counted == units {
+            return Some(offset);
+        }
+        if counted > units {
+            return None;
+        }
+        counted += c.len_utf16();
+    }
+    (counted == units).then_some(content.len())
+}
+
+/// 行列（从 1 开始，列按字节计）对应的字节偏移
+fn line_column_offset(content: &str, line: usize, column: usize) -> Option<usize> {
+    let mut offset = 0;
+    for (index, text) in content.split_inclusive('\n').enumerate() {
+        if index + 1 == line {
+            return (column >= 1 && column - 1 <= text.len()).then_some(offset + column - 1);
+        }
+        offset += text.len();
+    }
+    // 允许指向文件末尾
+    (line == content.split_inclusive('\n').count() + 1 && column == 1).then_some(content.len())
+}
+
+/// 应用替换，返回 (新内容, 应用数, 因重叠跳过数)
+///
+/// 与先前建议重叠的建议被跳过；接受的修改从后往前应用，偏移互不影响。
+fn apply_edits(content: &str, edits: Vec<(usize, usize, &str)>) -> (String, usize, usize) {
+    let mut accepted: Vec<(usize, usize, &str)> = Vec::new();
+    let mut overlapping = 0;
+    for edit in edits {
+        let overlaps = accepted
+            .iter()
+            .any(|other| (edit.0 < other.1 && other.0 < edit.1) || (edit.0 == other.0 && (edit.0 == edit.1 || other.0 == other.1)));
+        if overlaps {
+            overlapping += 1;
+        } else {
+            accepted.push(edit);
+        }
+    }
+
+    accepted.sort_by_key(|edit| std::cmp::Reverse(edit.0));
+    let mut result = content.to_string();
+    for (start, end, replacement) in &accepted {
+        result.replace_range(*start..*end, replacement);
+    }
+    (result, accepted.len(), overlapping)
+}
+
+/// 解析 LLM 补丁响应
+fn parse_patch_response(response: &str) -> Result<(usize, usize, String, String), LlmError> {
+    let json_str = match (response.find('{'), response.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => response,
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(json_str).map_err(|e| LlmError::Parse(format!("JSON 解析失败: {}", e)))?;
+
+    let line = |key: &str| {
+        value[key]
+            .as_u64()
+            .map(|n| n as usize)
+            .ok_or_else(|| LlmError::Parse(format!("缺少 {}", key)))
+    };
+    let replacement = value["replacement"]
+        .as_str()
+        .ok_or_else(|| LlmError::Parse("缺少 replacement".to_string()))?
+        .to_string();
+    let description = value["description"].as_str().unwrap_or("LLM 生成的补丁").to_string();
+    Ok((line("start_line")?, line("end_line")?, replacement, description))
+}
+
+/// 行级 unified diff（带 [`DIFF_CONTEXT`] 行上下文）
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let mut output = format!("--- a/{}\n+++ b/{}\n", path, path);
+    let changed: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| !matches!(op, DiffOp::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        return output;
+    }
+
+    // 把相距不超过 2 * DIFF_CONTEXT 的修改合并成一个 hunk
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        let start = i.saturating_sub(DIFF_CONTEXT);
+        let end = (i + DIFF_CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let (mut old_start, mut new_start) = (0, 0);
+        for op in &ops[..start] {
+            let (o, n) = op.counts();
+            old_start += o;
+            new_start += n;
+        }
+        let (old_count, new_count) = ops[start..end]
+            .iter()
+            .map(DiffOp::counts)
+            .fold((0, 0), |acc, (o, n)| (acc.0 + o, acc.1 + n));
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        ));
+        for op in &ops[start..end] {
+            match op {
+                DiffOp::Equal(line) => output.push_str(&format!(" {}\n", line)),
+                DiffOp::Delete(line) => output.push_str(&format!("-{}\n", line)),
+                DiffOp::Insert(line) => output.push_str(&format!("+{}\n", line)),
+            }
+        }
+    }
+    output
+}
+
+enum DiffOp<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+impl DiffOp<'_> {
+    /// (旧文件行数, 新文件行数)
+    fn counts(&self) -> (usize, usize) {
+        match self {
+            DiffOp::Equal(_) => (1, 1),
+            DiffOp::Delete(_) => (1, 0),
+            DiffOp::Insert(_) => (0, 1),
+        }
+    }
+}
+
+/// 基于最长公共子序列的行级 diff（先去掉公共前后缀，中间部分通常很小）
+///
+/// 中间部分的 LCS 表超过 [`MAX_DIFF_CELLS`] 时不再逐行对齐，整段删除后整段插入。
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffOp<'a>> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = old[..prefix].iter().map(|l| DiffOp::Equal(l)).collect();
+    if old_mid.len().saturating_mul(new_mid.len()) > MAX_DIFF_CELLS {
+        ops.extend(old_mid.iter().map(|l| DiffOp::Delete(l)));
+        ops.extend(new_mid.iter().map(|l| DiffOp::Insert(l)));
+        ops.extend(old[old.len() - suffix..].iter().map(|l| DiffOp::Equal(l)));
+        return ops;
+    }
+
+    // lcs[i][j] = old_mid[i..] 与 new_mid[j..] 的 LCS 长度
+    let mut lcs = vec![vec![0usize; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() || j < new_mid.len() {
+        if i < old_mid.len() && j < new_mid.len() && old_mid[i] == new_mid[j] {
+            ops.push(DiffOp::Equal(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if i < old_mid.len() && (j == new_mid.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(DiffOp::Delete(old_mid[i]));
+            i += 1;
+        } else {
+            ops.push(DiffOp::Insert(new_mid[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[old.len() - suffix..].iter().map(|l| DiffOp::Equal(l)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_fixer::diagnostics::{parse_diagnostics, DiagnosticLevel, DiagnosticSource};
+
+    fn diagnostic_with(file: &str, suggestions: Vec<Suggestion>) -> Diagnostic {
+        Diagnostic {
+            source: DiagnosticSource::Rustc,
+            level: DiagnosticLevel::Warning,
+            file: file.to_string(),
+            line: 1,
+            column: 1,
+            message: "test".to_string(),
+            code: None,
+            suggestions,
+        }
+    }
+
+    fn suggestion(file: &str, range: EditRange, replacement: &str) -> Suggestion {
+        Suggestion {
+            message: "fix".to_string(),
+            file: file.to_string(),
+            range,
+            replacement: replacement.to_string(),
+            machine_applicable: true,
+        }
+    }
+
+    #[test]
+    fn test_resolve_range() {
+        let content = "ab\ncde\n";
+        assert_eq!(resolve_range(content, &EditRange::Bytes { start: 3, end: 6 }), Some((3, 6)));
+        assert_eq!(resolve_range(content, &EditRange::Bytes { start: 3, end: 60 }), None);
+        let range = EditRange::LineColumn { start_line: 2, start_column: 2, end_line: 2, end_column: 4 };
+        assert_eq!(resolve_range(content, &range), Some((4, 6)));
+        let range = EditRange::LineColumn { start_line: 9, start_column: 1, end_line: 9, end_column: 1 };
+        assert_eq!(resolve_range(content, &range), None);
+
+        // UTF-16 偏移：“é” 占 1 个码元 2 个字节，“😀” 占 2 个码元 4 个字节
+        let content = "é😀x";
+        assert_eq!(resolve_range(content, &EditRange::Utf16 { start: 3, end: 4 }), Some((6, 7)));
+        assert_eq!(resolve_range(content, &EditRange::Utf16 { start: 4, end: 4 }), Some((7, 7)));
+        assert_eq!(resolve_range(content, &EditRange::Utf16 { start: 2, end: 3 }), None);
+        assert_eq!(resolve_range(content, &EditRange::Utf16 { start: 0, end: 5 }), None);
+    }
+
+    #[test]
+    fn test_from_diagnostics_rejects_paths_outside_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(project.join("a.js"), "let a = 1\n").unwrap();
+        std::fs::write(dir.path().join("outside.js"), "let b = 2\n").unwrap();
+
+        let outside = dir.path().join("outside.js").display().to_string();
+        let diagnostics = vec![
+            diagnostic_with("../outside.js", vec![suggestion("../outside.js", EditRange::Bytes { start: 9, end: 9 }, ";")]),
+            diagnostic_with(&outside, vec![suggestion(&outside, EditRange::Bytes { start: 9, end: 9 }, ";")]),
+            diagnostic_with("a.js", vec![suggestion("a.js", EditRange::Bytes { start: 9, end: 9 }, ";")]),
+        ];
+
+        let fix = CodeFix::from_diagnostics(&diagnostics, &project).unwrap();
+        assert_eq!(fix.patches.len(), 1);
+        assert!(fix.patches[0].path.ends_with("a.js"));
+        assert_eq!(fix.skipped.len(), 2);
+        assert!(fix.skipped.iter().all(|s| s.contains("不在项目目录")));
+    }
+
+    #[test]
+    fn test_diff_lines_falls_back_for_large_inputs() {
+        let old: Vec<String> = (0..3000).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..3000).map(|i| format!("new {}", i)).collect();
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+
+        let ops = diff_lines(&old, &new);
+        assert_eq!(ops.len(), 6000);
+        assert!(ops[..3000].iter().all(|op| matches!(op, DiffOp::Delete(_))));
+        assert!(ops[3000..].iter().all(|op| matches!(op, DiffOp::Insert(_))));
+    }
+
+    #[test]
+    fn test_apply_edits_skips_overlaps() {
+        let (text, applied, overlapping) = apply_edits("let x = 1;", vec![(4, 5, "_x"), (4, 9, "y = 2"), (10, 10, "\n")]);
+        assert_eq!(text, "let _x = 1;\n");
+        assert_eq!((applied, overlapping), (2, 1));
+    }
+
+    #[test]
+    fn test_from_diagnostics_and_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "fn main() {\n    let x = 1;\n}\n").unwrap();
+
+        let mut not_applicable = suggestion("main.rs", EditRange::Bytes { start: 0, end: 2 }, "pub fn");
+        not_applicable.machine_applicable = false;
+        let diagnostics = vec![diagnostic_with(
+            "main.rs",
+            vec![suggestion("main.rs", EditRange::Bytes { start: 20, end: 21 }, "_x"), not_applicable],
+        )];
+
+        let fix = CodeFix::from_diagnostics(&diagnostics, dir.path()).unwrap();
+        assert_eq!(fix.patches.len(), 1);
+        assert!(fix.skipped.is_empty());
+        let diff = fix.diff();
+        assert!(diff.contains("-    let x = 1;"));
+        assert!(diff.contains("+    let _x = 1;"));
+
+        assert_eq!(fix.apply(), Ok(1));
+        let content = std::fs::read_to_string(dir.path().join("main.rs")).unwrap();
+        assert_eq!(content, "fn main() {\n    let _x = 1;\n}\n");
+
+        // 文件已变化，不再覆盖
+        assert!(fix.apply().is_err());
+        assert_eq!(fix.to_strategy().name, COMPILER_FIX_STRATEGY);
+
+        // 没有可应用的建议
+        assert!(CodeFix::from_diagnostics(&parse_diagnostics("a.c:1:1: error: x"), dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\n";
+        let new = "a\nB\nc\nd\ne\nf\ng\nh\nI\n";
+        let diff = unified_diff("x.txt", old, new);
+        assert!(diff.starts_with("--- a/x.txt\n+++ b/x.txt\n"));
+        assert!(diff.contains("@@ -1,4 +1,4 @@\n a\n-b\n+B\n c\n d\n"));
+        assert!(diff.contains("@@ -7,3 +7,3 @@\n g\n h\n-i\n+I\n"));
+        assert_eq!(unified_diff("x", "same\n", "same\n"), "--- a/x\n+++ b/x\n");
+    }
+
+    #[test]
+    fn test_parse_patch_response() {
+        let response = "```json\n{\"start_line\": 2, \"end_line\": 3, \"replacement\": \"let y = 2;\", \"description\": \"声明 y\"}\n```";
+        let (start, end, replacement, description) = parse_patch_response(response).unwrap();
+        assert_eq!((start, end), (2, 3));
+        assert_eq!(replacement, "let y = 2;");
+        assert_eq!(description, "声明 y");
+        assert!(parse_patch_response("{\"start_line\": 1}").is_err());
+    }
+}
@@ -0,0 +1,500 @@
+//! 离线命令纠错
+//!
+//! 命令不存在或子命令拼写错误时，在不调用 LLM 的情况下推测用户想输入的命令。
+//!
+//! 候选来源：
+//! - `$PATH` 中的可执行文件
+//! - 命令历史（HistoryManager）中用过的命令与子命令
+//! - 常见工具的子命令表（`git comit` → `git commit`，`cargo biuld` → `cargo build`）
+//!
+//! 排序依据 `string_similarity`，并参考用户过去对纠正建议的采纳情况（FeedbackLearner）。
+
+use super::feedback::{FeedbackRecord, FeedbackType};
+use crate::dsl::intent::matcher::string_similarity;
+use crate::history::HistoryEntry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// 纠正建议对应的修复策略名称（反馈学习据此识别纠错记录）
+pub const CORRECTION_STRATEGY: &str = "纠正拼写";
+
+/// 候选的最低相似度
+const MIN_SIMILARITY: f64 = 0.6;
+
+/// 最多给出的建议数
+const MAX_SUGGESTIONS: usize = 3;
+
+/// 常见工具的子命令表
+const KNOWN_SUBCOMMANDS: &[(&str, &[&str])] = &[
+    (
+        "git",
+        &[
+            "add", "am", "bisect", "blame", "branch", "checkout", "cherry-pick", "clean", "clone",
+            "commit", "config", "diff", "fetch", "grep", "init", "log", "merge", "mv", "pull",
+            "push", "rebase", "reflog", "remote", "reset", "restore", "revert", "rm", "show",
+            "stash", "status", "switch", "tag", "worktree",
+        ],
+    ),
+    (
+        "cargo",
+        &[
+            "add", "bench", "build", "check", "clean", "clippy", "doc", "fetch", "fix", "fmt",
+            "init", "install", "metadata", "new", "publish", "remove", "run", "search", "test",
+            "tree", "uninstall", "update",
+        ],
+    ),
+    (
+        "npm",
+        &[
+            "audit", "cache", "ci", "config", "init", "install", "link", "list", "outdated",
+            "pack", "publish", "run", "start", "test", "uninstall", "update", "version",
+        ],
+    ),
+    (
+        "docker",
+        &[
+            "build", "compose", "exec", "images", "inspect", "kill", "login", "logs", "network",
+            "ps", "pull", "push", "restart", "rm", "rmi", "run", "start", "stop", "tag", "volume",
+        ],
+    ),
+    (
+        "kubectl",
+        &[
+            "apply", "config", "create", "delete", "describe", "edit", "exec", "expose", "get",
+            "logs", "port-forward", "rollout", "scale",
+        ],
+    ),
+    (
+        "pip",
+        &["download", "freeze", "install", "list", "show", "uninstall"],
+    ),
+    (
+        "go",
+        &["build", "clean", "doc", "env", "fmt", "generate", "get", "install", "mod", "run", "test", "vet"],
+    ),
+    (
+        "systemctl",
+        &["disable", "enable", "reload", "restart", "start", "status", "stop"],
+    ),
+];
+
+/// 候选来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrectionSource {
+    /// `$PATH` 中的可执行文件
+    Path,
+    /// 命令历史
+    History,
+    /// 内置子命令表
+    Subcommand,
+}
+
+impl fmt::Display for CorrectionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CorrectionSource::Path => write!(f, "PATH"),
+            CorrectionSource::History => write!(f, "历史命令"),
+            CorrectionSource::Subcommand => write!(f, "常见子命令"),
+        }
+    }
+}
+
+/// 一条纠正建议
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    /// 纠正后的完整命令
+    pub command: String,
+    /// 拼错的词
+    pub typo: String,
+    /// 替换后的词
+    pub replacement: String,
+    /// 候选来源
+    pub source: CorrectionSource,
+    /// 排序得分（相似度 + 历史与反馈加成）
+    pub score: f64,
+}
+
+/// 离线命令纠错器
+#[derive(Debug, Default)]
+pub struct CommandCorrector {
+    /// 可执行文件名
+    executables: HashSet<String>,
+    /// 历史中的程序名 → 使用次数
+    history_programs: HashMap<String, u32>,
+    /// 历史中的 (程序名, 子命令) → 使用次数
+    history_subcommands: HashMap<(String, String), u32>,
+    /// (拼错的词, 替换词) → 采纳次数 - 拒绝次数
+    feedback: HashMap<(String, String), i32>,
+}
+
+impl CommandCorrector {
+    /// 创建空的纠错器（只使用内置子命令表）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用当前 `$PATH` 中的可执行文件
+    pub fn from_env() -> Self {
+        Self::new().with_executables(path_executables())
+    }
+
+    /// 添加可执行文件名
+    pub fn with_executables(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.executables.extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    /// 添加命令历史
+    ///
+    /// 只采用 shell 命令（`!` 开头）或程序名是已知可执行文件的记录，
+    /// 自然语言输入和系统命令不作为候选。
+    pub fn with_history<'a>(mut self, entries: impl IntoIterator<Item = &'a HistoryEntry>) -> Self {
+        for entry in entries {
+            let (line, is_shell) = match entry.command.trim().strip_prefix('!') {
+                Some(rest) => (rest, true),
+                None => (entry.command.trim(), false),
+            };
+            let mut words = line.split_whitespace();
+            let Some(program) = words.next() else {
+                continue;
+            };
+            if !is_shell && !self.executables.contains(program) {
+                continue;
+            }
+
+            *self.history_programs.entry(program.to_string()).or_default() += entry.count;
+            if let Some(sub) = words.next().filter(|w| is_subcommand_word(w)) {
+                *self
+                    .history_subcommands
+                    .entry((program.to_string(), sub.to_string()))
+                    .or_default() += entry.count;
+            }
+        }
+        self
+    }
+
+    /// 参考过去对纠正建议的反馈
+    pub fn with_feedback<'a>(mut self, records: impl IntoIterator<Item = &'a FeedbackRecord>) -> Self {
+        for record in records {
+            if record.strategy_name != CORRECTION_STRATEGY {
+                continue;
+            }
+            let corrected = record.modified_command.as_deref().unwrap_or(&record.strategy_command);
+            let Some(pair) = replaced_word(&record.original_command, corrected) else {
+                continue;
+            };
+
+            let delta = if record.is_positive() {
+                1
+            } else if record.feedback == FeedbackType::Rejected {
+                -1
+            } else {
+                0
+            };
+            *self.feedback.entry(pair).or_default() += delta;
+        }
+        self
+    }
+
+    /// 推测用户想输入的命令（按得分降序，最多 3 条）
+    ///
+    /// 程序名已知时检查子命令拼写，否则纠正程序名。
+    pub fn correct(&self, command: &str) -> Vec<Correction> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let Some(&program) = words.first() else {
+            return Vec::new();
+        };
+
+        let mut corrections = if self.is_known_program(program) {
+            match words.get(1) {
+                Some(sub) if is_subcommand_word(sub) => self.correct_subcommand(command, program, sub),
+                _ => Vec::new(),
+            }
+        } else {
+            self.correct_program(command, program)
+        };
+
+        corrections.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.replacement.cmp(&b.replacement))
+        });
+        corrections.truncate(MAX_SUGGESTIONS);
+        corrections
+    }
+
+    fn is_known_program(&self, program: &str) -> bool {
+        self.executables.contains(program)
+            || self.history_programs.contains_key(program)
+            || KNOWN_SUBCOMMANDS.iter().any(|(tool, _)| *tool == program)
+    }
+
+    fn correct_program(&self, command: &str, typo: &str) -> Vec<Correction> {
+        let candidates = self
+            .executables
+            .iter()
+            .map(|name| (name.as_str(), CorrectionSource::Path))
+            .chain(
+                self.history_programs
+                    .keys()
+                    .filter(|name| !self.executables.contains(*name))
+                    .map(|name| (name.as_str(), CorrectionSource::History)),
+            );
+
+        candidates
+            .filter_map(|(candidate, source)| {
+                let similarity = similarity(typo, candidate)?;
+                let uses = self.history_programs.get(candidate).copied().unwrap_or(0);
+                Some(Correction {
+                    command: replace_word(command, 0, candidate),
+                    typo: typo.to_string(),
+                    replacement: candidate.to_string(),
+                    source,
+                    score: similarity + history_boost(uses) + self.feedback_boost(typo, candidate),
+                })
+            })
+            .collect()
+    }
+
+    fn correct_subcommand(&self, command: &str, program: &str, typo: &str) -> Vec<Correction> {
+        let builtin: &[&str] = KNOWN_SUBCOMMANDS
+            .iter()
+            .find(|(tool, _)| *tool == program)
+            .map(|(_, subs)| *subs)
+            .unwrap_or(&[]);
+        let used = |sub: &str| {
+            self.history_subcommands
+                .get(&(program.to_string(), sub.to_string()))
+                .copied()
+                .unwrap_or(0)
+        };
+
+        // 子命令本身有效（内置表或历史中用过）时不做纠正
+        if builtin.contains(&typo) || used(typo) > 0 {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<(&str, CorrectionSource)> =
+            builtin.iter().map(|sub| (*sub, CorrectionSource::Subcommand)).collect();
+        for (tool, sub) in self.history_subcommands.keys() {
+            if tool == program && !builtin.contains(&sub.as_str()) {
+                candidates.push((sub.as_str(), CorrectionSource::History));
+            }
+        }
+
+        candidates
+            .into_iter()
+            .filter_map(|(candidate, source)| {
+                let similarity = similarity(typo, candidate)?;
+                Some(Correction {
+                    command: replace_word(command, 1, candidate),
+                    typo: typo.to_string(),
+                    replacement: candidate.to_string(),
+                    source,
+                    score: similarity + history_boost(used(candidate)) + self.feedback_boost(typo, candidate),
+                })
+            })
+            .collect()
+    }
+
+    fn feedback_boost(&self, typo: &str, candidate: &str) -> f64 {
+        let net = self
+            .feedback
+            .get(&(typo.to_string(), candidate.to_string()))
+            .copied()
+            .unwrap_or(0);
+        0.15 * net.clamp(-2, 2) as f64
+    }
+}
+
+/// 候选相似度（低于阈值时为 None）
+///
+/// 相邻字母互换（`sl` → `ls`）按编辑距离计算相似度很低，单独视为高相似。
+fn similarity(typo: &str, candidate: &str) -> Option<f64> {
+    if typo == candidate {
+        return None;
+    }
+    let mut score = string_similarity(typo, candidate);
+    if is_transposition(typo, candidate) {
+        score = score.max(0.8);
+    }
+    (score >= MIN_SIMILARITY).then_some(score)
+}
+
+/// 历史使用次数带来的加成（最多 0.2）
+fn history_boost(uses: u32) -> f64 {
+    0.05 * uses.min(4) as f64
+}
+
+/// 两个词是否只差一次相邻字符互换
+fn is_transposition(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.len() != b.len() {
+        return false;
+    }
+    let diffs: Vec<usize> = (0..a.len()).filter(|&i| a[i] != b[i]).collect();
+    matches!(diffs.as_slice(), [i, j] if *j == i + 1 && a[*i] == b[*j] && a[*j] == b[*i])
+}
+
+/// 看起来像子命令的词（排除选项、路径和赋值）
+fn is_subcommand_word(word: &str) -> bool {
+    !word.starts_with('-')
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 替换命令中的第 `index` 个词，保留其余部分的原样（引号、空白）
+fn replace_word(command: &str, index: usize, replacement: &str) -> String {
+    let Some(word) = command.split_whitespace().nth(index) else {
+        return command.to_string();
+    };
+    let start = word.as_ptr() as usize - command.as_ptr() as usize;
+    format!("{}{}{}", &command[..start], replacement, &command[start + word.len()..])
+}
+
+/// 原命令与纠正后命令中第一个被替换的词
+fn replaced_word(original: &str, corrected: &str) -> Option<(String, String)> {
+    original
+        .split_whitespace()
+        .zip(corrected.split_whitespace())
+        .find(|(a, b)| a != b)
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+}
+
+/// 扫描 `$PATH` 中的可执行文件名
+fn path_executables() -> HashSet<String> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return HashSet::new();
+    };
+
+    let mut names = HashSet::new();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if is_executable(&metadata) {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.insert(name.to_string());
+                }
+            }
+        }
+    }
+    names
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    metadata.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_fixer::{ErrorAnalysis, FixOutcome, FixStrategy};
+
+    fn entry(command: &str, count: u32) -> HistoryEntry {
+        let mut entry = HistoryEntry::new(command.to_string());
+        entry.count = count;
+        entry
+    }
+
+    fn feedback(original: &str, corrected: &str, feedback: FeedbackType, outcome: FixOutcome) -> FeedbackRecord {
+        let analysis = ErrorAnalysis::new(String::new(), original.to_string());
+        let strategy = FixStrategy::new(CORRECTION_STRATEGY, corrected, "", 3);
+        FeedbackRecord::new(&analysis, &strategy, feedback, outcome)
+    }
+
+    #[test]
+    fn test_subcommand_typos() {
+        let corrector = CommandCorrector::new();
+
+        let fixes = corrector.correct("git comit -m \"fix  bug\"");
+        assert_eq!(fixes[0].command, "git commit -m \"fix  bug\"");
+        assert_eq!(fixes[0].source, CorrectionSource::Subcommand);
+
+        assert_eq!(corrector.correct("cargo biuld --release")[0].command, "cargo build --release");
+
+        // 有效子命令、选项不做纠正
+        assert!(corrector.correct("git commit -m x").is_empty());
+        assert!(corrector.correct("git --version").is_empty());
+    }
+
+    #[test]
+    fn test_program_typos() {
+        let corrector = CommandCorrector::new().with_executables(["ls", "grep", "python3", "cargo"]);
+
+        assert_eq!(corrector.correct("sl -la")[0].command, "ls -la");
+        assert_eq!(corrector.correct("grpe foo file")[0].replacement, "grep");
+        assert_eq!(corrector.correct("pyhton3 main.py")[0].command, "python3 main.py");
+        assert!(corrector.correct("zzzzzz").is_empty());
+        assert!(corrector.correct("").is_empty());
+    }
+
+    #[test]
+    fn test_history_candidates() {
+        // 历史中用过的自定义工具和子命令也作为候选
+        let corrector = CommandCorrector::new()
+            .with_executables(["git"])
+            .with_history([
+                &entry("!deploytool --prod", 3),
+                &entry("git sync-upstream", 2),
+                &entry("帮我统计代码行数", 5),
+            ]);
+
+        let fixes = corrector.correct("deploytol --prod");
+        assert_eq!(fixes[0].replacement, "deploytool");
+        assert_eq!(fixes[0].source, CorrectionSource::History);
+
+        assert_eq!(corrector.correct("git sync-upstraem")[0].replacement, "sync-upstream");
+        assert!(corrector.correct("git sync-upstream").is_empty());
+        assert!(!corrector.history_programs.contains_key("帮我统计代码行数"));
+    }
+
+    #[test]
+    fn test_ranking_uses_history_and_feedback() {
+        // "gti" 与 gt、git 相似度接近
+        let base = CommandCorrector::new().with_executables(["gt", "git"]);
+        let first = base.correct("gti status")[0].replacement.clone();
+        assert_eq!(first, "git");
+
+        let records = [
+            feedback("gti status", "gt status", FeedbackType::Accepted, FixOutcome::Success),
+            feedback("gti log", "gt log", FeedbackType::Accepted, FixOutcome::Success),
+            feedback("gti log", "git log", FeedbackType::Rejected, FixOutcome::Failure),
+        ];
+        let learned = CommandCorrector::new()
+            .with_executables(["gt", "git"])
+            .with_feedback(records.iter());
+        assert_eq!(learned.correct("gti status")[0].replacement, "gt");
+
+        let with_history = CommandCorrector::new()
+            .with_executables(["gt", "git"])
+            .with_history([&entry("gt build", 4)]);
+        assert_eq!(with_history.correct("gti status")[0].replacement, "gt");
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert!(is_transposition("sl", "ls"));
+        assert!(is_transposition("biuld", "build"));
+        assert!(!is_transposition("abc", "cba"));
+        assert_eq!(replace_word("  git  comit -a", 1, "commit"), "  git  commit -a");
+        assert_eq!(
+            replaced_word("git comit -a", "git commit -a"),
+            Some(("comit".to_string(), "commit".to_string()))
+        );
+    }
+}
@@ -0,0 +1,615 @@
+//! 结构化编译器诊断
+//!
+//! 把编译器、类型检查器和解释器的输出解析为 文件/行/列/消息/建议替换 记录，
+//! 而不是只对原始 stderr 做正则匹配。
+//!
+//! 支持的格式：
+//! - `cargo --message-format=json` 与 `rustc --error-format=json`（含机器可应用的建议）
+//! - rustc / cargo 默认的人类可读输出
+//! - gcc / clang（含 `-fdiagnostics-parseable-fixits` 的 fix-it 提示）
+//! - `tsc`（`file(1,2)` 与 `--pretty` 两种格式）
+//! - `eslint --format json`（含 `fix`）
+//! - Python traceback
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// 诊断来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSource {
+    /// rustc（JSON 或人类可读格式）
+    Rustc,
+    /// gcc / clang
+    Gcc,
+    /// TypeScript 编译器
+    Tsc,
+    /// ESLint
+    Eslint,
+    /// Python 解释器
+    Python,
+}
+
+impl fmt::Display for DiagnosticSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticSource::Rustc => write!(f, "rustc"),
+            DiagnosticSource::Gcc => write!(f, "gcc/clang"),
+            DiagnosticSource::Tsc => write!(f, "tsc"),
+            DiagnosticSource::Eslint => write!(f, "eslint"),
+            DiagnosticSource::Python => write!(f, "python"),
+        }
+    }
+}
+
+/// 诊断级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    /// 错误
+    Error,
+    /// 警告
+    Warning,
+    /// 附注
+    Note,
+}
+
+impl DiagnosticLevel {
+    fn parse(level: &str) -> Self {
+        match level.to_lowercase().as_str() {
+            "error" | "fatal error" | "error: internal compiler error" => Self::Error,
+            "warning" => Self::Warning,
+            _ => Self::Note,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticLevel::Error => write!(f, "错误"),
+            DiagnosticLevel::Warning => write!(f, "警告"),
+            DiagnosticLevel::Note => write!(f, "附注"),
+        }
+    }
+}
+
+/// 替换范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditRange {
+    /// 文件内的字节偏移 `[start, end)`
+    Bytes { start: usize, end: usize },
+    /// 文件内的 UTF-16 码元偏移 `[start, end)`（ESLint 的 `fix.range`），应用时按文件内容换算为字节偏移
+    Utf16 { start: usize, end: usize },
+    /// 行列位置（从 1 开始，列按字节计，结束位置不含）
+    LineColumn {
+        start_line: usize,
+        start_column: usize,
+        end_line: usize,
+        end_column: usize,
+    },
+}
+
+/// 建议替换
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// 建议说明
+    pub message: String,
+    /// 要修改的文件
+    pub file: String,
+    /// 替换范围
+    pub range: EditRange,
+    /// 替换文本
+    pub replacement: String,
+    /// 是否可以不经人工修改直接应用
+    pub machine_applicable: bool,
+}
+
+/// 一条诊断
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// 来源
+    pub source: DiagnosticSource,
+    /// 级别
+    pub level: DiagnosticLevel,
+    /// 文件路径（与编译器输出一致，通常相对于当前目录）
+    pub file: String,
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 列号（从 1 开始）
+    pub column: usize,
+    /// 诊断消息
+    pub message: String,
+    /// 错误代码（E0425、TS2322、no-unused-vars 等）
+    pub code: Option<String>,
+    /// 建议替换
+    pub suggestions: Vec<Suggestion>,
+}
+
+impl Diagnostic {
+    fn new(source: DiagnosticSource, level: DiagnosticLevel, file: &str, line: usize, column: usize, message: &str) -> Self {
+        Self {
+            source,
+            level,
+            file: file.to_string(),
+            line,
+            column,
+            message: message.trim().to_string(),
+            code: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// 可以直接应用的建议
+    pub fn applicable_suggestions(&self) -> impl Iterator<Item = &Suggestion> {
+        self.suggestions.iter().filter(|s| s.machine_applicable)
+    }
+}
+
+/// 解析命令输出中的所有诊断
+///
+/// 依次尝试各格式的解析器；同一位置、同一消息的诊断只保留一条
+/// （cargo JSON 中的 `rendered` 字段会让人类可读格式再出现一次）。
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let lines: Vec<&str> = output.lines().map(clean_line).collect();
+
+    let mut diagnostics = parse_rust_json(&lines);
+    if diagnostics.is_empty() {
+        diagnostics.extend(parse_rustc_human(&lines));
+    }
+    diagnostics.extend(parse_gcc(&lines));
+    diagnostics.extend(parse_tsc(&lines));
+    diagnostics.extend(parse_eslint_json(output));
+    diagnostics.extend(parse_python_traceback(&lines));
+
+    let mut seen = std::collections::HashSet::new();
+    diagnostics.retain(|d| seen.insert((d.file.clone(), d.line, d.column, d.message.clone())));
+    diagnostics
+}
+
+/// 按文件分组（保持首次出现的顺序）
+pub fn group_by_file(diagnostics: &[Diagnostic]) -> Vec<(&str, Vec<&Diagnostic>)> {
+    let mut groups: Vec<(&str, Vec<&Diagnostic>)> = Vec::new();
+    for diagnostic in diagnostics {
+        match groups.iter_mut().find(|(file, _)| *file == diagnostic.file) {
+            Some((_, items)) => items.push(diagnostic),
+            None => groups.push((diagnostic.file.as_str(), vec![diagnostic])),
+        }
+    }
+    groups
+}
+
+/// 去掉执行器加在输出前的标记（错误码前缀、`stderr: `）
+fn clean_line(line: &str) -> &str {
+    static PREFIX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[E\d+\] [^:]+: ").unwrap());
+
+    let line = match PREFIX.find(line) {
+        Some(m) => &line[m.end()..],
+        None => line,
+    };
+    line.strip_prefix("stderr: ").unwrap_or(line)
+}
+
+// ========== rustc JSON ==========
+
+/// `cargo --message-format=json`（`reason: compiler-message`）或 `rustc --error-format=json`
+fn parse_rust_json(lines: &[&str]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for line in lines {
+        let Some(start) = line.find('{') else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<Value>(&line[start..]) else {
+            continue;
+        };
+
+        let message = match value["reason"].as_str() {
+            Some("compiler-message") => &value["message"],
+            Some(_) => continue,
+            None if value["$message_type"] == "diagnostic" || value["spans"].is_array() => &value,
+            None => continue,
+        };
+        if let Some(diagnostic) = rust_json_diagnostic(message) {
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+fn rust_json_diagnostic(message: &Value) -> Option<Diagnostic> {
+    let spans = message["spans"].as_array()?;
+    let primary = spans
+        .iter()
+        .find(|s| s["is_primary"].as_bool().unwrap_or(false))
+        .or_else(|| spans.first())?;
+
+    let mut diagnostic = Diagnostic::new(
+        DiagnosticSource::Rustc,
+        DiagnosticLevel::parse(message["level"].as_str().unwrap_or("error")),
+        primary["file_name"].as_str()?,
+        primary["line_start"].as_u64()? as usize,
+        primary["column_start"].as_u64().unwrap_or(1) as usize,
+        message["message"].as_str().unwrap_or(""),
+    );
+    diagnostic.code = message["code"]["code"].as_str().map(str::to_string);
+
+    // 建议替换可能在顶层 span，也可能在 help 子消息里
+    let mut suggestions: Vec<Suggestion> = spans
+        .iter()
+        .filter_map(|span| rust_json_suggestion(span, message["message"].as_str().unwrap_or("")))
+        .collect();
+    for child in message["children"].as_array().into_iter().flatten() {
+        let child_message = child["message"].as_str().unwrap_or("");
+        for span in child["spans"].as_array().into_iter().flatten() {
+            suggestions.extend(rust_json_suggestion(span, child_message));
+        }
+    }
+    diagnostic.suggestions = suggestions;
+    Some(diagnostic)
+}
+
+fn rust_json_suggestion(span: &Value, message: &str) -> Option<Suggestion> {
+    let replacement = span["suggested_replacement"].as_str()?;
+    Some(Suggestion {
+        message: message.to_string(),
+        file: span["file_name"].as_str()?.to_string(),
+        range: EditRange::Bytes {
+            start: span["byte_start"].as_u64()? as usize,
+            end: span["byte_end"].as_u64()? as usize,
+        },
+        replacement: replacement.to_string(),
+        machine_applicable: span["suggestion_applicability"] == "MachineApplicable",
+    })
+}
+
+// ========== rustc 人类可读格式 ==========
+
+/// `error[E0425]: cannot find value ...` 后跟 `  --> src/main.rs:3:5`
+fn parse_rustc_human(lines: &[&str]) -> Vec<Diagnostic> {
+    static HEADER: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap());
+    static LOCATION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*--> (.+?):(\d+):(\d+)\s*$").unwrap());
+
+    let mut diagnostics = Vec::new();
+    let mut pending: Option<(DiagnosticLevel, Option<String>, String)> = None;
+    for line in lines {
+        if let Some(caps) = HEADER.captures(line) {
+            pending = Some((
+                DiagnosticLevel::parse(&caps[1]),
+                caps.get(2).map(|m| m.as_str().to_string()),
+                caps[3].to_string(),
+            ));
+        } else if let Some(caps) = LOCATION.captures(line) {
+            // 只取紧跟在标题后的第一个位置
+            if let Some((level, code, message)) = pending.take() {
+                let mut diagnostic = Diagnostic::new(
+                    DiagnosticSource::Rustc,
+                    level,
+                    &caps[1],
+                    caps[2].parse().unwrap_or(1),
+                    caps[3].parse().unwrap_or(1),
+                    &message,
+                );
+                diagnostic.code = code;
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+    diagnostics
+}
+
+// ========== gcc / clang ==========
+
+/// `main.c:3:5: error: ...`，以及 `fix-it:"main.c":{3:5-3:8}:"text"`
+fn parse_gcc(lines: &[&str]) -> Vec<Diagnostic> {
+    static HEADER: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"^(.+?\.(?:c|cc|cpp|cxx|h|hh|hpp|m|mm)):(\d+):(\d+): (fatal error|error|warning|note): (.+)$")
+            .unwrap()
+    });
+    static FIX_IT: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"^fix-it:"(.+?)":\{(\d+):(\d+)-(\d+):(\d+)\}:"(.*)"$"#).unwrap());
+    static WARNING_FLAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s*\[(-W[\w=-]+)\]$").unwrap());
+
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in lines {
+        if let Some(caps) = HEADER.captures(line) {
+            let message = &caps[5];
+            let code = WARNING_FLAG.captures(message).map(|c| c[1].to_string());
+            let message = WARNING_FLAG.replace(message, "");
+            let mut diagnostic = Diagnostic::new(
+                DiagnosticSource::Gcc,
+                DiagnosticLevel::parse(&caps[4]),
+                &caps[1],
+                caps[2].parse().unwrap_or(1),
+                caps[3].parse().unwrap_or(1),
+                &message,
+            );
+            diagnostic.code = code;
+            diagnostics.push(diagnostic);
+        } else if let Some(caps) = FIX_IT.captures(line) {
+            let Some(last) = diagnostics.last_mut() else {
+                continue;
+            };
+            let number = |i: usize| caps[i].parse().unwrap_or(1);
+            last.suggestions.push(Suggestion {
+                message: "fix-it".to_string(),
+                file: caps[1].to_string(),
+                range: EditRange::LineColumn {
+                    start_line: number(2),
+                    start_column: number(3),
+                    end_line: number(4),
+                    end_column: number(5),
+                },
+                replacement: unescape_c(&caps[6]),
+                machine_applicable: true,
+            });
+        }
+    }
+    diagnostics
+}
+
+/// fix-it 文本中的 C 风格转义
+fn unescape_c(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+// ========== tsc ==========
+
+/// `src/app.ts(3,5): error TS2322: ...` 或 `src/app.ts:3:5 - error TS2322: ...`
+fn parse_tsc(lines: &[&str]) -> Vec<Diagnostic> {
+    static PLAIN: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(.+?\.[cm]?[jt]sx?)\((\d+),(\d+)\): (error|warning) (TS\d+): (.+)$").unwrap());
+    static PRETTY: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^(.+?\.[cm]?[jt]sx?):(\d+):(\d+) - (error|warning) (TS\d+): (.+)$").unwrap());
+
+    lines
+        .iter()
+        .filter_map(|line| PLAIN.captures(line).or_else(|| PRETTY.captures(line)))
+        .map(|caps| {
+            let mut diagnostic = Diagnostic::new(
+                DiagnosticSource::Tsc,
+                DiagnosticLevel::parse(&caps[4]),
+                &caps[1],
+                caps[2].parse().unwrap_or(1),
+                caps[3].parse().unwrap_or(1),
+                &caps[6],
+            );
+            diagnostic.code = Some(caps[5].to_string());
+            diagnostic
+        })
+        .collect()
+}
+
+// ========== eslint ==========
+
+/// `eslint --format json`：`[{"filePath": ..., "messages": [...]}]`
+fn parse_eslint_json(output: &str) -> Vec<Diagnostic> {
+    let Some(start) = output.find("[{\"filePath\"") else {
+        return Vec::new();
+    };
+    let Some(end) = output.rfind(']') else {
+        return Vec::new();
+    };
+    let Ok(files) = serde_json::from_str::<Vec<Value>>(&output[start..=end]) else {
+        return Vec::new();
+    };
+
+    let mut diagnostics = Vec::new();
+    for file in &files {
+        let Some(path) = file["filePath"].as_str() else {
+            continue;
+        };
+        for message in file["messages"].as_array().into_iter().flatten() {
+            let level = match message["severity"].as_u64() {
+                Some(2) => DiagnosticLevel::Error,
+                Some(1) => DiagnosticLevel::Warning,
+                _ => DiagnosticLevel::Note,
+            };
+            let mut diagnostic = Diagnostic::new(
+                DiagnosticSource::Eslint,
+                level,
+                path,
+                message["line"].as_u64().unwrap_or(1) as usize,
+                message["column"].as_u64().unwrap_or(1) as usize,
+                message["message"].as_str().unwrap_or(""),
+            );
+            diagnostic.code = message["ruleId"].as_str().map(str::to_string);
+
+            let fix = &message["fix"];
+            if let (Some(range), Some(text)) = (fix["range"].as_array(), fix["text"].as_str()) {
+                if let [Some(start), Some(end)] = [range.first(), range.get(1)].map(|v| v.and_then(Value::as_u64)) {
+                    diagnostic.suggestions.push(Suggestion {
+                        message: format!("eslint --fix ({})", diagnostic.code.as_deref().unwrap_or("fix")),
+                        file: path.to_string(),
+                        range: EditRange::Utf16 { start: start as usize, end: end as usize },
+                        replacement: text.to_string(),
+                        machine_applicable: true,
+                    });
+                }
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    diagnostics
+}
+
+// ========== Python traceback ==========
+
+/// 取 traceback 最内层的栈帧作为位置，最后一行 `XxxError: ...` 作为消息
+fn parse_python_traceback(lines: &[&str]) -> Vec<Diagnostic> {
+    static FRAME: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^\s*File "(.+?)", line (\d+)"#).unwrap());
+    static EXCEPTION: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"^([A-Za-z_][\w.]*(?:Error|Exception|Interrupt|Exit|Warning)): ?(.*)$").unwrap());
+
+    let mut diagnostics = Vec::new();
+    let mut frame: Option<(String, usize)> = None;
+    let mut in_traceback = false;
+    for line in lines {
+        if line.starts_with("Traceback (most recent call last)") {
+            in_traceback = true;
+            frame = None;
+            continue;
+        }
+        if let Some(caps) = FRAME.captures(line) {
+            // 语法错误没有 "Traceback" 标题，直接给出 File 行
+            in_traceback = true;
+            frame = Some((caps[1].to_string(), caps[2].parse().unwrap_or(1)));
+            continue;
+        }
+        if !in_traceback {
+            continue;
+        }
+        if let Some(caps) = EXCEPTION.captures(line) {
+            if let Some((file, line_no)) = frame.take() {
+                let message = if caps[2].is_empty() {
+                    caps[1].to_string()
+                } else {
+                    format!("{}: {}", &caps[1], &caps[2])
+                };
+                let mut diagnostic =
+                    Diagnostic::new(DiagnosticSource::Python, DiagnosticLevel::Error, &file, line_no, 1, &message);
+                diagnostic.code = Some(caps[1].to_string());
+                diagnostics.push(diagnostic);
+            }
+            in_traceback = false;
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_json() {
+        let line = r#"{"reason":"compiler-message","package_id":"app","message":{"message":"unused variable: `x`","code":{"code":"unused_variables"},"level":"warning","spans":[{"file_name":"src/main.rs","byte_start":16,"byte_end":17,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"suggested_replacement":null}],"children":[{"message":"if this is intentional, prefix it with an underscore","level":"help","spans":[{"file_name":"src/main.rs","byte_start":16,"byte_end":17,"line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true,"suggested_replacement":"_x","suggestion_applicability":"MachineApplicable"}],"children":[]}],"rendered":"warning: unused variable"}}
+{"reason":"build-finished","success":false}"#;
+
+        let diagnostics = parse_diagnostics(line);
+        assert_eq!(diagnostics.len(), 1);
+        let d = &diagnostics[0];
+        assert_eq!(d.source, DiagnosticSource::Rustc);
+        assert_eq!(d.level, DiagnosticLevel::Warning);
+        assert_eq!((d.file.as_str(), d.line, d.column), ("src/main.rs", 2, 9));
+        assert_eq!(d.code.as_deref(), Some("unused_variables"));
+        assert_eq!(d.suggestions.len(), 1);
+        assert_eq!(d.suggestions[0].replacement, "_x");
+        assert_eq!(d.suggestions[0].range, EditRange::Bytes { start: 16, end: 17 });
+        assert!(d.suggestions[0].machine_applicable);
+    }
+
+    #[test]
+    fn test_rustc_human() {
+        let output = "[E304] Shell 命令执行失败: stderr: error[E0425]: cannot find value `y` in this scope
+ --> src/main.rs:3:13
+  |
+3 |     let z = y + 1;
+  |             ^ help: a local variable with a similar name exists: `x`
+
+warning: unused import: `std::fs`
+ --> src/lib.rs:1:5
+
+error: aborting due to 1 previous error";
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("E0425"));
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 13));
+        assert_eq!(diagnostics[1].level, DiagnosticLevel::Warning);
+        assert_eq!(diagnostics[1].file, "src/lib.rs");
+    }
+
+    #[test]
+    fn test_gcc_with_fixits() {
+        let output = r#"main.c:5:3: error: use of undeclared identifier 'prinft'; did you mean 'printf'?
+fix-it:"main.c":{5:3-5:9}:"printf"
+main.c:2:7: warning: unused variable 'n' [-Wunused-variable]"#;
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].source, DiagnosticSource::Gcc);
+        assert_eq!(diagnostics[0].suggestions[0].replacement, "printf");
+        assert_eq!(
+            diagnostics[0].suggestions[0].range,
+            EditRange::LineColumn { start_line: 5, start_column: 3, end_line: 5, end_column: 9 }
+        );
+        assert_eq!(diagnostics[1].code.as_deref(), Some("-Wunused-variable"));
+        assert_eq!(diagnostics[1].message, "unused variable 'n'");
+    }
+
+    #[test]
+    fn test_tsc() {
+        let output = "src/app.ts(4,7): error TS2322: Type 'string' is not assignable to type 'number'.
+src/util.tsx:10:1 - error TS2304: Cannot find name 'foo'.";
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("TS2322"));
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (4, 7));
+        assert_eq!(diagnostics[1].file, "src/util.tsx");
+    }
+
+    #[test]
+    fn test_eslint_json() {
+        let output = r#"[{"filePath":"/p/src/a.js","messages":[{"ruleId":"semi","severity":2,"message":"Missing semicolon.","line":1,"column":12,"fix":{"range":[11,11],"text":";"}},{"ruleId":"no-unused-vars","severity":1,"message":"'b' is defined but never used.","line":2,"column":5}],"errorCount":1}]"#;
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].level, DiagnosticLevel::Error);
+        assert_eq!(diagnostics[0].suggestions[0].range, EditRange::Utf16 { start: 11, end: 11 });
+        assert_eq!(diagnostics[1].level, DiagnosticLevel::Warning);
+        assert!(diagnostics[1].suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_python_traceback() {
+        let output = r#"Traceback (most recent call last):
+  File "main.py", line 10, in <module>
+    run()
+  File "/app/lib/util.py", line 4, in run
+    return 1 / 0
+ZeroDivisionError: division by zero"#;
+
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "/app/lib/util.py");
+        assert_eq!(diagnostics[0].line, 4);
+        assert_eq!(diagnostics[0].message, "ZeroDivisionError: division by zero");
+
+        let syntax = "  File \"bad.py\", line 2\n    def f(:\n          ^\nSyntaxError: invalid syntax";
+        let diagnostics = parse_diagnostics(syntax);
+        assert_eq!(diagnostics[0].code.as_deref(), Some("SyntaxError"));
+        assert_eq!(diagnostics[0].line, 2);
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        let output = "a.c:1:1: error: x\nb.c:2:1: error: y\na.c:3:1: warning: z";
+        let diagnostics = parse_diagnostics(output);
+        let groups = group_by_file(&diagnostics);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "a.c");
+        assert_eq!(groups[0].1.len(), 2);
+
+        assert!(parse_diagnostics("all good").is_empty());
+    }
+}
@@ -4,7 +4,7 @@
 //! - 收集用户对修复建议的反馈
 //! - 追踪修复效果
 //! - 学习用户偏好
-//! - 优化策略排序
+//! - 优化策略排序（按相似上下文的成功率，旧记录逐渐衰减）
 //!
 //! 设计理念（一分为三）：
 //! - 收集层：记录反馈数据
@@ -12,6 +12,7 @@
 //! - 应用层：优化决策和推荐
 
 use super::{ErrorAnalysis, FixStrategy};
+use crate::project_context::ProjectContext;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -47,6 +48,123 @@ pub enum FixOutcome {
     Unknown,
 }
 
+/// 记录半衰期（天）：越旧的反馈在排序中权重越低
+const DECAY_HALF_LIFE_DAYS: f64 = 30.0;
+
+/// 排序时的先验权重（把样本少的策略拉向中等分数）
+const PRIOR_WEIGHT: f64 = 1.0;
+
+/// 上下文特征维度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContextDimension {
+    /// 项目类型（Rust、Python ...）
+    ProjectType,
+    /// 命令的程序名
+    CommandHead,
+    /// 匹配的错误模式
+    ErrorPattern,
+    /// 操作系统
+    Os,
+    /// 工作目录
+    Cwd,
+}
+
+impl ContextDimension {
+    /// 全部维度及其在上下文相似度中的权重
+    pub const ALL: [(ContextDimension, f64); 5] = [
+        (ContextDimension::ProjectType, 0.3),
+        (ContextDimension::CommandHead, 0.25),
+        (ContextDimension::ErrorPattern, 0.25),
+        (ContextDimension::Cwd, 0.1),
+        (ContextDimension::Os, 0.1),
+    ];
+
+    /// 在 `FeedbackRecord::context` 中的键
+    pub fn key(&self) -> &'static str {
+        match self {
+            ContextDimension::ProjectType => "project_type",
+            ContextDimension::CommandHead => "command_head",
+            ContextDimension::ErrorPattern => "error_pattern",
+            ContextDimension::Os => "os",
+            ContextDimension::Cwd => "cwd",
+        }
+    }
+}
+
+impl std::fmt::Display for ContextDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextDimension::ProjectType => write!(f, "项目类型"),
+            ContextDimension::CommandHead => write!(f, "命令"),
+            ContextDimension::ErrorPattern => write!(f, "错误模式"),
+            ContextDimension::Os => write!(f, "操作系统"),
+            ContextDimension::Cwd => write!(f, "工作目录"),
+        }
+    }
+}
+
+/// 反馈发生时的上下文特征
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeedbackContext {
+    values: HashMap<&'static str, String>,
+}
+
+impl FeedbackContext {
+    /// 采集当前环境的上下文（项目类型、工作目录、操作系统）及命令特征
+    pub fn current(analysis: &ErrorAnalysis) -> Self {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        Self::default()
+            .with(ContextDimension::ProjectType, ProjectContext::detect_project_type(&cwd).name())
+            .with(ContextDimension::Cwd, cwd.display().to_string())
+            .with(ContextDimension::Os, std::env::consts::OS)
+            .with(ContextDimension::CommandHead, command_head(&analysis.command))
+            .with(ContextDimension::ErrorPattern, analysis.pattern_name.clone().unwrap_or_default())
+    }
+
+    /// 设置某个维度的值（空值视为未知）
+    pub fn with(mut self, dimension: ContextDimension, value: impl Into<String>) -> Self {
+        let value = value.into();
+        if !value.is_empty() {
+            self.values.insert(dimension.key(), value);
+        }
+        self
+    }
+
+    /// 某个维度的值
+    pub fn get(&self, dimension: ContextDimension) -> Option<&str> {
+        self.values.get(dimension.key()).map(String::as_str)
+    }
+
+    /// 与另一上下文的相似度 (0.0 - 1.0)
+    ///
+    /// 相同维度计满分，任一方未知计一半，不同计 0。
+    pub fn similarity(&self, other: &FeedbackContext) -> f64 {
+        ContextDimension::ALL
+            .iter()
+            .map(|(dimension, weight)| match (self.get(*dimension), other.get(*dimension)) {
+                (Some(a), Some(b)) if a == b => *weight,
+                (Some(_), Some(_)) => 0.0,
+                _ => weight * 0.5,
+            })
+            .sum()
+    }
+}
+
+/// 命令的程序名（跳过环境变量赋值）
+fn command_head(command: &str) -> String {
+    command
+        .split_whitespace()
+        .find(|word| !word.contains('='))
+        .map(|word| word.rsplit('/').next().unwrap_or(word).to_string())
+        .unwrap_or_default()
+}
+
+/// 按记录时间衰减的权重
+fn decay_weight(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> f64 {
+    let age_days = (now - timestamp).num_seconds().max(0) as f64 / 86_400.0;
+    0.5f64.powf(age_days / DECAY_HALF_LIFE_DAYS)
+}
+
 /// 反馈记录
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeedbackRecord {
@@ -107,6 +225,7 @@ impl FeedbackRecord {
             modified_command: None,
             context: HashMap::new(),
         }
+        .with_feedback_context(&FeedbackContext::current(analysis))
     }
 
     /// 设置修改后的命令
@@ -121,6 +240,43 @@ impl FeedbackRecord {
         self
     }
 
+    /// 写入上下文特征
+    pub fn with_feedback_context(mut self, context: &FeedbackContext) -> Self {
+        for (key, value) in &context.values {
+            self.context.insert(key.to_string(), value.clone());
+        }
+        self
+    }
+
+    /// 记录的上下文特征（旧记录缺少的维度视为未知，错误模式取自记录本身）
+    pub fn feedback_context(&self) -> FeedbackContext {
+        let mut context = FeedbackContext::default();
+        for (dimension, _) in ContextDimension::ALL {
+            if let Some(value) = self.context.get(dimension.key()) {
+                context = context.with(dimension, value.clone());
+            }
+        }
+        if context.get(ContextDimension::ErrorPattern).is_none() {
+            context = context.with(ContextDimension::ErrorPattern, self.error_pattern.clone());
+        }
+        if context.get(ContextDimension::CommandHead).is_none() {
+            context = context.with(ContextDimension::CommandHead, command_head(&self.original_command));
+        }
+        context
+    }
+
+    /// 单条记录的效果得分（与 `StrategyStats` 相同的 0.4 采纳 + 0.6 成功）
+    fn effectiveness(&self) -> f64 {
+        let accepted = matches!(self.feedback, FeedbackType::Accepted | FeedbackType::Modified);
+        let success = match self.outcome {
+            FixOutcome::Success => 1.0,
+            FixOutcome::Partial => 0.75,
+            FixOutcome::Failure => 0.0,
+            FixOutcome::Unknown => 0.5,
+        };
+        0.4 * if accepted { 1.0 } else { 0.0 } + 0.6 * success
+    }
+
     /// 判断是否为正面反馈
     pub fn is_positive(&self) -> bool {
         matches!(
@@ -343,6 +499,73 @@ impl FeedbackLearner {
         strategies
     }
 
+    /// 按相似上下文中的效果排序策略
+    ///
+    /// 每条记录的权重 = 与当前上下文的相似度 × 时间衰减；
+    /// 策略得分是加权效果得分，并以中等分数为先验，样本少的策略不会被极端排序。
+    pub async fn rerank_strategies_in_context(
+        &self,
+        mut strategies: Vec<FixStrategy>,
+        context: &FeedbackContext,
+    ) -> Vec<FixStrategy> {
+        let scores = {
+            let records = self.records.read().await;
+            contextual_scores(&records, context, Utc::now())
+        };
+        let score = |name: &str| scores.get(name).copied().unwrap_or(0.5);
+
+        // 稳定排序：得分相同的策略保持原有优先级
+        strategies.sort_by(|a, b| {
+            score(&b.name)
+                .partial_cmp(&score(&a.name))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        strategies
+    }
+
+    /// 按上下文维度分组的效果统计（用于 `/fix stats`）
+    pub async fn context_breakdown(&self) -> Vec<ContextBreakdown> {
+        let records = self.records.read().await;
+        let now = Utc::now();
+
+        let mut breakdown = Vec::new();
+        for (dimension, _) in ContextDimension::ALL {
+            let mut groups: HashMap<String, Vec<&FeedbackRecord>> = HashMap::new();
+            for record in records.iter() {
+                let context = record.feedback_context();
+                if let Some(value) = context.get(dimension) {
+                    groups.entry(value.to_string()).or_default().push(record);
+                }
+            }
+
+            let mut rows: Vec<ContextBreakdown> = groups
+                .into_iter()
+                .map(|(value, group)| {
+                    let positive = group.iter().filter(|r| r.is_positive()).count();
+                    let best_strategy = weighted_scores(
+                        group.iter().map(|record| (*record, decay_weight(record.timestamp, now))),
+                    )
+                    .into_iter()
+                    .max_by(|a, b| {
+                        a.1.partial_cmp(&b.1)
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                            .then_with(|| b.0.cmp(&a.0))
+                    });
+                    ContextBreakdown {
+                        dimension,
+                        value,
+                        total: group.len(),
+                        success_rate: positive as f64 / group.len() as f64,
+                        best_strategy,
+                    }
+                })
+                .collect();
+            rows.sort_by(|a, b| b.total.cmp(&a.total).then_with(|| a.value.cmp(&b.value)));
+            breakdown.extend(rows);
+        }
+        breakdown
+    }
+
     /// 获取最近的反馈记录
     pub async fn get_recent_records(&self, limit: usize) -> Vec<FeedbackRecord> {
         let records = self.records.read().await;
@@ -506,6 +729,49 @@ pub struct LearningSummary {
     pub top_strategies: Vec<StrategyStats>,
 }
 
+/// 某个上下文取值下的效果统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextBreakdown {
+    /// 上下文维度
+    pub dimension: ContextDimension,
+    /// 维度取值（如 "Rust"、"cargo"）
+    pub value: String,
+    /// 记录数
+    pub total: usize,
+    /// 正面反馈比例
+    pub success_rate: f64,
+    /// 该上下文中得分最高的策略（按时间衰减加权）
+    pub best_strategy: Option<(String, f64)>,
+}
+
+/// 各策略在给定上下文中的得分
+fn contextual_scores(
+    records: &[FeedbackRecord],
+    context: &FeedbackContext,
+    now: DateTime<Utc>,
+) -> HashMap<String, f64> {
+    weighted_scores(records.iter().map(|record| {
+        let weight = context.similarity(&record.feedback_context()) * decay_weight(record.timestamp, now);
+        (record, weight)
+    }))
+}
+
+/// 按权重汇总各策略的效果得分（以中等分数为先验）
+fn weighted_scores<'a>(records: impl IntoIterator<Item = (&'a FeedbackRecord, f64)>) -> HashMap<String, f64> {
+    let mut sums: HashMap<&str, (f64, f64)> = HashMap::new();
+    for (record, weight) in records {
+        let entry = sums.entry(record.strategy_name.as_str()).or_default();
+        entry.0 += weight * record.effectiveness();
+        entry.1 += weight;
+    }
+
+    sums.into_iter()
+        .map(|(name, (sum, weight))| {
+            (name.to_string(), (sum + 0.5 * PRIOR_WEIGHT) / (weight + PRIOR_WEIGHT))
+        })
+        .collect()
+}
+
 /// 持久化数据结构
 #[derive(Debug, Serialize, Deserialize)]
 struct StorageData {
@@ -643,4 +909,98 @@ mod tests {
         assert_eq!(summary.positive_feedbacks, 3);
         assert_eq!(summary.overall_success_rate, 0.6);
     }
+
+    fn record_in(project: &str, strategy: &str, feedback: FeedbackType, outcome: FixOutcome) -> FeedbackRecord {
+        let analysis = ErrorAnalysis::new("error".to_string(), "make build".to_string());
+        let strategy = FixStrategy::new(strategy, "fix", "desc", 3);
+        let context = FeedbackContext::default().with(ContextDimension::ProjectType, project);
+        FeedbackRecord::new(&analysis, &strategy, feedback, outcome).with_feedback_context(&context)
+    }
+
+    #[test]
+    fn test_feedback_context() {
+        let mut analysis = ErrorAnalysis::new("error".to_string(), "RUST_LOG=debug /usr/bin/cargo build".to_string());
+        analysis.pattern_name = Some("rust_compile_error".to_string());
+        let strategy = FixStrategy::new("test", "fix", "desc", 3);
+        let record = FeedbackRecord::new(&analysis, &strategy, FeedbackType::Accepted, FixOutcome::Success);
+
+        let context = record.feedback_context();
+        assert_eq!(context.get(ContextDimension::CommandHead), Some("cargo"));
+        assert_eq!(context.get(ContextDimension::ErrorPattern), Some("rust_compile_error"));
+        assert_eq!(context.get(ContextDimension::Os), Some(std::env::consts::OS));
+        assert!(context.get(ContextDimension::ProjectType).is_some());
+        assert!((context.similarity(&context) - 1.0).abs() < 1e-9);
+
+        // 旧记录没有上下文：错误模式与命令从记录本身推断，其余维度未知
+        let mut old = record.clone();
+        old.context.clear();
+        let old_context = old.feedback_context();
+        assert_eq!(old_context.get(ContextDimension::CommandHead), Some("cargo"));
+        assert!(old_context.get(ContextDimension::Os).is_none());
+        assert!(context.similarity(&old_context) < 1.0);
+        assert!(context.similarity(&old_context) > 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_rerank_in_context() {
+        let learner = FeedbackLearner::new();
+
+        // pip_install 在 Python 项目中有效，在 Rust 项目中无效；cargo_fix 相反
+        for _ in 0..3 {
+            learner.record_feedback(record_in("Python", "pip_install", FeedbackType::Accepted, FixOutcome::Success)).await;
+            learner.record_feedback(record_in("Rust", "pip_install", FeedbackType::Rejected, FixOutcome::Failure)).await;
+            learner.record_feedback(record_in("Rust", "cargo_fix", FeedbackType::Accepted, FixOutcome::Success)).await;
+        }
+        learner.record_feedback(record_in("Python", "cargo_fix", FeedbackType::Rejected, FixOutcome::Failure)).await;
+
+        let strategies = vec![
+            FixStrategy::new("pip_install", "pip install x", "", 3),
+            FixStrategy::new("cargo_fix", "cargo fix", "", 3),
+        ];
+        let rust = FeedbackContext::default().with(ContextDimension::ProjectType, "Rust");
+        let python = FeedbackContext::default().with(ContextDimension::ProjectType, "Python");
+
+        let ranked = learner.rerank_strategies_in_context(strategies.clone(), &rust).await;
+        assert_eq!(ranked[0].name, "cargo_fix");
+        let ranked = learner.rerank_strategies_in_context(strategies, &python).await;
+        assert_eq!(ranked[0].name, "pip_install");
+    }
+
+    #[test]
+    fn test_decay_prefers_recent_records() {
+        let now = Utc::now();
+        let mut old_success = record_in("Rust", "old_fix", FeedbackType::Accepted, FixOutcome::Success);
+        old_success.timestamp = now - chrono::Duration::days(180);
+        let recent_failure = record_in("Rust", "old_fix", FeedbackType::Rejected, FixOutcome::Failure);
+
+        assert!(decay_weight(old_success.timestamp, now) < 0.02);
+        assert!((decay_weight(now, now) - 1.0).abs() < 1e-9);
+
+        let context = FeedbackContext::default().with(ContextDimension::ProjectType, "Rust");
+        let scores = contextual_scores(&[old_success, recent_failure], &context, now);
+        assert!(scores["old_fix"] < 0.5);
+    }
+
+    #[tokio::test]
+    async fn test_context_breakdown() {
+        let learner = FeedbackLearner::new();
+        learner.record_feedback(record_in("Rust", "cargo_fix", FeedbackType::Accepted, FixOutcome::Success)).await;
+        learner.record_feedback(record_in("Rust", "pip_install", FeedbackType::Rejected, FixOutcome::Failure)).await;
+        learner.record_feedback(record_in("Python", "pip_install", FeedbackType::Accepted, FixOutcome::Success)).await;
+
+        let breakdown = learner.context_breakdown().await;
+        let rust = breakdown
+            .iter()
+            .find(|b| b.dimension == ContextDimension::ProjectType && b.value == "Rust")
+            .unwrap();
+        assert_eq!(rust.total, 2);
+        assert_eq!(rust.success_rate, 0.5);
+        assert_eq!(rust.best_strategy.as_ref().unwrap().0, "cargo_fix");
+
+        let make = breakdown
+            .iter()
+            .find(|b| b.dimension == ContextDimension::CommandHead)
+            .unwrap();
+        assert_eq!((make.value.as_str(), make.total), ("make", 3));
+    }
 }
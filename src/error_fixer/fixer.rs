@@ -3,9 +3,33 @@
 //! 基于错误分析生成并应用修复方案
 
 use super::analyzer::{ErrorAnalysis, ErrorCategory};
+use super::corrector::{CommandCorrector, CORRECTION_STRATEGY};
 use crate::llm::{LlmClient, LlmError, Message};
 use serde::{Deserialize, Serialize};
 
+/// 修复后的验证方式（后置条件）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verification {
+    /// 重新执行原命令，成功才算修复（默认）
+    #[default]
+    RerunOriginal,
+    /// 执行指定的检查命令
+    Command(String),
+    /// 修复命令本身成功即可（修复命令替代了原命令，如拼写纠正）
+    FixSucceeds,
+}
+
+impl std::fmt::Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verification::RerunOriginal => write!(f, "重新执行原命令"),
+            Verification::Command(command) => write!(f, "执行检查: {}", command),
+            Verification::FixSucceeds => write!(f, "修复命令成功"),
+        }
+    }
+}
+
 /// 修复策略
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FixStrategy {
@@ -26,6 +50,14 @@ pub struct FixStrategy {
 
     /// 预期效果
     pub expected_outcome: String,
+
+    /// 修复后的验证方式
+    #[serde(default)]
+    pub verification: Verification,
+
+    /// 修复命令是否（改写后）执行原命令，如拼写纠正、sudo、换端口、重试
+    #[serde(default)]
+    pub runs_original: bool,
 }
 
 impl FixStrategy {
@@ -43,6 +75,8 @@ impl FixStrategy {
             requires_confirmation: risk_level >= 5,
             risk_level,
             expected_outcome: String::new(),
+            verification: Verification::default(),
+            runs_original: false,
         }
     }
 
@@ -52,6 +86,24 @@ impl FixStrategy {
         self
     }
 
+    /// 设置是否需要用户确认
+    pub fn with_confirmation(mut self, required: bool) -> Self {
+        self.requires_confirmation = required;
+        self
+    }
+
+    /// 设置修复后的验证方式
+    pub fn with_verification(mut self, verification: Verification) -> Self {
+        self.verification = verification;
+        self
+    }
+
+    /// 标记修复命令是否（改写后）执行原命令
+    pub fn with_runs_original(mut self, runs_original: bool) -> Self {
+        self.runs_original = runs_original;
+        self
+    }
+
     /// 判断是否为高风险操作
     pub fn is_high_risk(&self) -> bool {
         self.risk_level >= 7
@@ -107,12 +159,43 @@ impl ErrorFixer {
     ///
     /// # 返回
     /// 修复策略列表（按优先级排序）
+    ///
+    /// 命令类错误使用当前 `$PATH` 做拼写纠正；需要结合命令历史与反馈时
+    /// 使用 [`ErrorFixer::generate_strategies_with_corrector`]。
     pub fn generate_strategies(analysis: &ErrorAnalysis) -> Vec<FixStrategy> {
+        let corrector = if analysis.category == ErrorCategory::Command {
+            CommandCorrector::from_env()
+        } else {
+            CommandCorrector::new()
+        };
+        Self::generate_strategies_with_corrector(analysis, &corrector)
+    }
+
+    /// 使用指定的纠错器生成修复策略
+    pub fn generate_strategies_with_corrector(
+        analysis: &ErrorAnalysis,
+        corrector: &CommandCorrector,
+    ) -> Vec<FixStrategy> {
         let mut strategies = Vec::new();
 
+        // 模式自带的修复命令优先（可自动修复的内置模式风险较低，其余需确认）
+        let risk_level = if analysis.auto_fixable { 3 } else { 5 };
+        let description = analysis.suggested_fixes.first().cloned().unwrap_or_default();
+        for command in &analysis.fix_commands {
+            strategies.push(
+                FixStrategy::new(
+                    analysis.pattern_name.clone().unwrap_or_else(|| "模式修复".to_string()),
+                    command,
+                    description.clone(),
+                    risk_level,
+                )
+                .with_confirmation(!analysis.auto_fixable),
+            );
+        }
+
         match &analysis.category {
             ErrorCategory::Command => {
-                strategies.extend(Self::fix_command_not_found(analysis));
+                strategies.extend(Self::fix_command_not_found(analysis, corrector));
             }
             ErrorCategory::Permission => {
                 strategies.extend(Self::fix_permission_denied(analysis));
@@ -138,7 +221,8 @@ impl ErrorFixer {
                         "简单重试原命令",
                         2,
                     )
-                    .with_outcome("可能解决临时性问题"),
+                    .with_outcome("可能解决临时性问题")
+                    .with_runs_original(true),
                 );
             }
         }
@@ -229,9 +313,25 @@ impl ErrorFixer {
 
     // ========== 特定错误类型的修复策略 ==========
 
-    fn fix_command_not_found(analysis: &ErrorAnalysis) -> Vec<FixStrategy> {
+    fn fix_command_not_found(analysis: &ErrorAnalysis, corrector: &CommandCorrector) -> Vec<FixStrategy> {
         let mut strategies = Vec::new();
 
+        // 策略 0: 离线拼写纠正（执行的是另一条命令，需要用户确认）
+        for correction in corrector.correct(&analysis.command) {
+            strategies.push(
+                FixStrategy::new(
+                    CORRECTION_STRATEGY,
+                    correction.command,
+                    format!("{} → {}（{}）", correction.typo, correction.replacement, correction.source),
+                    3,
+                )
+                .with_confirmation(true)
+                .with_outcome("执行纠正后的命令")
+                .with_verification(Verification::FixSucceeds)
+                .with_runs_original(true),
+            );
+        }
+
         // 提取命令名称
         if let Some(ref details) = analysis.details {
             if let Some(ref cmd) = details.command {
@@ -289,7 +389,9 @@ impl ErrorFixer {
                 "以管理员身份重新执行",
                 8,
             )
-            .with_outcome("获得所需权限"),
+            .with_outcome("获得所需权限")
+            .with_verification(Verification::FixSucceeds)
+            .with_runs_original(true),
         );
 
         strategies
@@ -375,7 +477,7 @@ impl ErrorFixer {
                 .with_outcome("找到占用进程"),
             );
 
-            // 策略 2: 使用其他端口
+            // 策略 2: 使用其他端口（重新运行的通常是长期运行的服务，需用户确认，不自动执行）
             strategies.push(
                 FixStrategy::new(
                     "使用其他端口",
@@ -383,7 +485,10 @@ impl ErrorFixer {
                     format!("改用端口 {}", port + 1),
                     3,
                 )
-                .with_outcome("使用新端口运行"),
+                .with_outcome("使用新端口运行")
+                .with_confirmation(true)
+                .with_verification(Verification::FixSucceeds)
+                .with_runs_original(true),
             );
         }
 
@@ -496,10 +601,55 @@ mod tests {
         assert_eq!(port, Some(8080));
     }
 
+    #[test]
+    fn test_port_change_requires_confirmation() {
+        let analysis = ErrorAnalysis::new(
+            "Error: Address already in use :8080".to_string(),
+            "python -m http.server 8080".to_string(),
+        );
+        let strategies = ErrorFixer::fix_port_in_use(&analysis);
+        let rerun = strategies.iter().find(|s| s.name == "使用其他端口").unwrap();
+        assert_eq!(rerun.command, "python -m http.server 8081");
+        assert!(rerun.requires_confirmation);
+    }
+
     #[test]
     fn test_is_common_tool() {
         assert!(ErrorFixer::is_common_tool("git"));
         assert!(ErrorFixer::is_common_tool("curl"));
         assert!(!ErrorFixer::is_common_tool("foobar"));
     }
+
+    #[test]
+    fn test_pattern_fix_commands_come_first() {
+        let mut analysis = ErrorAnalysis::new("Error: relation \"users\" does not exist".to_string(), "psql".to_string());
+        analysis.pattern_name = Some("postgres_missing_relation".to_string());
+        analysis.category = ErrorCategory::Tool("postgres".to_string());
+        analysis.suggested_fixes.push("运行数据库迁移".to_string());
+        analysis.fix_commands.push("sqlx migrate run".to_string());
+
+        let strategies = ErrorFixer::generate_strategies(&analysis);
+        assert_eq!(strategies[0].command, "sqlx migrate run");
+        assert_eq!(strategies[0].name, "postgres_missing_relation");
+        assert!(strategies[0].requires_confirmation);
+        // 之后仍有通用策略
+        assert!(strategies.len() > 1);
+    }
+
+    #[test]
+    fn test_command_typo_corrections_come_first() {
+        let analyzer = crate::error_fixer::ErrorAnalyzer::new();
+        let analysis = analyzer.analyze(
+            "git comit -m wip",
+            "git: 'comit' is not a git command. See 'git --help'.",
+        );
+        assert_eq!(analysis.category, ErrorCategory::Command);
+
+        let corrector = CommandCorrector::new();
+        let strategies = ErrorFixer::generate_strategies_with_corrector(&analysis, &corrector);
+        assert_eq!(strategies[0].name, CORRECTION_STRATEGY);
+        assert_eq!(strategies[0].command, "git commit -m wip");
+        assert!(strategies[0].requires_confirmation);
+        assert!(strategies[0].description.contains("comit → commit"));
+    }
 }
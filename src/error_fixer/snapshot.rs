@@ -0,0 +1,205 @@
+//! 工作区快照
+//!
+//! 自动修复执行前记录工作目录中的文件内容，修复未通过验证时据此回滚：
+//! 还原被修改或删除的文件，删除修复新建的文件。
+//!
+//! 跳过版本库与构建产物目录；文件过多或过大时快照不完整，
+//! 未记录的文件无法还原（`is_complete` 为 false）。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// 不纳入快照的目录
+const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules", "__pycache__", ".venv", "venv", "dist", "build"];
+
+/// 快照最多记录的文件数
+const MAX_FILES: usize = 5_000;
+
+/// 单个文件的大小上限（字节）
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// 快照总大小上限（字节）
+const MAX_TOTAL_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 文件变化
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileChange {
+    /// 新建
+    Created(PathBuf),
+    /// 修改
+    Modified(PathBuf),
+    /// 删除
+    Deleted(PathBuf),
+}
+
+impl FileChange {
+    /// 变化的文件路径
+    pub fn path(&self) -> &Path {
+        match self {
+            FileChange::Created(path) | FileChange::Modified(path) | FileChange::Deleted(path) => path,
+        }
+    }
+}
+
+/// 工作区快照
+#[derive(Debug, Clone)]
+pub struct WorkspaceSnapshot {
+    /// 快照根目录
+    root: PathBuf,
+    /// 文件路径 → 内容
+    files: HashMap<PathBuf, Vec<u8>>,
+    /// 是否记录了全部文件
+    complete: bool,
+}
+
+impl WorkspaceSnapshot {
+    /// 记录目录下的文件
+    pub fn capture(root: &Path) -> Self {
+        let mut files = HashMap::new();
+        let mut complete = true;
+        let mut total: u64 = 0;
+
+        for path in walk(root) {
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if files.len() >= MAX_FILES || metadata.len() > MAX_FILE_SIZE || total + metadata.len() > MAX_TOTAL_SIZE {
+                complete = false;
+                continue;
+            }
+            if let Ok(content) = std::fs::read(&path) {
+                total += content.len() as u64;
+                files.insert(path, content);
+            }
+        }
+
+        Self {
+            root: root.to_path_buf(),
+            files,
+            complete,
+        }
+    }
+
+    /// 快照根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 是否记录了全部文件
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// 记录的文件数
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// 是否没有记录任何文件
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// 当前磁盘状态相对快照的变化（按路径排序）
+    ///
+    /// 快照不完整时，未记录的既有文件会被当作新建文件，因此只在快照完整时报告新建。
+    pub fn changes(&self) -> Vec<FileChange> {
+        let mut changes = Vec::new();
+        for (path, content) in &self.files {
+            match std::fs::read(path) {
+                Ok(current) if current == *content => {}
+                Ok(_) => changes.push(FileChange::Modified(path.clone())),
+                Err(_) => changes.push(FileChange::Deleted(path.clone())),
+            }
+        }
+        if self.complete {
+            for path in walk(&self.root) {
+                if !self.files.contains_key(&path) {
+                    changes.push(FileChange::Created(path));
+                }
+            }
+        }
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+
+    /// 回滚到快照状态，返回回滚的变化
+    pub fn restore(&self) -> Result<Vec<FileChange>, String> {
+        let changes = self.changes();
+        for change in &changes {
+            let result = match change {
+                FileChange::Created(path) => std::fs::remove_file(path),
+                FileChange::Modified(path) | FileChange::Deleted(path) => {
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+                    }
+                    std::fs::write(path, &self.files[path])
+                }
+            };
+            result.map_err(|e| format!("{}: {}", change.path().display(), e))?;
+        }
+        Ok(changes)
+    }
+}
+
+/// 遍历目录下的普通文件（跳过 [`SKIPPED_DIRS`]）
+fn walk(root: &Path) -> impl Iterator<Item = PathBuf> {
+    WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("keep.txt"), "keep").unwrap();
+        std::fs::write(root.join("edit.txt"), "before").unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/remove.txt"), "remove").unwrap();
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join("target/ignored.o"), "obj").unwrap();
+
+        let snapshot = WorkspaceSnapshot::capture(root);
+        assert!(snapshot.is_complete());
+        assert_eq!(snapshot.len(), 3);
+        assert!(snapshot.changes().is_empty());
+
+        std::fs::write(root.join("edit.txt"), "after").unwrap();
+        std::fs::remove_file(root.join("sub/remove.txt")).unwrap();
+        std::fs::write(root.join("new.txt"), "new").unwrap();
+        std::fs::write(root.join("target/ignored.o"), "changed").unwrap();
+
+        let changes = snapshot.changes();
+        assert_eq!(
+            changes,
+            vec![
+                FileChange::Modified(root.join("edit.txt")),
+                FileChange::Created(root.join("new.txt")),
+                FileChange::Deleted(root.join("sub/remove.txt")),
+            ]
+        );
+
+        let restored = snapshot.restore().unwrap();
+        assert_eq!(restored.len(), 3);
+        assert_eq!(std::fs::read_to_string(root.join("edit.txt")).unwrap(), "before");
+        assert_eq!(std::fs::read_to_string(root.join("sub/remove.txt")).unwrap(), "remove");
+        assert!(!root.join("new.txt").exists());
+        // 跳过的目录不受影响
+        assert_eq!(std::fs::read_to_string(root.join("target/ignored.o")).unwrap(), "changed");
+        assert!(snapshot.changes().is_empty());
+    }
+}
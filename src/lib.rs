@@ -34,7 +34,9 @@ pub mod llm_manager;
 pub mod log_analyzer;      // ✨ Phase 6: 日志分析工具
 pub mod memory;
 pub mod project_context;   // ✨ Phase 6: 项目上下文感知
+pub mod session;           // 命名会话（保存、恢复、导出）
 pub mod shell_executor;
+pub mod shell_util;        // Shell 转义工具
 pub mod spinner;
 pub mod stats;             // ✨ Phase 9: 统计与可视化系统
 pub mod system_monitor;    // ✨ Phase 6: 系统监控工具
@@ -59,5 +61,5 @@ pub use task::{
     ExecutionContext, ExecutionPlan, PlanAnalysis, ProgressCallback, SubTask, TaskDecomposer,
     TaskError, TaskExecutor, TaskPlanner, TaskProgress, TaskResult, TaskStatus, TaskType,
 };
-pub use tool::{Parameter, ParameterType, Tool, ToolRegistry};
+pub use tool::{OutputRefs, Parameter, ParameterType, Tool, ToolRegistry};
 pub use tool_executor::{ExecutionMode, ToolCallRequest, ToolCallResult, ToolExecutor};
@@ -4,9 +4,17 @@
 //! - 持有 LLM 客户端实例
 //! - 管理主备 LLM（primary/fallback）
 //! - 提供统一的调用接口
+//! - 保存对话式调用（`chat_stream`）的消息历史（命名会话中才随请求发送）
 
 use crate::llm::{DeepseekClient, LlmClient, LlmError, Message};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// 对话式调用随请求发送的最多历史消息数
+const MAX_HISTORY_MESSAGES: usize = 20;
+
+/// 最多保留的历史消息数（超出时丢弃最早的消息）
+const MAX_STORED_HISTORY: usize = 200;
 
 /// LLM 管理器
 pub struct LlmManager {
@@ -16,6 +24,10 @@ pub struct LlmManager {
     fallback: Option<Arc<dyn LlmClient>>,
     /// Deepseek 客户端引用（用于流式输出）
     deepseek_client: Option<Arc<DeepseekClient>>,
+    /// 对话消息历史（用户提问与回答）
+    history: Mutex<Vec<Message>>,
+    /// 对话式调用是否附带历史（命名会话中启用）
+    attach_history: AtomicBool,
 }
 
 impl LlmManager {
@@ -24,6 +36,8 @@ impl LlmManager {
             primary: None,
             fallback: None,
             deepseek_client: None,
+            history: Mutex::new(Vec::new()),
+            attach_history: AtomicBool::new(false),
         }
     }
 
@@ -65,28 +79,72 @@ impl LlmManager {
     }
 
     /// 流式 chat（实时输出）
+    ///
+    /// 成功后把本轮问答追加到历史；启用了附带历史（命名会话）时，
+    /// 请求附带最近的消息历史。
     pub async fn chat_stream<F>(&self, query: &str, callback: F) -> Result<String, LlmError>
     where
         F: FnMut(&str),
     {
-        let messages = vec![Message::user(query)];
+        let mut messages = if self.attach_history.load(Ordering::Relaxed) {
+            self.recent_history(MAX_HISTORY_MESSAGES)
+        } else {
+            Vec::new()
+        };
+        messages.push(Message::user(query));
 
         // 优先使用 Deepseek 流式输出
-        if let Some(deepseek_client) = &self.deepseek_client {
-            return deepseek_client.chat_stream(&messages, callback).await;
-        }
-
-        // 否则降级到普通 chat（一次性输出）
-        let client = self
-            .fallback
-            .as_ref()
-            .or(self.primary.as_ref())
-            .ok_or_else(|| LlmError::Config("No LLM configured".to_string()))?;
+        let response = if let Some(deepseek_client) = &self.deepseek_client {
+            deepseek_client.chat_stream(&messages, callback).await?
+        } else {
+            // 否则降级到普通 chat（一次性输出）
+            let client = self
+                .fallback
+                .as_ref()
+                .or(self.primary.as_ref())
+                .ok_or_else(|| LlmError::Config("No LLM configured".to_string()))?;
+            client.chat(messages).await?
+        };
 
-        let response = client.chat(messages).await?;
+        let mut history = self.history.lock().unwrap();
+        history.push(Message::user(query));
+        history.push(Message::assistant(response.clone()));
+        trim_history(&mut history);
         Ok(response)
     }
 
+    /// 设置对话式调用是否附带消息历史
+    pub fn set_attach_history(&self, enabled: bool) {
+        self.attach_history.store(enabled, Ordering::Relaxed);
+    }
+
+    /// 对话式调用是否附带消息历史
+    pub fn attach_history(&self) -> bool {
+        self.attach_history.load(Ordering::Relaxed)
+    }
+
+    /// 消息历史
+    pub fn history(&self) -> Vec<Message> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// 最近 N 条历史消息
+    fn recent_history(&self, n: usize) -> Vec<Message> {
+        let history = self.history.lock().unwrap();
+        history[history.len().saturating_sub(n)..].to_vec()
+    }
+
+    /// 替换消息历史（用于恢复会话）
+    pub fn set_history(&self, mut messages: Vec<Message>) {
+        trim_history(&mut messages);
+        *self.history.lock().unwrap() = messages;
+    }
+
+    /// 清空消息历史
+    pub fn clear_history(&self) {
+        self.history.lock().unwrap().clear();
+    }
+
     /// 诊断主 LLM
     pub async fn diagnose_primary(&self) -> String {
         match &self.primary {
@@ -104,6 +162,12 @@ impl LlmManager {
     }
 }
 
+/// 只保留最近的 [`MAX_STORED_HISTORY`] 条消息
+fn trim_history(history: &mut Vec<Message>) {
+    let excess = history.len().saturating_sub(MAX_STORED_HISTORY);
+    history.drain(..excess);
+}
+
 impl Default for LlmManager {
     fn default() -> Self {
         Self::new()
@@ -179,6 +243,45 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_llm_manager_chat_stream_history() {
+        let mut manager = LlmManager::new();
+        manager.set_fallback(Arc::new(MockClient {
+            name: "fallback".to_string(),
+        }));
+
+        // 默认（无命名会话）不附带历史，但仍然记录
+        let first = manager.chat_stream("hello", |_| {}).await.unwrap();
+        assert_eq!(first, "fallback: received 1 messages");
+        let second = manager.chat_stream("again", |_| {}).await.unwrap();
+        assert_eq!(second, "fallback: received 1 messages");
+        assert_eq!(manager.history().len(), 4);
+
+        manager.set_attach_history(true);
+        let third = manager.chat_stream("more", |_| {}).await.unwrap();
+        assert_eq!(third, "fallback: received 5 messages");
+        assert_eq!(manager.history().len(), 6);
+        manager.set_attach_history(false);
+
+        // 单次 chat 不使用也不记录历史
+        assert_eq!(manager.chat("once").await.unwrap(), "fallback: received 1 messages");
+        assert_eq!(manager.history().len(), 6);
+
+        manager.clear_history();
+        assert!(manager.history().is_empty());
+    }
+
+    #[test]
+    fn test_llm_manager_history_capped() {
+        let manager = LlmManager::new();
+        let messages = (0..MAX_STORED_HISTORY + 10).map(|i| Message::user(i.to_string())).collect();
+        manager.set_history(messages);
+
+        let history = manager.history();
+        assert_eq!(history.len(), MAX_STORED_HISTORY);
+        assert_eq!(history[0].content.as_deref(), Some("10"));
+    }
+
     #[tokio::test]
     async fn test_llm_manager_diagnose() {
         let mut manager = LlmManager::new();
@@ -28,7 +28,9 @@ mod log_analyzer;  // ✨ Phase 6: 日志分析工具
 mod memory;
 mod project_context;  // ✨ Phase 6: 项目上下文感知
 mod repl;
+mod session;  // 命名会话（保存、恢复、导出）
 mod shell_executor;
+mod shell_util;  // Shell 转义工具
 mod spinner;
 mod stats;  // ✨ Phase 9: 统计与可视化
 mod system_monitor;  // ✨ Phase 6: 系统监控工具
@@ -63,6 +65,10 @@ struct Args {
     #[arg(short, long)]
     lang: Option<String>,
 
+    /// 启动时恢复（不存在则新建）的命名会话
+    #[arg(long)]
+    session: Option<String>,
+
     /// 子命令
     #[command(subcommand)]
     command: Option<Commands>,
@@ -85,6 +91,38 @@ enum Commands {
         #[arg(short, long)]
         path: bool,
     },
+
+    /// 无交互执行计划文件（用于 CI）
+    RunPlan {
+        /// 计划文件（YAML）
+        file: PathBuf,
+
+        /// 报告格式: jsonl 或 junit
+        #[arg(short, long, default_value = "jsonl")]
+        format: String,
+
+        /// 最多同时执行的任务数
+        #[arg(short, long)]
+        parallelism: Option<usize>,
+
+        /// 报告输出文件（默认标准输出）
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// 解释错误输出（如 `cmd 2>&1 | realconsole explain`）
+    Explain {
+        /// 错误输出文件（默认标准输入）
+        file: Option<PathBuf>,
+
+        /// 产生错误的原命令（可选）
+        #[arg(long)]
+        command: Option<String>,
+
+        /// 不使用 LLM 增强分析
+        #[arg(long)]
+        no_llm: bool,
+    },
 }
 
 /// 根据配置创建 LLM 客户端
@@ -187,6 +225,109 @@ fn show_config(config_path: &str, show_path: bool) {
     }
 }
 
+/// 无交互执行计划文件
+///
+/// 退出码：0 全部通过，1 有任务失败，2 计划无法加载或执行
+async fn run_plan(
+    file: PathBuf,
+    format: String,
+    parallelism: Option<usize>,
+    output: Option<PathBuf>,
+) -> i32 {
+    use task::headless::{self, HeadlessOptions};
+
+    let format = match format.parse() {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let out: Box<dyn std::io::Write + Send> = match &output {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("无法创建报告文件 {}: {}", path.display(), e);
+                return 2;
+            }
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    let options = HeadlessOptions {
+        format,
+        parallelism,
+        ..HeadlessOptions::default()
+    };
+
+    match headless::run_plan_file(&file, &options, out).await {
+        Ok(result) if headless::passed(&result) => 0,
+        Ok(_) => 1,
+        Err(e) => {
+            eprintln!("{}: {}", file.display(), e);
+            2
+        }
+    }
+}
+
+/// 解释错误输出，返回进程退出码
+async fn run_explain(
+    config_path: &str,
+    file: Option<PathBuf>,
+    command: Option<String>,
+    no_llm: bool,
+) -> i32 {
+    use commands::explain_cmd::{explain_error_output, format_explanation, read_explain_input};
+
+    let source = file.as_deref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|| "-".to_string());
+    if let Some(path) = &file {
+        if !path.is_file() {
+            eprintln!("文件不存在: {}", path.display());
+            return 2;
+        }
+    }
+    let output = match read_explain_input(&source) {
+        Ok(output) if !output.trim().is_empty() => output,
+        Ok(_) => {
+            eprintln!("没有要解释的内容");
+            return 2;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+
+    // LLM 可选：配置缺失或客户端创建失败时只做本地分析
+    let llm = if no_llm {
+        None
+    } else {
+        config::Config::from_file(config_path).ok().and_then(|cfg| {
+            [cfg.llm.primary, cfg.llm.fallback]
+                .iter()
+                .flatten()
+                .find_map(|provider| create_llm_client(provider).ok())
+        })
+    };
+
+    // 与 REPL 共用用户错误模式与历史反馈
+    let analyzer = error_fixer::ErrorAnalyzer::with_user_patterns();
+    let mut learner = error_fixer::FeedbackLearner::new();
+    if let Some(config_dir) = dirs::config_dir() {
+        learner = learner.with_storage(config_dir.join("realconsole").join("feedback.json"));
+        let _ = learner.load_from_disk().await;
+    }
+    let explanation = explain_error_output(
+        &output,
+        command.as_deref().unwrap_or(""),
+        &analyzer,
+        Some(&learner),
+        llm.as_deref(),
+    )
+    .await;
+    println!("{}", format_explanation(&explanation));
+    0
+}
+
 /// 尝试从配置文件所在目录加载 .env 文件
 fn load_env_file(config_path: &str) {
     // 获取配置文件所在目录
@@ -244,6 +385,13 @@ async fn main() {
                 show_config(&args.config, path);
                 return;
             }
+            Commands::RunPlan { file, format, parallelism, output } => {
+                process::exit(run_plan(file, format, parallelism, output).await);
+            }
+            Commands::Explain { file, command, no_llm } => {
+                load_env_file(&args.config);
+                process::exit(run_explain(&args.config, file, command, no_llm).await);
+            }
         }
     }
 
@@ -302,6 +450,9 @@ async fn main() {
     // 注册系统监控命令（Phase 6）
     commands::register_system_commands(&mut registry);
 
+    // 注册 Pipeline DSL 命令
+    commands::register_pipe_commands(&mut registry, config.intent.pipeline_backend, config.display.mode);
+
     // 创建 Agent
     let mut agent = agent::Agent::new(config.clone(), registry);
 
@@ -367,7 +518,12 @@ async fn main() {
 
     // 注册记忆管理命令（需要访问 agent 的 memory）
     let memory = agent.memory();
-    commands::register_memory_commands(&mut agent.registry, memory);
+    let long_term_memory = agent.long_term_memory();
+    commands::register_memory_commands(&mut agent.registry, memory, long_term_memory);
+
+    // 注册命令输出管理命令（$1、$last）
+    let output_store = agent.output_store();
+    commands::register_output_commands(&mut agent.registry, output_store);
 
     // 注册执行日志命令（需要访问 agent 的 exec_logger）
     let exec_logger = agent.exec_logger();
@@ -384,7 +540,34 @@ async fn main() {
     // ✨ Phase 10: 注册任务分解与规划命令
     let llm_mgr_for_task = agent.llm_manager();
     let shell_exec_for_task = agent.shell_executor_with_fixer.clone();
-    commands::register_task_commands(&mut agent.registry, llm_mgr_for_task, shell_exec_for_task);
+    let tools_for_task = agent.tool_registry();
+    commands::register_task_commands(
+        &mut agent.registry,
+        llm_mgr_for_task,
+        shell_exec_for_task,
+        tools_for_task,
+    );
+
+    // 注册错误解释命令
+    let llm_mgr_for_explain = agent.llm_manager();
+    let shell_exec_for_explain = agent.shell_executor_with_fixer.clone();
+    commands::register_explain_commands(&mut agent.registry, shell_exec_for_explain, llm_mgr_for_explain);
+
+    // 注册会话管理命令
+    let session_manager = agent.session_manager();
+    commands::register_session_commands(&mut agent.registry, Arc::clone(&session_manager));
+
+    // 启动时恢复命名会话（不存在则以当前状态新建）
+    if let Some(name) = args.session.as_deref() {
+        let result = if session_manager.store().exists(name) {
+            session_manager.switch(name).await.map(|_| ())
+        } else {
+            session_manager.save_as(name).await.map(|_| ())
+        };
+        if let Err(e) = result {
+            eprintln!("{} {}", "会话恢复失败:".red(), e);
+        }
+    }
 
     // 运行模式
     if let Some(input) = args.once {
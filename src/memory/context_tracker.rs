@@ -128,8 +128,37 @@ pub struct ReferenceRecord {
     pub resolved_at: DateTime<Utc>,
 }
 
+/// 指代消解后的输入
+#[derive(Debug, Clone)]
+pub struct ResolvedInput {
+    /// 替换指代词后的文本
+    pub text: String,
+
+    /// 本次输入中解析成功的指代（按出现顺序）
+    pub references: Vec<ReferenceRecord>,
+}
+
+impl ResolvedInput {
+    /// 是否有指代被替换
+    pub fn is_rewritten(&self) -> bool {
+        !self.references.is_empty()
+    }
+}
+
+/// 输入中可被消解的指代短语（长短语在前，避免被短的前缀截断）
+const REFERENCE_PHRASES: &[&str] = &[
+    "上一个命令", "上一条命令", "刚才的命令", "上个命令",
+    "这个文件", "那个文件", "该文件",
+    "这个目录", "那个目录", "该目录",
+    "它",
+    "the last command", "the previous command", "last command", "previous command",
+    "this file", "that file",
+    "this directory", "that directory", "this folder", "that folder",
+    "it", "this", "that",
+];
+
 /// 工作上下文
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct WorkingContext {
     /// 当前工作目录
     pub current_directory: Option<PathBuf>,
@@ -161,6 +190,9 @@ pub struct ContextTracker {
     /// 最大指代历史数量
     max_reference_history: usize,
 
+    /// 当前输入中解析出的指代（供意图提取与 LLM 提示词使用）
+    current_references: Vec<ReferenceRecord>,
+
     /// 实体提取器（正则表达式）
     extractor: EntityExtractor,
 }
@@ -173,6 +205,7 @@ impl ContextTracker {
             working_context: WorkingContext::default(),
             reference_history: Vec::new(),
             max_reference_history: 20,
+            current_references: Vec::new(),
             extractor: EntityExtractor::new(),
         }
     }
@@ -216,20 +249,28 @@ impl ContextTracker {
         // 特定指代词映射
         let entity = match pronoun_lower.as_str() {
             "它" | "it" | "this" | "that" => {
-                // 返回最近的实体
+                // 返回最近的实体，没有时回退到最近操作的文件
                 self.get_most_recent_entity()
+                    .or_else(|| self.resolve_from_working_context(pronoun))
             }
-            "这个文件" | "该文件" | "this file" => {
+            "这个文件" | "那个文件" | "该文件" | "this file" | "that file" => {
                 // 返回最近的文件
                 self.get_most_recent_entity_by_type("文件")
+                    .or_else(|| self.resolve_from_working_context(pronoun))
             }
-            "这个目录" | "该目录" | "this directory" => {
+            "这个目录" | "那个目录" | "该目录" | "this directory" | "that directory"
+            | "this folder" | "that folder" => {
                 // 返回最近的目录
                 self.get_most_recent_entity_by_type("目录")
             }
-            "上一个命令" | "刚才的命令" | "last command" => {
-                // 返回最近的命令
-                self.get_most_recent_entity_by_type("命令")
+            "上一个命令" | "上一条命令" | "刚才的命令" | "上个命令" | "last command"
+            | "the last command" | "previous command" | "the previous command" => {
+                // 返回最近的命令，没有时回退到最近执行的命令
+                self.get_most_recent_entity_by_type("命令").or_else(|| {
+                    self.working_context.last_command.as_ref().map(|cmd| {
+                        Entity::new(EntityType::Command(cmd.clone()), "工作上下文".to_string(), 0.8)
+                    })
+                })
             }
             _ => {
                 // 尝试从工作上下文获取
@@ -256,10 +297,76 @@ impl ContextTracker {
         entity
     }
 
-    /// 获取最近的实体
+    /// 获取最近的实体（数字不作为指代对象）
     fn get_most_recent_entity(&mut self) -> Option<Entity> {
         // LruCache 会自动把最近访问的移到前面
-        self.recent_entities.iter().next().map(|(_, e)| e.clone())
+        self.recent_entities
+            .iter()
+            .find(|(_, e)| !matches!(e.entity_type, EntityType::Number(_)))
+            .map(|(_, e)| e.clone())
+    }
+
+    /// 消解输入中的指代词
+    ///
+    /// 识别中英文指代（"它"、"这个文件"、"上一个命令"、"it"、"this file"、
+    /// "the last command" 等），替换为最近提到的实体。引号内的内容保持不变；
+    /// 命令以反引号包裹，便于和上下文区分。
+    pub fn resolve_input(&mut self, input: &str) -> ResolvedInput {
+        self.current_references.clear();
+
+        let mut text = String::with_capacity(input.len());
+        let mut quote: Option<char> = None;
+        let mut rest = input;
+
+        while let Some(c) = rest.chars().next() {
+            // 引号内原样保留
+            if let Some(q) = quote {
+                if c == q {
+                    quote = None;
+                }
+            } else if matches!(c, '"' | '`')
+                || (c == '\'' && !text.chars().next_back().is_some_and(char::is_alphanumeric))
+            {
+                // 单引号紧跟单词时视为撇号（don't、command's）
+                quote = Some(c);
+            } else if let Some((phrase, len)) = match_reference_phrase(&text, rest) {
+                if let Some(entity) = self.resolve_reference(phrase) {
+                    let replacement = match &entity.entity_type {
+                        EntityType::Command(cmd) => format!("`{}`", cmd),
+                        other => other.display_name(),
+                    };
+                    // 与中文相邻时补空格，保持路径可被识别
+                    let is_cjk = |c: char| !c.is_ascii() && c.is_alphanumeric();
+                    if text.chars().next_back().is_some_and(is_cjk) {
+                        text.push(' ');
+                    }
+                    text.push_str(&replacement);
+                    if rest[len..].chars().next().is_some_and(is_cjk) {
+                        text.push(' ');
+                    }
+                    self.current_references.push(ReferenceRecord {
+                        pronoun: rest[..len].to_string(),
+                        resolved_entity: entity,
+                        resolved_at: Utc::now(),
+                    });
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+
+        ResolvedInput {
+            text,
+            references: self.current_references.clone(),
+        }
+    }
+
+    /// 当前输入中解析出的指代
+    pub fn current_references(&self) -> &[ReferenceRecord] {
+        &self.current_references
     }
 
     /// 按类型获取最近的实体
@@ -305,6 +412,20 @@ impl ContextTracker {
         }
     }
 
+    /// 获取工作上下文
+    pub fn working_context(&self) -> &WorkingContext {
+        &self.working_context
+    }
+
+    /// 恢复工作上下文与实体（实体按最近使用顺序传入，用于恢复会话）
+    pub fn restore(&mut self, working_context: WorkingContext, entities: Vec<Entity>) {
+        self.clear();
+        self.working_context = working_context;
+        for entity in entities.into_iter().rev() {
+            self.recent_entities.put(entity.entity_type.display_name(), entity);
+        }
+    }
+
     /// 获取所有实体（按最近使用顺序）
     pub fn get_all_entities(&mut self) -> Vec<Entity> {
         self.recent_entities.iter().map(|(_, e)| e.clone()).collect()
@@ -612,6 +733,40 @@ impl ContextTracker {
     }
 }
 
+/// 匹配位于 `rest` 开头的指代短语，返回（规范短语, 原文字节长度）
+///
+/// 英文短语要求完整单词，"it"/"this"/"that" 仅在作宾语（其后没有单词）时匹配，
+/// 如 "is it possible to…" 中的 "it" 与 "it's" 都不匹配；"其它" 中的 "它" 不视为指代。
+fn match_reference_phrase(before: &str, rest: &str) -> Option<(&'static str, usize)> {
+    let prev = before.chars().next_back();
+    let is_word = |c: char| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/');
+
+    REFERENCE_PHRASES.iter().find_map(|&phrase| {
+        let candidate = rest.get(..phrase.len())?;
+        if phrase.is_ascii() {
+            if !candidate.eq_ignore_ascii_case(phrase) || prev.is_some_and(is_word) {
+                return None;
+            }
+            let after = &rest[phrase.len()..];
+            if after.chars().next().is_some_and(is_word) {
+                return None;
+            }
+            // "it's" 是 "it is" 的缩写，不替换
+            if phrase == "it" && after.starts_with(['\'', '’']) {
+                return None;
+            }
+            if matches!(phrase, "it" | "this" | "that")
+                && after.trim_start().chars().next().is_some_and(|c| c.is_alphanumeric())
+            {
+                return None;
+            }
+        } else if candidate != phrase || (phrase == "它" && prev == Some('其')) {
+            return None;
+        }
+        Some((phrase, phrase.len()))
+    })
+}
+
 impl Default for ContextTracker {
     fn default() -> Self {
         Self::new()
@@ -655,10 +810,10 @@ impl EntityExtractor {
     fn new() -> Self {
         Self {
             // 匹配文件路径（绝对路径或相对路径，带扩展名）
-            file_regex: Regex::new(r"(?:^|[\s,;])((?:[./~])?(?:[a-zA-Z0-9_-]+/)*[a-zA-Z0-9_-]+\.[a-zA-Z0-9]+)").unwrap(),
+            file_regex: Regex::new(r"(?:^|[\s,;])((?:\.{1,2}/|~/|[./~])?(?:[a-zA-Z0-9_-]+/)*[a-zA-Z0-9_-]+\.[a-zA-Z0-9]+)").unwrap(),
 
             // 匹配目录路径（以 / 结尾或常见目录名）
-            dir_regex: Regex::new(r"(?:^|[\s,;])((?:[./~])?(?:[a-zA-Z0-9_-]+/)+)").unwrap(),
+            dir_regex: Regex::new(r"(?:^|[\s,;])((?:\.{1,2}/|~/|[./~])?(?:[a-zA-Z0-9_-]+/)+)").unwrap(),
 
             // 匹配 URL
             url_regex: Regex::new(r"https?://[^\s]+").unwrap(),
@@ -840,6 +995,50 @@ mod tests {
         assert!(matches!(entity.entity_type, EntityType::File(_)));
     }
 
+    #[test]
+    fn test_resolve_input() {
+        let mut tracker = ContextTracker::new();
+        let entities = tracker.extract_entities("cat ./src/main.rs");
+        tracker.record_entities(entities);
+
+        let resolved = tracker.resolve_input("delete it");
+        assert_eq!(resolved.text, "delete ./src/main.rs");
+        assert_eq!(resolved.references.len(), 1);
+        assert_eq!(resolved.references[0].pronoun, "it");
+
+        let resolved = tracker.resolve_input("删除它的备份");
+        assert_eq!(resolved.text, "删除 ./src/main.rs 的备份");
+        assert_eq!(tracker.current_references().len(), 1);
+
+        // 引号内、单词内部、其它、it's、作主语的 it 与作定语的 this 不替换
+        for input in [
+            "git commit -m \"fix it\"",
+            "edit items",
+            "其它文件",
+            "it's fine",
+            "is it possible to undo",
+            "is this ok",
+        ] {
+            let resolved = tracker.resolve_input(input);
+            assert_eq!(resolved.text, input);
+            assert!(!resolved.is_rewritten());
+        }
+    }
+
+    #[test]
+    fn test_resolve_input_last_command() {
+        let mut tracker = ContextTracker::new();
+        assert!(!tracker.resolve_input("show the last command's output").is_rewritten());
+
+        tracker.update_working_context(WorkingContextUpdate::LastCommand("ls -la".to_string()));
+        let resolved = tracker.resolve_input("show the last command's output");
+        assert_eq!(resolved.text, "show `ls -la`'s output");
+        assert_eq!(resolved.references[0].pronoun, "the last command");
+
+        let resolved = tracker.resolve_input("重新执行上一个命令");
+        assert_eq!(resolved.text, "重新执行 `ls -la`");
+    }
+
     #[test]
     fn test_entity_extractor() {
         let extractor = EntityExtractor::new();
@@ -0,0 +1,973 @@
+//! 长期记忆
+//!
+//! 短期记忆（[`Memory`](super::Memory)）只保留最近 N 条，搜索是线性子串匹配。
+//! 长期记忆跨会话保存条目（最多 [`DEFAULT_MAX_ENTRIES`] 条，可配置），并提供：
+//!
+//! - **全文检索**：倒排索引 + BM25 排序（英文按单词、中文按字二元组切分）
+//! - **语义检索**：可选的嵌入器（[`Embedder`]），按余弦相似度召回字面不同但意思相近的条目
+//! - **过滤**：按条目类型、时间范围、会话过滤（[`MemoryQuery`]）
+//! - **自动召回**：为 LLM 提示词附上相关的历史条目（[`LongTermMemory::recall_context`]）
+//!
+//! 条目以 JSONL 追加写入磁盘，启动时重建索引；超出上限时丢弃最早的条目并重写文件。
+//! 嵌入向量按模型保存在旁边的 `*.vectors.jsonl` 中，挂载嵌入器时只加载已有向量，
+//! 缺少向量的条目在之后每次添加条目时分批补算。嵌入器失败后（如 Ollama 不可用）
+//! 在冷却期内停止嵌入，退化为纯全文检索。
+
+use super::memory_core::{EntryType, MemoryEntry};
+use crate::dsl::intent::embedding::{cosine_similarity, Embedder, FailFastEmbedder};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// BM25 词频饱和参数
+const BM25_K1: f64 = 1.2;
+
+/// BM25 文档长度归一化参数
+const BM25_B: f64 = 0.75;
+
+/// 语义召回的最低余弦相似度
+const SEMANTIC_THRESHOLD: f64 = 0.5;
+
+/// 自动召回要求的最低查询词覆盖率
+const RECALL_MIN_COVERAGE: f64 = 0.5;
+
+/// 召回条目在提示词中的最大字符数
+const RECALL_PREVIEW_CHARS: usize = 200;
+
+/// 单个条目保存的最大字符数
+const MAX_CONTENT_CHARS: usize = 4000;
+
+/// 默认最多保留的条目数
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// 每次添加条目时最多补算的向量数
+const EMBED_BATCH_SIZE: usize = 16;
+
+/// 终端颜色控制序列
+pub(super) static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1b\[[0-9;?]*[A-Za-z]").unwrap());
+
+/// 长期记忆条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LongTermEntry {
+    /// 条目 ID（递增）
+    pub id: u64,
+    /// 所属会话
+    pub session: String,
+    /// 记忆内容
+    #[serde(flatten)]
+    pub entry: MemoryEntry,
+}
+
+impl LongTermEntry {
+    /// 格式化输出（含日期与会话）
+    pub fn format(&self) -> String {
+        format!(
+            "#{} [{}] ({}) {}: {}",
+            self.id,
+            self.entry.timestamp.format("%Y-%m-%d %H:%M"),
+            self.session,
+            self.entry.entry_type,
+            self.entry.content
+        )
+    }
+}
+
+/// 检索条件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryQuery {
+    /// 查询文本（为空时按时间倒序列出符合过滤条件的条目）
+    pub text: String,
+    /// 条目类型（为空表示不限）
+    pub types: Vec<EntryType>,
+    /// 起始时间（含）
+    pub since: Option<DateTime<Utc>>,
+    /// 截止时间（含）
+    pub until: Option<DateTime<Utc>>,
+    /// 会话
+    pub session: Option<String>,
+    /// 最多返回条数
+    pub limit: usize,
+}
+
+impl MemoryQuery {
+    /// 创建检索条件（默认最多 10 条）
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            limit: 10,
+            ..Self::default()
+        }
+    }
+
+    /// 限定条目类型
+    pub fn with_types(mut self, types: Vec<EntryType>) -> Self {
+        self.types = types;
+        self
+    }
+
+    /// 限定起始时间
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    /// 限定截止时间
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    /// 限定会话
+    pub fn with_session(mut self, session: impl Into<String>) -> Self {
+        self.session = Some(session.into());
+        self
+    }
+
+    /// 设置最多返回条数
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// 解析命令参数
+    ///
+    /// 支持的过滤项：`type:shell,user`、`since:7d`（也可 `24h`、`30m`、`2026-10-01`）、
+    /// `until:2026-10-18`、`session:<名称>`、`limit:<N>`；其余词为查询文本。
+    pub fn parse(args: &str) -> Result<Self, String> {
+        let mut query = Self::new("");
+        let mut words = Vec::new();
+
+        for word in args.split_whitespace() {
+            let Some((key, value)) = word.split_once(':') else {
+                words.push(word);
+                continue;
+            };
+            match key {
+                "type" => {
+                    for name in value.split(',').filter(|s| !s.is_empty()) {
+                        let entry_type = EntryType::parse(name).ok_or_else(|| format!("未知类型: {}", name))?;
+                        query.types.push(entry_type);
+                    }
+                }
+                "since" => query.since = Some(parse_time(value, false)?),
+                "until" => query.until = Some(parse_time(value, true)?),
+                "session" => query.session = Some(value.to_string()),
+                "limit" => query.limit = value.parse().map_err(|_| format!("无效的条数: {}", value))?,
+                // 不是过滤项（如 URL、`host:port`），视为查询文本
+                _ => words.push(word),
+            }
+        }
+
+        query.text = words.join(" ");
+        Ok(query)
+    }
+
+    /// 条目是否满足过滤条件（不含查询文本）
+    pub fn matches(&self, entry: &LongTermEntry) -> bool {
+        (self.types.is_empty() || self.types.contains(&entry.entry.entry_type))
+            && self.since.is_none_or(|since| entry.entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.entry.timestamp <= until)
+            && self.session.as_ref().is_none_or(|session| &entry.session == session)
+    }
+}
+
+/// 解析时间：相对时长（`7d`、`24h`、`30m`、`2w`）或日期（`YYYY-MM-DD`）
+///
+/// `end_of_day` 为 true 时日期取当天结束时刻（用于 `until`）。
+fn parse_time(value: &str, end_of_day: bool) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let time = if end_of_day {
+            date.and_hms_opt(23, 59, 59)
+        } else {
+            date.and_hms_opt(0, 0, 0)
+        };
+        return time.map(|t| t.and_utc()).ok_or_else(|| format!("无效的日期: {}", value));
+    }
+
+    let invalid = || format!("无效的时间: {}（示例: 7d、24h、30m、2026-10-01）", value);
+    let split = value.len().checked_sub(1).ok_or_else(invalid)?;
+    if !value.is_char_boundary(split) {
+        return Err(invalid());
+    }
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "m" => Duration::minutes(amount),
+        "h" => Duration::hours(amount),
+        "d" => Duration::days(amount),
+        "w" => Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+    Ok(Utc::now() - duration)
+}
+
+/// 检索结果
+#[derive(Debug, Clone)]
+pub struct MemoryHit<'a> {
+    /// 条目
+    pub entry: &'a LongTermEntry,
+    /// 综合得分（归一化 BM25 + 语义相似度 × 权重）
+    pub score: f64,
+    /// BM25 原始得分
+    pub bm25: f64,
+    /// 语义相似度（未启用语义检索时为 0）
+    pub semantic: f64,
+    /// 命中的查询词比例
+    pub coverage: f64,
+}
+
+/// 切分检索词
+///
+/// 英文与数字按单词切分并转小写（丢弃单字母），中文按相邻两字切分
+/// （单字的中文片段保留单字）。
+pub fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut cjk_run: Vec<char> = Vec::new();
+
+    let flush_word = |word: &mut String, tokens: &mut Vec<String>| {
+        if word.chars().count() > 1 || (!word.is_empty() && word.chars().all(|c| c.is_ascii_digit())) {
+            tokens.push(word.to_lowercase());
+        }
+        word.clear();
+    };
+    let flush_cjk = |run: &mut Vec<char>, tokens: &mut Vec<String>| {
+        if run.len() == 1 {
+            tokens.push(run[0].to_string());
+        } else {
+            tokens.extend(run.windows(2).map(|pair| pair.iter().collect::<String>()));
+        }
+        run.clear();
+    };
+
+    for c in text.chars() {
+        if is_cjk(c) {
+            flush_word(&mut word, &mut tokens);
+            cjk_run.push(c);
+        } else if c.is_alphanumeric() || c == '_' {
+            flush_cjk(&mut cjk_run, &mut tokens);
+            word.push(c);
+        } else {
+            flush_word(&mut word, &mut tokens);
+            flush_cjk(&mut cjk_run, &mut tokens);
+        }
+    }
+    flush_word(&mut word, &mut tokens);
+    flush_cjk(&mut cjk_run, &mut tokens);
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}')
+}
+
+/// 长期记忆
+pub struct LongTermMemory {
+    /// 全部条目（按 ID 递增）
+    entries: Vec<LongTermEntry>,
+    /// 倒排索引：词 → (条目下标, 词频)
+    postings: HashMap<String, Vec<(usize, u32)>>,
+    /// 每个条目的词数
+    doc_lens: Vec<u32>,
+    /// 所有条目的总词数
+    total_len: u64,
+    /// 嵌入向量（与 entries 一一对应）
+    vectors: Vec<Option<Vec<f32>>>,
+    /// 嵌入器（语义检索，失败后冷却期内不再调用）
+    embedder: Option<FailFastEmbedder>,
+    /// 最多保留的条目数
+    max_entries: usize,
+    /// 语义相似度在综合得分中的权重
+    semantic_weight: f64,
+    /// 持久化文件
+    path: Option<PathBuf>,
+    /// 当前会话
+    session: String,
+    /// 下一个条目 ID
+    next_id: u64,
+}
+
+impl LongTermMemory {
+    /// 创建仅内存的长期记忆
+    pub fn new(session: impl Into<String>) -> Self {
+        Self {
+            entries: Vec::new(),
+            postings: HashMap::new(),
+            doc_lens: Vec::new(),
+            total_len: 0,
+            vectors: Vec::new(),
+            embedder: None,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            semantic_weight: 1.0,
+            path: None,
+            session: session.into(),
+            next_id: 1,
+        }
+    }
+
+    /// 从 JSONL 文件加载并重建索引（文件不存在时从空开始，损坏的行被跳过）
+    pub fn open(path: impl AsRef<Path>, session: impl Into<String>) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut memory = Self::new(session);
+
+        if path.exists() {
+            let file = File::open(&path).map_err(|e| format!("无法打开文件: {}", e))?;
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| format!("读取失败: {}", e))?;
+                if let Ok(entry) = serde_json::from_str::<LongTermEntry>(&line) {
+                    memory.next_id = memory.next_id.max(entry.id + 1);
+                    memory.index(entry);
+                }
+            }
+        }
+
+        memory.path = Some(path);
+        Ok(memory)
+    }
+
+    /// 默认存储路径：`~/.realconsole/memory/long_term.jsonl`
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("memory")
+            .join("long_term.jsonl")
+    }
+
+    /// 启用语义检索
+    ///
+    /// 只从向量文件加载同一模型已计算的向量，不调用嵌入器；
+    /// 缺少向量的条目在之后添加条目时分批补算（见 [`Self::embed_pending`]）。
+    pub fn with_embedder(mut self, embedder: Arc<dyn Embedder>) -> Self {
+        let model = embedder.model_id();
+        let mut saved = self.vectors_path().map(|path| load_vectors(&path, &model)).unwrap_or_default();
+
+        self.embedder = Some(FailFastEmbedder::new(embedder));
+        self.vectors = self.entries.iter().map(|entry| saved.remove(&entry.id)).collect();
+        self
+    }
+
+    /// 设置最多保留的条目数（默认 [`DEFAULT_MAX_ENTRIES`]）
+    ///
+    /// 已超出上限时立即丢弃最早的条目。
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        if self.entries.len() > self.max_entries {
+            let _ = self.compact();
+        }
+        self
+    }
+
+    /// 设置语义相似度权重（默认 1.0）
+    pub fn with_semantic_weight(mut self, weight: f64) -> Self {
+        self.semantic_weight = weight;
+        self
+    }
+
+    /// 当前会话
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    /// 切换当前会话（之后添加的条目归入新会话）
+    pub fn set_session(&mut self, session: impl Into<String>) {
+        self.session = session.into();
+    }
+
+    /// 持久化文件
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// 是否启用了语义检索
+    pub fn has_embedder(&self) -> bool {
+        self.embedder.is_some()
+    }
+
+    /// 嵌入器是否已失败
+    pub fn embedder_failed(&self) -> bool {
+        self.embedder.as_ref().is_some_and(FailFastEmbedder::failed)
+    }
+
+    /// 缺少向量的条目数（未启用语义检索时为 0）
+    pub fn pending_vectors(&self) -> usize {
+        if self.embedder.is_none() {
+            return 0;
+        }
+        self.vectors.iter().filter(|vector| vector.is_none()).count()
+    }
+
+    /// 向量文件：`long_term.jsonl` → `long_term.vectors.jsonl`
+    fn vectors_path(&self) -> Option<PathBuf> {
+        self.path.as_ref().map(|path| path.with_extension("vectors.jsonl"))
+    }
+
+    /// 计算文本的嵌入向量（嵌入器失败后冷却期内不再调用）
+    fn embed(&self, text: &str) -> Option<Vec<f32>> {
+        self.embedder.as_ref()?.embed(text).ok()
+    }
+
+    /// 计算条目的嵌入向量并追加到向量文件（写入失败时向量仍保留在内存中）
+    fn embed_entry(&self, entry: &LongTermEntry) -> Option<Vec<f32>> {
+        let vector = self.embed(&entry.entry.content)?;
+        if let (Some(path), Some(embedder)) = (self.vectors_path(), &self.embedder) {
+            let record = VectorRecord {
+                id: entry.id,
+                model: embedder.model_id(),
+                vector: vector.clone(),
+            };
+            let _ = append_line(&path, &record);
+        }
+        Some(vector)
+    }
+
+    /// 为缺少向量的条目补算向量（从最新的条目开始，最多 `limit` 个），返回补算数
+    pub fn embed_pending(&mut self, limit: usize) -> usize {
+        let mut embedded = 0;
+        for i in (0..self.entries.len()).rev() {
+            if embedded >= limit || self.embedder.is_none() || self.embedder_failed() {
+                break;
+            }
+            if self.vectors[i].is_some() {
+                continue;
+            }
+            let Some(vector) = self.embed_entry(&self.entries[i]) else {
+                break;
+            };
+            self.vectors[i] = Some(vector);
+            embedded += 1;
+        }
+        embedded
+    }
+
+    /// 条目数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// 索引中的词数
+    pub fn term_count(&self) -> usize {
+        self.postings.len()
+    }
+
+    /// 所有出现过的会话（按最近活动时间倒序）
+    pub fn sessions(&self) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| seen.insert(entry.session.as_str()))
+            .map(|entry| entry.session.as_str())
+            .collect()
+    }
+
+    /// 添加条目到当前会话，返回条目 ID
+    ///
+    /// 去掉终端颜色控制序列，超过 4000 字符的内容被截断。
+    /// 配置了持久化文件时立即追加写入；写入失败时条目仍保留在内存中。
+    /// 条目数超出上限一成后丢弃最早的条目，压缩回上限。
+    pub fn add(&mut self, content: impl Into<String>, entry_type: EntryType) -> Result<u64, String> {
+        let content = content.into();
+        let content = ANSI_ESCAPE.replace_all(&content, "");
+        let content: String = if content.chars().count() > MAX_CONTENT_CHARS {
+            format!("{}...", content.chars().take(MAX_CONTENT_CHARS).collect::<String>())
+        } else {
+            content.into_owned()
+        };
+
+        let entry = LongTermEntry {
+            id: self.next_id,
+            session: self.session.clone(),
+            entry: MemoryEntry::new(content, entry_type),
+        };
+        self.next_id += 1;
+
+        let persisted = match &self.path {
+            Some(path) => append_line(path, &entry),
+            None => Ok(()),
+        };
+
+        let id = entry.id;
+        self.index(entry);
+        self.embed_pending(EMBED_BATCH_SIZE);
+        if self.entries.len() > self.max_entries + self.max_entries / 10 {
+            self.compact()?;
+        }
+        persisted.map(|_| id)
+    }
+
+    /// 丢弃最早的条目直到不超过上限，重建索引并重写持久化文件
+    fn compact(&mut self) -> Result<(), String> {
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        if excess == 0 {
+            return Ok(());
+        }
+
+        let entries: Vec<LongTermEntry> = self.entries.drain(excess..).collect();
+        let vectors: Vec<Option<Vec<f32>>> = self.vectors.drain(excess..).collect();
+        self.entries.clear();
+        self.vectors.clear();
+        self.postings.clear();
+        self.doc_lens.clear();
+        self.total_len = 0;
+        for entry in entries {
+            self.index(entry);
+        }
+        self.vectors = vectors;
+
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        rewrite_lines(path, &self.entries)?;
+        let first_id = self.entries.first().map_or(0, |entry| entry.id);
+        if let Some(vectors_path) = self.vectors_path().filter(|p| p.exists()) {
+            let kept: Vec<VectorRecord> = read_lines::<VectorRecord>(&vectors_path)
+                .into_iter()
+                .filter(|record| record.id >= first_id)
+                .collect();
+            rewrite_lines(&vectors_path, &kept)?;
+        }
+        Ok(())
+    }
+
+    /// 将条目加入索引（向量留空，由 [`Self::embed_pending`] 补算）
+    fn index(&mut self, entry: LongTermEntry) {
+        let doc = self.entries.len();
+        let tokens = tokenize(&entry.entry.content);
+
+        let mut frequencies: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *frequencies.entry(token.clone()).or_default() += 1;
+        }
+        for (term, tf) in frequencies {
+            self.postings.entry(term).or_default().push((doc, tf));
+        }
+
+        self.doc_lens.push(tokens.len() as u32);
+        self.total_len += tokens.len() as u64;
+        self.vectors.push(None);
+        self.entries.push(entry);
+    }
+
+    /// 检索
+    ///
+    /// 有查询文本时按综合得分排序（同分时较新的在前）；
+    /// 没有查询文本时按时间倒序列出符合过滤条件的条目。
+    pub fn search(&self, query: &MemoryQuery) -> Vec<MemoryHit<'_>> {
+        let terms: Vec<String> = {
+            let mut seen = HashSet::new();
+            tokenize(&query.text).into_iter().filter(|t| seen.insert(t.clone())).collect()
+        };
+
+        if query.text.trim().is_empty() {
+            return self
+                .entries
+                .iter()
+                .rev()
+                .filter(|entry| query.matches(entry))
+                .take(query.limit)
+                .map(|entry| MemoryHit {
+                    entry,
+                    score: 0.0,
+                    bm25: 0.0,
+                    semantic: 0.0,
+                    coverage: 0.0,
+                })
+                .collect();
+        }
+        if terms.is_empty() && self.embedder.is_none() {
+            return Vec::new();
+        }
+
+        // BM25
+        let mut bm25: HashMap<usize, f64> = HashMap::new();
+        let mut matched_terms: HashMap<usize, usize> = HashMap::new();
+        let n = self.entries.len() as f64;
+        let avg_len = if self.entries.is_empty() {
+            1.0
+        } else {
+            (self.total_len as f64 / n).max(1.0)
+        };
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let df = postings.len() as f64;
+            let idf = (1.0 + (n - df + 0.5) / (df + 0.5)).ln();
+            for &(doc, tf) in postings {
+                let tf = tf as f64;
+                let len_norm = 1.0 - BM25_B + BM25_B * self.doc_lens[doc] as f64 / avg_len;
+                *bm25.entry(doc).or_default() += idf * tf * (BM25_K1 + 1.0) / (tf + BM25_K1 * len_norm);
+                *matched_terms.entry(doc).or_default() += 1;
+            }
+        }
+
+        // 语义相似度
+        let mut semantic: HashMap<usize, f64> = HashMap::new();
+        if let Some(query_vector) = self.embed(&query.text) {
+            for (doc, vector) in self.vectors.iter().enumerate() {
+                if let Some(vector) = vector {
+                    let similarity = cosine_similarity(&query_vector, vector);
+                    if similarity >= SEMANTIC_THRESHOLD {
+                        semantic.insert(doc, similarity);
+                    }
+                }
+            }
+        }
+
+        let max_bm25 = bm25.values().copied().fold(0.0, f64::max);
+        let candidates: HashSet<usize> = bm25.keys().chain(semantic.keys()).copied().collect();
+
+        let mut hits: Vec<MemoryHit<'_>> = candidates
+            .into_iter()
+            .filter(|&doc| query.matches(&self.entries[doc]))
+            .map(|doc| {
+                let raw = bm25.get(&doc).copied().unwrap_or(0.0);
+                let similarity = semantic.get(&doc).copied().unwrap_or(0.0);
+                let normalized = if max_bm25 > 0.0 { raw / max_bm25 } else { 0.0 };
+                MemoryHit {
+                    entry: &self.entries[doc],
+                    score: normalized + similarity * self.semantic_weight,
+                    bm25: raw,
+                    semantic: similarity,
+                    coverage: matched_terms.get(&doc).copied().unwrap_or(0) as f64 / terms.len().max(1) as f64,
+                }
+            })
+            .collect();
+
+        hits.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.entry.id.cmp(&a.entry.id))
+        });
+        hits.truncate(query.limit);
+        hits
+    }
+
+    /// 为 LLM 提示词召回相关的历史条目
+    ///
+    /// 只召回与查询足够相关的条目（命中至少一半查询词，或语义相似），
+    /// 跳过与查询内容相同的条目（通常就是刚记录的当前输入）。
+    /// 没有相关条目时返回 None。
+    pub fn recall_context(&self, text: &str, limit: usize) -> Option<String> {
+        let query = MemoryQuery::new(text)
+            .with_types(vec![EntryType::User, EntryType::Assistant, EntryType::Shell])
+            .with_limit(limit + 1);
+
+        let lines: Vec<String> = self
+            .search(&query)
+            .into_iter()
+            .filter(|hit| hit.entry.entry.content.trim() != text.trim())
+            .filter(|hit| hit.coverage >= RECALL_MIN_COVERAGE || hit.semantic >= SEMANTIC_THRESHOLD)
+            .take(limit)
+            .map(|hit| {
+                let content: String = hit.entry.entry.content.chars().take(RECALL_PREVIEW_CHARS).collect();
+                let ellipsis = if hit.entry.entry.content.chars().count() > RECALL_PREVIEW_CHARS {
+                    "..."
+                } else {
+                    ""
+                };
+                format!(
+                    "- [{}] {}: {}{}",
+                    hit.entry.entry.timestamp.format("%Y-%m-%d %H:%M"),
+                    hit.entry.entry.entry_type,
+                    content.replace('\n', " "),
+                    ellipsis
+                )
+            })
+            .collect();
+
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines.join("\n"))
+        }
+    }
+}
+
+/// 向量文件中的一条记录
+#[derive(Serialize, Deserialize)]
+struct VectorRecord {
+    id: u64,
+    model: String,
+    vector: Vec<f32>,
+}
+
+/// 从向量文件加载指定模型的向量（文件不存在或损坏的行被跳过）
+fn load_vectors(path: &Path, model: &str) -> HashMap<u64, Vec<f32>> {
+    read_lines::<VectorRecord>(path)
+        .into_iter()
+        .filter(|record| record.model == model)
+        .map(|record| (record.id, record.vector))
+        .collect()
+}
+
+/// 读取 JSONL 文件中的全部记录（文件不存在或损坏的行被跳过）
+fn read_lines<T: for<'de> Deserialize<'de>>(path: &Path) -> Vec<T> {
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect()
+}
+
+/// 用给定记录重写 JSONL 文件（先写临时文件再替换）
+fn rewrite_lines<T: Serialize>(path: &Path, records: &[T]) -> Result<(), String> {
+    let mut content = String::new();
+    for record in records {
+        content.push_str(&serde_json::to_string(record).map_err(|e| format!("序列化失败: {}", e))?);
+        content.push('\n');
+    }
+    let tmp = path.with_extension("jsonl.tmp");
+    std::fs::write(&tmp, content).map_err(|e| format!("写入文件失败: {}", e))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("替换文件失败: {}", e))
+}
+
+/// 追加一条记录到 JSONL 文件
+fn append_line<T: Serialize>(path: &Path, record: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        if !parent.as_os_str().is_empty() && !parent.exists() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("打开文件失败: {}", e))?;
+    let json = serde_json::to_string(record).map_err(|e| format!("序列化失败: {}", e))?;
+    writeln!(file, "{}", json).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::intent::embedding::HashingEmbedder;
+    use std::sync::atomic::Ordering;
+
+    fn sample() -> LongTermMemory {
+        let mut memory = LongTermMemory::new("s1");
+        memory.add("docker compose up fails with port 5432 already in use", EntryType::User).unwrap();
+        memory.add("Stop the local postgres service before starting docker compose", EntryType::Assistant).unwrap();
+        memory.add("cargo build --release", EntryType::Shell).unwrap();
+        memory.set_session("s2");
+        memory.add("如何查看磁盘使用情况", EntryType::User).unwrap();
+        memory.add("df -h", EntryType::Shell).unwrap();
+        memory
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Docker-Compose up: port_5432 a"), vec!["docker", "compose", "up", "port_5432"]);
+        assert_eq!(tokenize("查看磁盘 df"), vec!["查看", "看磁", "磁盘", "df"]);
+        assert_eq!(tokenize("端 口 8080"), vec!["端", "口", "8080"]);
+    }
+
+    #[test]
+    fn test_bm25_ranking_and_filters() {
+        let memory = sample();
+
+        let hits = memory.search(&MemoryQuery::new("docker compose port"));
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].entry.id, 1);
+        assert!(hits[0].bm25 > hits[1].bm25);
+        assert_eq!(hits[0].coverage, 1.0);
+
+        let hits = memory.search(&MemoryQuery::new("docker").with_types(vec![EntryType::Assistant]));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.entry.entry_type, EntryType::Assistant);
+
+        let hits = memory.search(&MemoryQuery::new("磁盘"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.session, "s2");
+
+        // 无查询文本：按时间倒序列出
+        let hits = memory.search(&MemoryQuery::new("").with_session("s1"));
+        assert_eq!(hits.iter().map(|h| h.entry.id).collect::<Vec<_>>(), vec![3, 2, 1]);
+
+        let hits = memory.search(&MemoryQuery::new("docker").with_since(Utc::now() + Duration::hours(1)));
+        assert!(hits.is_empty());
+        assert_eq!(memory.sessions(), vec!["s2", "s1"]);
+    }
+
+    #[test]
+    fn test_semantic_recall() {
+        let mut plain = sample();
+        plain.add("restart the nginx services", EntryType::User).unwrap();
+        // 字面只命中 "nginx"，覆盖率不足，不召回
+        assert!(plain.recall_context("restarting nginx service", 3).is_none());
+
+        let mut memory = sample().with_embedder(Arc::new(HashingEmbedder::default()));
+        assert!(memory.has_embedder());
+        memory.add("restart the nginx services", EntryType::User).unwrap();
+
+        let hits = memory.search(&MemoryQuery::new("restarting nginx service"));
+        assert_eq!(hits[0].entry.entry.content, "restart the nginx services");
+        assert!(hits[0].semantic >= SEMANTIC_THRESHOLD);
+
+        let context = memory.recall_context("restarting nginx service", 3).unwrap();
+        assert!(context.contains("restart the nginx services"));
+    }
+
+    #[test]
+    fn test_query_parse() {
+        let query = MemoryQuery::parse("postgres type:shell,user since:7d limit:3 localhost:5432").unwrap();
+        assert_eq!(query.text, "postgres localhost:5432");
+        assert_eq!(query.types, vec![EntryType::Shell, EntryType::User]);
+        assert_eq!(query.limit, 3);
+        assert!(query.since.unwrap() < Utc::now() - Duration::days(6));
+
+        let query = MemoryQuery::parse("until:2026-10-01").unwrap();
+        assert_eq!(query.until.unwrap().format("%H:%M:%S").to_string(), "23:59:59");
+
+        assert!(MemoryQuery::parse("type:robot").is_err());
+        assert!(MemoryQuery::parse("since:soon").is_err());
+    }
+
+    #[test]
+    fn test_recall_context() {
+        let memory = sample();
+
+        let context = memory.recall_context("why does docker compose fail on port 5432", 3).unwrap();
+        assert!(context.contains("port 5432 already in use"));
+        assert!(!context.contains("cargo build"));
+
+        // 与当前输入相同的条目不召回
+        assert!(memory.recall_context("cargo build --release", 3).is_none());
+        assert!(memory.recall_context("unrelated question about kubernetes", 3).is_none());
+    }
+
+    #[test]
+    fn test_persistence_across_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("memory").join("long_term.jsonl");
+
+        let mut first = LongTermMemory::open(&path, "first").unwrap();
+        first.add("git rebase -i HEAD~3", EntryType::Shell).unwrap();
+        first.add("squash the last three commits", EntryType::User).unwrap();
+
+        let mut second = LongTermMemory::open(&path, "second").unwrap();
+        second.add("\x1b[32m✓\x1b[0m done", EntryType::Assistant).unwrap();
+        assert_eq!(second.search(&MemoryQuery::new("done"))[0].entry.entry.content, "✓ done");
+        assert_eq!(second.len(), 3);
+        assert_eq!(second.add("git log", EntryType::Shell).unwrap(), 4);
+
+        let hits = second.search(&MemoryQuery::new("rebase"));
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].entry.session, "first");
+    }
+
+    #[test]
+    fn test_retention_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long_term.jsonl");
+
+        let mut memory = LongTermMemory::open(&path, "s").unwrap().with_max_entries(10);
+        for i in 0..11 {
+            memory.add(format!("command {}", i), EntryType::Shell).unwrap();
+        }
+        // 不超过上限一成时不重写文件
+        assert_eq!(memory.len(), 11);
+
+        memory.add("command 11", EntryType::Shell).unwrap();
+        assert_eq!(memory.len(), 10);
+        assert!(memory.search(&MemoryQuery::new("command 0")).iter().all(|hit| hit.entry.id > 2));
+        assert_eq!(memory.search(&MemoryQuery::new("11"))[0].entry.id, 12);
+
+        let reopened = LongTermMemory::open(&path, "s").unwrap();
+        assert_eq!(reopened.len(), 10);
+        assert_eq!(reopened.with_max_entries(4).len(), 4);
+        assert_eq!(LongTermMemory::open(&path, "s").unwrap().len(), 4);
+    }
+
+    /// 记录调用次数的嵌入器，`fail` 为真时总是失败
+    #[derive(Debug, Default)]
+    struct CountingEmbedder {
+        calls: std::sync::atomic::AtomicUsize,
+        fail: bool,
+    }
+
+    impl Embedder for CountingEmbedder {
+        fn model_id(&self) -> String {
+            "counting".to_string()
+        }
+
+        fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            if self.fail {
+                return Err("connection refused".to_string());
+            }
+            HashingEmbedder::default().embed(text)
+        }
+    }
+
+    #[test]
+    fn test_vectors_persisted_and_failures_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("long_term.jsonl");
+
+        let embedder = Arc::new(CountingEmbedder::default());
+        let mut first = LongTermMemory::open(&path, "s").unwrap().with_embedder(embedder.clone());
+        first.add("restart the nginx services", EntryType::User).unwrap();
+        first.add("df -h", EntryType::Shell).unwrap();
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 2);
+        assert!(dir.path().join("long_term.vectors.jsonl").exists());
+
+        // 重启后复用已保存的向量，不再调用嵌入器
+        let embedder = Arc::new(CountingEmbedder::default());
+        let second = LongTermMemory::open(&path, "s").unwrap().with_embedder(embedder.clone());
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 0);
+        assert_eq!(second.pending_vectors(), 0);
+        let hits = second.search(&MemoryQuery::new("restarting nginx service"));
+        assert!(hits[0].semantic >= SEMANTIC_THRESHOLD);
+
+        // 嵌入器失败后冷却期内不再调用
+        let embedder = Arc::new(CountingEmbedder { fail: true, ..Default::default() });
+        let mut failing = sample().with_embedder(embedder.clone());
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 0);
+        failing.add("git status", EntryType::Shell).unwrap();
+        failing.search(&MemoryQuery::new("docker"));
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 1);
+        assert!(failing.embedder_failed());
+        assert_eq!(failing.search(&MemoryQuery::new("docker compose")).len(), 2);
+    }
+
+    #[test]
+    fn test_missing_vectors_embedded_in_batches() {
+        let mut plain = LongTermMemory::new("s");
+        for i in 0..EMBED_BATCH_SIZE + 4 {
+            plain.add(format!("entry {}", i), EntryType::Shell).unwrap();
+        }
+
+        // 挂载嵌入器时不计算向量
+        let embedder = Arc::new(CountingEmbedder::default());
+        let mut memory = plain.with_embedder(embedder.clone());
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), 0);
+        assert_eq!(memory.pending_vectors(), EMBED_BATCH_SIZE + 4);
+
+        // 每次添加条目补算一批（含新条目本身）
+        memory.add("new entry", EntryType::Shell).unwrap();
+        assert_eq!(embedder.calls.load(Ordering::Relaxed), EMBED_BATCH_SIZE);
+        assert_eq!(memory.pending_vectors(), 5);
+        assert!(memory.vectors.last().unwrap().is_some());
+
+        assert_eq!(memory.embed_pending(usize::MAX), 5);
+        assert_eq!(memory.pending_vectors(), 0);
+    }
+}
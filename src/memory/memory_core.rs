@@ -32,6 +32,20 @@ pub enum EntryType {
     Tool,
 }
 
+impl EntryType {
+    /// 从名称解析（不区分大小写，支持缩写 u/a/s/sh/t）
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "user" | "u" => Some(EntryType::User),
+            "assistant" | "a" => Some(EntryType::Assistant),
+            "system" | "s" => Some(EntryType::System),
+            "shell" | "sh" => Some(EntryType::Shell),
+            "tool" | "t" => Some(EntryType::Tool),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for EntryType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -187,6 +201,12 @@ impl Memory {
         self.entries.clear();
     }
 
+    /// 用给定条目替换所有记忆（超出容量时保留最新的，用于恢复会话）
+    pub fn restore(&mut self, entries: Vec<MemoryEntry>) {
+        let skip = entries.len().saturating_sub(self.capacity);
+        self.entries = entries.into_iter().skip(skip).collect();
+    }
+
     /// 获取记忆数量
     pub fn len(&self) -> usize {
         self.entries.len()
@@ -5,14 +5,22 @@
 //! Phase 9.1: 上下文理解增强
 
 pub mod context_tracker;
+pub mod long_term;
 pub mod memory_core;
+pub mod output_store;
 
 // 导出 memory_core 的所有公共类型
 pub use memory_core::{EntryType, Memory};
 #[allow(unused_imports)]
 pub use memory_core::MemoryEntry;
 
+// 导出长期记忆的公共类型
+pub use long_term::{LongTermEntry, LongTermMemory, MemoryHit, MemoryQuery};
+
+// 导出命令输出存储的公共类型
+pub use output_store::{CapturedOutput, OutputKind, OutputStore};
+
 // 导出 context_tracker 的公共类型
-pub use context_tracker::{ContextTracker, WorkingContextUpdate};
+pub use context_tracker::{ContextTracker, ResolvedInput, WorkingContextUpdate};
 #[allow(unused_imports)]
 pub use context_tracker::{ContextStats, Entity, EntityType, ReferenceRecord, WorkingContext};
@@ -0,0 +1,462 @@
+//! 命令输出存储
+//!
+//! 短期记忆只保留输出的前 200 个字符。输出存储保存 Shell 命令与工具调用的完整输出
+//! （有上限），并分配简短的引用 `$1`、`$2`……，`$last` 指最近一次输出：
+//!
+//! - **Shell 命令**：展开为保存输出的临时文件路径（`grep error $3`）；引号内、
+//!   转义的 `$N` 以及使用位置参数的命令（`sh -c`、`set --`、函数定义、循环）保持原样
+//! - **自然语言 / 工具参数**：展开为输出内容（"总结 $3"）
+//!
+//! 只保留最近的若干条输出，超出上限的内容被截断。输出文件写入仅当前用户可访问的
+//! 临时目录，存储释放时删除。
+
+use super::long_term::ANSI_ESCAPE;
+use crate::shell_util::shell_quote;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tempfile::TempDir;
+
+/// 默认保留的输出条数
+pub const DEFAULT_MAX_OUTPUTS: usize = 50;
+
+/// 默认单条输出的最大字节数
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// 输出引用：`$3`、`$last`
+static HANDLE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$(\d+|last)\b").unwrap());
+
+/// 使用 Shell 位置参数的命令：其中的 `$1` 是位置参数而非输出引用
+///
+/// `sh -c`、`set --`、函数定义（`f() {`、`function f`）与循环（`for`、`while` 等）。
+static POSITIONAL_CONTEXT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\bset\s+--|\b(?:ba|z|da|k)?sh\s+-c\b|\w\s*\(\s*\)\s*\{|\bfunction\s+\w|(?:^|[;&|(]|\n)\s*(?:for|while|until|select)\s",
+    )
+    .unwrap()
+});
+
+/// 输出来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    /// Shell 命令（含意图生成的命令）
+    Shell,
+    /// 工具调用
+    Tool,
+}
+
+impl OutputKind {
+    /// 显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputKind::Shell => "shell",
+            OutputKind::Tool => "tool",
+        }
+    }
+}
+
+/// 一条捕获的输出
+#[derive(Debug, Clone)]
+pub struct CapturedOutput {
+    /// 编号（`$id`）
+    pub id: usize,
+    /// 产生输出的命令或工具调用
+    pub source: String,
+    /// 来源类型
+    pub kind: OutputKind,
+    /// 输出内容（已去掉颜色控制序列）
+    pub content: String,
+    /// 是否因超出上限被截断
+    pub truncated: bool,
+    /// 命令是否成功
+    pub success: bool,
+    /// 捕获时间
+    pub captured_at: DateTime<Utc>,
+}
+
+impl CapturedOutput {
+    /// 引用名（`$3`）
+    pub fn handle(&self) -> String {
+        format!("${}", self.id)
+    }
+
+    /// 输出行数
+    pub fn line_count(&self) -> usize {
+        self.content.lines().count()
+    }
+
+    /// 按正则筛选输出行，返回（行号, 行内容），行号从 1 开始
+    pub fn grep(&self, pattern: &str) -> Result<Vec<(usize, &str)>, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("无效的正则表达式: {}", e))?;
+        Ok(self
+            .content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line))
+            .map(|(i, line)| (i + 1, line))
+            .collect())
+    }
+}
+
+/// 命令输出存储
+#[derive(Debug)]
+pub struct OutputStore {
+    /// 最近的输出（旧的在前）
+    outputs: VecDeque<CapturedOutput>,
+    /// 下一个编号
+    next_id: usize,
+    /// 最多保留条数
+    max_outputs: usize,
+    /// 单条最大字节数
+    max_bytes: usize,
+    /// 指定的输出文件目录（未指定时使用 `temp_dir`）
+    spool_dir: Option<PathBuf>,
+    /// 首次展开 Shell 引用时创建的私有临时目录（0700，释放时删除）
+    temp_dir: OnceLock<TempDir>,
+}
+
+impl OutputStore {
+    /// 创建输出存储（默认保留 50 条，每条最多 64 KiB）
+    pub fn new() -> Self {
+        Self {
+            outputs: VecDeque::new(),
+            next_id: 1,
+            max_outputs: DEFAULT_MAX_OUTPUTS,
+            max_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            spool_dir: None,
+            temp_dir: OnceLock::new(),
+        }
+    }
+
+    /// 设置保留条数与单条最大字节数
+    pub fn with_limits(mut self, max_outputs: usize, max_bytes: usize) -> Self {
+        self.max_outputs = max_outputs.max(1);
+        self.max_bytes = max_bytes.max(1);
+        self
+    }
+
+    /// 设置输出文件目录（由调用方负责清理）
+    pub fn with_spool_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.spool_dir = Some(dir.into());
+        self
+    }
+
+    /// 保存的输出条数
+    pub fn len(&self) -> usize {
+        self.outputs.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.outputs.is_empty()
+    }
+
+    /// 捕获一条输出，返回编号；空输出不保存
+    pub fn capture(
+        &mut self,
+        source: impl Into<String>,
+        kind: OutputKind,
+        content: &str,
+        success: bool,
+    ) -> Option<usize> {
+        let content = ANSI_ESCAPE.replace_all(content, "");
+        let content = content.trim_end();
+        if content.trim().is_empty() {
+            return None;
+        }
+
+        let truncated = content.len() > self.max_bytes;
+        let content = if truncated {
+            let mut end = self.max_bytes;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+            &content[..end]
+        } else {
+            content
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.outputs.push_back(CapturedOutput {
+            id,
+            source: source.into(),
+            kind,
+            content: content.to_string(),
+            truncated,
+            success,
+            captured_at: Utc::now(),
+        });
+
+        while self.outputs.len() > self.max_outputs {
+            if let Some(old) = self.outputs.pop_front() {
+                self.remove_spooled(old.id);
+            }
+        }
+        Some(id)
+    }
+
+    /// 按编号获取输出
+    pub fn get(&self, id: usize) -> Option<&CapturedOutput> {
+        self.outputs.iter().find(|o| o.id == id)
+    }
+
+    /// 最近一次输出
+    pub fn last(&self) -> Option<&CapturedOutput> {
+        self.outputs.back()
+    }
+
+    /// 解析引用：`$3`、`3`、`$last`、`last`
+    pub fn resolve(&self, handle: &str) -> Option<&CapturedOutput> {
+        match handle.trim_start_matches('$') {
+            "last" => self.last(),
+            id => id.parse().ok().and_then(|id| self.get(id)),
+        }
+    }
+
+    /// 全部输出（旧的在前）
+    pub fn list(&self) -> impl Iterator<Item = &CapturedOutput> {
+        self.outputs.iter()
+    }
+
+    /// 清空输出
+    pub fn clear(&mut self) {
+        for output in &self.outputs {
+            self.remove_spooled(output.id);
+        }
+        self.outputs.clear();
+    }
+
+    /// 文本中引用到的输出（去重，按出现顺序）
+    pub fn referenced(&self, text: &str) -> Vec<&CapturedOutput> {
+        let mut found: Vec<&CapturedOutput> = Vec::new();
+        for cap in HANDLE.captures_iter(text) {
+            if let Some(output) = self.resolve(&cap[1]) {
+                if !found.iter().any(|o| o.id == output.id) {
+                    found.push(output);
+                }
+            }
+        }
+        found
+    }
+
+    /// 把文本中的引用替换为输出内容（用于工具参数）
+    ///
+    /// 不存在的引用保持原样。
+    pub fn expand_content(&self, text: &str) -> String {
+        HANDLE
+            .replace_all(text, |cap: &regex::Captures| match self.resolve(&cap[1]) {
+                Some(output) => output.content.clone(),
+                None => cap[0].to_string(),
+            })
+            .into_owned()
+    }
+
+    /// 把 Shell 命令中的引用替换为输出文件路径（已按 Shell 规则加引号）
+    ///
+    /// 输出按需写入临时目录。只展开未加引号、未转义的引用；
+    /// 引号内的内容、不存在的引用以及 `sh -c`、`set --` 命令保持原样。
+    pub fn expand_for_shell(&self, command: &str) -> Result<String, String> {
+        if !HANDLE.is_match(command) || POSITIONAL_CONTEXT.is_match(command) {
+            return Ok(command.to_string());
+        }
+
+        let bare = unquoted_positions(command);
+        let mut expanded = String::with_capacity(command.len());
+        let mut last = 0;
+        for cap in HANDLE.captures_iter(command) {
+            let whole = cap.get(0).unwrap();
+            if !bare[whole.start()] {
+                continue;
+            }
+            if let Some(output) = self.resolve(&cap[1]) {
+                let path = self.spool(output)?;
+                expanded.push_str(&command[last..whole.start()]);
+                expanded.push_str(&shell_quote(&path.display().to_string()));
+                last = whole.end();
+            }
+        }
+        expanded.push_str(&command[last..]);
+        Ok(expanded)
+    }
+
+    /// 保存输出到文件
+    pub fn save(&self, handle: &str, path: &Path) -> Result<(), String> {
+        let output = self
+            .resolve(handle)
+            .ok_or_else(|| format!("输出不存在: {}", handle))?;
+        std::fs::write(path, format!("{}\n", output.content))
+            .map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+    }
+
+    /// 输出文件目录（不存在时创建私有临时目录）
+    fn spool_dir(&self) -> Result<&Path, String> {
+        if let Some(dir) = &self.spool_dir {
+            std::fs::create_dir_all(dir).map_err(|e| format!("创建目录 {} 失败: {}", dir.display(), e))?;
+            return Ok(dir);
+        }
+        if self.temp_dir.get().is_none() {
+            let mut builder = tempfile::Builder::new();
+            builder.prefix("realconsole-outputs-");
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                builder.permissions(std::fs::Permissions::from_mode(0o700));
+            }
+            let dir = builder.tempdir().map_err(|e| format!("创建临时目录失败: {}", e))?;
+            // 并发创建时保留先写入的目录，多余的目录随 TempDir 释放被删除
+            let _ = self.temp_dir.set(dir);
+        }
+        Ok(self.temp_dir.get().expect("临时目录已创建").path())
+    }
+
+    /// 删除已写入的输出文件
+    fn remove_spooled(&self, id: usize) {
+        let dir = self.spool_dir.as_deref().or_else(|| self.temp_dir.get().map(TempDir::path));
+        if let Some(dir) = dir {
+            let _ = std::fs::remove_file(dir.join(format!("{}.txt", id)));
+        }
+    }
+
+    /// 把输出写入临时文件
+    fn spool(&self, output: &CapturedOutput) -> Result<PathBuf, String> {
+        let path = self.spool_dir()?.join(format!("{}.txt", output.id));
+        std::fs::write(&path, format!("{}\n", output.content))
+            .map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?;
+        Ok(path)
+    }
+}
+
+/// 标记命令中每个字节是否位于引号外且未被转义
+fn unquoted_positions(command: &str) -> Vec<bool> {
+    let mut bare = vec![false; command.len()];
+    let (mut single, mut double, mut escaped) = (false, false, false);
+    for (i, c) in command.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !single => escaped = true,
+            '\'' if !double => single = !single,
+            '"' if !single => double = !double,
+            _ => bare[i] = !single && !double,
+        }
+    }
+    bare
+}
+
+impl Default for OutputStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_in(dir: &Path) -> OutputStore {
+        OutputStore::new().with_spool_dir(dir)
+    }
+
+    #[test]
+    fn test_capture_and_resolve() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store_in(dir.path());
+
+        assert_eq!(store.capture("ls", OutputKind::Shell, "a.txt\nb.txt\n", true), Some(1));
+        assert_eq!(store.capture("true", OutputKind::Shell, "  \n", true), None);
+        assert_eq!(store.capture("pwd", OutputKind::Shell, "\x1b[32m/tmp\x1b[0m", true), Some(2));
+
+        assert_eq!(store.resolve("$1").unwrap().source, "ls");
+        assert_eq!(store.resolve("$last").unwrap().content, "/tmp");
+        assert_eq!(store.resolve("2").unwrap().line_count(), 1);
+        assert!(store.resolve("$9").is_none());
+    }
+
+    #[test]
+    fn test_private_spool_dir_removed_on_drop() {
+        let mut store = OutputStore::new();
+        store.capture("env", OutputKind::Shell, "TOKEN=secret", true);
+        let expanded = store.expand_for_shell("cat $1").unwrap();
+        let path = PathBuf::from(expanded.trim_start_matches("cat ").trim_matches('\''));
+        let dir = path.parent().unwrap().to_path_buf();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "TOKEN=secret\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        drop(store);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store_in(dir.path()).with_limits(2, 4);
+
+        store.capture("a", OutputKind::Shell, "一二三", true);
+        store.capture("b", OutputKind::Shell, "ok", true);
+        store.capture("c", OutputKind::Tool, "ok", true);
+
+        assert_eq!(store.len(), 2);
+        assert!(store.get(1).is_none());
+        assert!(store.get(2).is_some());
+
+        let mut store = store_in(dir.path()).with_limits(2, 4);
+        store.capture("a", OutputKind::Shell, "一二三", true);
+        let output = store.get(1).unwrap();
+        assert!(output.truncated);
+        assert_eq!(output.content, "一");
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = store_in(dir.path());
+        store.capture("cat log", OutputKind::Shell, "error: disk full\nok", true);
+
+        assert_eq!(store.expand_content("summarise $1 and $7"), "summarise error: disk full\nok and $7");
+        assert_eq!(store.referenced("compare $1 with $last").len(), 1);
+
+        let expanded = store.expand_for_shell("grep error $1 | echo '$1' \"$1\" \\$1").unwrap();
+        let path = dir.path().join("1.txt");
+        let quoted = shell_quote(&path.display().to_string());
+        assert_eq!(expanded, format!("grep error {} | echo '$1' \"$1\" \\$1", quoted));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "error: disk full\nok\n");
+
+        // 位置参数保持原样
+        for command in [
+            "sh -c 'echo $1' x $1",
+            "set -- a b; echo $1",
+            "bash -c \"wc -l $1\"",
+            "greet() { echo hi $1; }; greet bob",
+            "function greet { echo $1; }",
+            "for f in *.log; do wc -l $1; done",
+            "ls | while read line; do echo $1; done",
+        ] {
+            assert_eq!(store.expand_for_shell(command).unwrap(), command);
+        }
+        assert_ne!(store.expand_for_shell("grep format $1").unwrap(), "grep format $1");
+
+        // 路径中有空格时加引号
+        let spaced = tempfile::tempdir().unwrap();
+        let spaced_dir = spaced.path().join("my outputs");
+        let mut spaced_store = store_in(&spaced_dir);
+        spaced_store.capture("ls", OutputKind::Shell, "a.txt", true);
+        let expanded = spaced_store.expand_for_shell("wc -l $last").unwrap();
+        assert_eq!(expanded, format!("wc -l '{}'", spaced_dir.join("1.txt").display()));
+
+        let matches = store.get(1).unwrap().grep("^error").unwrap();
+        assert_eq!(matches, vec![(1, "error: disk full")]);
+        assert!(store.get(1).unwrap().grep("(").is_err());
+    }
+}
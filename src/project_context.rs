@@ -45,6 +45,20 @@ pub enum ProjectType {
     Unknown,
 }
 
+impl ProjectType {
+    /// 项目类型名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectType::Rust { .. } => "Rust",
+            ProjectType::Python { .. } => "Python",
+            ProjectType::Node { .. } => "Node.js",
+            ProjectType::Go { .. } => "Go",
+            ProjectType::Java { .. } => "Java",
+            ProjectType::Unknown => "Unknown",
+        }
+    }
+}
+
 /// Java 构建文件类型
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum JavaBuildFile {
@@ -92,8 +106,8 @@ impl ProjectContext {
         }
     }
 
-    /// 检测项目类型
-    fn detect_project_type(dir: &Path) -> ProjectType {
+    /// 检测项目类型（不读取 Git 信息）
+    pub fn detect_project_type(dir: &Path) -> ProjectType {
         // 按优先级检测
 
         // Rust
@@ -257,14 +271,7 @@ impl ProjectContext {
 
     /// 获取项目类型描述
     pub fn type_description(&self) -> &str {
-        match &self.project_type {
-            ProjectType::Rust { .. } => "Rust",
-            ProjectType::Python { .. } => "Python",
-            ProjectType::Node { .. } => "Node.js",
-            ProjectType::Go { .. } => "Go",
-            ProjectType::Java { .. } => "Java",
-            ProjectType::Unknown => "Unknown",
-        }
+        self.project_type.name()
     }
 
     /// 获取构建命令建议
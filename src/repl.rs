@@ -3,22 +3,80 @@
 //! 使用 rustyline 提供基础的 readline 功能
 //! ✨ Phase 8: 集成命令历史记录和 Ctrl+R 搜索
 //! ✨ Phase 11: 多语言支持
+//!
+//! `/pipe` 命令支持 Tab 补全 Pipeline DSL 的操作名与关键字
 
 use crate::agent::Agent;
+use crate::dsl::pipeline::syntax;
 use crate::history::SortStrategy;
 use crate::i18n;
 use colored::Colorize;
+use rustyline::completion::{Completer, Pair};
 use rustyline::config::Configurer;
 use rustyline::error::ReadlineError;
-use rustyline::{DefaultEditor, Result as RustyResult};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper, Result as RustyResult};
 use std::env;
 
 /// REPL 退出信号
 const QUIT_SIGNAL: &str = "__QUIT__";
 
+/// REPL 行编辑器
+type ReplEditor = Editor<ReplHelper, DefaultHistory>;
+
+/// 行编辑辅助：为 `/pipe` 命令提供 Tab 补全
+struct ReplHelper {
+    /// `/pipe ` 命令前缀（随配置的命令前缀变化）
+    pipe_prefix: String,
+}
+
+impl ReplHelper {
+    fn new(prefix: &str) -> Self {
+        Self {
+            pipe_prefix: format!("{}pipe ", prefix),
+        }
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> RustyResult<(usize, Vec<Pair>)> {
+        let offset = self.pipe_prefix.len();
+        if !line.starts_with(&self.pipe_prefix) || pos < offset {
+            return Ok((pos, Vec::new()));
+        }
+
+        let (start, candidates) = syntax::complete(&line[offset..], pos - offset);
+        let pairs = candidates
+            .into_iter()
+            .map(|c| Pair {
+                display: c.clone(),
+                replacement: c,
+            })
+            .collect();
+
+        Ok((offset + start, pairs))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
 /// 运行 REPL 循环
 pub fn run(agent: &Agent) -> RustyResult<()> {
-    let mut rl = DefaultEditor::new()?;
+    let mut rl = ReplEditor::new()?;
+    rl.set_helper(Some(ReplHelper::new(&agent.config.prefix)));
 
     // ✨ Phase 8: 配置历史记录行为（使用 Configurer trait）
     rl.set_max_history_size(1000)?;  // 与 HistoryManager 的容量保持一致
@@ -97,7 +155,7 @@ fn print_welcome() {
 /// ✨ Phase 8: 从 HistoryManager 加载历史到 rustyline Editor
 ///
 /// 这样用户可以使用 Ctrl+R 反向搜索历史命令
-fn load_history_to_editor(rl: &mut DefaultEditor, agent: &Agent) {
+fn load_history_to_editor(rl: &mut ReplEditor, agent: &Agent) {
     // 使用 tokio runtime 访问异步的 HistoryManager
     tokio::task::block_in_place(|| {
         tokio::runtime::Handle::current().block_on(async {
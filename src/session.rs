@@ -0,0 +1,568 @@
+//! 命名会话
+//!
+//! 记忆、工作上下文、未完成的多轮对话、工作目录与 LLM 消息历史都只存在于进程内，
+//! 重启即丢失。命名会话把这些状态保存到 `~/.realconsole/sessions/<名称>.json`，
+//! 可随时切换、恢复，并导出为 Markdown 或 JSON 记录（便于事故复盘时分享）。
+//!
+//! 当前会话有名称时，每次输入处理完后自动保存，对话式 LLM 调用也只在命名会话中
+//! 附带消息历史。
+
+use crate::conversation::context::ConversationContext;
+use crate::conversation::{get_current_conversation, set_current_conversation, ConversationManager};
+use crate::llm::{Message, MessageRole};
+use crate::llm_manager::LlmManager;
+use crate::memory::{ContextTracker, Entity, EntryType, LongTermMemory, Memory, MemoryEntry, MemoryQuery, WorkingContext};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+/// 会话名称最大长度
+const MAX_NAME_LEN: usize = 64;
+
+/// 会话快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// 会话名称
+    pub name: String,
+    /// 创建时间
+    pub created_at: DateTime<Utc>,
+    /// 最后保存时间
+    pub updated_at: DateTime<Utc>,
+    /// 工作目录
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+    /// 短期记忆
+    #[serde(default)]
+    pub memory: Vec<MemoryEntry>,
+    /// 工作上下文
+    #[serde(default)]
+    pub working_context: WorkingContext,
+    /// 最近提到的实体（按最近使用顺序）
+    #[serde(default)]
+    pub entities: Vec<Entity>,
+    /// 未完成的多轮对话
+    #[serde(default)]
+    pub conversations: Vec<ConversationContext>,
+    /// 当前对话 ID
+    #[serde(default)]
+    pub current_conversation: Option<String>,
+    /// LLM 消息历史
+    #[serde(default)]
+    pub llm_history: Vec<Message>,
+}
+
+impl SessionSnapshot {
+    /// 创建空会话
+    pub fn new(name: impl Into<String>) -> Self {
+        let now = Utc::now();
+        Self {
+            name: name.into(),
+            created_at: now,
+            updated_at: now,
+            cwd: None,
+            memory: Vec::new(),
+            working_context: WorkingContext::default(),
+            entities: Vec::new(),
+            conversations: Vec::new(),
+            current_conversation: None,
+            llm_history: Vec::new(),
+        }
+    }
+
+    /// 导出为 JSON 记录
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化失败: {}", e))
+    }
+
+    /// 导出为 Markdown 记录
+    pub fn to_markdown(&self) -> String {
+        let mut md = format!("# 会话: {}\n\n", self.name);
+        md.push_str(&format!("- 创建时间: {}\n", self.created_at.format("%Y-%m-%d %H:%M:%S UTC")));
+        md.push_str(&format!("- 最后更新: {}\n", self.updated_at.format("%Y-%m-%d %H:%M:%S UTC")));
+        if let Some(cwd) = &self.cwd {
+            md.push_str(&format!("- 工作目录: `{}`\n", cwd.display()));
+        }
+        if let Some(task) = &self.working_context.current_task {
+            md.push_str(&format!("- 当前任务: {}\n", task));
+        }
+        if let Some(command) = &self.working_context.last_command {
+            md.push_str(&format!("- 最近命令: `{}`\n", command));
+        }
+        if !self.conversations.is_empty() {
+            md.push_str(&format!("- 未完成的对话: {}\n", self.conversations.len()));
+        }
+
+        md.push_str("\n## 记录\n");
+        if self.memory.is_empty() {
+            md.push_str("\n（无）\n");
+        }
+        for entry in &self.memory {
+            let time = entry.timestamp.format("%Y-%m-%d %H:%M:%S");
+            match entry.entry_type {
+                EntryType::Shell => {
+                    md.push_str(&format!("\n### {} $\n\n```sh\n{}\n```\n", time, entry.content));
+                }
+                EntryType::User => {
+                    md.push_str(&format!("\n### {} USER\n\n{}\n", time, entry.content));
+                }
+                _ => {
+                    md.push_str(&format!("\n### {} {}\n\n```text\n{}\n```\n", time, entry.entry_type, entry.content));
+                }
+            }
+        }
+
+        if !self.llm_history.is_empty() {
+            md.push_str("\n## LLM 对话\n");
+            for message in &self.llm_history {
+                let role = match message.role {
+                    MessageRole::System => "System",
+                    MessageRole::User => "User",
+                    MessageRole::Assistant => "Assistant",
+                    MessageRole::Tool => "Tool",
+                };
+                md.push_str(&format!("\n**{}**: {}\n", role, message.content.as_deref().unwrap_or("")));
+            }
+        }
+        md
+    }
+}
+
+/// 会话摘要（列表用）
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    /// 名称
+    pub name: String,
+    /// 最后保存时间
+    pub updated_at: DateTime<Utc>,
+    /// 记忆条数
+    pub entries: usize,
+    /// 工作目录
+    pub cwd: Option<PathBuf>,
+}
+
+/// 会话存储（每个会话一个 JSON 文件）
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+impl SessionStore {
+    /// 创建会话存储
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 默认存储目录：`~/.realconsole/sessions`
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("sessions")
+    }
+
+    /// 存储目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// 会话是否存在
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).is_file()
+    }
+
+    /// 保存会话
+    pub fn save(&self, snapshot: &SessionSnapshot) -> Result<PathBuf, String> {
+        validate_name(&snapshot.name)?;
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("创建目录失败: {}", e))?;
+
+        let path = self.path(&snapshot.name);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, snapshot.to_json()?).map_err(|e| format!("写入失败: {}", e))?;
+        std::fs::rename(&tmp, &path).map_err(|e| format!("写入失败: {}", e))?;
+        Ok(path)
+    }
+
+    /// 加载会话
+    pub fn load(&self, name: &str) -> Result<SessionSnapshot, String> {
+        validate_name(name)?;
+        let content = std::fs::read_to_string(self.path(name)).map_err(|_| format!("会话不存在: {}", name))?;
+        serde_json::from_str(&content).map_err(|e| format!("会话文件损坏 ({}): {}", name, e))
+    }
+
+    /// 删除会话
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        validate_name(name)?;
+        std::fs::remove_file(self.path(name)).map_err(|_| format!("会话不存在: {}", name))
+    }
+
+    /// 列出会话（按最后保存时间倒序，跳过无法解析的文件）
+    pub fn list(&self) -> Vec<SessionSummary> {
+        let Ok(dir) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut sessions: Vec<SessionSummary> = dir
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|entry| {
+                let content = std::fs::read_to_string(entry.path()).ok()?;
+                let snapshot: SessionSnapshot = serde_json::from_str(&content).ok()?;
+                Some(SessionSummary {
+                    name: snapshot.name,
+                    updated_at: snapshot.updated_at,
+                    entries: snapshot.memory.len(),
+                    cwd: snapshot.cwd,
+                })
+            })
+            .collect();
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        sessions
+    }
+}
+
+/// 校验会话名称（用作文件名）
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.chars().count() <= MAX_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("无效的会话名称: {}（只能包含字母、数字、- _ .）", name))
+    }
+}
+
+/// 会话管理器
+///
+/// 持有 Agent 各状态组件的引用，负责在会话与进程状态之间保存和恢复。
+pub struct SessionManager {
+    store: SessionStore,
+    /// 当前会话名称（未命名时为 None）与创建时间
+    current: Mutex<Option<(String, DateTime<Utc>)>>,
+    memory: Arc<RwLock<Memory>>,
+    long_term: Arc<RwLock<LongTermMemory>>,
+    context_tracker: Arc<RwLock<ContextTracker>>,
+    conversations: Arc<RwLock<ConversationManager>>,
+    llm_manager: Arc<RwLock<LlmManager>>,
+}
+
+impl SessionManager {
+    /// 创建会话管理器（初始为未命名会话）
+    pub fn new(
+        store: SessionStore,
+        memory: Arc<RwLock<Memory>>,
+        long_term: Arc<RwLock<LongTermMemory>>,
+        context_tracker: Arc<RwLock<ContextTracker>>,
+        conversations: Arc<RwLock<ConversationManager>>,
+        llm_manager: Arc<RwLock<LlmManager>>,
+    ) -> Self {
+        Self {
+            store,
+            current: Mutex::new(None),
+            memory,
+            long_term,
+            context_tracker,
+            conversations,
+            llm_manager,
+        }
+    }
+
+    /// 会话存储
+    pub fn store(&self) -> &SessionStore {
+        &self.store
+    }
+
+    /// 当前会话名称
+    pub fn current(&self) -> Option<String> {
+        self.current.lock().unwrap().as_ref().map(|(name, _)| name.clone())
+    }
+
+    /// 记录当前进程状态
+    pub async fn capture(&self, name: &str) -> SessionSnapshot {
+        let created_at = match self.current.lock().unwrap().as_ref() {
+            Some((current, created_at)) if current == name => *created_at,
+            _ => Utc::now(),
+        };
+
+        let mut tracker = self.context_tracker.write().await;
+        SessionSnapshot {
+            name: name.to_string(),
+            created_at,
+            updated_at: Utc::now(),
+            cwd: std::env::current_dir().ok(),
+            memory: self.memory.read().await.dump().into_iter().cloned().collect(),
+            working_context: tracker.working_context().clone(),
+            entities: tracker.get_all_entities(),
+            conversations: self.conversations.read().await.export_active(),
+            current_conversation: get_current_conversation(),
+            llm_history: self.llm_manager.read().await.history(),
+        }
+    }
+
+    /// 把快照恢复到进程状态（工作目录不存在时保持当前目录）
+    async fn apply(&self, snapshot: SessionSnapshot) {
+        if let Some(cwd) = snapshot.cwd.as_ref().filter(|cwd| cwd.is_dir()) {
+            let _ = std::env::set_current_dir(cwd);
+        }
+
+        self.memory.write().await.restore(snapshot.memory);
+        self.context_tracker
+            .write()
+            .await
+            .restore(snapshot.working_context, snapshot.entities);
+
+        let current_conversation = snapshot
+            .current_conversation
+            .filter(|id| snapshot.conversations.iter().any(|c| &c.id == id));
+        self.conversations.write().await.import(snapshot.conversations);
+        set_current_conversation(current_conversation);
+
+        {
+            let llm_manager = self.llm_manager.read().await;
+            llm_manager.set_history(snapshot.llm_history);
+            llm_manager.set_attach_history(true);
+        }
+        self.long_term.write().await.set_session(snapshot.name.clone());
+        *self.current.lock().unwrap() = Some((snapshot.name, snapshot.created_at));
+    }
+
+    /// 保存当前会话（未命名时不保存，返回 None）
+    pub async fn save(&self) -> Result<Option<PathBuf>, String> {
+        let Some(name) = self.current() else {
+            return Ok(None);
+        };
+        let snapshot = self.capture(&name).await;
+        self.store.save(&snapshot).map(Some)
+    }
+
+    /// 以新名称保存当前状态，并将其设为当前会话
+    pub async fn save_as(&self, name: &str) -> Result<PathBuf, String> {
+        validate_name(name)?;
+        let snapshot = self.capture(name).await;
+        let path = self.store.save(&snapshot)?;
+        self.llm_manager.read().await.set_attach_history(true);
+        self.long_term.write().await.set_session(name);
+        *self.current.lock().unwrap() = Some((snapshot.name, snapshot.created_at));
+        Ok(path)
+    }
+
+    /// 新建空会话（先保存当前会话）
+    pub async fn create(&self, name: &str) -> Result<(), String> {
+        validate_name(name)?;
+        if self.store.exists(name) {
+            return Err(format!("会话已存在: {}（使用 /session switch {} 切换）", name, name));
+        }
+        self.save().await?;
+
+        let mut snapshot = SessionSnapshot::new(name);
+        snapshot.cwd = std::env::current_dir().ok();
+        self.store.save(&snapshot)?;
+        self.apply(snapshot).await;
+        Ok(())
+    }
+
+    /// 切换到已保存的会话（先保存当前会话）
+    pub async fn switch(&self, name: &str) -> Result<SessionSnapshot, String> {
+        let snapshot = self.store.load(name)?;
+        self.save().await?;
+        self.apply(snapshot.clone()).await;
+        Ok(snapshot)
+    }
+
+    /// 恢复会话：指定名称时切换到该会话，否则恢复最近保存的会话
+    pub async fn resume(&self, name: Option<&str>) -> Result<SessionSnapshot, String> {
+        let name = match name {
+            Some(name) => name.to_string(),
+            None => self
+                .store
+                .list()
+                .into_iter()
+                .map(|s| s.name)
+                .find(|name| Some(name) != self.current().as_ref())
+                .ok_or_else(|| "没有可恢复的会话".to_string())?,
+        };
+        self.switch(&name).await
+    }
+
+    /// 删除已保存的会话（不能删除当前会话）
+    pub fn delete(&self, name: &str) -> Result<(), String> {
+        if self.current().as_deref() == Some(name) {
+            return Err(format!("不能删除当前会话: {}", name));
+        }
+        self.store.delete(name)
+    }
+
+    /// 导出会话记录
+    ///
+    /// 记录优先取自长期记忆中该会话的全部条目（不受短期记忆容量限制）。
+    pub async fn export(&self, name: Option<&str>, format: ExportFormat) -> Result<String, String> {
+        let mut snapshot = match name {
+            Some(name) if self.current().as_deref() != Some(name) => self.store.load(name)?,
+            _ => {
+                let name = self.current().ok_or_else(|| "当前会话未命名（使用 /session save <名称>）".to_string())?;
+                self.capture(&name).await
+            }
+        };
+
+        let long_term = self.long_term.read().await;
+        let query = MemoryQuery::new("").with_session(snapshot.name.clone()).with_limit(usize::MAX);
+        let mut transcript: Vec<MemoryEntry> = long_term.search(&query).into_iter().map(|hit| hit.entry.entry.clone()).collect();
+        if !transcript.is_empty() {
+            transcript.reverse();
+            snapshot.memory = transcript;
+        }
+
+        match format {
+            ExportFormat::Markdown => Ok(snapshot.to_markdown()),
+            ExportFormat::Json => snapshot.to_json(),
+        }
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Markdown
+    Markdown,
+    /// JSON
+    Json,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ExportFormat::Markdown),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("未知的导出格式: {}（支持 md、json）", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::{clear_current_conversation, ParameterSpec, ParameterType};
+    use crate::memory::WorkingContextUpdate;
+
+    fn manager(dir: &Path) -> SessionManager {
+        SessionManager::new(
+            SessionStore::new(dir),
+            Arc::new(RwLock::new(Memory::new(100))),
+            Arc::new(RwLock::new(LongTermMemory::new("startup"))),
+            Arc::new(RwLock::new(ContextTracker::new())),
+            Arc::new(RwLock::new(ConversationManager::new(300))),
+            Arc::new(RwLock::new(LlmManager::new())),
+        )
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("incident-42").is_ok());
+        assert!(validate_name("发布_v1.2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name(".hidden").is_err());
+        assert!(validate_name("a b").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_save_switch_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = manager(dir.path());
+        let cwd = std::env::current_dir().unwrap();
+
+        sessions.create("incident").await.unwrap();
+        assert_eq!(sessions.current().as_deref(), Some("incident"));
+        assert_eq!(sessions.long_term.read().await.session(), "incident");
+
+        sessions.memory.write().await.add("kubectl get pods".to_string(), EntryType::Shell);
+        sessions
+            .context_tracker
+            .write()
+            .await
+            .update_working_context(WorkingContextUpdate::CurrentTask("排查 502".to_string()));
+        let id = {
+            let mut conversations = sessions.conversations.write().await;
+            let id = conversations.start_conversation("deploy").unwrap();
+            conversations
+                .add_parameter_spec(&id, ParameterSpec::new("env", ParameterType::String, "环境"))
+                .unwrap();
+            id
+        };
+        set_current_conversation(Some(id.clone()));
+        sessions
+            .llm_manager
+            .read()
+            .await
+            .set_history(vec![Message::user("为什么 502"), Message::assistant("上游超时")]);
+
+        // 新会话：状态被清空，incident 已保存
+        sessions.create("scratch").await.unwrap();
+        assert!(sessions.memory.read().await.is_empty());
+        assert!(sessions.context_tracker.read().await.working_context().current_task.is_none());
+        assert_eq!(sessions.conversations.read().await.active_count(), 0);
+        assert!(get_current_conversation().is_none());
+        assert!(sessions.llm_manager.read().await.history().is_empty());
+
+        // 切回 incident：全部恢复
+        let snapshot = sessions.switch("incident").await.unwrap();
+        assert_eq!(snapshot.memory.len(), 1);
+        assert_eq!(sessions.memory.read().await.recent(1)[0].content, "kubectl get pods");
+        assert_eq!(
+            sessions.context_tracker.read().await.working_context().current_task.as_deref(),
+            Some("排查 502")
+        );
+        assert_eq!(sessions.conversations.read().await.active_count(), 1);
+        assert_eq!(get_current_conversation(), Some(id));
+        assert_eq!(sessions.llm_manager.read().await.history().len(), 2);
+        assert!(sessions.llm_manager.read().await.attach_history());
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+
+        let names: Vec<String> = sessions.store().list().into_iter().map(|s| s.name).collect();
+        assert_eq!(names.len(), 2);
+        assert!(sessions.delete("incident").is_err());
+        sessions.delete("scratch").unwrap();
+        assert_eq!(sessions.store().list().len(), 1);
+        clear_current_conversation();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_resume_latest_and_export() {
+        let dir = tempfile::tempdir().unwrap();
+        let sessions = manager(dir.path());
+
+        sessions.save_as("review").await.unwrap();
+        sessions.long_term.write().await.add("df -h", EntryType::Shell).unwrap();
+        sessions.long_term.write().await.add("/dev/sda1 91%", EntryType::Assistant).unwrap();
+        sessions.save().await.unwrap();
+
+        let markdown = sessions.export(None, ExportFormat::Markdown).await.unwrap();
+        assert!(markdown.starts_with("# 会话: review"));
+        assert!(markdown.contains("```sh\ndf -h\n```"));
+        assert!(markdown.contains("/dev/sda1 91%"));
+
+        let json = sessions.export(Some("review"), ExportFormat::Json).await.unwrap();
+        let parsed: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.memory.len(), 2);
+
+        // 另一个进程：恢复最近的会话
+        let restarted = manager(dir.path());
+        let snapshot = restarted.resume(None).await.unwrap();
+        assert_eq!(snapshot.name, "review");
+        assert_eq!(restarted.current().as_deref(), Some("review"));
+
+        assert!(sessions.export(Some("missing"), ExportFormat::Json).await.is_err());
+        assert!("pdf".parse::<ExportFormat>().is_err());
+    }
+}
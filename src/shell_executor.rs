@@ -11,14 +11,20 @@
 
 use crate::error::{ErrorCode, FixSuggestion, RealError};
 use crate::error_fixer::{
-    ErrorAnalysis, ErrorAnalyzer, FeedbackLearner, FeedbackRecord, FeedbackType, FixOutcome,
-    FixStrategy,
+    CommandCorrector, Diagnostic, ErrorAnalysis, ErrorAnalyzer, ErrorCategory, FeedbackContext,
+    FeedbackLearner, FeedbackRecord, FeedbackType, FileChange, FixOutcome, FixStrategy, Verification,
+    WorkspaceSnapshot,
 };
+use crate::history::{HistoryManager, SortStrategy};
 use crate::llm::LlmClient;
+use crate::project_context::{ProjectContext, ProjectType};
 use regex::Regex;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
 use tokio::time::timeout;
 
 /// 最大输出大小（字节）
@@ -46,7 +52,7 @@ const DANGEROUS_PATTERNS: &[&str] = &[
 ];
 
 /// 检查命令是否安全
-fn is_safe_command(command: &str) -> Result<(), RealError> {
+pub(crate) fn is_safe_command(command: &str) -> Result<(), RealError> {
     // 检查空命令
     if command.trim().is_empty() {
         return Err(RealError::new(
@@ -200,6 +206,52 @@ pub async fn execute_shell(command: &str) -> Result<String, RealError> {
     Ok(result_text)
 }
 
+/// 一次自动修复尝试的记录
+#[derive(Debug, Clone)]
+pub struct FixAttempt {
+    /// 策略名称
+    pub strategy_name: String,
+    /// 修复命令
+    pub command: String,
+    /// 修复命令是否执行成功
+    pub fix_succeeded: bool,
+    /// 修复命令输出
+    pub fix_output: String,
+    /// 验证方式
+    pub verification: Verification,
+    /// 验证输出（修复命令失败时未验证）
+    pub verification_output: Option<String>,
+    /// 是否通过验证
+    pub verified: bool,
+    /// 回滚的文件变化
+    pub rolled_back: Vec<FileChange>,
+    /// 回滚失败的原因
+    pub rollback_error: Option<String>,
+    /// 快照是否覆盖了全部文件
+    pub snapshot_complete: bool,
+    /// 耗时（修复 + 验证）
+    pub duration: Duration,
+}
+
+impl FixAttempt {
+    /// 一行摘要
+    pub fn summary(&self) -> String {
+        let status = if self.verified {
+            "通过验证".to_string()
+        } else if !self.fix_succeeded {
+            "修复命令失败".to_string()
+        } else {
+            format!("未通过验证（{}）", self.verification)
+        };
+        let rollback = match (&self.rollback_error, self.rolled_back.len()) {
+            (Some(error), _) => format!("，回滚失败: {}", error),
+            (None, 0) => String::new(),
+            (None, n) => format!("，已回滚 {} 个文件", n),
+        };
+        format!("{}: {} → {}{}", self.strategy_name, self.command, status, rollback)
+    }
+}
+
 /// Shell 执行器结果（带错误分析）
 pub struct ExecutionResult {
     /// 命令是否成功
@@ -213,6 +265,12 @@ pub struct ExecutionResult {
 
     /// 建议的修复策略（如果有）
     pub fix_strategies: Vec<FixStrategy>,
+
+    /// 从错误输出解析出的编译器诊断
+    pub diagnostics: Vec<Diagnostic>,
+
+    /// 自动修复尝试记录（按时间顺序）
+    pub fix_attempts: Vec<FixAttempt>,
 }
 
 impl ExecutionResult {
@@ -223,6 +281,8 @@ impl ExecutionResult {
             output,
             error_analysis: None,
             fix_strategies: Vec::new(),
+            diagnostics: Vec::new(),
+            fix_attempts: Vec::new(),
         }
     }
 
@@ -237,8 +297,22 @@ impl ExecutionResult {
             output,
             error_analysis,
             fix_strategies,
+            diagnostics: Vec::new(),
+            fix_attempts: Vec::new(),
         }
     }
+
+    /// 附加编译器诊断
+    pub fn with_diagnostics(mut self, diagnostics: Vec<Diagnostic>) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// 附加自动修复尝试记录
+    pub fn with_fix_attempts(mut self, attempts: Vec<FixAttempt>) -> Self {
+        self.fix_attempts = attempts;
+        self
+    }
 }
 
 /// 带错误自动修复的 Shell 执行器
@@ -261,6 +335,12 @@ pub struct ShellExecutorWithFixer {
 
     /// 反馈学习器（Week 3）
     feedback_learner: Arc<FeedbackLearner>,
+
+    /// 命令历史（可选，用于拼写纠正的候选）
+    history: Option<Arc<RwLock<HistoryManager>>>,
+
+    /// 自动修复前快照的目录（默认当前目录）
+    snapshot_root: Option<PathBuf>,
 }
 
 impl ShellExecutorWithFixer {
@@ -271,6 +351,8 @@ impl ShellExecutorWithFixer {
             llm: None,
             enable_llm_analysis: false,
             feedback_learner: Arc::new(FeedbackLearner::new()),
+            history: None,
+            snapshot_root: None,
         }
     }
 
@@ -298,6 +380,17 @@ impl ShellExecutorWithFixer {
         self
     }
 
+    /// 设置错误分析器（例如加载了用户模式的分析器）
+    pub fn with_analyzer(mut self, analyzer: ErrorAnalyzer) -> Self {
+        self.analyzer = analyzer;
+        self
+    }
+
+    /// 获取错误分析器的引用
+    pub fn analyzer(&self) -> &ErrorAnalyzer {
+        &self.analyzer
+    }
+
     /// 设置反馈学习器
     pub fn with_feedback_learner(mut self, learner: Arc<FeedbackLearner>) -> Self {
         self.feedback_learner = learner;
@@ -309,6 +402,29 @@ impl ShellExecutorWithFixer {
         self.feedback_learner.clone()
     }
 
+    /// 设置命令历史（拼写纠正时参考用过的命令）
+    pub fn with_history(mut self, history: Arc<RwLock<HistoryManager>>) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// 设置自动修复前快照的目录
+    pub fn with_snapshot_root(mut self, root: PathBuf) -> Self {
+        self.snapshot_root = Some(root);
+        self
+    }
+
+    /// 构建拼写纠错器：PATH + 命令历史 + 过去对纠正建议的反馈
+    async fn command_corrector(&self) -> CommandCorrector {
+        let mut corrector = CommandCorrector::from_env();
+        if let Some(history) = &self.history {
+            let entries = history.read().await.all(SortStrategy::Frequency);
+            corrector = corrector.with_history(&entries);
+        }
+        let records = self.feedback_learner.get_recent_records(usize::MAX).await;
+        corrector.with_feedback(&records)
+    }
+
     /// 禁用 LLM 增强分析（仅使用规则）
     pub fn disable_llm_analysis(mut self) -> Self {
         self.enable_llm_analysis = false;
@@ -344,7 +460,12 @@ impl ShellExecutorWithFixer {
 
                 // 生成修复策略
                 use crate::error_fixer::ErrorFixer;
-                let mut strategies = ErrorFixer::generate_strategies(&analysis);
+                let corrector = if analysis.category == ErrorCategory::Command {
+                    self.command_corrector().await
+                } else {
+                    CommandCorrector::new()
+                };
+                let mut strategies = ErrorFixer::generate_strategies_with_corrector(&analysis, &corrector);
 
                 // LLM 增强修复策略（如果启用且可用）
                 if self.enable_llm_analysis {
@@ -360,53 +481,138 @@ impl ShellExecutorWithFixer {
                     .filter(|s| self.is_safe_fix_strategy(s))
                     .collect();
 
-                // 使用学习到的数据重新排序策略（Week 3）
-                let ranked_strategies = self.feedback_learner.rerank_strategies(safe_strategies).await;
+                // 使用学习到的数据重新排序策略（参考相似上下文中的效果）
+                let context = FeedbackContext::current(&analysis);
+                let ranked_strategies = self
+                    .feedback_learner
+                    .rerank_strategies_in_context(safe_strategies, &context)
+                    .await;
 
+                let diagnostics = crate::error_fixer::diagnostics::parse_diagnostics(&error_output);
                 ExecutionResult::failure(error_output, Some(analysis), ranked_strategies)
+                    .with_diagnostics(diagnostics)
             }
         }
     }
 
     /// 执行命令并尝试自动修复
     ///
-    /// 如果命令失败且有可用的低风险修复策略，自动应用修复并重试
+    /// 命令失败时依次尝试低风险、无需确认的修复策略（每个策略最多一次）：
+    /// 执行前对工作目录做快照，执行后按策略的后置条件验证（默认重新执行原命令），
+    /// 未通过验证则回滚文件变化并尝试下一个策略。每次尝试都记录在结果中并反馈给学习器。
     ///
     /// # Arguments
     /// * `command` - 要执行的命令
-    /// * `max_retries` - 最大重试次数
+    /// * `max_attempts` - 最多尝试的修复次数
     ///
     /// # Returns
-    /// * `ExecutionResult` - 最终结果
-    pub async fn execute_with_auto_fix(&self, command: &str, max_retries: usize) -> ExecutionResult {
-        let mut current_command = command.to_string();
-        let mut attempt = 0;
-
-        loop {
-            attempt += 1;
-            let result = self.execute_with_analysis(&current_command).await;
-
-            // 如果成功或达到最大重试次数，返回结果
-            if result.success || attempt >= max_retries {
-                return result;
-            }
+    /// * `ExecutionResult` - 最终结果（含修复尝试记录）
+    pub async fn execute_with_auto_fix(&self, command: &str, max_attempts: usize) -> ExecutionResult {
+        let mut result = self.execute_with_analysis(command).await;
+        let mut attempts = Vec::new();
+        let mut tried = HashSet::new();
 
+        while !result.success && attempts.len() < max_attempts {
             // 查找可自动应用的低风险修复策略（带安全检查）
-            let auto_fix = result.fix_strategies.iter()
+            let auto_fix = result
+                .fix_strategies
+                .iter()
                 .find(|s| {
                     s.risk_level < 5
-                    && !s.requires_confirmation
-                    && self.is_safe_fix_strategy(s)
-                });
-
-            if let Some(fix) = auto_fix {
-                // 应用修复并重试
-                current_command = fix.command.clone();
-                continue;
+                        && !s.requires_confirmation
+                        && self.is_safe_fix_strategy(s)
+                        && !tried.contains(&s.command)
+                })
+                .cloned();
+            let Some(fix) = auto_fix else {
+                break;
+            };
+            tried.insert(fix.command.clone());
+
+            let attempt = self.apply_verified_fix(command, &fix).await;
+            if let Some(analysis) = &result.error_analysis {
+                let outcome = if attempt.verified { FixOutcome::Success } else { FixOutcome::Failure };
+                self.record_feedback(analysis, &fix, FeedbackType::Accepted, outcome).await;
             }
 
-            // 没有可自动应用的修复，返回结果
-            return result;
+            let verified = attempt.verified;
+            let output = attempt
+                .verification_output
+                .clone()
+                .unwrap_or_else(|| attempt.fix_output.clone());
+            attempts.push(attempt);
+
+            if verified {
+                result = ExecutionResult::success(output);
+            }
+        }
+
+        result.with_fix_attempts(attempts)
+    }
+
+    /// 应用一个修复策略并验证，未通过验证时回滚文件变化
+    ///
+    /// # Arguments
+    /// * `original` - 原命令（`Verification::RerunOriginal` 时重新执行）
+    /// * `fix` - 修复策略
+    pub async fn apply_verified_fix(&self, original: &str, fix: &FixStrategy) -> FixAttempt {
+        let started = Instant::now();
+        // 未指定快照目录时使用当前目录所在的项目根目录；不在项目中时不创建快照，
+        // 以免在 $HOME 之类的目录下遍历大量文件
+        let root = self.snapshot_root.clone().or_else(|| {
+            std::env::current_dir().ok().and_then(|dir| project_root(&dir))
+        });
+        let (snapshot, snapshot_error) = match root {
+            Some(root) => (
+                tokio::task::spawn_blocking(move || WorkspaceSnapshot::capture(&root))
+                    .await
+                    .ok(),
+                "无法创建快照",
+            ),
+            None => (None, "当前目录不在项目中，未创建快照"),
+        };
+
+        let (fix_succeeded, fix_output) = match execute_shell(&fix.command).await {
+            Ok(output) => (true, output),
+            Err(e) => (false, e.to_string()),
+        };
+
+        let verification_output = if fix_succeeded {
+            let check = match &fix.verification {
+                Verification::RerunOriginal => Some(original),
+                Verification::Command(command) => Some(command.as_str()),
+                Verification::FixSucceeds => None,
+            };
+            match check {
+                Some(check) => Some(execute_shell(check).await),
+                None => None,
+            }
+        } else {
+            None
+        };
+        let verified = fix_succeeded && !matches!(verification_output, Some(Err(_)));
+
+        let (rolled_back, rollback_error) = match (&snapshot, verified) {
+            (Some(snapshot), false) => match snapshot.restore() {
+                Ok(changes) => (changes, None),
+                Err(e) => (Vec::new(), Some(e)),
+            },
+            (None, false) => (Vec::new(), Some(snapshot_error.to_string())),
+            _ => (Vec::new(), None),
+        };
+
+        FixAttempt {
+            strategy_name: fix.name.clone(),
+            command: fix.command.clone(),
+            fix_succeeded,
+            fix_output,
+            verification: fix.verification.clone(),
+            verification_output: verification_output.map(|r| r.unwrap_or_else(|e| e.to_string())),
+            verified,
+            rolled_back,
+            rollback_error,
+            snapshot_complete: snapshot.as_ref().map(WorkspaceSnapshot::is_complete).unwrap_or(false),
+            duration: started.elapsed(),
         }
     }
 
@@ -440,6 +646,15 @@ impl Default for ShellExecutorWithFixer {
     }
 }
 
+/// 查找目录所在的项目根目录（含 `.git` 或可识别的项目文件的最近祖先）
+fn project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| {
+            d.join(".git").exists() || !matches!(ProjectContext::detect_project_type(d), ProjectType::Unknown)
+        })
+        .map(Path::to_path_buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -759,4 +974,79 @@ mod tests {
         let learner2 = executor.feedback_learner();
         assert!(Arc::ptr_eq(&learner, &learner2));
     }
+
+    // ========== 验证与回滚测试 ==========
+
+    #[tokio::test]
+    async fn test_verified_fix_rolls_back_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("config.txt");
+        std::fs::write(&file, "original").unwrap();
+        let executor = ShellExecutorWithFixer::new().with_snapshot_root(dir.path().to_path_buf());
+
+        // 修复命令改了文件并新建文件，但验证失败 → 全部回滚
+        let fix = FixStrategy::new(
+            "改配置",
+            format!("echo changed > {} && touch {}", file.display(), dir.path().join("new.txt").display()),
+            "",
+            3,
+        )
+        .with_verification(Verification::Command("false".to_string()));
+        let attempt = executor.apply_verified_fix("true", &fix).await;
+
+        assert!(attempt.fix_succeeded);
+        assert!(!attempt.verified);
+        assert!(attempt.snapshot_complete);
+        assert_eq!(attempt.rolled_back.len(), 2);
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), "original");
+        assert!(!dir.path().join("new.txt").exists());
+        assert!(attempt.summary().contains("已回滚 2 个文件"));
+    }
+
+    #[tokio::test]
+    async fn test_verified_fix_keeps_changes_on_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("ready");
+        let executor = ShellExecutorWithFixer::new().with_snapshot_root(dir.path().to_path_buf());
+
+        // 后置条件：重新执行原命令（原命令依赖修复创建的文件）
+        let original = format!("test -f {}", marker.display());
+        let fix = FixStrategy::new("创建标记", format!("touch {}", marker.display()), "", 2);
+        let attempt = executor.apply_verified_fix(&original, &fix).await;
+
+        assert!(attempt.verified);
+        assert!(attempt.rolled_back.is_empty());
+        assert!(marker.exists());
+
+        // 修复命令本身失败 → 不验证，直接回滚
+        let failing = FixStrategy::new("失败修复", format!("touch {} && false", dir.path().join("x").display()), "", 2)
+            .with_verification(Verification::FixSucceeds);
+        let attempt = executor.apply_verified_fix(&original, &failing).await;
+        assert!(!attempt.fix_succeeded);
+        assert!(attempt.verification_output.is_none());
+        assert!(!dir.path().join("x").exists());
+    }
+
+    #[tokio::test]
+    async fn test_auto_fix_records_attempts() {
+        let dir = tempfile::tempdir().unwrap();
+        let executor = ShellExecutorWithFixer::new().with_snapshot_root(dir.path().to_path_buf());
+
+        // 成功的命令不触发修复
+        let result = executor.execute_with_auto_fix("echo ok", 3).await;
+        assert!(result.success);
+        assert!(result.fix_attempts.is_empty());
+
+        // 自动修复次数受限
+        let result = executor.execute_with_auto_fix("nonexistent_cmd_xyz_12345", 0).await;
+        assert!(!result.success);
+        assert!(result.fix_attempts.is_empty());
+
+        // 每个策略最多尝试一次，未通过验证的尝试都有记录
+        let result = executor.execute_with_auto_fix("nonexistent_cmd_xyz_12345", 5).await;
+        assert!(!result.success);
+        let commands: HashSet<&str> = result.fix_attempts.iter().map(|a| a.command.as_str()).collect();
+        assert_eq!(commands.len(), result.fix_attempts.len());
+        assert!(result.fix_attempts.iter().all(|a| !a.verified));
+    }
 }
@@ -0,0 +1,41 @@
+//! Shell 相关的小工具
+//!
+//! 把任意文本拼进 `/bin/sh -c` 执行的命令时，必须先转义为单个 shell 单词。
+
+/// 将文本转义为单个 shell 单词（单引号包裹）
+///
+/// 只含安全字符的文本原样返回。
+pub fn shell_quote(value: &str) -> String {
+    if !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c))
+    {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// 还原 [`shell_quote`] 产生的单引号包裹（其他文本原样返回）
+pub fn shell_unquote(value: &str) -> String {
+    match value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        Some(inner) => inner.replace(r"'\''", "'"),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("src/main.rs"), "src/main.rs");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_unquote(&shell_quote("it's")), "it's");
+        assert_eq!(shell_unquote("plain"), "plain");
+    }
+}
@@ -0,0 +1,341 @@
+//! 步骤缓存 (StepCache)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 类似构建系统：任务声明输入（文件、glob、环境变量）后，执行器对命令与输入内容求哈希，
+//! 若与上一次成功执行时相同（且声明的输出仍未改变），则直接复用上次的结果而不再执行。
+//! 未声明输入的任务总是执行。
+
+use super::types::{SubTask, TaskType};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 最多保留的缓存条目数
+const MAX_ENTRIES: usize = 500;
+
+/// 一条缓存记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// 写入缓存的任务 ID
+    pub task_id: String,
+    /// 上次成功执行的输出
+    pub output: String,
+    /// 声明输出的哈希（未声明输出时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outputs_hash: Option<String>,
+    /// 写入时间
+    pub cached_at: DateTime<Utc>,
+}
+
+/// 步骤缓存
+///
+/// 以输入哈希为键；指定了文件路径时，每次写入后保存到磁盘（JSON）。
+/// 内部加锁，可在并行执行的任务间共享。
+#[derive(Debug, Default)]
+pub struct StepCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    path: Option<PathBuf>,
+}
+
+impl StepCache {
+    /// 仅内存缓存
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载（文件不存在或损坏时从空缓存开始）
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let entries = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            entries: Mutex::new(entries),
+            path: Some(path),
+        }
+    }
+
+    /// 查找缓存：输入哈希相同且声明的输出未改变时命中
+    pub fn lookup(&self, task: &SubTask, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.lock().ok()?.get(key).cloned()?;
+        if entry.outputs_hash != outputs_hash(task) {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// 记录一次成功执行
+    pub fn store(&self, task: &SubTask, key: &str, output: &str) -> Result<(), String> {
+        let mut entries = self.entries.lock().map_err(|_| "步骤缓存不可用".to_string())?;
+        entries.insert(
+            key.to_string(),
+            CacheEntry {
+                task_id: task.id.clone(),
+                output: output.to_string(),
+                outputs_hash: outputs_hash(task),
+                cached_at: Utc::now(),
+            },
+        );
+
+        // 超出上限时淘汰最旧的条目
+        if entries.len() > MAX_ENTRIES {
+            let mut by_age: Vec<(String, DateTime<Utc>)> =
+                entries.iter().map(|(k, e)| (k.clone(), e.cached_at)).collect();
+            by_age.sort_by_key(|(_, cached_at)| *cached_at);
+            for (key, _) in by_age.into_iter().take(entries.len() - MAX_ENTRIES) {
+                entries.remove(&key);
+            }
+        }
+
+        self.save(&entries)
+    }
+
+    /// 缓存条目数
+    pub fn len(&self) -> usize {
+        self.entries.lock().map(|e| e.len()).unwrap_or(0)
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn save(&self, entries: &HashMap<String, CacheEntry>) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(entries)
+            .map_err(|e| format!("序列化步骤缓存失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入步骤缓存失败: {}", e))
+    }
+}
+
+/// 计算任务的输入哈希（任务未声明输入或不可缓存时返回 None）
+///
+/// 哈希覆盖任务类型、命令、结构化参数以及每个输入的当前内容：
+/// - `env:NAME` 或 `$NAME`：环境变量的值（未设置与空值不同）
+/// - 其他：文件、目录（递归）或 glob 模式匹配到的全部文件的路径与内容
+pub fn input_key(task: &SubTask) -> Option<String> {
+    if task.inputs.is_empty() || task.task_type == TaskType::UserInput {
+        return None;
+    }
+
+    let mut hasher = Fnv::new();
+    hasher.field(&format!("{:?}", task.task_type));
+    hasher.field(&task.command);
+    hasher.field(&task.args.as_ref().map(|a| a.to_string()).unwrap_or_default());
+
+    for input in &task.inputs {
+        match env_name(input) {
+            Some(name) => {
+                hasher.field(&format!("env:{}", name));
+                match std::env::var(name) {
+                    Ok(value) => hasher.field(&value),
+                    Err(_) => hasher.field("\0unset"),
+                }
+            }
+            None => {
+                hasher.field(input);
+                hash_files(&mut hasher, input);
+            }
+        }
+    }
+
+    Some(hasher.hex())
+}
+
+/// 声明输出的哈希（未声明输出时为 None；缺失的输出计入哈希，因此删除输出会使缓存失效）
+fn outputs_hash(task: &SubTask) -> Option<String> {
+    if task.outputs.is_empty() {
+        return None;
+    }
+
+    let mut hasher = Fnv::new();
+    for output in &task.outputs {
+        hasher.field(output);
+        hash_files(&mut hasher, output);
+    }
+    Some(hasher.hex())
+}
+
+fn env_name(input: &str) -> Option<&str> {
+    input
+        .strip_prefix("env:")
+        .or_else(|| input.strip_prefix('$'))
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// 把路径或 glob 匹配到的全部文件（排序后）计入哈希
+fn hash_files(hasher: &mut Fnv, pattern: &str) {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let matches: Vec<PathBuf> = match glob::glob(pattern) {
+        Ok(paths) => paths.filter_map(Result::ok).collect(),
+        Err(_) => vec![PathBuf::from(pattern)],
+    };
+
+    for path in matches {
+        if path.is_dir() {
+            files.extend(
+                walkdir::WalkDir::new(&path)
+                    .into_iter()
+                    .filter_map(Result::ok)
+                    .filter(|e| e.file_type().is_file())
+                    .map(|e| e.into_path()),
+            );
+        } else if path.exists() {
+            files.push(path);
+        }
+    }
+    files.sort();
+
+    if files.is_empty() {
+        hasher.field("\0missing");
+        return;
+    }
+    for file in files {
+        hasher.field(&file.to_string_lossy());
+        match std::fs::read(&file) {
+            Ok(content) => hasher.bytes(&content),
+            Err(_) => hasher.field("\0unreadable"),
+        }
+    }
+}
+
+/// FNV-1a 64 位哈希（结果跨版本稳定，可以持久化）
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+        // 长度作为分隔，避免相邻字段拼接产生歧义
+        for byte in (bytes.len() as u64).to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn field(&mut self, text: &str) {
+        self.bytes(text.as_bytes());
+    }
+
+    fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn task_with_inputs(dir: &Path, inputs: &[&str]) -> SubTask {
+        SubTask::new("build", "Build", "cat src/*.txt")
+            .with_inputs(inputs.iter().map(|i| dir.join(i).to_string_lossy().to_string()))
+    }
+
+    #[test]
+    fn test_no_inputs_not_cacheable() {
+        assert!(input_key(&SubTask::new("t1", "Echo", "echo hi")).is_none());
+        let ask = SubTask::new("t1", "Ask", "").with_task_type(TaskType::UserInput).with_inputs(["x"]);
+        assert!(input_key(&ask).is_none());
+    }
+
+    #[test]
+    fn test_key_tracks_file_content_and_command() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/a.txt"), "one").unwrap();
+
+        let task = task_with_inputs(dir.path(), &["src/*.txt"]);
+        let key = input_key(&task).unwrap();
+        assert_eq!(input_key(&task).unwrap(), key);
+
+        // 命令改变
+        let mut changed = task.clone();
+        changed.command = "cat src/a.txt".to_string();
+        assert_ne!(input_key(&changed).unwrap(), key);
+
+        // 文件内容改变
+        std::fs::write(dir.path().join("src/a.txt"), "two").unwrap();
+        let modified = input_key(&task).unwrap();
+        assert_ne!(modified, key);
+
+        // glob 匹配到新文件
+        std::fs::write(dir.path().join("src/b.txt"), "").unwrap();
+        assert_ne!(input_key(&task).unwrap(), modified);
+
+        // 目录输入递归计入
+        let by_dir = task_with_inputs(dir.path(), &["src"]);
+        let dir_key = input_key(&by_dir).unwrap();
+        std::fs::write(dir.path().join("src/b.txt"), "new").unwrap();
+        assert_ne!(input_key(&by_dir).unwrap(), dir_key);
+    }
+
+    #[test]
+    fn test_key_tracks_env_vars() {
+        let name = "REALCONSOLE_STEP_CACHE_TEST";
+        let task = SubTask::new("t1", "Env", "echo $X").with_inputs([format!("env:{}", name)]);
+        let unset = input_key(&task).unwrap();
+
+        std::env::set_var(name, "1");
+        let set = input_key(&task).unwrap();
+        assert_ne!(set, unset);
+        let dollar = SubTask::new("t1", "Env", "echo $X").with_inputs([format!("${}", name)]);
+        assert_eq!(input_key(&dollar).unwrap(), set);
+        std::env::remove_var(name);
+    }
+
+    #[test]
+    fn test_lookup_checks_outputs() {
+        let dir = TempDir::new().unwrap();
+        let input = dir.path().join("in.txt");
+        let output = dir.path().join("out.txt");
+        std::fs::write(&input, "data").unwrap();
+        std::fs::write(&output, "built").unwrap();
+
+        let task = SubTask::new("t1", "Build", "cp in.txt out.txt")
+            .with_inputs([input.to_string_lossy().to_string()])
+            .with_outputs([output.to_string_lossy().to_string()]);
+        let key = input_key(&task).unwrap();
+
+        let cache = StepCache::new();
+        assert!(cache.lookup(&task, &key).is_none());
+        cache.store(&task, &key, "ok").unwrap();
+        assert_eq!(cache.lookup(&task, &key).unwrap().output, "ok");
+
+        // 输出被删除后不再命中
+        std::fs::remove_file(&output).unwrap();
+        assert!(cache.lookup(&task, &key).is_none());
+    }
+
+    #[test]
+    fn test_cache_persistence() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cache").join("steps.json");
+        let task = SubTask::new("t1", "Env", "true").with_inputs(["env:HOME"]);
+        let key = input_key(&task).unwrap();
+
+        StepCache::load(&path).store(&task, &key, "done").unwrap();
+
+        let reloaded = StepCache::load(&path);
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.lookup(&task, &key).unwrap().task_id, "t1");
+    }
+}
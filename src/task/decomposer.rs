@@ -5,9 +5,12 @@
 //! 负责将用户的高层次目标分解为可执行的子任务序列
 
 use super::error::{TaskError, TaskResult};
-use super::types::{ExecutionContext, SubTask, TaskType};
+use super::history::{DecompositionHistory, DecompositionRecord};
+use super::types::{ExecutionContext, ExecutionPlan, SubTask, TaskResult as SubTaskResult, TaskType};
+use crate::error_fixer::ErrorAnalysis;
 use crate::llm::LlmClient;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -30,18 +33,17 @@ struct SubTaskJson {
     task_type: String,
     #[serde(default)]
     skippable: bool,
+    #[serde(default)]
+    args: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    inputs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    outputs: Vec<String>,
 }
 
 impl From<SubTaskJson> for SubTask {
     fn from(json: SubTaskJson) -> Self {
-        let task_type = match json.task_type.to_lowercase().as_str() {
-            "shell" => TaskType::Shell,
-            "fileoperation" => TaskType::FileOperation,
-            "network" => TaskType::Network,
-            "validation" => TaskType::Validation,
-            "userinput" => TaskType::UserInput,
-            _ => TaskType::Shell, // 默认为 Shell
-        };
+        let task_type = TaskType::from_name(&json.task_type).unwrap_or(TaskType::Shell); // 默认为 Shell
 
         SubTask {
             id: json.id,
@@ -53,21 +55,35 @@ impl From<SubTaskJson> for SubTask {
             task_type,
             skippable: json.skippable,
             retry_policy: None,
+            args: json.args,
+            timeout: None,
+            inputs: json.inputs,
+            outputs: json.outputs,
         }
     }
 }
 
-/// 分解记录（用于学习和优化）
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-struct DecompositionRecord {
-    goal: String,
-    context: ExecutionContext,
-    subtasks: Vec<SubTask>,
-    timestamp: chrono::DateTime<chrono::Utc>,
-    success: bool,
+impl From<&SubTask> for SubTaskJson {
+    fn from(task: &SubTask) -> Self {
+        SubTaskJson {
+            id: task.id.clone(),
+            name: task.name.clone(),
+            description: task.description.clone(),
+            command: task.command.clone(),
+            estimated_time: task.estimated_time,
+            depends_on: task.depends_on.clone(),
+            task_type: format!("{:?}", task.task_type),
+            skippable: task.skippable,
+            args: task.args.clone(),
+            inputs: task.inputs.clone(),
+            outputs: task.outputs.clone(),
+        }
+    }
 }
 
+/// 提示词中最多附带的历史示例数
+const MAX_EXAMPLES: usize = 2;
+
 /// 任务分解器
 ///
 /// 使用 LLM 将复杂任务分解为可执行的子任务序列
@@ -75,8 +91,8 @@ pub struct TaskDecomposer {
     /// LLM 客户端
     llm: Arc<dyn LlmClient>,
 
-    /// 历史分解记录（用于学习）
-    history: Arc<RwLock<Vec<DecompositionRecord>>>,
+    /// 分解历史（相似目标的成功计划作为 few-shot 示例）
+    history: Arc<RwLock<DecompositionHistory>>,
 
     /// 最大子任务数量限制
     max_subtasks: usize,
@@ -87,7 +103,7 @@ impl TaskDecomposer {
     pub fn new(llm: Arc<dyn LlmClient>) -> Self {
         Self {
             llm,
-            history: Arc::new(RwLock::new(Vec::new())),
+            history: Arc::new(RwLock::new(DecompositionHistory::new())),
             max_subtasks: 20,
         }
     }
@@ -98,6 +114,12 @@ impl TaskDecomposer {
         self
     }
 
+    /// 使用共享的分解历史（通常是持久化的历史）
+    pub fn with_history(mut self, history: Arc<RwLock<DecompositionHistory>>) -> Self {
+        self.history = history;
+        self
+    }
+
     /// 分解任务
     ///
     /// # Arguments
@@ -118,19 +140,50 @@ impl TaskDecomposer {
         let validated_tasks = self.validate_tasks(subtasks)?;
 
         // 3. 记录历史
-        self.record_decomposition(goal, context, &validated_tasks, true)
+        self.record_decomposition(goal, context, &validated_tasks)
             .await;
 
         Ok(validated_tasks)
     }
 
+    /// 重新规划失败后的剩余计划
+    ///
+    /// 将失败输出、错误分析与剩余任务交给 LLM，返回替换剩余计划的新任务。
+    /// 新任务可以依赖已完成的任务，但 ID 不能与之重复。
+    pub async fn replan(
+        &self,
+        plan: &ExecutionPlan,
+        failed: &SubTaskResult,
+        analysis: Option<&ErrorAnalysis>,
+        completed: &[SubTask],
+        remaining: &[SubTask],
+        context: &ExecutionContext,
+    ) -> TaskResult<Vec<SubTask>> {
+        let prompt = self.build_replan_prompt(plan, failed, analysis, completed, remaining, context);
+
+        let messages = vec![crate::llm::Message::user(prompt)];
+        let response = self
+            .llm
+            .chat(messages)
+            .await
+            .map_err(|e| TaskError::LlmError(e.to_string()))?;
+
+        let tasks = self.parse_llm_response(&response)?;
+        let completed_ids: HashSet<String> = completed.iter().map(|t| t.id.clone()).collect();
+        self.validate_tasks_against(tasks, &completed_ids)
+    }
+
     /// 使用 LLM 分解任务
     async fn decompose_with_llm(
         &self,
         goal: &str,
         context: &ExecutionContext,
     ) -> TaskResult<Vec<SubTask>> {
-        let prompt = self.build_decomposition_prompt(goal, context);
+        let prompt = {
+            let history = self.history.read().await;
+            let examples = history.examples(goal, MAX_EXAMPLES);
+            self.build_decomposition_prompt(goal, context, &examples)
+        };
 
         // 调用 LLM
         let messages = vec![crate::llm::Message::user(prompt)];
@@ -145,8 +198,13 @@ impl TaskDecomposer {
     }
 
     /// 构建分解提示词
-    fn build_decomposition_prompt(&self, goal: &str, context: &ExecutionContext) -> String {
-        format!(
+    fn build_decomposition_prompt(
+        &self,
+        goal: &str,
+        context: &ExecutionContext,
+        examples: &[(&DecompositionRecord, f64)],
+    ) -> String {
+        let mut prompt = format!(
             r#"你是一个任务分解专家。请将以下目标分解为可执行的子任务序列。
 
 目标: {}
@@ -190,7 +248,12 @@ impl TaskDecomposer {
 4. 提供合理的时间估计（单位：秒）
 5. 按执行顺序排列任务
 6. task_type 只能是: Shell, FileOperation, Network, Validation, UserInput
+   - Shell: 执行 command
+   - Validation: 断言检查，command 可写 "exists <路径>"、"<命令> =~ <正则>" 或普通命令（退出码为 0 即通过）
+   - UserInput: 向用户提问，可选 "args": {{"prompt": "问题", "confirm": true}}
+   - FileOperation / Network: 可选 "args": {{"tool": "read_file|write_file|list_dir|http_get|http_post", "args": {{...}}}}，未提供 args 时按 command 执行
 7. 如果任务失败可以跳过，设置 skippable 为 true
+   - 可在 command 中引用前置任务的结果: {{{{t1.output}}}}、{{{{t1.output | regex:版本 (\\d+)}}}}（被引用的任务必须写入 depends_on）
 8. 任务数量不超过 {} 个
 9. 每个任务的 id 必须唯一
 10. 输出必须是有效的 JSON 格式
@@ -217,6 +280,108 @@ impl TaskDecomposer {
             context.user,
             context.os,
             self.max_subtasks
+        );
+
+        if !examples.is_empty() {
+            prompt.push_str("\n参考：以下是相似目标过去执行成功的计划，适用时请沿用其中经过验证的步骤和命令:\n");
+            for (i, (record, similarity)) in examples.iter().enumerate() {
+                let tasks: Vec<SubTaskJson> = record.subtasks.iter().map(SubTaskJson::from).collect();
+                let json = serde_json::to_string(&serde_json::json!({ "tasks": tasks }))
+                    .unwrap_or_default();
+                prompt.push_str(&format!(
+                    "\n示例 {}（相似度 {:.0}%）\n目标: {}\n{}\n",
+                    i + 1,
+                    similarity * 100.0,
+                    record.goal,
+                    json
+                ));
+            }
+        }
+
+        prompt
+    }
+
+    /// 构建重新规划提示词
+    fn build_replan_prompt(
+        &self,
+        plan: &ExecutionPlan,
+        failed: &SubTaskResult,
+        analysis: Option<&ErrorAnalysis>,
+        completed: &[SubTask],
+        remaining: &[SubTask],
+        context: &ExecutionContext,
+    ) -> String {
+        let describe = |tasks: &[SubTask]| -> String {
+            if tasks.is_empty() {
+                return "（无）".to_string();
+            }
+            tasks
+                .iter()
+                .map(|t| format!("- {} [{}] {}: $ {}", t.id, t.task_type.to_string(), t.name, t.command))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let analysis_text = match analysis {
+            Some(a) => {
+                let mut text = format!("- 类别: {:?}\n- 严重程度: {:?}", a.category, a.severity);
+                if !a.possible_causes.is_empty() {
+                    text.push_str(&format!("\n- 可能原因: {}", a.possible_causes.join("; ")));
+                }
+                if !a.suggested_fixes.is_empty() {
+                    text.push_str(&format!("\n- 建议修复: {}", a.suggested_fixes.join("; ")));
+                }
+                text
+            }
+            None => "（无）".to_string(),
+        };
+
+        format!(
+            r#"你是一个任务规划专家。以下计划在执行中有任务失败，请修订剩余部分。
+
+目标: {}
+
+当前上下文:
+- 工作目录: {}
+- 系统: {}
+- Shell: {}
+
+已完成的任务（不会重新执行，新任务可以在 depends_on 中引用它们）:
+{}
+
+失败的任务:
+- {} {}: $ {}
+失败输出:
+{}
+
+错误分析:
+{}
+
+原计划的剩余任务（包括失败的任务）:
+{}
+
+请输出替换剩余任务的新任务列表，格式与原计划相同:
+{{"tasks": [{{"id": "...", "name": "...", "description": "...", "command": "...", "estimated_time": 10, "depends_on": [], "task_type": "Shell", "skippable": false}}]}}
+
+要求:
+1. 针对失败原因调整命令，不要原样重复失败的命令
+2. 新任务的 id 不能与已完成任务的 id 相同
+3. depends_on 只能引用新任务或已完成的任务
+4. 任务数量不超过 {} 个
+5. 只输出 JSON，不要有其他解释文字
+"#,
+            plan.goal,
+            context.working_dir,
+            context.os,
+            context.shell,
+            describe(completed),
+            failed.task.id,
+            failed.task.name,
+            failed.task.command,
+            failed.error.as_deref().unwrap_or("（无输出）"),
+            analysis_text,
+            describe(remaining),
+            self.max_subtasks
         )
     }
 
@@ -273,7 +438,16 @@ impl TaskDecomposer {
     }
 
     /// 验证任务合理性
-    fn validate_tasks(&self, mut tasks: Vec<SubTask>) -> TaskResult<Vec<SubTask>> {
+    fn validate_tasks(&self, tasks: Vec<SubTask>) -> TaskResult<Vec<SubTask>> {
+        self.validate_tasks_against(tasks, &HashSet::new())
+    }
+
+    /// 验证任务合理性（`existing` 为计划中已存在、可被依赖但不可重用 ID 的任务）
+    fn validate_tasks_against(
+        &self,
+        mut tasks: Vec<SubTask>,
+        existing: &HashSet<String>,
+    ) -> TaskResult<Vec<SubTask>> {
         // 1. 检查任务数量
         if tasks.is_empty() {
             return Err(TaskError::ParseError("任务列表为空".to_string()));
@@ -285,7 +459,7 @@ impl TaskDecomposer {
         }
 
         // 2. 检查 ID 唯一性
-        let mut ids = std::collections::HashSet::new();
+        let mut ids = existing.clone();
         for task in &tasks {
             if !ids.insert(task.id.clone()) {
                 return Err(TaskError::ParseError(format!(
@@ -320,29 +494,16 @@ impl TaskDecomposer {
         Ok(tasks)
     }
 
-    /// 记录分解历史
+    /// 记录分解历史（写入失败不影响分解结果）
     async fn record_decomposition(
         &self,
         goal: &str,
         context: &ExecutionContext,
         subtasks: &[SubTask],
-        success: bool,
     ) {
-        let record = DecompositionRecord {
-            goal: goal.to_string(),
-            context: context.clone(),
-            subtasks: subtasks.to_vec(),
-            timestamp: chrono::Utc::now(),
-            success,
-        };
-
-        let mut history = self.history.write().await;
-        history.push(record);
-
-        // 限制历史记录数量（保留最近 100 条）
-        if history.len() > 100 {
-            let drain_count = history.len() - 100;
-            history.drain(0..drain_count);
+        let record = DecompositionRecord::new(goal, context, subtasks);
+        if let Err(e) = self.history.write().await.record(record) {
+            eprintln!("⚠ 警告: {}", e);
         }
     }
 
@@ -353,7 +514,9 @@ impl TaskDecomposer {
 
     /// 清空历史记录
     pub async fn clear_history(&self) {
-        self.history.write().await.clear();
+        if let Err(e) = self.history.write().await.clear() {
+            eprintln!("⚠ 警告: {}", e);
+        }
     }
 }
 
@@ -568,4 +731,47 @@ mod tests {
         decomposer.clear_history().await;
         assert_eq!(decomposer.history_count().await, 0);
     }
+
+    #[tokio::test]
+    async fn test_prompt_includes_successful_examples() {
+        let llm = Arc::new(MockLlmClient {
+            response: r#"{"tasks": [{"id":"t1","name":"Build","description":"desc","command":"cargo build --release","estimated_time":10,"task_type":"Shell"}]}"#.to_string(),
+        });
+        let context = ExecutionContext::current();
+
+        let mut proven = DecompositionRecord::new(
+            "build the rust project",
+            &context,
+            &[SubTask::new("b1", "Build", "cargo build --release")],
+        );
+        proven.outcome = Some(crate::task::history::PlanOutcome {
+            success: true,
+            completed: 1,
+            failed: 0,
+            total_time: 3,
+            recorded_at: chrono::Utc::now(),
+        });
+        let mut history = DecompositionHistory::new();
+        history.record(proven).unwrap();
+        let history = Arc::new(RwLock::new(history));
+
+        let decomposer = TaskDecomposer::new(llm).with_history(Arc::clone(&history));
+        let examples_prompt = {
+            let history = history.read().await;
+            decomposer.build_decomposition_prompt(
+                "build rust project",
+                &context,
+                &history.examples("build rust project", MAX_EXAMPLES),
+            )
+        };
+        assert!(examples_prompt.contains("示例 1"));
+        assert!(examples_prompt.contains("cargo build --release"));
+
+        let plain = decomposer.build_decomposition_prompt("清理临时文件", &context, &[]);
+        assert!(!plain.contains("示例 1"));
+
+        // 新的分解写入共享历史
+        decomposer.decompose("build rust project", &context).await.unwrap();
+        assert_eq!(history.read().await.len(), 2);
+    }
 }
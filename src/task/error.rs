@@ -45,6 +45,18 @@ pub enum TaskError {
     #[error("Shell 执行错误: {0}")]
     ShellExecutionError(String),
 
+    /// 验证断言未通过
+    #[error("验证失败: {0}")]
+    ValidationFailed(String),
+
+    /// 工具调用错误
+    #[error("工具调用失败: {0}")]
+    ToolError(String),
+
+    /// 用户未提供输入或拒绝继续
+    #[error("用户输入: {0}")]
+    UserInputRejected(String),
+
     /// IO 错误
     #[error("IO 错误: {0}")]
     IoError(#[from] std::io::Error),
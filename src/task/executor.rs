@@ -3,22 +3,38 @@
 //! Phase 10: 任务分解与规划系统
 //!
 //! 负责按照执行计划执行任务，支持串行/并行执行、进度反馈、错误处理
+//!
+//! 任务在其前置任务结束后立即启动，不必等待整个阶段完成；
+//! 任务失败时，依赖它的下游任务被标记为阻塞（Blocked）而不会执行。
 
+use super::cache::{self, StepCache};
 use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::handlers::{self, Assertion, InputProvider, ToolCallSpec};
+use super::store::{PlanCheckpoint, PlanStore};
+use super::template;
 use super::types::{
-    ExecutionMode, ExecutionPlan, ExecutionResult, ExecutionStage, RetryPolicy, SubTask,
-    TaskProgress, TaskResult, TaskStatus,
+    DependencyGraph, ExecutionMode, ExecutionPlan, ExecutionResult, FailurePolicy, RetryPolicy,
+    SubTask, TaskProgress, TaskResult, TaskStatus, TaskType,
 };
 use crate::shell_executor::ShellExecutorWithFixer;
+use crate::tool::ToolRegistry;
 use chrono::Utc;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use tokio::task::JoinSet;
 use tokio::time::sleep;
 
 /// 进度回调函数类型
 pub type ProgressCallback = Arc<dyn Fn(TaskProgress) + Send + Sync>;
 
+/// 任务状态回调类型（任务开始与结束时调用，参数为任务 ID 与新状态）
+pub type StatusCallback = Arc<dyn Fn(&str, &TaskStatus) + Send + Sync>;
+
+/// 任务结果回调类型（每个任务得到最终结果时调用）
+pub type ResultCallback = Arc<dyn Fn(&TaskResult) + Send + Sync>;
+
 /// 任务执行器
 ///
 /// 按照执行计划执行任务，支持串行和并行执行模式
@@ -29,11 +45,35 @@ pub struct TaskExecutor {
     /// 进度回调
     progress_callback: Option<ProgressCallback>,
 
+    /// 任务状态回调
+    status_callback: Option<StatusCallback>,
+
+    /// 任务结果回调
+    result_callback: Option<ResultCallback>,
+
     /// 当前执行状态
     state: Arc<RwLock<ExecutorState>>,
 
     /// 超时设置（秒）
     timeout: Option<u64>,
+
+    /// 检查点存储（每完成一个任务写一次）
+    store: Option<Arc<PlanStore>>,
+
+    /// 最多同时执行的任务数
+    max_parallelism: usize,
+
+    /// 工具注册表（FileOperation / Network 任务使用）
+    tools: Option<Arc<RwLock<ToolRegistry>>>,
+
+    /// 用户输入来源（UserInput 任务使用，默认读取标准输入）
+    input_provider: InputProvider,
+
+    /// 步骤缓存（声明了输入的任务在输入未变时复用上次结果）
+    cache: Option<Arc<StepCache>>,
+
+    /// 忽略缓存强制执行（成功结果仍写入缓存）
+    force: bool,
 }
 
 /// 执行器内部状态
@@ -81,8 +121,16 @@ impl TaskExecutor {
         Self {
             shell_executor,
             progress_callback: None,
+            status_callback: None,
+            result_callback: None,
             state: Arc::new(RwLock::new(ExecutorState::new())),
             timeout: None,
+            store: None,
+            max_parallelism: 4,
+            tools: None,
+            input_provider: handlers::stdin_input_provider(),
+            cache: None,
+            force: false,
         }
     }
 
@@ -92,14 +140,93 @@ impl TaskExecutor {
         self
     }
 
+    /// 设置任务状态回调（用于实时显示依赖图等）
+    pub fn with_status_callback(mut self, callback: StatusCallback) -> Self {
+        self.status_callback = Some(callback);
+        self
+    }
+
+    /// 设置任务结果回调（用于流式报告等）
+    pub fn with_result_callback(mut self, callback: ResultCallback) -> Self {
+        self.result_callback = Some(callback);
+        self
+    }
+
     /// 设置任务超时（秒）
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout = Some(timeout);
         self
     }
 
+    /// 设置最多同时执行的任务数（至少为 1）
+    pub fn with_max_parallelism(mut self, max: usize) -> Self {
+        self.max_parallelism = max.max(1);
+        self
+    }
+
+    /// 设置工具注册表（FileOperation / Network 任务通过它调用工具）
+    pub fn with_tool_registry(mut self, tools: Arc<RwLock<ToolRegistry>>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    /// 设置用户输入来源（UserInput 任务使用）
+    pub fn with_input_provider(mut self, provider: InputProvider) -> Self {
+        self.input_provider = provider;
+        self
+    }
+
+    /// 设置检查点存储
+    ///
+    /// 执行开始时保存计划，之后每完成一个任务就把结果写入检查点，
+    /// 中途退出或崩溃后可通过 [`TaskExecutor::resume`] 继续。
+    pub fn with_store(mut self, store: Arc<PlanStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// 设置步骤缓存
+    pub fn with_step_cache(mut self, cache: Arc<StepCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// 设置是否忽略缓存强制执行
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
     /// 执行计划
     pub async fn execute(&self, plan: ExecutionPlan) -> TaskOpResult<ExecutionResult> {
+        self.resume(plan, Vec::new()).await
+    }
+
+    /// 恢复执行计划
+    ///
+    /// `previous` 中已成功的任务不再执行，直接复用其结果；
+    /// 其余任务（失败、跳过或未执行）照常执行。
+    pub async fn resume(
+        &self,
+        plan: ExecutionPlan,
+        previous: Vec<TaskResult>,
+    ) -> TaskOpResult<ExecutionResult> {
+        let previous: HashMap<String, TaskResult> = previous
+            .into_iter()
+            .filter(|r| r.status.is_success())
+            .map(|r| (r.task.id.clone(), r))
+            .collect();
+
+        if let Some(store) = &self.store {
+            let mut checkpoint = PlanCheckpoint::new(plan.clone());
+            for result in previous.values() {
+                checkpoint.record(result.clone());
+            }
+            if let Err(e) = store.save(&checkpoint) {
+                eprintln!("⚠ 警告: 保存任务检查点失败: {}", e);
+            }
+        }
+
         // 初始化状态
         {
             let mut state = self.state.write().await;
@@ -112,121 +239,208 @@ impl TaskExecutor {
         }
 
         let start_time = Instant::now();
-        let mut all_results = Vec::new();
-
-        // 逐阶段执行
-        for (stage_idx, stage) in plan.stages.iter().enumerate() {
+        let graph = plan.dependency_graph();
+        let prerequisites = Self::prerequisites(&plan);
+        let stage_of: HashMap<&str, usize> = plan
+            .stages
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, stage)| stage.tasks.iter().map(move |t| (t.id.as_str(), idx)))
+            .collect();
+
+        let mut results: HashMap<String, TaskResult> = HashMap::new();
+        let mut running: JoinSet<TaskResult> = JoinSet::new();
+        let mut running_ids: HashSet<String> = HashSet::new();
+        // fail-fast 触发后的停止原因
+        let mut halted: Option<String> = None;
+
+        loop {
             // 检查是否被取消
             if self.is_cancelled().await {
+                running.abort_all();
                 return Err(TaskError::ExecutionCancelled);
             }
 
-            // 更新当前阶段
-            {
-                let mut state = self.state.write().await;
-                state.current_stage = stage_idx;
-            }
+            // 1. 处理无需执行的任务：已成功（恢复）、依赖失败（阻塞）、fail-fast 后（取消）
+            for task in plan.tasks() {
+                if results.contains_key(&task.id) || running_ids.contains(&task.id) {
+                    continue;
+                }
 
-            // 报告进度
-            self.report_progress().await;
+                let settled = if let Some(done) = previous.get(&task.id) {
+                    Some(done.clone())
+                } else if let Some(reason) = Self::blocking_reason(&graph, &task.id, &results) {
+                    Some(Self::unexecuted(task, TaskStatus::Blocked, reason))
+                } else {
+                    halted
+                        .as_ref()
+                        .map(|reason| Self::unexecuted(task, TaskStatus::Cancelled, reason.clone()))
+                };
 
-            // 根据执行模式执行任务
-            let stage_results = match stage.execution_mode {
-                ExecutionMode::Sequential => {
-                    self.execute_sequential(stage).await?
+                if let Some(result) = settled {
+                    if !previous.contains_key(&task.id) {
+                        self.checkpoint(&plan.id, &result);
+                    }
+                    self.notify_status(&task.id, &result.status);
+                    self.notify_result(&result);
+                    results.insert(task.id.clone(), result);
+                    self.state.write().await.completed_tasks += 1;
                 }
-                ExecutionMode::Parallel => {
-                    self.execute_parallel(stage).await?
+            }
+
+            // 2. 启动所有前置任务已结束的任务（不必等待整个阶段）
+            if halted.is_none() {
+                for task in plan.tasks() {
+                    if running.len() >= self.max_parallelism {
+                        break;
+                    }
+                    if results.contains_key(&task.id) || running_ids.contains(&task.id) {
+                        continue;
+                    }
+                    let ready = prerequisites[&task.id]
+                        .iter()
+                        .all(|dep| results.contains_key(dep));
+                    if !ready {
+                        continue;
+                    }
+
+                    self.state.write().await.current_stage = stage_of[task.id.as_str()];
+                    running_ids.insert(task.id.clone());
+
+                    // 执行前替换对前置任务结果的引用（{{t1.output}} 等）
+                    let executor = self.clone_for_task();
+                    let task = task.clone();
+                    let rendered = template::render_task(&task, &results);
+                    running.spawn(async move {
+                        match rendered {
+                            Ok(rendered) => executor.execute_task(&rendered).await,
+                            Err(e) => Self::unexecuted(&task, TaskStatus::Failed, e.to_string()),
+                        }
+                    });
                 }
+            }
+
+            // 3. 等待任一任务完成
+            let Some(joined) = running.join_next().await else {
+                break;
             };
+            let result = joined.map_err(|e| TaskError::Other(format!("任务执行失败: {}", e)))?;
+
+            self.checkpoint(&plan.id, &result);
+            running_ids.remove(&result.task.id);
+
+            if result.status == TaskStatus::Failed && plan.failure_policy == FailurePolicy::FailFast {
+                halted.get_or_insert_with(|| format!("fail-fast: 任务 {} 失败后停止执行", result.task.id));
+            }
+
+            self.notify_status(&result.task.id, &result.status);
+            self.notify_result(&result);
 
-            all_results.extend(stage_results);
+            results.insert(result.task.id.clone(), result);
+            self.state.write().await.completed_tasks += 1;
+
+            // 报告进度
+            self.report_progress().await;
         }
 
         let elapsed = start_time.elapsed().as_secs() as u32;
 
+        // 按计划顺序整理结果（依赖无法满足的任务视为阻塞）
+        let all_results: Vec<TaskResult> = plan
+            .tasks()
+            .map(|task| {
+                results.remove(&task.id).unwrap_or_else(|| {
+                    Self::unexecuted(task, TaskStatus::Blocked, "依赖无法满足".to_string())
+                })
+            })
+            .collect();
+
         // 统计结果
-        let completed = all_results.iter().filter(|r| r.status == TaskStatus::Success).count();
-        let failed = all_results.iter().filter(|r| r.status == TaskStatus::Failed).count();
-        let skipped = all_results.iter().filter(|r| r.status == TaskStatus::Skipped).count();
+        let count = |status: TaskStatus| all_results.iter().filter(|r| r.status == status).count();
         let total_tasks = plan.total_tasks();
 
         Ok(ExecutionResult {
             plan_id: plan.id,
             total_tasks,
-            completed_tasks: completed,
-            failed_tasks: failed,
-            skipped_tasks: skipped,
+            completed_tasks: count(TaskStatus::Success),
+            failed_tasks: count(TaskStatus::Failed),
+            skipped_tasks: count(TaskStatus::Skipped),
+            blocked_tasks: count(TaskStatus::Blocked) + count(TaskStatus::Cancelled),
             total_time: elapsed,
             task_results: all_results,
         })
     }
 
-    /// 串行执行阶段
-    async fn execute_sequential(
-        &self,
-        stage: &ExecutionStage,
-    ) -> TaskOpResult<Vec<TaskResult>> {
-        let mut results = Vec::new();
-
-        for task in &stage.tasks {
-            let result = self.execute_task(task).await;
-            results.push(result);
+    /// 计算每个任务开始前必须结束的任务
+    ///
+    /// - 显式依赖（`depends_on`）
+    /// - 串行阶段内的前一个任务
+    /// - 没有声明依赖的任务：前一阶段的全部任务（保持原有的阶段顺序）
+    ///
+    /// 后两者只约束顺序，不会因失败而阻塞。
+    fn prerequisites(plan: &ExecutionPlan) -> HashMap<String, Vec<String>> {
+        let known: HashSet<&str> = plan.tasks().map(|t| t.id.as_str()).collect();
+        let mut prerequisites = HashMap::new();
+        let mut previous_stage: Vec<String> = Vec::new();
+
+        for stage in &plan.stages {
+            let mut previous_in_stage: Option<&str> = None;
+
+            for task in &stage.tasks {
+                let mut deps: Vec<String> = task
+                    .depends_on
+                    .iter()
+                    .filter(|dep| known.contains(dep.as_str()))
+                    .cloned()
+                    .collect();
+
+                if task.depends_on.is_empty() {
+                    deps.extend(previous_stage.iter().cloned());
+                }
+                if stage.execution_mode == ExecutionMode::Sequential {
+                    deps.extend(previous_in_stage.map(str::to_string));
+                }
 
-            // 更新状态
-            {
-                let mut state = self.state.write().await;
-                state.completed_tasks += 1;
+                prerequisites.insert(task.id.clone(), deps);
+                previous_in_stage = Some(&task.id);
             }
 
-            // 报告进度
-            self.report_progress().await;
+            previous_stage = stage.tasks.iter().map(|t| t.id.clone()).collect();
         }
 
-        Ok(results)
+        prerequisites
     }
 
-    /// 并行执行阶段
-    async fn execute_parallel(
-        &self,
-        stage: &ExecutionStage,
-    ) -> TaskOpResult<Vec<TaskResult>> {
-        let mut handles = Vec::new();
-
-        // 为每个任务创建并发任务
-        for task in stage.tasks.clone() {
-            let executor = self.clone_for_task();
-
-            let handle = tokio::spawn(async move {
-                executor.execute_task(&task).await
-            });
-
-            handles.push(handle);
-        }
-
-        // 等待所有任务完成
-        let mut results = Vec::new();
-        for handle in handles {
-            match handle.await {
-                Ok(result) => {
-                    results.push(result);
-
-                    // 更新状态
-                    {
-                        let mut state = self.state.write().await;
-                        state.completed_tasks += 1;
-                    }
+    /// 若某个显式依赖已失败（或被阻塞、取消），返回阻塞原因
+    fn blocking_reason(
+        graph: &DependencyGraph,
+        task_id: &str,
+        results: &HashMap<String, TaskResult>,
+    ) -> Option<String> {
+        graph.get_dependencies(task_id).iter().find_map(|dep| {
+            results
+                .get(dep)
+                .filter(|r| r.status.blocks_dependents())
+                .map(|r| match r.status {
+                    TaskStatus::Failed => format!("依赖的任务 {} 失败", dep),
+                    _ => format!("依赖的任务 {} 未执行", dep),
+                })
+        })
+    }
 
-                    // 报告进度
-                    self.report_progress().await;
-                }
-                Err(e) => {
-                    return Err(TaskError::Other(format!("任务执行失败: {}", e)));
-                }
-            }
+    /// 构造未执行任务的结果
+    fn unexecuted(task: &SubTask, status: TaskStatus, reason: String) -> TaskResult {
+        let now = Utc::now();
+        TaskResult {
+            task: task.clone(),
+            status,
+            output: String::new(),
+            error: Some(reason),
+            start_time: now,
+            end_time: now,
+            duration: 0,
+            cached: false,
         }
-
-        Ok(results)
     }
 
     /// 执行单个任务
@@ -237,7 +451,27 @@ impl TaskExecutor {
             state.current_task = task.name.clone();
         }
 
+        // 输入与命令未变：复用上次成功的结果
+        let cache_key = self.cache.as_ref().and_then(|_| cache::input_key(task));
+        if let (Some(cache), Some(key), false) = (&self.cache, &cache_key, self.force) {
+            if let Some(entry) = cache.lookup(task, key) {
+                self.notify_status(&task.id, &TaskStatus::Running);
+                let now = Utc::now();
+                return TaskResult {
+                    task: task.clone(),
+                    status: TaskStatus::Success,
+                    output: entry.output,
+                    error: None,
+                    start_time: now,
+                    end_time: now,
+                    duration: 0,
+                    cached: true,
+                };
+            }
+        }
+
         self.report_progress().await;
+        self.notify_status(&task.id, &TaskStatus::Running);
 
         let start_time = Utc::now();
 
@@ -247,6 +481,12 @@ impl TaskExecutor {
         let end_time = Utc::now();
         let duration = (end_time - start_time).num_seconds() as u32;
 
+        if let (Some(cache), Some(key), TaskStatus::Success) = (&self.cache, &cache_key, &status) {
+            if let Err(e) = cache.store(task, key, &output) {
+                eprintln!("⚠ 警告: {}", e);
+            }
+        }
+
         TaskResult {
             task: task.clone(),
             status,
@@ -255,13 +495,17 @@ impl TaskExecutor {
             start_time,
             end_time,
             duration,
+            cached: false,
         }
     }
 
     /// 带重试的任务执行
     async fn execute_with_retry(&self, task: &SubTask) -> (TaskStatus, String, Option<String>) {
-        // 默认重试策略
-        let default_policy = RetryPolicy::simple(3);
+        // 默认重试策略（用户输入不重试，避免重复提问）
+        let default_policy = match task.task_type {
+            TaskType::UserInput => RetryPolicy::simple(0),
+            _ => RetryPolicy::simple(3),
+        };
         let retry_policy = task.retry_policy.as_ref().unwrap_or(&default_policy);
 
         for attempt in 0..=retry_policy.max_retries {
@@ -276,7 +520,7 @@ impl TaskExecutor {
             }
 
             // 执行命令
-            let result = self.execute_command(&task.command).await;
+            let result = self.run_task(task).await;
 
             match result {
                 Ok(output) => {
@@ -299,6 +543,32 @@ impl TaskExecutor {
         (TaskStatus::Failed, String::new(), Some("重试次数用尽".to_string()))
     }
 
+    /// 按任务类型分派执行
+    ///
+    /// `FileOperation` / `Network` 任务带有工具调用参数时通过 ToolRegistry 执行，
+    /// 否则（如 LLM 只给出了命令）按 Shell 命令执行。
+    async fn run_task(&self, task: &SubTask) -> TaskOpResult<String> {
+        match task.task_type {
+            TaskType::Shell => self.execute_command(task).await,
+            TaskType::Validation => {
+                Assertion::from_task(task)?
+                    .evaluate(task.timeout.or(self.timeout))
+                    .await
+            }
+            TaskType::UserInput => handlers::prompt_user(task, &self.input_provider).await,
+            TaskType::FileOperation | TaskType::Network => match ToolCallSpec::from_task(task)? {
+                Some(spec) => {
+                    let registry = self
+                        .tools
+                        .as_ref()
+                        .ok_or_else(|| TaskError::ToolError("未配置工具注册表".to_string()))?;
+                    spec.invoke(&*registry.read().await)
+                }
+                None => self.execute_command(task).await,
+            },
+        }
+    }
+
     /// 预处理命令（修复常见问题）
     ///
     /// 处理独立的 cd 命令问题：检测是否为单独的 cd 命令并警告
@@ -318,12 +588,12 @@ impl TaskExecutor {
     }
 
     /// 执行命令
-    async fn execute_command(&self, command: &str) -> TaskOpResult<String> {
+    async fn execute_command(&self, task: &SubTask) -> TaskOpResult<String> {
         // 预处理命令
-        let processed_command = self.preprocess_command(command);
+        let processed_command = self.preprocess_command(&task.command);
 
-        // 应用超时
-        if let Some(timeout) = self.timeout {
+        // 应用超时（任务自身的超时优先）
+        if let Some(timeout) = task.timeout.or(self.timeout) {
             match tokio::time::timeout(
                 Duration::from_secs(timeout),
                 self.shell_executor.execute_with_analysis(&processed_command),
@@ -360,6 +630,29 @@ impl TaskExecutor {
         }
     }
 
+    /// 通知任务状态变化
+    fn notify_status(&self, task_id: &str, status: &TaskStatus) {
+        if let Some(callback) = &self.status_callback {
+            callback(task_id, status);
+        }
+    }
+
+    /// 通知任务最终结果
+    fn notify_result(&self, result: &TaskResult) {
+        if let Some(callback) = &self.result_callback {
+            callback(result);
+        }
+    }
+
+    /// 将任务结果写入检查点（失败只警告，不中断执行）
+    fn checkpoint(&self, plan_id: &str, result: &TaskResult) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.record_result(plan_id, result) {
+                eprintln!("⚠ 警告: 保存任务检查点失败: {}", e);
+            }
+        }
+    }
+
     /// 报告进度
     async fn report_progress(&self) {
         if let Some(callback) = &self.progress_callback {
@@ -408,8 +701,16 @@ impl TaskExecutor {
         Self {
             shell_executor: Arc::clone(&self.shell_executor),
             progress_callback: self.progress_callback.clone(),
+            status_callback: self.status_callback.clone(),
+            result_callback: self.result_callback.clone(),
             state: Arc::clone(&self.state),
             timeout: self.timeout,
+            store: self.store.clone(),
+            max_parallelism: self.max_parallelism,
+            tools: self.tools.clone(),
+            input_provider: Arc::clone(&self.input_provider),
+            cache: self.cache.clone(),
+            force: self.force,
         }
     }
 }
@@ -417,6 +718,7 @@ impl TaskExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::task::types::ExecutionStage;
 
     fn create_test_executor() -> TaskExecutor {
         let shell_executor = Arc::new(ShellExecutorWithFixer::new());
@@ -613,6 +915,25 @@ mod tests {
         let _ = std::fs::remove_dir_all("/tmp/test_realconsole_cd");
     }
 
+    #[tokio::test]
+    async fn test_standalone_cd_does_not_carry_over() {
+        // 独立 cd 不影响后续任务的工作目录；所有文件都写在临时目录中
+        let dir = tempfile::TempDir::new().unwrap();
+        let executor = create_test_executor();
+
+        let tasks = vec![
+            SubTask::new("t1", "Standalone cd", format!("cd {}", dir.path().display())),
+            SubTask::new("t2", "Print dir", "pwd"),
+            SubTask::new("t3", "cd && touch", format!("cd {} && touch marker.txt", dir.path().display())),
+        ];
+        let result = executor.execute(create_test_plan(tasks)).await.unwrap();
+
+        assert_eq!(result.completed_tasks, 3);
+        let pwd = result.task_results.iter().find(|r| r.task.id == "t2").unwrap();
+        assert_ne!(pwd.output.trim(), dir.path().display().to_string());
+        assert!(dir.path().join("marker.txt").exists());
+    }
+
     #[tokio::test]
     async fn test_cd_with_command_works() {
         // 测试 cd && command 的正确用法
@@ -633,4 +954,364 @@ mod tests {
         // 清理
         let _ = std::fs::remove_dir_all("/tmp/test_realconsole_cd2");
     }
+
+    #[tokio::test]
+    async fn test_checkpoint_and_resume() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let store = Arc::new(PlanStore::new(dir.path()));
+        let marker = dir.path().join("ran_t1");
+        let gate = dir.path().join("gate");
+
+        // t1 记录执行次数；t2 在 gate 文件出现前一直失败
+        let tasks = vec![
+            SubTask::new("t1", "Mark", format!("echo x >> {}", marker.display())),
+            SubTask::new("t2", "Gate", format!("test -f {}", gate.display()))
+                .with_retry_policy(RetryPolicy::simple(0)),
+        ];
+        let plan = create_test_plan(tasks);
+        let plan_id = plan.id.clone();
+
+        let executor = create_test_executor().with_store(Arc::clone(&store));
+        let result = executor.execute(plan).await.unwrap();
+        assert_eq!(result.failed_tasks, 1);
+
+        let checkpoint = store.load(&plan_id).unwrap();
+        assert_eq!(checkpoint.status_of("t1"), TaskStatus::Success);
+        assert_eq!(checkpoint.status_of("t2"), TaskStatus::Failed);
+        assert_eq!(checkpoint.next_task().unwrap().id, "t2");
+
+        // 修复后恢复执行：t1 不再执行，只重跑 t2
+        std::fs::write(&gate, "").unwrap();
+        let result = executor
+            .resume(checkpoint.plan.clone(), checkpoint.succeeded())
+            .await
+            .unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(std::fs::read_to_string(&marker).unwrap().lines().count(), 1);
+        assert!(store.load(&plan_id).unwrap().is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_resume_parallel_stage() {
+        let tasks = vec![
+            SubTask::new("t1", "Done before", "false"),
+            SubTask::new("t2", "Pending", "echo 'task2'"),
+        ];
+        let plan = ExecutionPlan::new(
+            "parallel resume",
+            vec![ExecutionStage::new(0, tasks.clone(), ExecutionMode::Parallel)],
+        );
+
+        // t1 之前已成功：即使命令现在会失败，也不会再执行
+        let now = Utc::now();
+        let previous = vec![TaskResult {
+            task: tasks[0].clone(),
+            status: TaskStatus::Success,
+            output: "cached".to_string(),
+            error: None,
+            start_time: now,
+            end_time: now,
+            duration: 0,
+            cached: false,
+        }];
+
+        let result = create_test_executor().resume(plan, previous).await.unwrap();
+        assert_eq!(result.completed_tasks, 2);
+        assert!(result.task_results.iter().any(|r| r.output == "cached"));
+    }
+
+    fn failing(id: &str) -> SubTask {
+        SubTask::new(id, id, "false").with_retry_policy(RetryPolicy::simple(0))
+    }
+
+    #[tokio::test]
+    async fn test_failure_blocks_dependents() {
+        let stages = vec![
+            ExecutionStage::new(0, vec![failing("t1"), SubTask::new("t3", "Independent", "echo 3")], ExecutionMode::Parallel),
+            ExecutionStage::new(
+                1,
+                vec![
+                    SubTask::new("t2", "Needs t1", "echo 2").with_dependency("t1"),
+                    SubTask::new("t4", "Needs t2", "echo 4").with_dependency("t2"),
+                ],
+                ExecutionMode::Parallel,
+            ),
+        ];
+        let plan = ExecutionPlan::new("blocked", stages);
+
+        let result = create_test_executor().execute(plan).await.unwrap();
+
+        let status: HashMap<&str, &TaskResult> =
+            result.task_results.iter().map(|r| (r.task.id.as_str(), r)).collect();
+        assert_eq!(status["t1"].status, TaskStatus::Failed);
+        assert_eq!(status["t3"].status, TaskStatus::Success);
+        assert_eq!(status["t2"].status, TaskStatus::Blocked);
+        assert!(status["t2"].error.as_ref().unwrap().contains("t1"));
+        // 阻塞会沿依赖链传递
+        assert_eq!(status["t4"].status, TaskStatus::Blocked);
+
+        assert_eq!(result.failed_tasks, 1);
+        assert_eq!(result.blocked_tasks, 2);
+        assert_eq!(result.completed_tasks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_skipped_dependency_does_not_block() {
+        let tasks = vec![
+            failing("t1").skippable(),
+            SubTask::new("t2", "Needs t1", "echo 2").with_dependency("t1"),
+        ];
+        let result = create_test_executor().execute(create_test_plan(tasks)).await.unwrap();
+
+        assert_eq!(result.skipped_tasks, 1);
+        assert_eq!(result.completed_tasks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_fail_fast_cancels_remaining() {
+        let tasks = vec![failing("t1"), SubTask::new("t2", "Unrelated", "echo 2")];
+        let plan = create_test_plan(tasks).with_failure_policy(FailurePolicy::FailFast);
+
+        let result = create_test_executor().execute(plan).await.unwrap();
+
+        assert_eq!(result.task_results[1].status, TaskStatus::Cancelled);
+        assert!(result.task_results[1].error.as_ref().unwrap().contains("fail-fast"));
+        assert_eq!(result.blocked_tasks, 1);
+
+        // 默认策略下无关任务照常执行
+        let tasks = vec![failing("t1"), SubTask::new("t2", "Unrelated", "echo 2")];
+        let result = create_test_executor().execute(create_test_plan(tasks)).await.unwrap();
+        assert_eq!(result.task_results[1].status, TaskStatus::Success);
+    }
+
+    #[tokio::test]
+    async fn test_tasks_start_when_dependencies_complete() {
+        // fast2 只依赖 fast1，不必等待同阶段的 slow 完成
+        let stages = vec![
+            ExecutionStage::new(
+                0,
+                vec![SubTask::new("slow", "Slow", "sleep 1"), SubTask::new("fast1", "Fast 1", "true")],
+                ExecutionMode::Parallel,
+            ),
+            ExecutionStage::new(
+                1,
+                vec![SubTask::new("fast2", "Fast 2", "true").with_dependency("fast1")],
+                ExecutionMode::Sequential,
+            ),
+        ];
+        let plan = ExecutionPlan::new("no barrier", stages);
+
+        let result = create_test_executor().execute(plan).await.unwrap();
+        assert!(result.is_success());
+
+        let slow = &result.task_results[0];
+        let fast2 = &result.task_results[2];
+        assert_eq!(fast2.task.id, "fast2");
+        assert!(fast2.end_time < slow.end_time);
+    }
+
+    #[test]
+    fn test_prerequisites_keep_stage_order() {
+        let stages = vec![
+            ExecutionStage::new(0, vec![SubTask::new("a", "A", "true"), SubTask::new("b", "B", "true")], ExecutionMode::Sequential),
+            ExecutionStage::new(1, vec![SubTask::new("c", "C", "true")], ExecutionMode::Sequential),
+            ExecutionStage::new(2, vec![SubTask::new("d", "D", "true").with_dependency("a")], ExecutionMode::Sequential),
+        ];
+        let plan = ExecutionPlan::new("order", stages);
+        let prerequisites = TaskExecutor::prerequisites(&plan);
+
+        assert!(prerequisites["a"].is_empty());
+        assert_eq!(prerequisites["b"], vec!["a"]);
+        // 未声明依赖的任务等待前一阶段
+        assert_eq!(prerequisites["c"], vec!["a", "b"]);
+        // 声明了依赖的任务只等待其依赖
+        assert_eq!(prerequisites["d"], vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_typed_task_handlers() {
+        use serde_json::json;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+
+        let mut registry = ToolRegistry::new();
+        crate::builtin_tools::register_builtin_tools(&mut registry);
+        let answers: InputProvider = Arc::new(|_: &str| Some("hello from user".to_string()));
+
+        let executor = create_test_executor()
+            .with_tool_registry(Arc::new(RwLock::new(registry)))
+            .with_input_provider(answers);
+
+        let tasks = vec![
+            SubTask::new("ask", "Ask", "").with_task_type(TaskType::UserInput),
+            SubTask::new("write", "Write", "")
+                .with_task_type(TaskType::FileOperation)
+                .with_args(json!({"tool": "write_file", "args": {"path": path.to_str().unwrap(), "content": "x"}}))
+                .with_dependency("ask"),
+            SubTask::new("check", "Check", format!("exists {}", path.display()))
+                .with_task_type(TaskType::Validation)
+                .with_dependency("write"),
+            SubTask::new("fail", "Fail", "exists /no/such/path")
+                .with_task_type(TaskType::Validation)
+                .with_retry_policy(RetryPolicy::simple(0)),
+        ];
+        let result = executor.execute(create_test_plan(tasks)).await.unwrap();
+
+        let results = &result.task_results;
+        assert_eq!(results[0].output, "hello from user");
+        assert_eq!(results[1].status, TaskStatus::Success);
+        assert!(results[1].output.contains("已写入"));
+        assert_eq!(results[2].status, TaskStatus::Success);
+        assert_eq!(results[3].status, TaskStatus::Failed);
+        assert!(results[3].error.as_ref().unwrap().contains("验证失败"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_task_without_registry_fails() {
+        use serde_json::json;
+
+        let task = SubTask::new("net", "Fetch", "")
+            .with_task_type(TaskType::Network)
+            .with_args(json!({"tool": "http_get", "args": {"url": "http://localhost"}}))
+            .with_retry_policy(RetryPolicy::simple(0));
+
+        let result = create_test_executor().execute(create_test_plan(vec![task])).await.unwrap();
+        assert_eq!(result.failed_tasks, 1);
+        assert!(result.task_results[0].error.as_ref().unwrap().contains("未配置工具注册表"));
+    }
+
+    #[tokio::test]
+    async fn test_output_references_between_tasks() {
+        let executor = create_test_executor();
+        let tasks = vec![
+            SubTask::new("t1", "Version", "echo 'tool v1.2.3'"),
+            SubTask::new("t2", "Quote", "printf '%s' \"it's; echo pwned\""),
+            SubTask::new(
+                "t3",
+                "Use",
+                "echo {{t1.output | regex:v(\\S+)}} {{t2.output}}",
+            )
+            .with_dependency("t1")
+            .with_dependency("t2"),
+        ];
+
+        let result = executor.execute(create_test_plan(tasks)).await.unwrap();
+        assert_eq!(result.completed_tasks, 3);
+        assert_eq!(result.task_results[2].output.trim(), "1.2.3 it's; echo pwned");
+        // 记录的是实际执行的命令
+        assert!(result.task_results[2].task.command.starts_with("echo 1.2.3 "));
+    }
+
+    #[tokio::test]
+    async fn test_unresolvable_reference_fails_task() {
+        let executor = create_test_executor();
+        let tasks = vec![
+            SubTask::new("t1", "Version", "echo none"),
+            SubTask::new("t2", "Use", "echo {{t1.output | regex:v(\\d+)}}")
+                .with_dependency("t1")
+                .with_retry_policy(RetryPolicy::simple(0)),
+        ];
+
+        let result = executor.execute(create_test_plan(tasks)).await.unwrap();
+        assert_eq!(result.task_results[1].status, TaskStatus::Failed);
+        assert!(result.task_results[1].error.as_ref().unwrap().contains("不匹配"));
+    }
+
+    #[tokio::test]
+    async fn test_status_callback() {
+        use std::sync::Mutex;
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let callback: StatusCallback = Arc::new(move |id: &str, status: &TaskStatus| {
+            recorded.lock().unwrap().push(format!("{}:{:?}", id, status));
+        });
+
+        let tasks = vec![
+            failing("t1"),
+            SubTask::new("t2", "Task 2", "echo 2").with_dependency("t1"),
+        ];
+        let executor = create_test_executor().with_status_callback(callback);
+        executor.execute(create_test_plan(tasks)).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(*events, vec!["t1:Running", "t1:Failed", "t2:Blocked"]);
+    }
+
+    #[tokio::test]
+    async fn test_step_cache_skips_unchanged_inputs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("input.txt");
+        let marker = dir.path().join("runs");
+        std::fs::write(&input, "v1").unwrap();
+
+        let tasks = vec![
+            SubTask::new("t1", "Build", format!("cat {} && echo x >> {}", input.display(), marker.display()))
+                .with_inputs([input.to_string_lossy().to_string()]),
+            SubTask::new("t2", "Always", "echo always"),
+        ];
+        let runs = || std::fs::read_to_string(&marker).unwrap().lines().count();
+
+        let cache = Arc::new(StepCache::new());
+        let executor = create_test_executor().with_step_cache(Arc::clone(&cache));
+
+        let first = executor.execute(create_test_plan(tasks.clone())).await.unwrap();
+        assert!(first.is_success());
+        assert!(!first.task_results[0].cached);
+
+        // 输入未变：复用缓存结果（包括输出），未声明输入的任务照常执行
+        let second = executor.execute(create_test_plan(tasks.clone())).await.unwrap();
+        assert!(second.task_results[0].cached);
+        assert!(second.task_results[0].output.contains("v1"));
+        assert!(!second.task_results[1].cached);
+        assert_eq!(runs(), 1);
+
+        // --force 绕过缓存
+        let forced = create_test_executor()
+            .with_step_cache(Arc::clone(&cache))
+            .with_force(true);
+        forced.execute(create_test_plan(tasks.clone())).await.unwrap();
+        assert_eq!(runs(), 2);
+
+        // 输入改变后重新执行
+        std::fs::write(&input, "v2").unwrap();
+        let changed = executor.execute(create_test_plan(tasks)).await.unwrap();
+        assert!(!changed.task_results[0].cached);
+        assert_eq!(runs(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_step_cache_hit_reports_running() {
+        use std::sync::Mutex;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let input = dir.path().join("input.txt");
+        std::fs::write(&input, "v1").unwrap();
+        let tasks = vec![SubTask::new("t1", "Build", format!("cat {}", input.display()))
+            .with_inputs([input.to_string_lossy().to_string()])];
+
+        let cache = Arc::new(StepCache::new());
+        create_test_executor()
+            .with_step_cache(Arc::clone(&cache))
+            .execute(create_test_plan(tasks.clone()))
+            .await
+            .unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&events);
+        let callback: StatusCallback = Arc::new(move |id: &str, status: &TaskStatus| {
+            recorded.lock().unwrap().push(format!("{}:{:?}", id, status));
+        });
+        let result = create_test_executor()
+            .with_step_cache(cache)
+            .with_status_callback(callback)
+            .execute(create_test_plan(tasks))
+            .await
+            .unwrap();
+
+        assert!(result.task_results[0].cached);
+        assert_eq!(*events.lock().unwrap(), vec!["t1:Running", "t1:Success"]);
+    }
 }
@@ -0,0 +1,435 @@
+//! 计划依赖图渲染 (Plan Graph)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 把执行计划的依赖关系画出来：终端里的 ASCII DAG（可按执行状态着色），
+//! 以及可贴进文档与评审的 Graphviz DOT、Mermaid。关键路径（估计耗时最长的
+//! 依赖链）在三种形式中都会被突出显示。
+
+use super::types::{ExecutionPlan, ExecutionResult, SubTask, TaskStatus};
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+
+/// 各任务的执行状态（未出现的任务视为 Pending）
+pub type StatusMap = HashMap<String, TaskStatus>;
+
+/// 从执行结果提取任务状态
+pub fn statuses(result: &ExecutionResult) -> StatusMap {
+    result
+        .task_results
+        .iter()
+        .map(|r| (r.task.id.clone(), r.status.clone()))
+        .collect()
+}
+
+/// 关键路径：按显式依赖计算的估计耗时最长的任务链
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CriticalPath {
+    /// 路径上的任务 ID（按执行顺序）
+    pub tasks: Vec<String>,
+    /// 路径总估计时间（秒）
+    pub time: u32,
+}
+
+impl CriticalPath {
+    /// 计算计划的关键路径
+    pub fn of(plan: &ExecutionPlan) -> Self {
+        let order = topological_order(plan);
+        let by_id: HashMap<&str, &SubTask> = plan.tasks().map(|t| (t.id.as_str(), t)).collect();
+
+        // 以每个任务结尾的最长路径耗时及其前驱
+        let mut finish: HashMap<&str, u32> = HashMap::new();
+        let mut prev: HashMap<&str, &str> = HashMap::new();
+        for task in &order {
+            let best = task
+                .depends_on
+                .iter()
+                .filter_map(|dep| finish.get_key_value(dep.as_str()))
+                .max_by_key(|(_, time)| **time)
+                .map(|(dep, time)| (*dep, *time));
+            let start = best.map_or(0, |(_, time)| time);
+            if let Some((dep, _)) = best {
+                prev.insert(task.id.as_str(), dep);
+            }
+            finish.insert(task.id.as_str(), start + task.estimated_time);
+        }
+
+        // 取结束最晚的任务，沿前驱回溯（同耗时取计划中靠前的任务）
+        let Some(last) = order
+            .iter()
+            .rev()
+            .max_by_key(|t| finish[t.id.as_str()])
+            .map(|t| t.id.as_str())
+        else {
+            return Self::default();
+        };
+
+        let mut tasks = vec![last.to_string()];
+        let mut current = last;
+        while let Some(dep) = prev.get(current) {
+            tasks.push(dep.to_string());
+            current = dep;
+        }
+        tasks.reverse();
+
+        let time = tasks.iter().map(|id| by_id[id.as_str()].estimated_time).sum();
+        Self { tasks, time }
+    }
+
+    /// 任务是否在关键路径上
+    pub fn contains(&self, task_id: &str) -> bool {
+        self.tasks.iter().any(|id| id == task_id)
+    }
+
+    /// 依赖边是否在关键路径上
+    pub fn has_edge(&self, from: &str, to: &str) -> bool {
+        self.tasks.windows(2).any(|w| w[0] == from && w[1] == to)
+    }
+}
+
+/// 按显式依赖的拓扑序排列任务（计划外的依赖被忽略，环上的任务排在最后）
+fn topological_order(plan: &ExecutionPlan) -> Vec<&SubTask> {
+    let ids: HashSet<&str> = plan.tasks().map(|t| t.id.as_str()).collect();
+    let mut placed: HashSet<&str> = HashSet::new();
+    let mut order = Vec::new();
+
+    loop {
+        let ready: Vec<&SubTask> = plan
+            .tasks()
+            .filter(|t| !placed.contains(t.id.as_str()))
+            .filter(|t| {
+                t.depends_on
+                    .iter()
+                    .all(|d| !ids.contains(d.as_str()) || placed.contains(d.as_str()))
+            })
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        for task in ready {
+            placed.insert(task.id.as_str());
+            order.push(task);
+        }
+    }
+
+    order.extend(plan.tasks().filter(|t| !placed.contains(t.id.as_str())));
+    order
+}
+
+/// 任务所在的层（最长依赖链的深度，从 0 开始）
+fn levels(plan: &ExecutionPlan) -> Vec<Vec<&SubTask>> {
+    let mut level: HashMap<&str, usize> = HashMap::new();
+    for task in topological_order(plan) {
+        let depth = task
+            .depends_on
+            .iter()
+            .filter_map(|d| level.get(d.as_str()))
+            .map(|l| l + 1)
+            .max()
+            .unwrap_or(0);
+        level.insert(task.id.as_str(), depth);
+    }
+
+    let mut layers: Vec<Vec<&SubTask>> = Vec::new();
+    for task in plan.tasks() {
+        let depth = level[task.id.as_str()];
+        if layers.len() <= depth {
+            layers.resize_with(depth + 1, Vec::new);
+        }
+        layers[depth].push(task);
+    }
+    layers
+}
+
+fn status_of<'a>(statuses: &'a StatusMap, task_id: &str) -> &'a TaskStatus {
+    statuses.get(task_id).unwrap_or(&TaskStatus::Pending)
+}
+
+/// 渲染 ASCII 依赖图（按层排列，★ 标记关键路径）
+pub fn render_ascii(plan: &ExecutionPlan, statuses: &StatusMap) -> String {
+    let critical = CriticalPath::of(plan);
+    let layers = levels(plan);
+    let mut output = String::new();
+
+    output.push_str(&format!(
+        "{} {} {}\n",
+        "关键路径".dimmed(),
+        critical.tasks.join(" → ").yellow().bold(),
+        format!(
+            "· {}秒 (总估计 {}秒)",
+            critical.time, plan.total_estimated_time
+        )
+        .dimmed()
+    ));
+
+    for (idx, layer) in layers.iter().enumerate() {
+        let is_last = idx == layers.len() - 1;
+        let (head, body) = match (idx, is_last) {
+            (0, true) => ("─", " "),
+            (0, false) => ("┌", "│"),
+            (_, true) => ("└", " "),
+            _ => ("├", "│"),
+        };
+        output.push_str(&format!("{} {}\n", head.dimmed(), format!("层 {}", idx + 1).dimmed()));
+
+        for task in layer {
+            let status = status_of(statuses, &task.id);
+            let marker = if critical.contains(&task.id) {
+                "★".yellow().to_string()
+            } else {
+                " ".to_string()
+            };
+            let label = format!("{} {}", task.id, task.name);
+            let label = if critical.contains(&task.id) {
+                label.bold().to_string()
+            } else {
+                label
+            };
+            let deps = if task.depends_on.is_empty() {
+                String::new()
+            } else {
+                format!("  ← {}", task.depends_on.join(", ")).dimmed().to_string()
+            };
+
+            output.push_str(&format!(
+                "{}  {} {} {} {}{}\n",
+                body.dimmed(),
+                marker,
+                status_icon(status),
+                label,
+                format!("{}s", task.estimated_time).dimmed(),
+                deps
+            ));
+        }
+    }
+
+    output
+}
+
+fn status_icon(status: &TaskStatus) -> String {
+    match status {
+        TaskStatus::Pending => "○".dimmed().to_string(),
+        TaskStatus::Running => "◐".cyan().to_string(),
+        TaskStatus::Success => "✓".green().to_string(),
+        TaskStatus::Failed => "✗".red().to_string(),
+        TaskStatus::Skipped => "⊘".yellow().to_string(),
+        TaskStatus::Blocked | TaskStatus::Cancelled => "⊗".yellow().to_string(),
+    }
+}
+
+/// 状态对应的填充色（DOT / Mermaid 共用）
+fn status_fill(status: &TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "#ffffff",
+        TaskStatus::Running => "#d6ecff",
+        TaskStatus::Success => "#d9f7d9",
+        TaskStatus::Failed => "#ffd6d6",
+        TaskStatus::Skipped => "#eeeeee",
+        TaskStatus::Blocked | TaskStatus::Cancelled => "#fff2cc",
+    }
+}
+
+/// 导出 Graphviz DOT
+pub fn to_dot(plan: &ExecutionPlan, statuses: &StatusMap) -> String {
+    let critical = CriticalPath::of(plan);
+    let ids: HashSet<&str> = plan.tasks().map(|t| t.id.as_str()).collect();
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let mut out = String::from("digraph plan {\n");
+    out.push_str("  rankdir=LR;\n");
+    out.push_str(&format!(
+        "  label=\"{}\\n总估计 {}s · 关键路径 {}s\";\n",
+        escape(&plan.goal),
+        plan.total_estimated_time,
+        critical.time
+    ));
+    out.push_str("  node [shape=box, style=\"rounded,filled\"];\n");
+
+    for task in plan.tasks() {
+        let emphasis = if critical.contains(&task.id) {
+            ", color=\"#d33\", penwidth=2"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\\n{}\\n{}s\", fillcolor=\"{}\"{}];\n",
+            escape(&task.id),
+            escape(&task.id),
+            escape(&task.name),
+            task.estimated_time,
+            status_fill(status_of(statuses, &task.id)),
+            emphasis
+        ));
+    }
+
+    for task in plan.tasks() {
+        for dep in task.depends_on.iter().filter(|d| ids.contains(d.as_str())) {
+            let emphasis = if critical.has_edge(dep, &task.id) {
+                " [color=\"#d33\", penwidth=2]"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\"{};\n",
+                escape(dep),
+                escape(&task.id),
+                emphasis
+            ));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// 导出 Mermaid 流程图
+pub fn to_mermaid(plan: &ExecutionPlan, statuses: &StatusMap) -> String {
+    let critical = CriticalPath::of(plan);
+    let ids: HashSet<&str> = plan.tasks().map(|t| t.id.as_str()).collect();
+    let node = |id: &str| -> String {
+        id.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect()
+    };
+    let escape = |s: &str| s.replace('"', "#quot;");
+
+    let mut out = String::from("flowchart LR\n");
+    out.push_str(&format!(
+        "  %% {} · 总估计 {}s · 关键路径 {}s\n",
+        plan.goal.replace('\n', " "),
+        plan.total_estimated_time,
+        critical.time
+    ));
+
+    for task in plan.tasks() {
+        out.push_str(&format!(
+            "  {}[\"{}: {} ({}s)\"]\n",
+            node(&task.id),
+            escape(&task.id),
+            escape(&task.name),
+            task.estimated_time
+        ));
+    }
+
+    let mut edge = 0;
+    let mut critical_edges = Vec::new();
+    for task in plan.tasks() {
+        for dep in task.depends_on.iter().filter(|d| ids.contains(d.as_str())) {
+            out.push_str(&format!("  {} --> {}\n", node(dep), node(&task.id)));
+            if critical.has_edge(dep, &task.id) {
+                critical_edges.push(edge.to_string());
+            }
+            edge += 1;
+        }
+    }
+
+    // 状态着色
+    for task in plan.tasks() {
+        let status = status_of(statuses, &task.id);
+        if *status != TaskStatus::Pending {
+            out.push_str(&format!("  style {} fill:{}\n", node(&task.id), status_fill(status)));
+        }
+    }
+
+    // 关键路径
+    if !critical.tasks.is_empty() {
+        out.push_str("  classDef critical stroke:#d33,stroke-width:3px\n");
+        let members: Vec<String> = critical.tasks.iter().map(|id| node(id)).collect();
+        out.push_str(&format!("  class {} critical\n", members.join(",")));
+    }
+    if !critical_edges.is_empty() {
+        out.push_str(&format!(
+            "  linkStyle {} stroke:#d33,stroke-width:3px\n",
+            critical_edges.join(",")
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::types::{ExecutionMode, ExecutionStage};
+
+    fn task(id: &str, time: u32, deps: &[&str]) -> SubTask {
+        let mut task = SubTask::new(id, format!("Task {}", id), "true").with_estimated_time(time);
+        for dep in deps {
+            task = task.with_dependency(*dep);
+        }
+        task
+    }
+
+    fn diamond() -> ExecutionPlan {
+        let stages = vec![
+            ExecutionStage::new(0, vec![task("a", 5, &[])], ExecutionMode::Sequential),
+            ExecutionStage::new(
+                1,
+                vec![task("b", 30, &["a"]), task("c", 10, &["a"])],
+                ExecutionMode::Parallel,
+            ),
+            ExecutionStage::new(2, vec![task("d", 5, &["b", "c"])], ExecutionMode::Sequential),
+        ];
+        ExecutionPlan::new("diamond \"goal\"", stages)
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let path = CriticalPath::of(&diamond());
+        assert_eq!(path.tasks, vec!["a", "b", "d"]);
+        assert_eq!(path.time, 40);
+        assert!(path.has_edge("a", "b"));
+        assert!(!path.has_edge("a", "c"));
+
+        let empty = ExecutionPlan::new("empty", Vec::new());
+        assert_eq!(CriticalPath::of(&empty), CriticalPath::default());
+    }
+
+    #[test]
+    fn test_levels() {
+        let plan = diamond();
+        let layers = levels(&plan);
+        let ids: Vec<Vec<&str>> = layers
+            .iter()
+            .map(|l| l.iter().map(|t| t.id.as_str()).collect())
+            .collect();
+        assert_eq!(ids, vec![vec!["a"], vec!["b", "c"], vec!["d"]]);
+    }
+
+    #[test]
+    fn test_render_ascii() {
+        let mut statuses = StatusMap::new();
+        statuses.insert("a".to_string(), TaskStatus::Success);
+
+        let text = render_ascii(&diamond(), &statuses);
+        assert!(text.contains("层 3"));
+        assert!(text.contains("b Task b"));
+        assert!(text.contains("← b, c"));
+        assert!(text.contains("40秒"));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut statuses = StatusMap::new();
+        statuses.insert("b".to_string(), TaskStatus::Failed);
+
+        let dot = to_dot(&diamond(), &statuses);
+        assert!(dot.starts_with("digraph plan {"));
+        assert!(dot.contains("diamond \\\"goal\\\""));
+        assert!(dot.contains("\"a\" -> \"b\" [color=\"#d33\", penwidth=2];"));
+        assert!(dot.contains("\"a\" -> \"c\";"));
+        assert!(dot.contains("fillcolor=\"#ffd6d6\""));
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let mermaid = to_mermaid(&diamond(), &StatusMap::new());
+        assert!(mermaid.starts_with("flowchart LR"));
+        assert!(mermaid.contains("a[\"a: Task a (5s)\"]"));
+        assert!(mermaid.contains("  a --> b\n"));
+        assert!(mermaid.contains("class a,b,d critical"));
+        // 边顺序: a->b(0), a->c(1), b->d(2), c->d(3)
+        assert!(mermaid.contains("linkStyle 0,2 stroke"));
+        assert!(!mermaid.contains("style a fill"));
+    }
+}
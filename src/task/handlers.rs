@@ -0,0 +1,429 @@
+//! 非 Shell 任务处理器
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 按 [`TaskType`] 分派任务：
+//! - `UserInput` - 暂停并在 REPL 中提示用户输入（或确认）
+//! - `Validation` - 评估断言：退出码、文件存在、输出匹配正则
+//! - `FileOperation` / `Network` - 通过 `ToolRegistry` 以类型化参数调用工具
+//!
+//! 各处理器从 `SubTask::args` 读取结构化参数；缺省时尽量从 `command` 推断。
+
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use crate::shell_util::shell_unquote;
+use super::types::{SubTask, TaskType};
+use crate::tool::ToolRegistry;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 用户输入提供者：接收提示语，返回用户输入（None 表示无法获取输入）
+pub type InputProvider = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// 从标准输入读取用户输入
+pub fn stdin_input_provider() -> InputProvider {
+    Arc::new(|prompt: &str| {
+        print!("{} ", prompt);
+        let _ = io::stdout().flush();
+
+        let mut input = String::new();
+        match io::stdin().read_line(&mut input) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(input.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    })
+}
+
+// ========== UserInput ==========
+
+/// 用户输入任务的参数
+///
+/// ```json
+/// {"prompt": "确认部署到生产环境?", "confirm": true}
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+struct UserInputArgs {
+    /// 提示语（默认使用任务描述或名称）
+    prompt: Option<String>,
+    /// 是否为确认型问题（只有 y/yes 才继续）
+    #[serde(default)]
+    confirm: bool,
+}
+
+/// 提示用户输入
+///
+/// 普通输入任务返回用户输入的内容作为任务输出；
+/// 确认型任务在用户未回答 y/yes 时失败。
+pub async fn prompt_user(task: &SubTask, provider: &InputProvider) -> TaskOpResult<String> {
+    let args: UserInputArgs = parse_args(task)?.unwrap_or_default();
+    let prompt = args.prompt.unwrap_or_else(|| {
+        if task.description.is_empty() {
+            task.name.clone()
+        } else {
+            task.description.clone()
+        }
+    });
+    let prompt = if args.confirm {
+        format!("{} [y/N]:", prompt)
+    } else {
+        format!("{}:", prompt)
+    };
+
+    // 读取输入会阻塞，放到阻塞线程中执行
+    let provider = Arc::clone(provider);
+    let answer = tokio::task::spawn_blocking(move || provider(&prompt))
+        .await
+        .map_err(|e| TaskError::Other(format!("读取用户输入失败: {}", e)))?
+        .ok_or_else(|| TaskError::UserInputRejected("未获取到输入".to_string()))?;
+
+    if args.confirm {
+        let answer = answer.trim().to_lowercase();
+        if matches!(answer.as_str(), "y" | "yes") {
+            Ok("已确认".to_string())
+        } else {
+            Err(TaskError::UserInputRejected("用户未确认".to_string()))
+        }
+    } else {
+        Ok(answer)
+    }
+}
+
+// ========== Validation ==========
+
+/// 验证断言
+///
+/// 在 `args` 中以 `check` 字段区分：
+///
+/// ```json
+/// {"check": "exit_code", "command": "cargo check", "expect": 0}
+/// {"check": "file_exists", "path": "target/release/app"}
+/// {"check": "output_matches", "command": "node --version", "pattern": "^v(18|20)"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "check", rename_all = "snake_case")]
+pub enum Assertion {
+    /// 命令退出码等于期望值
+    ExitCode {
+        command: String,
+        #[serde(default)]
+        expect: i32,
+    },
+    /// 文件或目录存在
+    FileExists { path: String },
+    /// 命令输出匹配正则
+    OutputMatches { command: String, pattern: String },
+}
+
+impl Assertion {
+    /// 从任务构造断言
+    ///
+    /// 优先使用 `args`；否则解析 `command` 的简写形式：
+    /// - `exists <路径>` - 文件存在
+    /// - `<命令> =~ <正则>` - 输出匹配
+    /// - 其他 - 命令退出码为 0
+    pub fn from_task(task: &SubTask) -> TaskOpResult<Self> {
+        if let Some(assertion) = parse_args(task)? {
+            return Ok(assertion);
+        }
+
+        let command = task.command.trim();
+        if command.is_empty() {
+            return Err(TaskError::ParseError(format!("验证任务 {} 缺少断言", task.id)));
+        }
+
+        if let Some(path) = command.strip_prefix("exists ") {
+            return Ok(Assertion::FileExists {
+                path: shell_unquote(path.trim()),
+            });
+        }
+
+        if let Some((cmd, pattern)) = command.split_once(" =~ ") {
+            return Ok(Assertion::OutputMatches {
+                command: cmd.trim().to_string(),
+                pattern: pattern.trim().to_string(),
+            });
+        }
+
+        Ok(Assertion::ExitCode {
+            command: command.to_string(),
+            expect: 0,
+        })
+    }
+
+    /// 评估断言，通过时返回说明
+    pub async fn evaluate(&self, timeout: Option<u64>) -> TaskOpResult<String> {
+        match self {
+            Assertion::FileExists { path } => {
+                if Path::new(path).exists() {
+                    Ok(format!("✓ 存在: {}", path))
+                } else {
+                    Err(TaskError::ValidationFailed(format!("不存在: {}", path)))
+                }
+            }
+            Assertion::ExitCode { command, expect } => {
+                let (code, output) = run_command(command, timeout).await?;
+                if code == *expect {
+                    Ok(format!("✓ 退出码 {}: {}", code, command))
+                } else {
+                    Err(TaskError::ValidationFailed(format!(
+                        "退出码 {}（期望 {}）: {}\n{}",
+                        code,
+                        expect,
+                        command,
+                        output.trim()
+                    )))
+                }
+            }
+            Assertion::OutputMatches { command, pattern } => {
+                let regex = Regex::new(pattern)
+                    .map_err(|e| TaskError::ParseError(format!("无效的正则 {}: {}", pattern, e)))?;
+                let (_, output) = run_command(command, timeout).await?;
+                if regex.is_match(&output) {
+                    Ok(format!("✓ 输出匹配 /{}/: {}", pattern, command))
+                } else {
+                    Err(TaskError::ValidationFailed(format!(
+                        "输出不匹配 /{}/: {}\n{}",
+                        pattern,
+                        command,
+                        output.trim()
+                    )))
+                }
+            }
+        }
+    }
+}
+
+/// 执行命令并返回（退出码, stdout+stderr）
+///
+/// 超时后子进程被终止，不会在后台继续运行。
+async fn run_command(command: &str, timeout: Option<u64>) -> TaskOpResult<(i32, String)> {
+    crate::shell_executor::is_safe_command(command)
+        .map_err(|e| TaskError::ShellExecutionError(e.to_string()))?;
+
+    #[cfg(unix)]
+    let (shell, flag) = ("/bin/sh", "-c");
+    #[cfg(windows)]
+    let (shell, flag) = ("cmd", "/C");
+
+    // 超时时 future 被丢弃，kill_on_drop 随之终止子进程
+    let run = tokio::process::Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .kill_on_drop(true)
+        .output();
+
+    let output = match timeout {
+        Some(secs) => tokio::time::timeout(Duration::from_secs(secs), run)
+            .await
+            .map_err(|_| TaskError::ShellExecutionError(format!("命令超时 ({} 秒)", secs)))?,
+        None => run.await,
+    }?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.code().unwrap_or(-1), text))
+}
+
+// ========== FileOperation / Network ==========
+
+/// 工具调用描述
+///
+/// ```json
+/// {"tool": "write_file", "args": {"path": "out.txt", "content": "hello"}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolCallSpec {
+    /// 工具名称
+    pub tool: String,
+    /// 工具参数
+    #[serde(default = "empty_object")]
+    pub args: JsonValue,
+}
+
+fn empty_object() -> JsonValue {
+    JsonValue::Object(Default::default())
+}
+
+impl ToolCallSpec {
+    /// 从任务构造工具调用（任务没有 `args` 时返回 None）
+    pub fn from_task(task: &SubTask) -> TaskOpResult<Option<Self>> {
+        let Some(spec) = parse_args::<ToolCallSpec>(task)? else {
+            return Ok(None);
+        };
+
+        let allowed = allowed_tools(&task.task_type);
+        if !allowed.contains(&spec.tool.as_str()) {
+            return Err(TaskError::ToolError(format!(
+                "工具 {} 不适用于 {} 任务（可用: {}）",
+                spec.tool,
+                task.task_type.to_string(),
+                allowed.join(", ")
+            )));
+        }
+        Ok(Some(spec))
+    }
+
+    /// 校验参数类型并调用工具
+    pub fn invoke(&self, registry: &ToolRegistry) -> TaskOpResult<String> {
+        let tool = registry
+            .get(&self.tool)
+            .ok_or_else(|| TaskError::ToolError(format!("未找到工具: {}", self.tool)))?;
+
+        tool.validate_args(&self.args)
+            .map_err(|e| TaskError::ToolError(format!("{}: {}", self.tool, e)))?;
+        tool.execute(self.args.clone())
+            .map_err(|e| TaskError::ToolError(format!("{}: {}", self.tool, e)))
+    }
+}
+
+/// 各任务类型可调用的工具
+pub fn allowed_tools(task_type: &TaskType) -> &'static [&'static str] {
+    match task_type {
+        TaskType::FileOperation => &["read_file", "write_file", "list_dir"],
+        TaskType::Network => &["http_get", "http_post"],
+        _ => &[],
+    }
+}
+
+/// 解析任务的结构化参数（没有 args 时返回 None）
+fn parse_args<T: for<'de> Deserialize<'de>>(task: &SubTask) -> TaskOpResult<Option<T>> {
+    task.args
+        .as_ref()
+        .map(|args| {
+            serde_json::from_value(args.clone())
+                .map_err(|e| TaskError::ParseError(format!("任务 {} 的参数无效: {}", task.id, e)))
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn validation(command: &str) -> SubTask {
+        SubTask::new("v", "Validate", command).with_task_type(TaskType::Validation)
+    }
+
+    #[test]
+    fn test_assertion_from_args_and_shorthand() {
+        let task = validation("").with_args(json!({"check": "file_exists", "path": "Cargo.toml"}));
+        assert_eq!(
+            Assertion::from_task(&task).unwrap(),
+            Assertion::FileExists { path: "Cargo.toml".to_string() }
+        );
+
+        assert_eq!(
+            Assertion::from_task(&validation("exists ./src")).unwrap(),
+            Assertion::FileExists { path: "./src".to_string() }
+        );
+        assert_eq!(
+            Assertion::from_task(&validation("echo v20 =~ ^v2")).unwrap(),
+            Assertion::OutputMatches { command: "echo v20".to_string(), pattern: "^v2".to_string() }
+        );
+        assert_eq!(
+            Assertion::from_task(&validation("test -d src")).unwrap(),
+            Assertion::ExitCode { command: "test -d src".to_string(), expect: 0 }
+        );
+
+        assert!(Assertion::from_task(&validation("")).is_err());
+        let bad = validation("").with_args(json!({"check": "unknown"}));
+        assert!(matches!(Assertion::from_task(&bad), Err(TaskError::ParseError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_assertion_evaluate() {
+        let exists = Assertion::FileExists { path: "Cargo.toml".to_string() };
+        assert!(exists.evaluate(None).await.is_ok());
+
+        let missing = Assertion::FileExists { path: "no/such/file".to_string() };
+        assert!(matches!(missing.evaluate(None).await, Err(TaskError::ValidationFailed(_))));
+
+        let code = Assertion::ExitCode { command: "exit 3".to_string(), expect: 3 };
+        assert!(code.evaluate(Some(5)).await.is_ok());
+        let code = Assertion::ExitCode { command: "exit 3".to_string(), expect: 0 };
+        assert!(code.evaluate(Some(5)).await.is_err());
+
+        let matches = Assertion::OutputMatches {
+            command: "echo version 1.2.3".to_string(),
+            pattern: r"\d+\.\d+\.\d+".to_string(),
+        };
+        assert!(matches.evaluate(None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_assertion_timeout_kills_command() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let marker = dir.path().join("marker");
+
+        let slow = Assertion::ExitCode {
+            command: format!("sleep 2 && touch {}", marker.display()),
+            expect: 0,
+        };
+        let result = slow.evaluate(Some(1)).await;
+        assert!(matches!(result, Err(TaskError::ShellExecutionError(_))));
+
+        // 超时的命令已被终止，不会在之后继续执行
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        assert!(!marker.exists());
+    }
+
+    #[tokio::test]
+    async fn test_prompt_user() {
+        let provider: InputProvider = Arc::new(|prompt: &str| {
+            assert!(prompt.starts_with("Deploy?"));
+            Some("yes".to_string())
+        });
+        let confirm = SubTask::new("u", "Ask", "")
+            .with_task_type(TaskType::UserInput)
+            .with_args(json!({"prompt": "Deploy?", "confirm": true}));
+        assert_eq!(prompt_user(&confirm, &provider).await.unwrap(), "已确认");
+
+        let refuse: InputProvider = Arc::new(|_: &str| Some("n".to_string()));
+        assert!(matches!(
+            prompt_user(&confirm, &refuse).await,
+            Err(TaskError::UserInputRejected(_))
+        ));
+
+        let name: InputProvider = Arc::new(|_: &str| Some("realconsole".to_string()));
+        let ask = SubTask::new("u", "Project name", "").with_task_type(TaskType::UserInput);
+        assert_eq!(prompt_user(&ask, &name).await.unwrap(), "realconsole");
+
+        let closed: InputProvider = Arc::new(|_: &str| None);
+        assert!(prompt_user(&ask, &closed).await.is_err());
+    }
+
+    #[test]
+    fn test_tool_call_spec() {
+        let mut registry = ToolRegistry::new();
+        crate::builtin_tools::register_builtin_tools(&mut registry);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("out.txt");
+        let task = SubTask::new("f", "Write", "")
+            .with_task_type(TaskType::FileOperation)
+            .with_args(json!({"tool": "write_file", "args": {"path": path.to_str().unwrap(), "content": "hi"}}));
+
+        let spec = ToolCallSpec::from_task(&task).unwrap().unwrap();
+        assert!(spec.invoke(&registry).is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hi");
+
+        // 参数类型错误
+        let bad = ToolCallSpec { tool: "write_file".to_string(), args: json!({"path": 1, "content": "x"}) };
+        assert!(matches!(bad.invoke(&registry), Err(TaskError::ToolError(e)) if e.contains("类型错误")));
+
+        // 工具与任务类型不符
+        let wrong = SubTask::new("n", "Fetch", "")
+            .with_task_type(TaskType::Network)
+            .with_args(json!({"tool": "write_file"}));
+        assert!(ToolCallSpec::from_task(&wrong).is_err());
+
+        // 没有 args 时交由 Shell 处理
+        let plain = SubTask::new("p", "Plain", "cp a b").with_task_type(TaskType::FileOperation);
+        assert!(ToolCallSpec::from_task(&plain).unwrap().is_none());
+    }
+}
@@ -0,0 +1,430 @@
+//! 无交互计划执行 (Headless Runner)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! `realconsole run-plan <file>` 的实现：在 CI 中执行与交互模式相同的计划文件，
+//! 以 JSON Lines 流式输出进度，或在结束时输出 JUnit XML（每个子任务一个 testcase）。
+//! 需要用户输入的任务在无交互模式下直接失败。
+
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::executor::TaskExecutor;
+use super::plan_file;
+use super::planner::TaskPlanner;
+use super::types::{ExecutionPlan, ExecutionResult, TaskResult, TaskStatus};
+use crate::shell_executor::ShellExecutorWithFixer;
+use crate::tool::ToolRegistry;
+use serde::Serialize;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// 报告格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// 每个事件一行 JSON（实时输出）
+    JsonLines,
+    /// 执行结束后输出 JUnit XML
+    JUnit,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "json" | "json-lines" => Ok(ReportFormat::JsonLines),
+            "junit" | "xml" => Ok(ReportFormat::JUnit),
+            other => Err(format!("未知的报告格式: {}（可用: jsonl, junit）", other)),
+        }
+    }
+}
+
+/// 无交互执行选项
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+    /// 报告格式
+    pub format: ReportFormat,
+    /// 最多同时执行的任务数（None 表示使用默认值）
+    pub parallelism: Option<usize>,
+    /// 默认任务超时（秒），任务自身的 timeout 优先
+    pub timeout: u64,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::JsonLines,
+            parallelism: None,
+            timeout: 300,
+        }
+    }
+}
+
+/// JSON Lines 事件
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ReportEvent {
+    PlanStarted {
+        plan_id: String,
+        goal: String,
+        total_tasks: usize,
+    },
+    TaskStarted {
+        task: String,
+    },
+    TaskFinished {
+        task: String,
+        name: String,
+        status: String,
+        duration: u32,
+        output: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(skip_serializing_if = "std::ops::Not::not")]
+        cached: bool,
+    },
+    PlanFinished {
+        success: bool,
+        completed: usize,
+        failed: usize,
+        skipped: usize,
+        blocked: usize,
+        total_time: u32,
+    },
+}
+
+impl ReportEvent {
+    fn task_finished(result: &TaskResult) -> Self {
+        ReportEvent::TaskFinished {
+            task: result.task.id.clone(),
+            name: result.task.name.clone(),
+            status: status_name(&result.status),
+            duration: result.duration,
+            output: result.output.clone(),
+            error: result.error.clone(),
+            cached: result.cached,
+        }
+    }
+
+    fn plan_finished(result: &ExecutionResult) -> Self {
+        ReportEvent::PlanFinished {
+            success: passed(result),
+            completed: result.completed_tasks,
+            failed: result.failed_tasks,
+            skipped: result.skipped_tasks,
+            blocked: result.blocked_tasks,
+            total_time: result.total_time,
+        }
+    }
+}
+
+fn status_name(status: &TaskStatus) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+/// CI 意义上的成功：没有失败、阻塞或取消的任务（可跳过任务的失败不影响结果）
+pub fn passed(result: &ExecutionResult) -> bool {
+    result.failed_tasks == 0 && result.blocked_tasks == 0
+}
+
+/// 共享的报告输出
+type SharedWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+
+fn emit(out: &SharedWriter, event: &ReportEvent) {
+    if let (Ok(line), Ok(mut out)) = (serde_json::to_string(event), out.lock()) {
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }
+}
+
+/// 加载计划文件并无交互执行，报告写入 `out`
+pub async fn run_plan_file(
+    path: &Path,
+    options: &HeadlessOptions,
+    out: Box<dyn Write + Send>,
+) -> TaskOpResult<ExecutionResult> {
+    let mut planner = TaskPlanner::new();
+    if let Some(max) = options.parallelism {
+        planner = planner.with_max_parallelism(max);
+    }
+    let plan = plan_file::load(path, &planner)?;
+    run_plan(plan, options, out).await
+}
+
+/// 无交互执行计划
+pub async fn run_plan(
+    plan: ExecutionPlan,
+    options: &HeadlessOptions,
+    out: Box<dyn Write + Send>,
+) -> TaskOpResult<ExecutionResult> {
+    let out: SharedWriter = Arc::new(Mutex::new(out));
+
+    let mut tools = ToolRegistry::new();
+    crate::builtin_tools::register_builtin_tools(&mut tools);
+    crate::advanced_tools::register_advanced_tools(&mut tools);
+
+    let mut executor = TaskExecutor::new(Arc::new(ShellExecutorWithFixer::new()))
+        .with_timeout(options.timeout)
+        .with_tool_registry(Arc::new(tokio::sync::RwLock::new(tools)))
+        .with_input_provider(Arc::new(|_: &str| None));
+    if let Some(max) = options.parallelism {
+        executor = executor.with_max_parallelism(max);
+    }
+
+    if options.format == ReportFormat::JsonLines {
+        emit(
+            &out,
+            &ReportEvent::PlanStarted {
+                plan_id: plan.id.clone(),
+                goal: plan.goal.clone(),
+                total_tasks: plan.total_tasks(),
+            },
+        );
+
+        let started = Arc::clone(&out);
+        let finished = Arc::clone(&out);
+        executor = executor
+            .with_status_callback(Arc::new(move |task_id: &str, status: &TaskStatus| {
+                if *status == TaskStatus::Running {
+                    emit(&started, &ReportEvent::TaskStarted { task: task_id.to_string() });
+                }
+            }))
+            .with_result_callback(Arc::new(move |result: &TaskResult| {
+                emit(&finished, &ReportEvent::task_finished(result));
+            }));
+    }
+
+    let result = executor.execute(plan.clone()).await?;
+
+    match options.format {
+        ReportFormat::JsonLines => emit(&out, &ReportEvent::plan_finished(&result)),
+        ReportFormat::JUnit => {
+            let mut out = out
+                .lock()
+                .map_err(|_| TaskError::Other("报告输出不可用".to_string()))?;
+            out.write_all(junit_xml(&plan, &result).as_bytes())?;
+            out.flush()?;
+        }
+    }
+
+    Ok(result)
+}
+
+/// 生成 JUnit XML（每个子任务一个 testcase）
+pub fn junit_xml(plan: &ExecutionPlan, result: &ExecutionResult) -> String {
+    let skipped = result
+        .task_results
+        .iter()
+        .filter(|r| matches!(r.status, TaskStatus::Skipped | TaskStatus::Blocked | TaskStatus::Cancelled))
+        .count();
+    let counts = format!(
+        "tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{}\"",
+        result.total_tasks, result.failed_tasks, skipped, result.total_time
+    );
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<testsuites name=\"realconsole\" {}>\n", counts));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" id=\"{}\" timestamp=\"{}\" {}>\n",
+        xml_escape(&plan.goal),
+        xml_escape(&plan.id),
+        plan.created_at.format("%Y-%m-%dT%H:%M:%S"),
+        counts
+    ));
+
+    for r in &result.task_results {
+        xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">\n",
+            xml_escape(&testcase_name(r)),
+            xml_escape(&plan.goal),
+            r.duration
+        ));
+
+        let message = xml_escape(r.error.as_deref().unwrap_or_default());
+        match r.status {
+            TaskStatus::Failed => xml.push_str(&format!(
+                "      <failure message=\"{}\" type=\"failed\"><![CDATA[$ {}\n{}]]></failure>\n",
+                message,
+                cdata(&r.task.command),
+                cdata(r.error.as_deref().unwrap_or_default())
+            )),
+            TaskStatus::Skipped | TaskStatus::Blocked | TaskStatus::Cancelled => xml.push_str(
+                &format!("      <skipped message=\"{}: {}\"/>\n", status_name(&r.status), message),
+            ),
+            _ => {}
+        }
+
+        if !r.output.is_empty() {
+            xml.push_str(&format!(
+                "      <system-out><![CDATA[{}]]></system-out>\n",
+                cdata(&r.output)
+            ));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn testcase_name(result: &TaskResult) -> String {
+    if result.task.name == result.task.id {
+        result.task.id.clone()
+    } else {
+        format!("{} {}", result.task.id, result.task.name)
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\n', "&#10;")
+}
+
+/// CDATA 内不能出现 `]]>`
+fn cdata(s: &str) -> String {
+    s.replace("]]>", "]]]]><![CDATA[>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use tempfile::TempDir;
+
+    /// 收集输出的 Writer
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Buffer {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    const PLAN: &str = r#"goal: ci <check>
+tasks:
+  - id: ok
+    command: echo hello
+  - id: broken
+    command: "false"
+    retry_policy:
+      max_retries: 0
+  - id: after
+    command: echo never
+    depends_on: [broken]
+  - id: ask
+    task_type: user_input
+    retry_policy:
+      max_retries: 0
+    skippable: true
+"#;
+
+    fn write_plan(dir: &TempDir) -> std::path::PathBuf {
+        let path = dir.path().join("ci.yaml");
+        std::fs::write(&path, PLAN).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_report_format_from_str() {
+        assert_eq!("jsonl".parse::<ReportFormat>().unwrap(), ReportFormat::JsonLines);
+        assert_eq!("JUnit".parse::<ReportFormat>().unwrap(), ReportFormat::JUnit);
+        assert!("yaml".parse::<ReportFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_plan_json_lines() {
+        let dir = TempDir::new().unwrap();
+        let buffer = Buffer::default();
+        let options = HeadlessOptions {
+            parallelism: Some(1),
+            ..HeadlessOptions::default()
+        };
+
+        let result = run_plan_file(&write_plan(&dir), &options, Box::new(buffer.clone()))
+            .await
+            .unwrap();
+        assert!(!passed(&result));
+
+        let events: Vec<serde_json::Value> = buffer
+            .text()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.first().unwrap()["event"], "plan_started");
+        assert_eq!(events.first().unwrap()["total_tasks"], 4);
+
+        let finished = |id: &str| {
+            events
+                .iter()
+                .find(|e| e["event"] == "task_finished" && e["task"] == id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(finished("ok")["status"], "success");
+        assert_eq!(finished("broken")["status"], "failed");
+        assert_eq!(finished("after")["status"], "blocked");
+        // 无交互模式下用户输入任务不会等待
+        assert_eq!(finished("ask")["status"], "skipped");
+        assert!(events.iter().any(|e| e["event"] == "task_started" && e["task"] == "ok"));
+
+        let last = events.last().unwrap();
+        assert_eq!(last["event"], "plan_finished");
+        assert_eq!(last["success"], false);
+        assert_eq!(last["failed"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_plan_junit() {
+        let dir = TempDir::new().unwrap();
+        let buffer = Buffer::default();
+        let options = HeadlessOptions {
+            format: ReportFormat::JUnit,
+            ..HeadlessOptions::default()
+        };
+
+        run_plan_file(&write_plan(&dir), &options, Box::new(buffer.clone()))
+            .await
+            .unwrap();
+
+        let xml = buffer.text();
+        assert!(xml.starts_with("<?xml"));
+        assert!(xml.contains("<testsuite name=\"ci &lt;check&gt;\""));
+        assert_eq!(xml.matches("<testcase ").count(), 4);
+        assert!(xml.contains("tests=\"4\" failures=\"1\" errors=\"0\" skipped=\"2\""));
+        assert!(xml.contains("<failure message="));
+        assert!(xml.contains("<skipped message=\"blocked: 依赖的任务 broken 失败"));
+        assert!(xml.contains("<system-out><![CDATA[hello"));
+    }
+
+    #[tokio::test]
+    async fn test_run_plan_invalid_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bad.yaml");
+        std::fs::write(&path, "goal: x\ntasks: []\n").unwrap();
+
+        let result = run_plan_file(&path, &HeadlessOptions::default(), Box::new(io::sink())).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cdata_escape() {
+        assert_eq!(cdata("a]]>b"), "a]]]]><![CDATA[>b");
+        assert_eq!(xml_escape("a\"<b>&"), "a&quot;&lt;b&gt;&amp;");
+    }
+}
@@ -0,0 +1,365 @@
+//! 分解历史 (DecompositionHistory)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 记录每次任务分解及其执行结果，并持久化到磁盘。
+//! 相似目标再次出现时，过去执行成功的计划作为 few-shot 示例提供给 LLM，
+//! 使反复出现的目标逐渐收敛到经过验证的计划。
+
+use super::types::{ExecutionContext, ExecutionPlan, ExecutionResult, SubTask};
+use crate::dsl::intent::embedding::{cosine_similarity, Embedder, HashingEmbedder};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 最多保留的记录数
+const MAX_RECORDS: usize = 100;
+
+/// 视为"相似目标"的最低相似度
+pub const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+/// 计划的执行结果摘要
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanOutcome {
+    /// 是否全部成功
+    pub success: bool,
+    /// 已完成任务数
+    pub completed: usize,
+    /// 失败任务数
+    pub failed: usize,
+    /// 总耗时（秒）
+    pub total_time: u32,
+    /// 记录时间
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// 一次分解记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecompositionRecord {
+    /// 用户目标
+    pub goal: String,
+    /// 分解时的工作目录
+    pub working_dir: String,
+    /// 分解时的操作系统
+    pub os: String,
+    /// 子任务（执行成功后更新为最终执行的计划，含修订）
+    pub subtasks: Vec<SubTask>,
+    /// 分解时间
+    pub timestamp: DateTime<Utc>,
+    /// 对应的计划 ID（执行后关联）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan_id: Option<String>,
+    /// 执行结果（未执行时为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<PlanOutcome>,
+}
+
+impl DecompositionRecord {
+    /// 创建分解记录（尚未执行）
+    pub fn new(goal: &str, context: &ExecutionContext, subtasks: &[SubTask]) -> Self {
+        Self {
+            goal: goal.to_string(),
+            working_dir: context.working_dir.clone(),
+            os: context.os.clone(),
+            subtasks: subtasks.to_vec(),
+            timestamp: Utc::now(),
+            plan_id: None,
+            outcome: None,
+        }
+    }
+
+    /// 是否执行成功
+    pub fn succeeded(&self) -> bool {
+        self.outcome.as_ref().is_some_and(|o| o.success)
+    }
+}
+
+/// 分解历史
+///
+/// 指定了文件路径时，每次变更后写回磁盘（JSON）。
+#[derive(Debug, Default)]
+pub struct DecompositionHistory {
+    records: Vec<DecompositionRecord>,
+    path: Option<PathBuf>,
+}
+
+impl DecompositionHistory {
+    /// 仅内存历史
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载（文件不存在或损坏时从空历史开始）
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let records = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            records,
+            path: Some(path),
+        }
+    }
+
+    /// 默认存储路径：`~/.realconsole/decomposition_history.json`
+    pub fn default_path() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("decomposition_history.json")
+    }
+
+    /// 记录一次分解（保留最近 100 条）
+    pub fn record(&mut self, record: DecompositionRecord) -> Result<(), String> {
+        self.records.push(record);
+        if self.records.len() > MAX_RECORDS {
+            let drain_count = self.records.len() - MAX_RECORDS;
+            self.records.drain(0..drain_count);
+        }
+        self.save()
+    }
+
+    /// 记录计划的执行结果
+    ///
+    /// 优先匹配已关联该计划的记录，否则关联同一目标最近一条未执行的记录。
+    /// 执行成功时，记录的子任务更新为实际执行的计划（包括修订后的任务）。
+    /// 返回是否找到对应记录。
+    pub fn record_outcome(
+        &mut self,
+        plan: &ExecutionPlan,
+        result: &ExecutionResult,
+    ) -> Result<bool, String> {
+        let index = self
+            .records
+            .iter()
+            .rposition(|r| r.plan_id.as_deref() == Some(plan.id.as_str()))
+            .or_else(|| {
+                self.records
+                    .iter()
+                    .rposition(|r| r.plan_id.is_none() && r.outcome.is_none() && r.goal == plan.goal)
+            });
+        let Some(index) = index else {
+            return Ok(false);
+        };
+
+        let record = &mut self.records[index];
+        let success = result.is_success();
+        record.plan_id = Some(plan.id.clone());
+        record.outcome = Some(PlanOutcome {
+            success,
+            completed: result.completed_tasks,
+            failed: result.failed_tasks,
+            total_time: result.total_time,
+            recorded_at: Utc::now(),
+        });
+        if success {
+            record.subtasks = plan.tasks().cloned().collect();
+        }
+
+        self.save()?;
+        Ok(true)
+    }
+
+    /// 与目标相似的历史记录（按相似度降序，最新的优先）
+    pub fn similar(&self, goal: &str, limit: usize) -> Vec<(&DecompositionRecord, f64)> {
+        let embedder = HashingEmbedder::default();
+        let Ok(query) = embedder.embed(goal) else {
+            return Vec::new();
+        };
+
+        let mut matches: Vec<(&DecompositionRecord, f64)> = self
+            .records
+            .iter()
+            .rev()
+            .filter_map(|record| {
+                let vector = embedder.embed(&record.goal).ok()?;
+                let similarity = cosine_similarity(&query, &vector);
+                (similarity >= SIMILARITY_THRESHOLD).then_some((record, similarity))
+            })
+            .collect();
+
+        // 稳定排序：相似度相同时保持"最新在前"
+        matches.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        matches.truncate(limit);
+        matches
+    }
+
+    /// 可作为 few-shot 示例的历史计划
+    ///
+    /// 只取执行成功的记录，任务命令完全相同的计划只保留一份。
+    pub fn examples(&self, goal: &str, limit: usize) -> Vec<(&DecompositionRecord, f64)> {
+        let mut seen: Vec<Vec<&str>> = Vec::new();
+        self.similar(goal, MAX_RECORDS)
+            .into_iter()
+            .filter(|(record, _)| record.succeeded())
+            .filter(|(record, _)| {
+                let commands: Vec<&str> = record.subtasks.iter().map(|t| t.command.as_str()).collect();
+                if seen.contains(&commands) {
+                    false
+                } else {
+                    seen.push(commands);
+                    true
+                }
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// 全部记录（按时间顺序）
+    pub fn records(&self) -> &[DecompositionRecord] {
+        &self.records
+    }
+
+    /// 记录数量
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// 清空历史
+    pub fn clear(&mut self) -> Result<(), String> {
+        self.records.clear();
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&self.records)
+            .map_err(|e| format!("序列化分解历史失败: {}", e))?;
+        std::fs::write(path, content).map_err(|e| format!("写入分解历史失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::planner::TaskPlanner;
+    use crate::task::types::{TaskResult, TaskStatus};
+    use tempfile::TempDir;
+
+    fn tasks(commands: &[&str]) -> Vec<SubTask> {
+        commands
+            .iter()
+            .enumerate()
+            .map(|(i, cmd)| SubTask::new(format!("t{}", i + 1), *cmd, *cmd))
+            .collect()
+    }
+
+    fn execute(plan: &ExecutionPlan, success: bool) -> ExecutionResult {
+        let status = if success { TaskStatus::Success } else { TaskStatus::Failed };
+        let task_results: Vec<TaskResult> = plan
+            .tasks()
+            .map(|task| TaskResult {
+                task: task.clone(),
+                status: status.clone(),
+                output: String::new(),
+                error: None,
+                start_time: Utc::now(),
+                end_time: Utc::now(),
+                duration: 1,
+                cached: false,
+            })
+            .collect();
+        let completed = task_results.len();
+        ExecutionResult {
+            plan_id: plan.id.clone(),
+            total_tasks: completed,
+            completed_tasks: completed,
+            failed_tasks: if success { 0 } else { completed },
+            skipped_tasks: 0,
+            blocked_tasks: 0,
+            total_time: 1,
+            task_results,
+        }
+    }
+
+    fn record(history: &mut DecompositionHistory, goal: &str, commands: &[&str]) -> ExecutionPlan {
+        let subtasks = tasks(commands);
+        history
+            .record(DecompositionRecord::new(goal, &ExecutionContext::current(), &subtasks))
+            .unwrap();
+        TaskPlanner::new().plan(goal, subtasks).unwrap()
+    }
+
+    #[test]
+    fn test_record_outcome_links_plan() {
+        let mut history = DecompositionHistory::new();
+        let plan = record(&mut history, "build the rust project", &["cargo build"]);
+
+        assert!(history.record_outcome(&plan, &execute(&plan, false)).unwrap());
+        assert!(!history.records()[0].succeeded());
+        assert_eq!(history.records()[0].plan_id.as_deref(), Some(plan.id.as_str()));
+
+        // 同一计划恢复执行后再次记录，覆盖之前的结果
+        assert!(history.record_outcome(&plan, &execute(&plan, true)).unwrap());
+        assert!(history.records()[0].succeeded());
+        assert_eq!(history.len(), 1);
+
+        let other = TaskPlanner::new().plan("unrelated", tasks(&["ls"])).unwrap();
+        assert!(!history.record_outcome(&other, &execute(&other, true)).unwrap());
+    }
+
+    #[test]
+    fn test_examples_only_successful_similar_plans() {
+        let mut history = DecompositionHistory::new();
+
+        let ok = record(&mut history, "build the rust project", &["cargo build"]);
+        history.record_outcome(&ok, &execute(&ok, true)).unwrap();
+        let failed = record(&mut history, "build rust project", &["make"]);
+        history.record_outcome(&failed, &execute(&failed, false)).unwrap();
+        let duplicate = record(&mut history, "build the rust project again", &["cargo build"]);
+        history.record_outcome(&duplicate, &execute(&duplicate, true)).unwrap();
+        record(&mut history, "deploy website to server", &["rsync"]);
+
+        let similar = history.similar("build rust project", 10);
+        assert_eq!(similar.len(), 3);
+        assert_eq!(similar[0].0.goal, "build rust project");
+
+        let examples = history.examples("build rust project", 5);
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].0.subtasks[0].command, "cargo build");
+
+        assert!(history.examples("统计日志中的错误", 5).is_empty());
+    }
+
+    #[test]
+    fn test_history_persistence() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("history.json");
+
+        let mut history = DecompositionHistory::load(&path);
+        let plan = record(&mut history, "统计代码行数", &["wc -l src/*.rs"]);
+        history.record_outcome(&plan, &execute(&plan, true)).unwrap();
+
+        let reloaded = DecompositionHistory::load(&path);
+        assert_eq!(reloaded.len(), 1);
+        assert!(reloaded.records()[0].succeeded());
+        assert_eq!(reloaded.examples("统计代码的行数", 1).len(), 1);
+
+        let mut reloaded = reloaded;
+        reloaded.clear().unwrap();
+        assert!(DecompositionHistory::load(&path).is_empty());
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut history = DecompositionHistory::new();
+        for i in 0..(MAX_RECORDS + 5) {
+            record(&mut history, &format!("goal {}", i), &["true"]);
+        }
+        assert_eq!(history.len(), MAX_RECORDS);
+        assert_eq!(history.records()[0].goal, "goal 5");
+    }
+}
@@ -13,6 +13,13 @@
 //! - [`TaskDecomposer`] - 任务分解器，使用 LLM 智能分解任务
 //! - [`TaskPlanner`] - 任务规划器，分析依赖并生成执行计划
 //! - [`TaskExecutor`] - 任务执行器，执行计划并提供进度反馈
+//! - [`PlanStore`] - 计划存储，持久化计划与任务结果，支持中断后恢复
+//! - [`Replanner`] - 自适应执行，任务失败后由 LLM 修订剩余计划并经用户批准
+//! - [`plan_file`] - YAML 计划文件，无需 LLM 即可编写、修改和执行计划
+//! - [`graph`] - 依赖图渲染（ASCII / Graphviz DOT / Mermaid），突出关键路径
+//!
+//! 子任务命令可以通过 `{{t1.output}}` 引用前置任务的结果，
+//! 详见 [`template`] 模块。
 //!
 //! # 使用示例
 //!
@@ -32,21 +39,36 @@
 //! let result = executor.execute(plan).await?;
 //! ```
 
+pub mod cache;
 pub mod decomposer;
 pub mod error;
 pub mod executor;
+pub mod graph;
+pub mod handlers;
+pub mod headless;
+pub mod history;
+pub mod plan_file;
 pub mod planner;
+pub mod replan;
+pub mod store;
+pub mod template;
 pub mod types;
 
 // 重新导出核心类型
 pub use decomposer::TaskDecomposer;
 pub use executor::TaskExecutor;
+pub use handlers::{Assertion, InputProvider};
 #[allow(unused_imports)]
-pub use executor::ProgressCallback;
+pub use executor::{ProgressCallback, ResultCallback, StatusCallback};
 pub use planner::TaskPlanner;
+pub use replan::Replanner;
+pub use store::{PlanCheckpoint, PlanStore};
 #[allow(unused_imports)]
-pub use planner::PlanAnalysis;
-pub use types::{ExecutionContext, ExecutionMode, ExecutionPlan, ExecutionResult, TaskStatus};
+pub use planner::{DependencyIssue, PlanAnalysis};
+pub use types::{
+    ExecutionContext, ExecutionMode, ExecutionPlan, ExecutionResult, FailurePolicy, PlanRevision,
+    TaskStatus,
+};
 #[allow(unused_imports)]
 pub use types::{
     DependencyGraph, ExecutionStage, RetryPolicy, SubTask, TaskProgress,
@@ -0,0 +1,444 @@
+//! 任务计划文件 (Plan Files)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 用 YAML 编写可重复执行的计划（发布检查清单、环境初始化等），不依赖 LLM。
+//! 任何由 LLM 生成的计划也可以保存为同样的格式，修改后再加载。
+//!
+//! ```yaml
+//! goal: 发布检查清单
+//! failure_policy: fail_fast        # 可选，默认 continue_on_error
+//! tasks:
+//!   - id: test
+//!     name: 运行测试
+//!     command: cargo test
+//!     inputs: [Cargo.toml, "src/**/*.rs", "env:RUSTFLAGS"]   # 可选，输入未变时跳过
+//!     estimated_time: 120
+//!     timeout: 600
+//!     retry_policy:
+//!       max_retries: 2
+//!       retry_interval: 5
+//!   - id: tag
+//!     command: git tag v{{test.output | regex:version (\S+)}}
+//!     depends_on: [test]
+//!     skippable: true
+//! ```
+//!
+//! 加载时由 [`TaskPlanner`] 校验重复 ID、未知依赖与循环依赖，错误定位到行。
+
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::planner::{DependencyIssue, TaskPlanner};
+use super::template;
+use super::types::{ExecutionPlan, FailurePolicy, RetryPolicy, SubTask, TaskType};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 计划文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlanFile {
+    /// 计划目标
+    pub goal: String,
+
+    /// 失败处理策略
+    #[serde(default, skip_serializing_if = "is_default_policy")]
+    pub failure_policy: FailurePolicy,
+
+    /// 任务列表
+    pub tasks: Vec<TaskSpec>,
+}
+
+/// 计划文件中的任务（对应 [`SubTask`]，省略的字段取默认值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskSpec {
+    pub id: String,
+
+    /// 默认与 id 相同
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+
+    #[serde(default)]
+    pub command: String,
+
+    #[serde(default = "default_estimated_time")]
+    pub estimated_time: u32,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+
+    /// shell / file_operation / network / validation / user_input
+    #[serde(default = "default_task_type")]
+    pub task_type: String,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub skippable: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_policy: Option<RetrySpec>,
+
+    /// 超时（秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<serde_json::Value>,
+
+    /// 输入（文件、glob 或 env:NAME），未改变时复用上次成功的结果
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+
+    /// 输出（文件或 glob），缺失或被修改时重新执行
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+}
+
+/// 重试策略（省略的字段取默认值）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetrySpec {
+    pub max_retries: u32,
+
+    #[serde(default = "default_retry_interval")]
+    pub retry_interval: u32,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub exponential_backoff: bool,
+}
+
+fn default_estimated_time() -> u32 {
+    10
+}
+
+fn default_task_type() -> String {
+    "shell".to_string()
+}
+
+fn default_retry_interval() -> u32 {
+    1
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_default_policy(policy: &FailurePolicy) -> bool {
+    *policy == FailurePolicy::default()
+}
+
+/// 计划文件中使用的任务类型名称
+fn task_type_name(task_type: &TaskType) -> &'static str {
+    match task_type {
+        TaskType::Shell => "shell",
+        TaskType::FileOperation => "file_operation",
+        TaskType::Network => "network",
+        TaskType::Validation => "validation",
+        TaskType::UserInput => "user_input",
+    }
+}
+
+impl From<&SubTask> for TaskSpec {
+    fn from(task: &SubTask) -> Self {
+        Self {
+            id: task.id.clone(),
+            name: if task.name == task.id { String::new() } else { task.name.clone() },
+            description: task.description.clone(),
+            command: task.command.clone(),
+            estimated_time: task.estimated_time,
+            depends_on: task.depends_on.clone(),
+            task_type: task_type_name(&task.task_type).to_string(),
+            skippable: task.skippable,
+            retry_policy: task.retry_policy.as_ref().map(|p| RetrySpec {
+                max_retries: p.max_retries,
+                retry_interval: p.retry_interval,
+                exponential_backoff: p.exponential_backoff,
+            }),
+            timeout: task.timeout,
+            args: task.args.clone(),
+            inputs: task.inputs.clone(),
+            outputs: task.outputs.clone(),
+        }
+    }
+}
+
+impl TaskSpec {
+    fn into_subtask(self) -> Result<SubTask, String> {
+        let task_type = TaskType::from_name(&self.task_type).ok_or_else(|| {
+            format!(
+                "任务 {} 的 task_type 无效: {}（可用: shell, file_operation, network, validation, user_input）",
+                self.id, self.task_type
+            )
+        })?;
+
+        Ok(SubTask {
+            name: if self.name.is_empty() { self.id.clone() } else { self.name },
+            id: self.id,
+            description: self.description,
+            command: self.command,
+            estimated_time: self.estimated_time,
+            depends_on: self.depends_on,
+            task_type,
+            skippable: self.skippable,
+            retry_policy: self.retry_policy.map(|r| RetryPolicy {
+                max_retries: r.max_retries,
+                retry_interval: r.retry_interval,
+                exponential_backoff: r.exponential_backoff,
+            }),
+            args: self.args,
+            timeout: self.timeout,
+            inputs: self.inputs,
+            outputs: self.outputs,
+        })
+    }
+}
+
+impl PlanFile {
+    /// 由执行计划生成计划文件
+    pub fn from_plan(plan: &ExecutionPlan) -> Self {
+        Self {
+            goal: plan.goal.clone(),
+            failure_policy: plan.failure_policy,
+            tasks: plan.tasks().map(TaskSpec::from).collect(),
+        }
+    }
+
+    /// 序列化为 YAML
+    pub fn to_yaml(&self) -> TaskOpResult<String> {
+        serde_yaml::to_string(self).map_err(|e| TaskError::ParseError(e.to_string()))
+    }
+}
+
+/// 从 YAML 文本解析并规划（`source` 用于错误信息，通常是文件路径）
+pub fn parse(text: &str, source: &str, planner: &TaskPlanner) -> TaskOpResult<ExecutionPlan> {
+    let error = |line: Option<usize>, message: String| {
+        TaskError::ParseError(match line {
+            Some(line) => format!("{}:{}: {}", source, line, message),
+            None => format!("{}: {}", source, message),
+        })
+    };
+
+    let file: PlanFile = serde_yaml::from_str(text)
+        .map_err(|e| error(e.location().map(|l| l.line()), e.to_string()))?;
+    if file.tasks.is_empty() {
+        return Err(error(None, "任务列表为空".to_string()));
+    }
+
+    let lines = TaskLines::scan(text);
+    let tasks = file
+        .tasks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, spec)| spec.into_subtask().map_err(|e| error(lines.task(idx), e)))
+        .collect::<TaskOpResult<Vec<SubTask>>>()?;
+
+    // 依赖检查：全部问题一起报告
+    let issues = planner.check_dependencies(&tasks);
+    if !issues.is_empty() {
+        let messages: Vec<String> = issues
+            .iter()
+            .map(|issue| {
+                let line = match issue {
+                    DependencyIssue::DuplicateId(id) => tasks
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, t)| &t.id == id)
+                        .nth(1)
+                        .and_then(|(idx, _)| lines.task(idx)),
+                    DependencyIssue::UnknownDependency { task, dependency } => tasks
+                        .iter()
+                        .position(|t| &t.id == task)
+                        .and_then(|idx| lines.reference(idx, dependency).or(lines.task(idx))),
+                    DependencyIssue::Cycle(cycle) => cycle
+                        .first()
+                        .and_then(|id| tasks.iter().position(|t| &t.id == id))
+                        .and_then(|idx| lines.task(idx)),
+                };
+                match line {
+                    Some(line) => format!("{}:{}: {}", source, line, issue),
+                    None => format!("{}: {}", source, issue),
+                }
+            })
+            .collect();
+        return Err(TaskError::ParseError(messages.join("\n")));
+    }
+
+    for (idx, task) in tasks.iter().enumerate() {
+        template::validate_task(task).map_err(|e| error(lines.task(idx), e.to_string()))?;
+    }
+
+    let plan = planner
+        .plan(file.goal, tasks)
+        .map_err(|e| error(None, e.to_string()))?;
+    Ok(plan.with_failure_policy(file.failure_policy))
+}
+
+/// 从文件加载计划
+pub fn load(path: &Path, planner: &TaskPlanner) -> TaskOpResult<ExecutionPlan> {
+    let text = std::fs::read_to_string(path)?;
+    parse(&text, &path.display().to_string(), planner)
+}
+
+/// 将计划保存为 YAML 文件
+pub fn save(plan: &ExecutionPlan, path: &Path) -> TaskOpResult<()> {
+    let yaml = PlanFile::from_plan(plan).to_yaml()?;
+    std::fs::write(path, yaml)?;
+    Ok(())
+}
+
+/// 匹配任务列表项的 `- id: xxx` 行
+static TASK_ID_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^\s*-\s+id:\s*["']?([^"'#\s]+)"#).unwrap());
+
+/// 任务在源文本中的位置（按任务在列表中的顺序）
+struct TaskLines<'a> {
+    lines: Vec<&'a str>,
+    /// 每个任务 `- id:` 行的下标（从 0 开始）
+    starts: Vec<usize>,
+}
+
+impl<'a> TaskLines<'a> {
+    fn scan(text: &'a str) -> Self {
+        let lines: Vec<&str> = text.lines().collect();
+        let starts = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| TASK_ID_LINE.is_match(line))
+            .map(|(idx, _)| idx)
+            .collect();
+        Self { lines, starts }
+    }
+
+    /// 第 idx 个任务的行号（从 1 开始）
+    fn task(&self, idx: usize) -> Option<usize> {
+        self.starts.get(idx).map(|line| line + 1)
+    }
+
+    /// 第 idx 个任务中提到 `word` 的行号
+    fn reference(&self, idx: usize, word: &str) -> Option<usize> {
+        let start = *self.starts.get(idx)?;
+        let end = self.starts.get(idx + 1).copied().unwrap_or(self.lines.len());
+        let pattern = Regex::new(&format!(r"(^|[^\w-]){}([^\w-]|$)", regex::escape(word))).ok()?;
+
+        let depends_line = (start..end).find(|&i| self.lines[i].trim_start().starts_with("depends_on:"))?;
+        (depends_line..end)
+            .find(|&i| pattern.is_match(self.lines[i]))
+            .map(|i| i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RUNBOOK: &str = r#"goal: 发布检查清单
+failure_policy: fail_fast
+tasks:
+  - id: test
+    name: 运行测试
+    command: cargo test
+    estimated_time: 120
+    timeout: 600
+    retry_policy:
+      max_retries: 2
+      retry_interval: 5
+  - id: lint
+    command: cargo clippy
+  - id: tag
+    command: git tag v1
+    depends_on:
+      - test
+      - lint
+    task_type: validation
+    skippable: true
+"#;
+
+    #[test]
+    fn test_parse_runbook() {
+        let plan = parse(RUNBOOK, "release.yaml", &TaskPlanner::new()).unwrap();
+
+        assert_eq!(plan.goal, "发布检查清单");
+        assert_eq!(plan.failure_policy, FailurePolicy::FailFast);
+        assert_eq!(plan.total_tasks(), 3);
+
+        let tasks: HashMap<&str, &SubTask> = plan.tasks().map(|t| (t.id.as_str(), t)).collect();
+        assert_eq!(tasks["test"].name, "运行测试");
+        assert_eq!(tasks["test"].timeout, Some(600));
+        assert_eq!(tasks["test"].retry_policy.as_ref().unwrap().retry_interval, 5);
+        assert_eq!(tasks["lint"].name, "lint");
+        assert_eq!(tasks["lint"].estimated_time, 10);
+        assert_eq!(tasks["tag"].task_type, TaskType::Validation);
+        assert!(tasks["tag"].skippable);
+    }
+
+    #[test]
+    fn test_unknown_dependency_points_to_line() {
+        let text = RUNBOOK.replace("      - lint", "      - lnit");
+        let err = parse(&text, "release.yaml", &TaskPlanner::new()).unwrap_err().to_string();
+        assert!(err.contains("release.yaml:18: 任务 tag 依赖的任务 lnit 不存在"), "{}", err);
+    }
+
+    #[test]
+    fn test_cycle_and_duplicate_reported_with_lines() {
+        let text = r#"goal: loop
+tasks:
+  - id: a
+    command: echo a
+    depends_on: [b]
+  - id: b
+    command: echo b
+    depends_on: [a]
+  - id: a
+    command: echo again
+"#;
+        let err = parse(text, "loop.yaml", &TaskPlanner::new()).unwrap_err().to_string();
+        assert!(err.contains("loop.yaml:9: 任务 ID 重复: a"), "{}", err);
+        assert!(err.contains("loop.yaml:3: 检测到循环依赖: a → b → a"), "{}", err);
+    }
+
+    #[test]
+    fn test_syntax_and_field_errors() {
+        let err = parse("goal: x\ntasks:\n  - id: a\n    comand: ls\n", "bad.yaml", &TaskPlanner::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("bad.yaml:4:"), "{}", err);
+        assert!(err.contains("comand"));
+
+        let err = parse(
+            "goal: x\ntasks:\n  - id: a\n    command: ls\n    task_type: magic\n",
+            "bad.yaml",
+            &TaskPlanner::new(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("bad.yaml:3:"), "{}", err);
+        assert!(err.contains("magic"));
+    }
+
+    #[test]
+    fn test_save_and_reload_roundtrip() {
+        let planner = TaskPlanner::new();
+        let plan = parse(RUNBOOK, "release.yaml", &planner).unwrap();
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("saved.yaml");
+        save(&plan, &path).unwrap();
+
+        let yaml = std::fs::read_to_string(&path).unwrap();
+        assert!(yaml.contains("failure_policy: fail_fast"));
+        assert!(yaml.contains("task_type: validation"));
+        assert!(!yaml.contains("name: lint"));
+
+        let reloaded = load(&path, &planner).unwrap();
+        assert_eq!(reloaded.total_tasks(), plan.total_tasks());
+        assert_eq!(reloaded.failure_policy, FailurePolicy::FailFast);
+        let tag = reloaded.tasks().find(|t| t.id == "tag").unwrap();
+        assert_eq!(tag.depends_on, vec!["test", "lint"]);
+    }
+}
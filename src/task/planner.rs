@@ -5,8 +5,11 @@
 //! 负责分析任务依赖关系，生成最优执行计划
 
 use super::error::{TaskError, TaskResult};
+use super::graph::CriticalPath;
+use super::template;
 use super::types::{DependencyGraph, ExecutionMode, ExecutionPlan, ExecutionStage, SubTask};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
 /// 任务规划器
 ///
@@ -56,6 +59,11 @@ impl TaskPlanner {
         // 1. 构建依赖图
         let dep_graph = self.build_dependency_graph(&tasks)?;
 
+        // 输出引用（{{t1.output}}）只能指向已声明的依赖
+        for task in &tasks {
+            template::validate_task(task)?;
+        }
+
         // 2. 拓扑排序（检测循环依赖）
         let sorted_tasks = self.topological_sort(&dep_graph)?;
 
@@ -70,6 +78,79 @@ impl TaskPlanner {
         Ok(ExecutionPlan::new(goal, stages))
     }
 
+    /// 检查任务列表的依赖问题（不生成计划）
+    ///
+    /// 比 [`plan`](Self::plan) 给出更具体的诊断：重复 ID、未知依赖，以及环上的具体任务。
+    pub fn check_dependencies(&self, tasks: &[SubTask]) -> Vec<DependencyIssue> {
+        let mut issues = Vec::new();
+
+        let mut ids = HashSet::new();
+        for task in tasks {
+            if !ids.insert(task.id.as_str()) {
+                issues.push(DependencyIssue::DuplicateId(task.id.clone()));
+            }
+        }
+
+        for task in tasks {
+            for dep in &task.depends_on {
+                if !ids.contains(dep.as_str()) {
+                    issues.push(DependencyIssue::UnknownDependency {
+                        task: task.id.clone(),
+                        dependency: dep.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(cycle) = Self::find_cycle(tasks) {
+            issues.push(DependencyIssue::Cycle(cycle));
+        }
+
+        issues
+    }
+
+    /// 深度优先查找一个依赖环（返回环上的任务，首尾相同）
+    fn find_cycle(tasks: &[SubTask]) -> Option<Vec<String>> {
+        // 重复 ID 时以第一个任务为准
+        let mut by_id: HashMap<&str, &SubTask> = HashMap::new();
+        for task in tasks {
+            by_id.entry(task.id.as_str()).or_insert(task);
+        }
+        let mut finished: HashSet<&str> = HashSet::new();
+
+        fn visit<'a>(
+            id: &'a str,
+            by_id: &HashMap<&'a str, &'a SubTask>,
+            path: &mut Vec<&'a str>,
+            finished: &mut HashSet<&'a str>,
+        ) -> Option<Vec<String>> {
+            if let Some(pos) = path.iter().position(|p| *p == id) {
+                let mut cycle: Vec<String> = path[pos..].iter().map(|s| s.to_string()).collect();
+                cycle.push(id.to_string());
+                return Some(cycle);
+            }
+            if finished.contains(id) {
+                return None;
+            }
+
+            path.push(id);
+            for dep in &by_id[id].depends_on {
+                if by_id.contains_key(dep.as_str()) {
+                    if let Some(cycle) = visit(dep.as_str(), by_id, path, finished) {
+                        return Some(cycle);
+                    }
+                }
+            }
+            path.pop();
+            finished.insert(id);
+            None
+        }
+
+        tasks
+            .iter()
+            .find_map(|t| visit(t.id.as_str(), &by_id, &mut Vec::new(), &mut finished))
+    }
+
     /// 构建依赖关系图
     ///
     /// 将任务列表转换为依赖图数据结构
@@ -263,6 +344,7 @@ impl TaskPlanner {
         } else {
             0.0
         };
+        let critical_path = CriticalPath::of(plan);
 
         PlanAnalysis {
             total_tasks,
@@ -273,6 +355,8 @@ impl TaskPlanner {
             parallel_time,
             time_saved,
             efficiency_gain: efficiency,
+            critical_path: critical_path.tasks,
+            critical_path_time: critical_path.time,
         }
     }
 }
@@ -283,6 +367,29 @@ impl Default for TaskPlanner {
     }
 }
 
+/// 依赖问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum DependencyIssue {
+    /// 任务 ID 重复
+    DuplicateId(String),
+    /// 依赖的任务不存在
+    UnknownDependency { task: String, dependency: String },
+    /// 循环依赖（环上的任务，首尾相同）
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for DependencyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DependencyIssue::DuplicateId(id) => write!(f, "任务 ID 重复: {}", id),
+            DependencyIssue::UnknownDependency { task, dependency } => {
+                write!(f, "任务 {} 依赖的任务 {} 不存在", task, dependency)
+            }
+            DependencyIssue::Cycle(cycle) => write!(f, "检测到循环依赖: {}", cycle.join(" → ")),
+        }
+    }
+}
+
 /// 执行计划分析结果
 #[derive(Debug, Clone)]
 pub struct PlanAnalysis {
@@ -309,6 +416,12 @@ pub struct PlanAnalysis {
 
     /// 效率提升（百分比）
     pub efficiency_gain: f64,
+
+    /// 关键路径（估计耗时最长的依赖链上的任务 ID）
+    pub critical_path: Vec<String>,
+
+    /// 关键路径估计时间（秒）
+    pub critical_path_time: u32,
 }
 
 #[cfg(test)]
@@ -428,6 +541,53 @@ mod tests {
         assert!(matches!(result, Err(TaskError::ParseError(_))));
     }
 
+    #[test]
+    fn test_check_dependencies() {
+        let planner = TaskPlanner::new();
+        let tasks = vec![
+            SubTask::new("a", "A", "cmd").with_dependency("c"),
+            SubTask::new("b", "B", "cmd").with_dependency("a").with_dependency("ghost"),
+            SubTask::new("c", "C", "cmd").with_dependency("b"),
+            SubTask::new("a", "A again", "cmd"),
+        ];
+
+        let issues = planner.check_dependencies(&tasks);
+        assert!(issues.contains(&DependencyIssue::DuplicateId("a".to_string())));
+        assert!(issues.contains(&DependencyIssue::UnknownDependency {
+            task: "b".to_string(),
+            dependency: "ghost".to_string(),
+        }));
+        let cycle = issues.iter().find_map(|i| match i {
+            DependencyIssue::Cycle(c) => Some(c.clone()),
+            _ => None,
+        });
+        assert_eq!(cycle.unwrap(), vec!["a", "c", "b", "a"]);
+        assert_eq!(
+            DependencyIssue::Cycle(vec!["a".into(), "b".into(), "a".into()]).to_string(),
+            "检测到循环依赖: a → b → a"
+        );
+
+        let ok = vec![SubTask::new("a", "A", "cmd"), SubTask::new("b", "B", "cmd").with_dependency("a")];
+        assert!(planner.check_dependencies(&ok).is_empty());
+    }
+
+    #[test]
+    fn test_output_reference_must_be_dependency() {
+        let planner = TaskPlanner::new();
+
+        let tasks = vec![
+            SubTask::new("t1", "Task 1", "cmd1"),
+            SubTask::new("t2", "Task 2", "echo {{t1.output}}"),
+        ];
+        assert!(matches!(planner.plan("test", tasks), Err(TaskError::ParseError(_))));
+
+        let tasks = vec![
+            SubTask::new("t1", "Task 1", "cmd1"),
+            SubTask::new("t2", "Task 2", "echo {{t1.output}}").with_dependency("t1"),
+        ];
+        assert!(planner.plan("test", tasks).is_ok());
+    }
+
     #[test]
     fn test_complex_dag() {
         //     t1
@@ -482,6 +642,8 @@ mod tests {
         assert_eq!(analysis.sequential_time, 45); // 10 + 20 + 15
         assert_eq!(analysis.parallel_time, 30); // 10 + max(20, 15)
         assert_eq!(analysis.time_saved, 15);
+        assert_eq!(analysis.critical_path, vec!["t1", "t2"]);
+        assert_eq!(analysis.critical_path_time, 30);
     }
 
     #[test]
@@ -0,0 +1,360 @@
+//! 失败后重新规划 (Adaptive Replanning)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 自适应执行：任务在重试后仍失败时，把失败输出、错误分析与剩余计划交给
+//! [`TaskDecomposer`] 提出修订，用户看到新旧任务的差异并批准后继续执行。
+//! 每次被批准的修订都记录在计划的 `revisions` 中，随检查点一起持久化。
+
+use super::decomposer::TaskDecomposer;
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::executor::TaskExecutor;
+use super::handlers::InputProvider;
+use super::planner::TaskPlanner;
+use super::types::{
+    ExecutionContext, ExecutionPlan, ExecutionResult, FailurePolicy, PlanRevision, SubTask,
+    TaskResult, TaskStatus,
+};
+use crate::error_fixer::ErrorAnalyzer;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::fmt;
+
+/// 剩余任务的一项变化
+#[derive(Debug, Clone)]
+pub enum TaskChange {
+    /// 保持不变
+    Unchanged(SubTask),
+    /// 被移除
+    Removed(SubTask),
+    /// 新增
+    Added(SubTask),
+    /// 同一 ID 的任务被修改
+    Modified { old: Box<SubTask>, new: Box<SubTask> },
+}
+
+impl fmt::Display for TaskChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskChange::Unchanged(t) => write!(f, "  {} {}: $ {}", t.id, t.name, t.command),
+            TaskChange::Removed(t) => write!(f, "- {} {}: $ {}", t.id, t.name, t.command),
+            TaskChange::Added(t) => write!(f, "+ {} {}: $ {}", t.id, t.name, t.command),
+            TaskChange::Modified { old, new } => write!(
+                f,
+                "~ {} {}: $ {}\n    (原: $ {})",
+                new.id, new.name, new.command, old.command
+            ),
+        }
+    }
+}
+
+/// 比较新旧剩余任务（按 ID 对应）
+pub fn diff_tasks(old: &[SubTask], new: &[SubTask]) -> Vec<TaskChange> {
+    let mut changes: Vec<TaskChange> = old
+        .iter()
+        .map(|o| match new.iter().find(|n| n.id == o.id) {
+            None => TaskChange::Removed(o.clone()),
+            Some(n) if same_task(o, n) => TaskChange::Unchanged(n.clone()),
+            Some(n) => TaskChange::Modified {
+                old: Box::new(o.clone()),
+                new: Box::new(n.clone()),
+            },
+        })
+        .collect();
+
+    changes.extend(
+        new.iter()
+            .filter(|n| !old.iter().any(|o| o.id == n.id))
+            .map(|n| TaskChange::Added(n.clone())),
+    );
+    changes
+}
+
+/// 渲染差异（每项一行）
+pub fn format_diff(changes: &[TaskChange]) -> String {
+    changes
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn same_task(a: &SubTask, b: &SubTask) -> bool {
+    a.command == b.command
+        && a.depends_on == b.depends_on
+        && a.task_type == b.task_type
+        && a.args == b.args
+}
+
+/// 用修订后的剩余任务重建计划
+///
+/// 已完成的任务保持原样，计划 ID、创建时间与失败策略不变，修订追加到历史。
+pub fn revise_plan(
+    plan: &ExecutionPlan,
+    completed: &[SubTask],
+    revision: PlanRevision,
+    planner: &TaskPlanner,
+) -> TaskOpResult<ExecutionPlan> {
+    let mut tasks = completed.to_vec();
+    tasks.extend(revision.tasks.iter().cloned());
+
+    let mut revised = planner.plan(plan.goal.clone(), tasks)?;
+    revised.id = plan.id.clone();
+    revised.created_at = plan.created_at;
+    revised.failure_policy = plan.failure_policy;
+    revised.revisions = plan.revisions.clone();
+    revised.revisions.push(revision);
+    Ok(revised)
+}
+
+/// 自适应执行器
+///
+/// 以 fail-fast 方式执行计划；出现失败时请求修订，经批准后从已完成的任务处继续。
+pub struct Replanner {
+    decomposer: TaskDecomposer,
+    planner: TaskPlanner,
+    approval: InputProvider,
+    max_revisions: usize,
+}
+
+impl Replanner {
+    /// 创建自适应执行器（批准方式为读取标准输入）
+    pub fn new(decomposer: TaskDecomposer) -> Self {
+        Self {
+            decomposer,
+            planner: TaskPlanner::new(),
+            approval: super::handlers::stdin_input_provider(),
+            max_revisions: 3,
+        }
+    }
+
+    /// 设置批准来源（收到差异与提问，回答 y/yes 表示批准）
+    pub fn with_approval(mut self, approval: InputProvider) -> Self {
+        self.approval = approval;
+        self
+    }
+
+    /// 设置单次执行中最多的修订次数
+    pub fn with_max_revisions(mut self, max: usize) -> Self {
+        self.max_revisions = max;
+        self
+    }
+
+    /// 执行计划，失败时尝试修订
+    ///
+    /// 返回最终的计划（包含本次的修订）与最后一轮的执行结果。
+    pub async fn run(
+        &self,
+        executor: &TaskExecutor,
+        plan: ExecutionPlan,
+        previous: Vec<TaskResult>,
+    ) -> TaskOpResult<(ExecutionPlan, ExecutionResult)> {
+        let context = ExecutionContext::current();
+        let mut plan = plan.with_failure_policy(FailurePolicy::FailFast);
+        let mut previous = previous;
+        let mut revisions = 0;
+
+        loop {
+            let result = executor.resume(plan.clone(), previous).await?;
+
+            let Some(failed) = result
+                .task_results
+                .iter()
+                .find(|r| r.status == TaskStatus::Failed)
+            else {
+                return Ok((plan, result));
+            };
+            if revisions >= self.max_revisions {
+                return Ok((plan, result));
+            }
+
+            let done: Vec<TaskResult> = result
+                .task_results
+                .iter()
+                .filter(|r| r.status.is_success())
+                .cloned()
+                .collect();
+            let done_ids: HashSet<&str> = done.iter().map(|r| r.task.id.as_str()).collect();
+            // 使用计划中的原始任务（结果里的命令可能已替换过输出引用）
+            let (completed, remaining): (Vec<SubTask>, Vec<SubTask>) = plan
+                .tasks()
+                .cloned()
+                .partition(|t| done_ids.contains(t.id.as_str()));
+
+            let error = failed.error.clone().unwrap_or_default();
+            let analysis = ErrorAnalyzer::new().analyze(&failed.task.command, &error);
+
+            let tasks = match self
+                .decomposer
+                .replan(&plan, failed, Some(&analysis), &completed, &remaining, &context)
+                .await
+            {
+                Ok(tasks) => tasks,
+                Err(e) => {
+                    eprintln!("⚠ 重新规划失败: {}", e);
+                    return Ok((plan, result));
+                }
+            };
+
+            let question = format!(
+                "任务 {} 失败，建议修订剩余计划:\n{}\n应用修订并继续? [y/N] ",
+                failed.task.id,
+                format_diff(&diff_tasks(&remaining, &tasks))
+            );
+            // 批准来源可能阻塞读取终端，放到阻塞线程中执行
+            let approval = std::sync::Arc::clone(&self.approval);
+            let approved = tokio::task::spawn_blocking(move || approval(&question))
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|answer| matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"));
+            if !approved {
+                return Ok((plan, result));
+            }
+
+            let revision = PlanRevision {
+                failed_task: failed.task.id.clone(),
+                error,
+                replaced: remaining,
+                tasks,
+                revised_at: Utc::now(),
+            };
+            plan = revise_plan(&plan, &completed, revision, &self.planner).map_err(|e| {
+                TaskError::Other(format!("修订后的计划无效: {}", e))
+            })?;
+            previous = done;
+            revisions += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ClientStats, LlmClient, LlmError, Message};
+    use crate::shell_executor::ShellExecutorWithFixer;
+    use crate::task::types::{ExecutionMode, ExecutionStage, RetryPolicy};
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    struct MockLlmClient {
+        response: String,
+    }
+
+    #[async_trait]
+    impl LlmClient for MockLlmClient {
+        async fn chat(&self, _messages: Vec<Message>) -> Result<String, LlmError> {
+            Ok(self.response.clone())
+        }
+
+        fn model(&self) -> &str {
+            "mock"
+        }
+
+        fn stats(&self) -> ClientStats {
+            ClientStats::new()
+        }
+
+        async fn diagnose(&self) -> String {
+            "Mock LLM Client".to_string()
+        }
+    }
+
+    fn replanner(response: &str, answer: &'static str, asked: Arc<Mutex<Vec<String>>>) -> Replanner {
+        let llm = Arc::new(MockLlmClient {
+            response: response.to_string(),
+        });
+        let approval: InputProvider = Arc::new(move |question: &str| {
+            asked.lock().unwrap().push(question.to_string());
+            Some(answer.to_string())
+        });
+        Replanner::new(TaskDecomposer::new(llm)).with_approval(approval)
+    }
+
+    fn sample_plan() -> ExecutionPlan {
+        let tasks = vec![
+            SubTask::new("t1", "Prepare", "echo ready"),
+            SubTask::new("t2", "Build", "exit 3")
+                .with_dependency("t1")
+                .with_retry_policy(RetryPolicy::simple(0)),
+            SubTask::new("t3", "Report", "echo done").with_dependency("t2"),
+        ];
+        ExecutionPlan::new("build", vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)])
+    }
+
+    const REVISION: &str = r#"{"tasks": [
+        {"id": "t2b", "name": "Build again", "description": "", "command": "echo built", "estimated_time": 1, "depends_on": ["t1"], "task_type": "Shell"},
+        {"id": "t3", "name": "Report", "description": "", "command": "echo done", "estimated_time": 1, "depends_on": ["t2b"], "task_type": "Shell"}
+    ]}"#;
+
+    #[test]
+    fn test_diff_tasks() {
+        let old = vec![
+            SubTask::new("a", "A", "echo a"),
+            SubTask::new("b", "B", "echo b"),
+            SubTask::new("c", "C", "echo c"),
+        ];
+        let new = vec![
+            SubTask::new("a", "A", "echo a"),
+            SubTask::new("b", "B", "echo bb"),
+            SubTask::new("d", "D", "echo d"),
+        ];
+
+        let changes = diff_tasks(&old, &new);
+        assert!(matches!(changes[0], TaskChange::Unchanged(_)));
+        assert!(matches!(changes[1], TaskChange::Modified { .. }));
+        assert!(matches!(changes[2], TaskChange::Removed(_)));
+        assert!(matches!(changes[3], TaskChange::Added(_)));
+
+        let text = format_diff(&changes);
+        assert!(text.contains("- c C: $ echo c"));
+        assert!(text.contains("+ d D: $ echo d"));
+        assert!(text.contains("(原: $ echo b)"));
+    }
+
+    #[tokio::test]
+    async fn test_approved_revision_continues() {
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let replanner = replanner(REVISION, "y", Arc::clone(&asked));
+        let executor = TaskExecutor::new(Arc::new(ShellExecutorWithFixer::new()));
+
+        let (plan, result) = replanner.run(&executor, sample_plan(), Vec::new()).await.unwrap();
+
+        assert!(result.is_success());
+        assert_eq!(plan.total_tasks(), 3);
+        assert_eq!(plan.revisions.len(), 1);
+        assert_eq!(plan.revisions[0].failed_task, "t2");
+        assert_eq!(plan.revisions[0].replaced.len(), 2);
+
+        let question = &asked.lock().unwrap()[0];
+        assert!(question.contains("- t2 Build: $ exit 3"));
+        assert!(question.contains("+ t2b Build again"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_revision_stops() {
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let replanner = replanner(REVISION, "n", Arc::clone(&asked));
+        let executor = TaskExecutor::new(Arc::new(ShellExecutorWithFixer::new()));
+
+        let (plan, result) = replanner.run(&executor, sample_plan(), Vec::new()).await.unwrap();
+
+        assert_eq!(asked.lock().unwrap().len(), 1);
+        assert!(plan.revisions.is_empty());
+        assert_eq!(result.failed_tasks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_revision_is_not_offered() {
+        // 新任务与已完成任务 ID 冲突
+        let response = r#"{"tasks": [{"id": "t1", "name": "X", "description": "", "command": "echo x", "estimated_time": 1, "task_type": "Shell"}]}"#;
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let replanner = replanner(response, "y", Arc::clone(&asked));
+        let executor = TaskExecutor::new(Arc::new(ShellExecutorWithFixer::new()));
+
+        let (plan, _) = replanner.run(&executor, sample_plan(), Vec::new()).await.unwrap();
+
+        assert!(asked.lock().unwrap().is_empty());
+        assert!(plan.revisions.is_empty());
+    }
+}
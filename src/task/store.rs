@@ -0,0 +1,323 @@
+//! 任务计划持久化 (PlanStore)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 将执行计划与每个任务的执行结果以 JSON 检查点形式保存到磁盘，
+//! 使退出或崩溃后仍可查看计划，并从第一个失败或未执行的任务继续。
+
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::types::{ExecutionPlan, SubTask, TaskResult, TaskStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 计划检查点
+///
+/// 保存计划本身以及每个任务最近一次的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanCheckpoint {
+    /// 执行计划
+    pub plan: ExecutionPlan,
+
+    /// 各任务最近一次的执行结果（每个任务至多一条）
+    pub results: Vec<TaskResult>,
+
+    /// 最后更新时间
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PlanCheckpoint {
+    /// 为新计划创建检查点（尚无执行结果）
+    pub fn new(plan: ExecutionPlan) -> Self {
+        Self {
+            plan,
+            results: Vec::new(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// 记录任务结果（覆盖该任务之前的结果）
+    pub fn record(&mut self, result: TaskResult) {
+        self.results.retain(|r| r.task.id != result.task.id);
+        self.results.push(result);
+        self.updated_at = Utc::now();
+    }
+
+    /// 任务当前状态（未执行过的任务为 Pending）
+    pub fn status_of(&self, task_id: &str) -> TaskStatus {
+        self.results
+            .iter()
+            .find(|r| r.task.id == task_id)
+            .map(|r| r.status.clone())
+            .unwrap_or(TaskStatus::Pending)
+    }
+
+    /// 已成功任务的结果（恢复执行时复用）
+    pub fn succeeded(&self) -> Vec<TaskResult> {
+        self.results
+            .iter()
+            .filter(|r| r.status.is_success())
+            .cloned()
+            .collect()
+    }
+
+    /// 按执行顺序找到第一个未成功的任务（恢复执行的起点）
+    pub fn next_task(&self) -> Option<&SubTask> {
+        self.plan
+            .stages
+            .iter()
+            .flat_map(|stage| stage.tasks.iter())
+            .find(|task| !self.status_of(&task.id).is_success())
+    }
+
+    /// 是否全部任务都已成功
+    pub fn is_finished(&self) -> bool {
+        self.next_task().is_none()
+    }
+
+    /// 已成功任务数
+    pub fn succeeded_count(&self) -> usize {
+        self.results.iter().filter(|r| r.status.is_success()).count()
+    }
+
+    /// 失败任务数
+    pub fn failed_count(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|r| r.status == TaskStatus::Failed)
+            .count()
+    }
+}
+
+/// 计划存储
+///
+/// 每个计划一个 `<plan-id>.json` 文件
+#[derive(Debug, Clone)]
+pub struct PlanStore {
+    dir: PathBuf,
+}
+
+impl PlanStore {
+    /// 创建存储（目录在首次保存时创建）
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// 默认存储目录：`~/.realconsole/plans`
+    pub fn default_dir() -> PathBuf {
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".realconsole")
+            .join("plans")
+    }
+
+    /// 存储目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 计划的步骤缓存文件：`<存储目录>/cache/<计划ID>.json`
+    ///
+    /// 每个计划单独缓存，不同计划中相同的相对路径输入互不影响。
+    pub fn cache_path(&self, plan_id: &str) -> TaskOpResult<PathBuf> {
+        validate_id(plan_id)?;
+        Ok(self.dir.join("cache").join(format!("{}.json", plan_id)))
+    }
+
+    /// 保存检查点
+    pub fn save(&self, checkpoint: &PlanCheckpoint) -> TaskOpResult<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let content = serde_json::to_string_pretty(checkpoint)
+            .map_err(|e| TaskError::ParseError(e.to_string()))?;
+
+        // 先写临时文件再重命名，避免崩溃时留下半个文件
+        let path = self.path_for(&checkpoint.plan.id)?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// 加载检查点（支持唯一的 ID 前缀）
+    pub fn load(&self, id: &str) -> TaskOpResult<PlanCheckpoint> {
+        let id = id.trim();
+        if id.is_empty() {
+            return Err(TaskError::PlanNotFound);
+        }
+
+        let exact = self.path_for(id)?;
+        if exact.exists() {
+            return Self::read(&exact);
+        }
+
+        let mut candidates: Vec<PathBuf> = self
+            .files()?
+            .into_iter()
+            .filter(|path| {
+                path.file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| stem.starts_with(id))
+            })
+            .collect();
+
+        match candidates.len() {
+            0 => Err(TaskError::PlanNotFound),
+            1 => Self::read(&candidates.remove(0)),
+            n => Err(TaskError::Other(format!("计划 ID 前缀 '{}' 匹配到 {} 个计划", id, n))),
+        }
+    }
+
+    /// 记录单个任务结果到检查点
+    pub fn record_result(&self, plan_id: &str, result: &TaskResult) -> TaskOpResult<()> {
+        let mut checkpoint = self.load(plan_id)?;
+        checkpoint.record(result.clone());
+        self.save(&checkpoint)
+    }
+
+    /// 列出所有已保存的计划（最近更新的在前，损坏的文件被忽略）
+    pub fn list(&self) -> TaskOpResult<Vec<PlanCheckpoint>> {
+        let mut checkpoints: Vec<PlanCheckpoint> = self
+            .files()?
+            .iter()
+            .filter_map(|path| Self::read(path).ok())
+            .collect();
+
+        checkpoints.sort_by_key(|c| std::cmp::Reverse(c.updated_at));
+        Ok(checkpoints)
+    }
+
+    /// 检查点文件路径（ID 只允许字母、数字、`_` 与 `-`，不能指向存储目录之外）
+    fn path_for(&self, id: &str) -> TaskOpResult<PathBuf> {
+        validate_id(id)?;
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+
+    fn files(&self) -> TaskOpResult<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect())
+    }
+
+    fn read(path: &Path) -> TaskOpResult<PlanCheckpoint> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| TaskError::ParseError(e.to_string()))
+    }
+}
+
+/// 计划 ID 只允许字母、数字、`_` 和 `-`（用作文件名，防止路径穿越）
+fn validate_id(id: &str) -> TaskOpResult<()> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')) {
+        return Err(TaskError::Other(format!("无效的计划 ID: '{}'", id)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::types::{ExecutionMode, ExecutionStage};
+    use tempfile::TempDir;
+
+    fn sample_plan() -> ExecutionPlan {
+        let tasks = vec![
+            SubTask::new("t1", "Task 1", "echo 1"),
+            SubTask::new("t2", "Task 2", "echo 2").with_dependency("t1"),
+        ];
+        ExecutionPlan::new("sample", vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)])
+    }
+
+    fn result(task: &SubTask, status: TaskStatus) -> TaskResult {
+        let now = Utc::now();
+        TaskResult {
+            task: task.clone(),
+            status,
+            output: String::new(),
+            error: None,
+            start_time: now,
+            end_time: now,
+            duration: 0,
+            cached: false,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_progress() {
+        let plan = sample_plan();
+        let t1 = plan.stages[0].tasks[0].clone();
+        let mut checkpoint = PlanCheckpoint::new(plan);
+
+        assert_eq!(checkpoint.next_task().unwrap().id, "t1");
+        assert_eq!(checkpoint.status_of("t1"), TaskStatus::Pending);
+
+        checkpoint.record(result(&t1, TaskStatus::Failed));
+        assert_eq!(checkpoint.failed_count(), 1);
+        assert_eq!(checkpoint.next_task().unwrap().id, "t1");
+
+        // 重新执行成功后覆盖旧结果
+        checkpoint.record(result(&t1, TaskStatus::Success));
+        assert_eq!(checkpoint.results.len(), 1);
+        assert_eq!(checkpoint.succeeded_count(), 1);
+        assert_eq!(checkpoint.next_task().unwrap().id, "t2");
+        assert!(!checkpoint.is_finished());
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_prefix() {
+        let dir = TempDir::new().unwrap();
+        let store = PlanStore::new(dir.path().join("plans"));
+        assert!(store.list().unwrap().is_empty());
+
+        let plan = sample_plan();
+        let id = plan.id.clone();
+        let t1 = plan.stages[0].tasks[0].clone();
+        store.save(&PlanCheckpoint::new(plan)).unwrap();
+        store.record_result(&id, &result(&t1, TaskStatus::Success)).unwrap();
+
+        let loaded = store.load(&id[..8]).unwrap();
+        assert_eq!(loaded.plan.goal, "sample");
+        assert_eq!(loaded.plan.stages[0].tasks[1].depends_on, vec!["t1"]);
+        assert_eq!(loaded.status_of("t1"), TaskStatus::Success);
+
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_store_load_missing() {
+        let dir = TempDir::new().unwrap();
+        let store = PlanStore::new(dir.path());
+
+        assert!(matches!(store.load("nope"), Err(TaskError::PlanNotFound)));
+        assert!(matches!(store.load(""), Err(TaskError::PlanNotFound)));
+    }
+
+    #[test]
+    fn test_store_rejects_invalid_ids() {
+        let dir = TempDir::new().unwrap();
+        let store = PlanStore::new(dir.path().join("plans"));
+        std::fs::write(dir.path().join("secret.json"), "{}").unwrap();
+
+        for id in ["../secret", "a/b", "x.y", "id with space"] {
+            assert!(matches!(store.load(id), Err(TaskError::Other(_))), "{}", id);
+        }
+
+        let mut plan = sample_plan();
+        plan.id = "../../escape".to_string();
+        assert!(store.save(&PlanCheckpoint::new(plan)).is_err());
+        assert!(!dir.path().join("escape.json").exists());
+        assert!(store.cache_path("../escape").is_err());
+    }
+
+    #[test]
+    fn test_cache_path_per_plan() {
+        let store = PlanStore::new("/tmp/plans");
+        let a = store.cache_path("plan-a").unwrap();
+        let b = store.cache_path("plan-b").unwrap();
+        assert_eq!(a, PathBuf::from("/tmp/plans/cache/plan-a.json"));
+        assert_ne!(a, b);
+    }
+}
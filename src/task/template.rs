@@ -0,0 +1,291 @@
+//! 任务输出引用 (Output Templates)
+//!
+//! Phase 10: 任务分解与规划系统
+//!
+//! 子任务命令可以引用前置任务的执行结果，在执行时才被替换：
+//!
+//! - `{{t1.output}}` - 任务 t1 的输出（去除首尾空白）
+//! - `{{t1.output | regex:版本 (\d+)}}` - 输出中正则的第一个捕获组（没有捕获组时为整个匹配）
+//!
+//! 替换进 shell 命令的文本会被单引号转义，输出中的特殊字符不会被 shell 解释；
+//! 结构化参数中的 `command` 字段同样会被执行，按命令处理。
+
+use super::error::{TaskError, TaskResult as TaskOpResult};
+use super::types::{SubTask, TaskResult};
+use crate::shell_util::shell_quote;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// 匹配 `{{ ... }}` 引用
+static REFERENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{\{\s*(.+?)\s*\}\}").unwrap());
+
+/// 引用的字段
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefField {
+    /// 完整输出
+    Output,
+    /// 输出中的正则捕获
+    Capture(String),
+}
+
+/// 对前置任务结果的一个引用
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateRef {
+    /// 被引用的任务 ID
+    pub task_id: String,
+    /// 引用的字段
+    pub field: RefField,
+}
+
+impl TemplateRef {
+    /// 解析 `{{ }}` 内部的表达式
+    fn parse(expr: &str) -> TaskOpResult<Self> {
+        let (path, filter) = match expr.split_once('|') {
+            Some((path, filter)) => (path.trim(), Some(filter.trim())),
+            None => (expr.trim(), None),
+        };
+
+        let (task_id, field) = path
+            .rsplit_once('.')
+            .ok_or_else(|| TaskError::ParseError(format!("无效的引用: {{{{{}}}}}", expr)))?;
+        if task_id.is_empty() {
+            return Err(TaskError::ParseError(format!("引用缺少任务 ID: {{{{{}}}}}", expr)));
+        }
+
+        let field = match (field, filter) {
+            ("output", None) => RefField::Output,
+            ("output", Some(filter)) => {
+                let pattern = filter.strip_prefix("regex:").ok_or_else(|| {
+                    TaskError::ParseError(format!("未知的过滤器: {}（仅支持 regex:）", filter))
+                })?;
+                Regex::new(pattern)
+                    .map_err(|e| TaskError::ParseError(format!("引用中的正则无效: {}", e)))?;
+                RefField::Capture(pattern.to_string())
+            }
+            (other, _) => {
+                return Err(TaskError::ParseError(format!(
+                    "未知的引用字段: {}（可用: output）",
+                    other
+                )))
+            }
+        };
+
+        Ok(Self {
+            task_id: task_id.to_string(),
+            field,
+        })
+    }
+
+    /// 从任务结果中取值
+    fn resolve(&self, results: &HashMap<String, TaskResult>) -> TaskOpResult<String> {
+        let result = results.get(&self.task_id).ok_or_else(|| {
+            TaskError::TaskNotFound(format!("引用的任务 {} 尚无执行结果", self.task_id))
+        })?;
+
+        match &self.field {
+            RefField::Output => Ok(result.output.trim().to_string()),
+            RefField::Capture(pattern) => {
+                // 模式在解析时已校验
+                let re = Regex::new(pattern).map_err(|e| TaskError::ParseError(e.to_string()))?;
+                let caps = re.captures(&result.output).ok_or_else(|| {
+                    TaskError::Other(format!("任务 {} 的输出不匹配 /{}/", self.task_id, pattern))
+                })?;
+                let matched = caps.get(1).or_else(|| caps.get(0)).map_or("", |m| m.as_str());
+                Ok(matched.to_string())
+            }
+        }
+    }
+}
+
+/// 提取文本中的全部引用
+pub fn references(text: &str) -> TaskOpResult<Vec<TemplateRef>> {
+    REFERENCE
+        .captures_iter(text)
+        .map(|caps| TemplateRef::parse(&caps[1]))
+        .collect()
+}
+
+/// 提取任务命令与参数中的全部引用
+pub fn task_references(task: &SubTask) -> TaskOpResult<Vec<TemplateRef>> {
+    let mut refs = references(&task.command)?;
+    if let Some(args) = &task.args {
+        for text in json_strings(args) {
+            refs.extend(references(text)?);
+        }
+    }
+    Ok(refs)
+}
+
+/// 校验任务的引用：只能引用已声明的依赖
+pub fn validate_task(task: &SubTask) -> TaskOpResult<()> {
+    for reference in task_references(task)? {
+        if !task.depends_on.contains(&reference.task_id) {
+            return Err(TaskError::ParseError(format!(
+                "任务 {} 引用了 {} 的结果，但未在 depends_on 中声明该依赖",
+                task.id, reference.task_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 用前置任务的结果替换任务中的引用
+///
+/// 命令中的替换值会被 shell 转义；结构化参数按原文替换，
+/// 但参数中的 `command` 字段（如验证断言的命令）同样会被 shell 执行，按命令转义。
+pub fn render_task(task: &SubTask, results: &HashMap<String, TaskResult>) -> TaskOpResult<SubTask> {
+    let mut rendered = task.clone();
+    rendered.command = render(&task.command, results, shell_quote)?;
+    if let Some(args) = &task.args {
+        rendered.args = Some(render_json(args, results, str::to_string)?);
+    }
+    Ok(rendered)
+}
+
+/// 替换文本中的引用，`escape` 用于处理替换值
+pub fn render(
+    text: &str,
+    results: &HashMap<String, TaskResult>,
+    escape: fn(&str) -> String,
+) -> TaskOpResult<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in REFERENCE.captures_iter(text) {
+        let whole = caps.get(0).expect("整体匹配总是存在");
+        let value = TemplateRef::parse(&caps[1])?.resolve(results)?;
+        output.push_str(&text[last..whole.start()]);
+        output.push_str(&escape(&value));
+        last = whole.end();
+    }
+    output.push_str(&text[last..]);
+    Ok(output)
+}
+
+fn render_json(
+    value: &JsonValue,
+    results: &HashMap<String, TaskResult>,
+    escape: fn(&str) -> String,
+) -> TaskOpResult<JsonValue> {
+    Ok(match value {
+        JsonValue::String(s) => JsonValue::String(render(s, results, escape)?),
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|item| render_json(item, results, escape))
+                .collect::<TaskOpResult<_>>()?,
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| {
+                    let escape = if k == "command" { shell_quote } else { escape };
+                    Ok((k.clone(), render_json(v, results, escape)?))
+                })
+                .collect::<TaskOpResult<_>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn json_strings(value: &JsonValue) -> Vec<&str> {
+    match value {
+        JsonValue::String(s) => vec![s.as_str()],
+        JsonValue::Array(items) => items.iter().flat_map(json_strings).collect(),
+        JsonValue::Object(map) => map.values().flat_map(json_strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::task::types::TaskStatus;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn result(id: &str, status: TaskStatus, output: &str) -> (String, TaskResult) {
+        let now = Utc::now();
+        let result = TaskResult {
+            task: SubTask::new(id, id, ""),
+            status,
+            output: output.to_string(),
+            error: None,
+            start_time: now,
+            end_time: now,
+            duration: 0,
+            cached: false,
+        };
+        (id.to_string(), result)
+    }
+
+    #[test]
+    fn test_parse_references() {
+        let refs = references("echo {{t1.output}} {{ t2.output }} {{t3.output | regex:v(\\d+)}}").unwrap();
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs[0].field, RefField::Output);
+        assert_eq!(refs[1].task_id, "t2");
+        assert_eq!(refs[2].field, RefField::Capture("v(\\d+)".to_string()));
+
+        assert!(references("echo {{t1.stdout}}").is_err());
+        assert!(references("echo {{t1.exit_code}}").is_err());
+        assert!(references("echo {{t1.output | upper}}").is_err());
+        assert!(references("echo {{t1.output | regex:(}}").is_err());
+    }
+
+    #[test]
+    fn test_validate_requires_dependency() {
+        let ok = SubTask::new("t2", "T2", "cat {{t1.output}}").with_dependency("t1");
+        assert!(validate_task(&ok).is_ok());
+
+        let missing = SubTask::new("t2", "T2", "cat {{t1.output}}");
+        assert!(validate_task(&missing).is_err());
+
+        let in_args = SubTask::new("t2", "T2", "")
+            .with_args(json!({"tool": "read_file", "args": {"path": "{{t1.output}}"}}));
+        assert!(validate_task(&in_args).is_err());
+    }
+
+    #[test]
+    fn test_render_with_escaping() {
+        let results: HashMap<_, _> = [
+            result("t1", TaskStatus::Success, "it's $(rm -rf /)\n"),
+            result("t3", TaskStatus::Success, "rustc 1.95.0 (abc)"),
+        ]
+        .into_iter()
+        .collect();
+
+        let rendered = render(
+            "echo {{t1.output}} {{t3.output | regex:rustc (\\S+)}}",
+            &results,
+            shell_quote,
+        )
+        .unwrap();
+        assert_eq!(rendered, r"echo 'it'\''s $(rm -rf /)' 1.95.0");
+
+        assert!(render("{{t3.output | regex:go\\d}}", &results, shell_quote).is_err());
+        assert!(render("{{t9.output}}", &results, shell_quote).is_err());
+    }
+
+    #[test]
+    fn test_render_task_args_unescaped() {
+        let results: HashMap<_, _> = [result("t1", TaskStatus::Success, "/tmp/a b.txt\n")].into_iter().collect();
+        let task = SubTask::new("t2", "T2", "exists {{t1.output}}")
+            .with_args(json!({"tool": "read_file", "args": {"path": "{{t1.output}}"}}));
+
+        let rendered = render_task(&task, &results).unwrap();
+        assert_eq!(rendered.command, "exists '/tmp/a b.txt'");
+        assert_eq!(rendered.args.unwrap()["args"]["path"], "/tmp/a b.txt");
+    }
+
+    #[test]
+    fn test_render_task_args_command_escaped() {
+        let results: HashMap<_, _> = [result("t1", TaskStatus::Success, "x; rm -rf ~")].into_iter().collect();
+        let task = SubTask::new("t2", "T2", "")
+            .with_args(json!({"check": "exit_code", "command": "test -f {{t1.output}}", "note": "{{t1.output}}"}));
+
+        let args = render_task(&task, &results).unwrap().args.unwrap();
+        assert_eq!(args["command"], "test -f 'x; rm -rf ~'");
+        assert_eq!(args["note"], "x; rm -rf ~");
+    }
+}
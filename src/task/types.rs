@@ -39,6 +39,22 @@ pub struct SubTask {
 
     /// 重试策略
     pub retry_policy: Option<RetryPolicy>,
+
+    /// 结构化参数（非 Shell 任务使用，如工具名与工具参数、断言定义）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<serde_json::Value>,
+
+    /// 超时（秒），覆盖执行器的默认超时
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+
+    /// 声明的输入（文件、目录、glob，或 `env:NAME` 环境变量），用于步骤缓存
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+
+    /// 声明的输出（文件、目录或 glob），缺失或被修改时缓存失效
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
 }
 
 impl SubTask {
@@ -54,6 +70,10 @@ impl SubTask {
             task_type: TaskType::Shell,
             skippable: false,
             retry_policy: None,
+            args: None,
+            timeout: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
         }
     }
 
@@ -86,6 +106,44 @@ impl SubTask {
         self.retry_policy = Some(policy);
         self
     }
+
+    /// 设置任务类型
+    pub fn with_task_type(mut self, task_type: TaskType) -> Self {
+        self.task_type = task_type;
+        self
+    }
+
+    /// 设置结构化参数
+    pub fn with_args(mut self, args: serde_json::Value) -> Self {
+        self.args = Some(args);
+        self
+    }
+
+    /// 设置超时（秒）
+    pub fn with_timeout(mut self, seconds: u64) -> Self {
+        self.timeout = Some(seconds);
+        self
+    }
+
+    /// 声明输入
+    pub fn with_inputs<I, S>(mut self, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.inputs.extend(inputs.into_iter().map(Into::into));
+        self
+    }
+
+    /// 声明输出
+    pub fn with_outputs<I, S>(mut self, outputs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.outputs.extend(outputs.into_iter().map(Into::into));
+        self
+    }
 }
 
 /// 任务类型
@@ -103,6 +161,20 @@ pub enum TaskType {
     UserInput,
 }
 
+impl TaskType {
+    /// 按名称解析任务类型（不区分大小写，允许 file_operation 等写法）
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace(['_', '-'], "").as_str() {
+            "shell" => Some(TaskType::Shell),
+            "fileoperation" => Some(TaskType::FileOperation),
+            "network" => Some(TaskType::Network),
+            "validation" => Some(TaskType::Validation),
+            "userinput" => Some(TaskType::UserInput),
+            _ => None,
+        }
+    }
+}
+
 impl ToString for TaskType {
     fn to_string(&self) -> String {
         match self {
@@ -151,7 +223,7 @@ impl RetryPolicy {
 /// 执行计划
 ///
 /// 包含任务的执行顺序和并行策略
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionPlan {
     /// 计划ID
     pub id: String,
@@ -170,6 +242,14 @@ pub struct ExecutionPlan {
 
     /// 创建时间
     pub created_at: DateTime<Utc>,
+
+    /// 失败处理策略
+    #[serde(default)]
+    pub failure_policy: FailurePolicy,
+
+    /// 重新规划历史（自适应执行中每次被批准的修订）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<PlanRevision>,
 }
 
 impl ExecutionPlan {
@@ -188,19 +268,82 @@ impl ExecutionPlan {
             total_estimated_time: total_time,
             parallel_stages,
             created_at: Utc::now(),
+            failure_policy: FailurePolicy::default(),
+            revisions: Vec::new(),
         }
     }
 
+    /// 设置失败处理策略
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
     /// 获取总任务数
     pub fn total_tasks(&self) -> usize {
         self.stages.iter().map(|s| s.tasks.len()).sum()
     }
+
+    /// 按计划顺序遍历所有任务
+    pub fn tasks(&self) -> impl Iterator<Item = &SubTask> {
+        self.stages.iter().flat_map(|stage| stage.tasks.iter())
+    }
+
+    /// 由计划中的任务构建依赖图（边：被依赖的任务 -> 依赖它的任务）
+    ///
+    /// 指向计划外任务的依赖会被忽略。
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for task in self.tasks() {
+            graph.add_node(task.clone());
+        }
+        for task in self.tasks() {
+            for dep in &task.depends_on {
+                if graph.nodes.contains_key(dep) {
+                    graph.add_edge(dep.clone(), task.id.clone());
+                }
+            }
+        }
+        graph
+    }
+}
+
+/// 失败处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// 继续执行与失败任务无关的任务，只阻塞其下游（默认）
+    #[default]
+    ContinueOnError,
+    /// 任一任务失败后不再启动新任务，未执行的任务标记为取消
+    FailFast,
+}
+
+/// 计划修订记录
+///
+/// 任务失败后由 LLM 提出、经用户批准的剩余计划替换
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanRevision {
+    /// 触发修订的失败任务
+    pub failed_task: String,
+
+    /// 失败原因
+    pub error: String,
+
+    /// 被替换的剩余任务
+    pub replaced: Vec<SubTask>,
+
+    /// 新的剩余任务
+    pub tasks: Vec<SubTask>,
+
+    /// 修订时间
+    pub revised_at: DateTime<Utc>,
 }
 
 /// 执行阶段
 ///
 /// 代表可以同时执行的一组任务
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionStage {
     /// 阶段编号
     pub stage_num: usize,
@@ -230,7 +373,7 @@ impl ExecutionStage {
 }
 
 /// 执行模式
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ExecutionMode {
     /// 串行执行
     Sequential,
@@ -239,7 +382,7 @@ pub enum ExecutionMode {
 }
 
 /// 任务执行结果
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskResult {
     /// 任务信息
     pub task: SubTask,
@@ -261,6 +404,10 @@ pub struct TaskResult {
 
     /// 执行时长（秒）
     pub duration: u32,
+
+    /// 是否复用了步骤缓存（未实际执行）
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
 }
 
 /// 任务状态
@@ -278,6 +425,8 @@ pub enum TaskStatus {
     Skipped,
     /// 取消
     Cancelled,
+    /// 阻塞（依赖的任务失败，未执行）
+    Blocked,
 }
 
 impl TaskStatus {
@@ -285,7 +434,11 @@ impl TaskStatus {
     pub fn is_terminal(&self) -> bool {
         matches!(
             self,
-            TaskStatus::Success | TaskStatus::Failed | TaskStatus::Skipped | TaskStatus::Cancelled
+            TaskStatus::Success
+                | TaskStatus::Failed
+                | TaskStatus::Skipped
+                | TaskStatus::Cancelled
+                | TaskStatus::Blocked
         )
     }
 
@@ -293,10 +446,15 @@ impl TaskStatus {
     pub fn is_success(&self) -> bool {
         matches!(self, TaskStatus::Success)
     }
+
+    /// 是否会阻塞依赖它的任务
+    pub fn blocks_dependents(&self) -> bool {
+        matches!(self, TaskStatus::Failed | TaskStatus::Blocked | TaskStatus::Cancelled)
+    }
 }
 
 /// 执行结果汇总
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionResult {
     /// 计划ID
     pub plan_id: String,
@@ -313,6 +471,10 @@ pub struct ExecutionResult {
     /// 跳过任务数
     pub skipped_tasks: usize,
 
+    /// 因依赖失败而阻塞（或被 fail-fast 取消）的任务数
+    #[serde(default)]
+    pub blocked_tasks: usize,
+
     /// 总耗时（秒）
     pub total_time: u32,
 
@@ -332,7 +494,7 @@ impl ExecutionResult {
         if self.total_tasks == 0 {
             return 1.0;
         }
-        (self.completed_tasks - self.failed_tasks) as f64 / self.total_tasks as f64
+        self.completed_tasks.saturating_sub(self.failed_tasks) as f64 / self.total_tasks as f64
     }
 }
 
@@ -534,6 +696,25 @@ mod tests {
         assert!(!TaskStatus::Running.is_terminal());
         assert!(TaskStatus::Success.is_success());
         assert!(!TaskStatus::Failed.is_success());
+        assert!(TaskStatus::Blocked.is_terminal());
+        assert!(TaskStatus::Blocked.blocks_dependents());
+        assert!(!TaskStatus::Skipped.blocks_dependents());
+    }
+
+    #[test]
+    fn test_plan_dependency_graph() {
+        let tasks = vec![
+            SubTask::new("t1", "Task 1", "cmd1"),
+            SubTask::new("t2", "Task 2", "cmd2").with_dependency("t1").with_dependency("external"),
+        ];
+        let plan = ExecutionPlan::new("graph", vec![ExecutionStage::new(0, tasks, ExecutionMode::Sequential)])
+            .with_failure_policy(FailurePolicy::FailFast);
+
+        let graph = plan.dependency_graph();
+        assert_eq!(graph.edges.get("t1"), Some(&vec!["t2".to_string()]));
+        assert!(!graph.edges.contains_key("external"));
+        assert_eq!(plan.failure_policy, FailurePolicy::FailFast);
+        assert_eq!(plan.tasks().count(), 2);
     }
 
     #[test]
@@ -569,6 +750,7 @@ mod tests {
             completed_tasks: 8,
             failed_tasks: 2,
             skipped_tasks: 0,
+            blocked_tasks: 0,
             total_time: 120,
             task_results: Vec::new(),
         };
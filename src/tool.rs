@@ -44,6 +44,18 @@ pub struct Parameter {
     pub default: Option<JsonValue>,
 }
 
+/// 工具参数中输出引用（`$3`、`$last`）的展开方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputRefs {
+    /// 不展开（默认）
+    #[default]
+    None,
+    /// 展开为输出内容（文本、JSON 处理类工具）
+    Content,
+    /// 展开为保存输出的文件路径（参数作为 shell 命令执行的工具）
+    Shell,
+}
+
 /// 工具定义
 pub struct Tool {
     /// 工具名称
@@ -57,6 +69,9 @@ pub struct Tool {
 
     /// 执行函数
     pub handler: Arc<dyn Fn(JsonValue) -> Result<String, String> + Send + Sync>,
+
+    /// 参数中输出引用的展开方式
+    pub output_refs: OutputRefs,
 }
 
 impl Tool {
@@ -70,9 +85,16 @@ impl Tool {
             description: description.to_string(),
             parameters,
             handler: Arc::new(handler),
+            output_refs: OutputRefs::None,
         }
     }
 
+    /// 设置参数中输出引用的展开方式
+    pub fn with_output_refs(mut self, output_refs: OutputRefs) -> Self {
+        self.output_refs = output_refs;
+        self
+    }
+
     /// 转换为 OpenAI Function Schema
     pub fn to_function_schema(&self) -> JsonValue {
         let mut properties = serde_json::Map::new();
@@ -128,12 +150,60 @@ impl Tool {
     }
 }
 
+impl Tool {
+    /// 校验参数类型
+    ///
+    /// 除必需参数外，还检查每个已提供参数的 JSON 类型与声明一致，
+    /// 并拒绝未声明的参数。用于非 LLM 来源的结构化调用（如任务计划）。
+    pub fn validate_args(&self, args: &JsonValue) -> Result<(), String> {
+        let obj = args
+            .as_object()
+            .ok_or_else(|| "参数格式错误，应为 JSON 对象".to_string())?;
+
+        for param in &self.parameters {
+            match obj.get(&param.name) {
+                None if param.required => return Err(format!("缺少必需参数: {}", param.name)),
+                Some(value) if !param.param_type.accepts(value) => {
+                    return Err(format!(
+                        "参数 {} 类型错误: 期望 {:?}，实际为 {}",
+                        param.name, param.param_type, value
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(unknown) = obj
+            .keys()
+            .find(|key| !self.parameters.iter().any(|p| &p.name == *key))
+        {
+            return Err(format!("未知参数: {}", unknown));
+        }
+
+        Ok(())
+    }
+}
+
+impl ParameterType {
+    /// JSON 值是否符合该参数类型
+    pub fn accepts(&self, value: &JsonValue) -> bool {
+        match self {
+            ParameterType::String => value.is_string(),
+            ParameterType::Number => value.is_number(),
+            ParameterType::Boolean => value.is_boolean(),
+            ParameterType::Object => value.is_object(),
+            ParameterType::Array => value.is_array(),
+        }
+    }
+}
+
 impl std::fmt::Debug for Tool {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Tool")
             .field("name", &self.name)
             .field("description", &self.description)
             .field("parameters", &self.parameters)
+            .field("output_refs", &self.output_refs)
             .finish()
     }
 }
@@ -368,6 +438,40 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_tool_validate_args() {
+        let tool = Tool::new(
+            "calculator",
+            "简单计算器",
+            vec![
+                Parameter {
+                    name: "a".to_string(),
+                    param_type: ParameterType::Number,
+                    description: "第一个数".to_string(),
+                    required: true,
+                    default: None,
+                },
+                Parameter {
+                    name: "op".to_string(),
+                    param_type: ParameterType::String,
+                    description: "操作符".to_string(),
+                    required: false,
+                    default: None,
+                },
+            ],
+            test_calculator_handler,
+        );
+
+        assert!(tool.validate_args(&json!({"a": 1})).is_ok());
+        assert!(tool.validate_args(&json!({"a": 1, "op": "add"})).is_ok());
+
+        let err = tool.validate_args(&json!({"a": "1"})).unwrap_err();
+        assert!(err.contains("类型错误"));
+        assert!(tool.validate_args(&json!({"op": "add"})).unwrap_err().contains("缺少"));
+        assert!(tool.validate_args(&json!({"a": 1, "b": 2})).unwrap_err().contains("未知参数"));
+        assert!(tool.validate_args(&json!([1])).is_err());
+    }
+
     #[test]
     fn test_registry() {
         let mut registry = ToolRegistry::new();
@@ -7,9 +7,11 @@
 //! - 工具结果反馈
 //! - ✨ Phase 5.2: 并行工具执行 + 执行统计
 //! - ✨ Phase 5.3 Week 3 Day 2: 工具响应缓存
+//! - 参数中的输出引用（`$3`、`$last`）按工具声明的方式展开（[`OutputRefs`]），工具结果写入输出存储
 
 use crate::llm::{LlmClient, LlmError, Message};
-use crate::tool::ToolRegistry;
+use crate::memory::{OutputKind, OutputStore};
+use crate::tool::{OutputRefs, ToolRegistry};
 use crate::tool_cache::{ToolCache, CacheStats};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -74,6 +76,9 @@ pub struct ToolExecutor {
 
     /// ✨ Phase 5.3 Week 3 Day 2: 工具响应缓存
     cache: Option<Arc<ToolCache>>,
+
+    /// 命令输出存储（展开参数中的引用、保存工具结果）
+    output_store: Option<Arc<RwLock<OutputStore>>>,
 }
 
 impl ToolExecutor {
@@ -89,6 +94,7 @@ impl ToolExecutor {
             max_tools_per_round,
             execution_mode: ExecutionMode::Parallel, // 默认并行执行
             cache: None, // 默认不启用缓存
+            output_store: None,
         }
     }
 
@@ -114,6 +120,13 @@ impl ToolExecutor {
         self
     }
 
+    /// 启用输出存储：声明了 [`OutputRefs`] 的工具参数中的 `$3`、`$last` 被展开，
+    /// 工具结果可被后续引用
+    pub fn with_output_store(mut self, store: Arc<RwLock<OutputStore>>) -> Self {
+        self.output_store = Some(store);
+        self
+    }
+
     /// ✨ Phase 5.3 Week 3 Day 2: 获取缓存统计
     pub async fn cache_stats(&self) -> Option<CacheStats> {
         if let Some(cache) = &self.cache {
@@ -131,9 +144,57 @@ impl ToolExecutor {
     ) -> ToolCallResult {
         let start = Instant::now();
 
+        // 展开参数中的输出引用（只对声明了展开方式的工具）
+        let arguments = match &self.output_store {
+            Some(store) => {
+                let mode = self
+                    .registry
+                    .read()
+                    .await
+                    .get(&call.name)
+                    .map(|tool| tool.output_refs)
+                    .unwrap_or_default();
+                match expand_output_handles(&call.arguments, mode, &*store.read().await) {
+                    Ok(arguments) => arguments,
+                    Err(error) => {
+                        return ToolCallResult {
+                            call_id: call.id.clone(),
+                            tool_name: call.name.clone(),
+                            success: false,
+                            content: format!("工具执行失败: {}", error),
+                            duration_ms: start.elapsed().as_millis() as u64,
+                        };
+                    }
+                }
+            }
+            None => call.arguments.clone(),
+        };
+
+        let result = self.execute_with_cache(call, &arguments, start).await;
+
+        // 保存工具结果，供后续以 $N 引用
+        if let Some(store) = &self.output_store {
+            store.write().await.capture(
+                format!("{}({})", call.name, arguments),
+                OutputKind::Tool,
+                &result.content,
+                result.success,
+            );
+        }
+
+        result
+    }
+
+    /// 执行工具（先查缓存）
+    async fn execute_with_cache(
+        &self,
+        call: &ToolCallRequest,
+        arguments: &JsonValue,
+        start: Instant,
+    ) -> ToolCallResult {
         // ✨ 尝试从缓存获取
         if let Some(cache) = &self.cache {
-            if let Some(cached_content) = cache.get(&call.name, &call.arguments).await {
+            if let Some(cached_content) = cache.get(&call.name, arguments).await {
                 return ToolCallResult {
                     call_id: call.id.clone(),
                     tool_name: call.name.clone(),
@@ -147,11 +208,11 @@ impl ToolExecutor {
         // 缓存未命中，执行工具
         let registry = self.registry.read().await;
 
-        let result = match registry.execute(&call.name, call.arguments.clone()) {
+        match registry.execute(&call.name, arguments.clone()) {
             Ok(content) => {
                 // ✨ 成功时写入缓存
                 if let Some(cache) = &self.cache {
-                    cache.set(&call.name, &call.arguments, content.clone()).await;
+                    cache.set(&call.name, arguments, content.clone()).await;
                 }
 
                 ToolCallResult {
@@ -169,9 +230,7 @@ impl ToolExecutor {
                 content: format!("工具执行失败: {}", error),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
-        };
-
-        result
+        }
     }
 
     /// 执行多个工具调用
@@ -288,6 +347,31 @@ impl ToolExecutor {
     }
 }
 
+/// 递归展开 JSON 字符串值中的输出引用
+///
+/// Shell 类工具展开为（已转义的）输出文件路径，从不把原始输出拼进命令。
+fn expand_output_handles(value: &JsonValue, mode: OutputRefs, store: &OutputStore) -> Result<JsonValue, String> {
+    Ok(match value {
+        JsonValue::String(text) => JsonValue::String(match mode {
+            OutputRefs::None => text.clone(),
+            OutputRefs::Content => store.expand_content(text),
+            OutputRefs::Shell => store.expand_for_shell(text)?,
+        }),
+        JsonValue::Array(items) => JsonValue::Array(
+            items
+                .iter()
+                .map(|v| expand_output_handles(v, mode, store))
+                .collect::<Result<_, _>>()?,
+        ),
+        JsonValue::Object(map) => JsonValue::Object(
+            map.iter()
+                .map(|(k, v)| Ok((k.clone(), expand_output_handles(v, mode, store)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,6 +460,86 @@ mod tests {
         assert_eq!(results[1].call_id, "call_2");
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_execute_tool_call_with_output_store() {
+        let mut registry = ToolRegistry::new();
+        registry.register(Tool::new(
+            "echo_text",
+            "回显文本",
+            vec![Parameter {
+                name: "text".to_string(),
+                param_type: ParameterType::String,
+                description: "文本".to_string(),
+                required: true,
+                default: None,
+            }],
+            |args| Ok(args["text"].as_str().unwrap_or_default().to_string()),
+        )
+        .with_output_refs(OutputRefs::Content));
+        registry.register(Tool::new(
+            "echo_command",
+            "回显命令",
+            vec![Parameter {
+                name: "command".to_string(),
+                param_type: ParameterType::String,
+                description: "命令".to_string(),
+                required: true,
+                default: None,
+            }],
+            |args| Ok(args["command"].as_str().unwrap_or_default().to_string()),
+        )
+        .with_output_refs(OutputRefs::Shell));
+        registry.register(Tool::new(
+            "echo_plain",
+            "回显文本（不展开引用）",
+            vec![Parameter {
+                name: "text".to_string(),
+                param_type: ParameterType::String,
+                description: "文本".to_string(),
+                required: true,
+                default: None,
+            }],
+            |args| Ok(args["text"].as_str().unwrap_or_default().to_string()),
+        ));
+
+        let spool = tempfile::tempdir().unwrap();
+        let store = Arc::new(RwLock::new(OutputStore::new().with_spool_dir(spool.path())));
+        store.write().await.capture("df -h", OutputKind::Shell, "disk full", false);
+        let executor = ToolExecutor::with_defaults(Arc::new(RwLock::new(registry)))
+            .with_output_store(Arc::clone(&store));
+
+        let call = ToolCallRequest {
+            id: "call_1".to_string(),
+            name: "echo_text".to_string(),
+            arguments: json!({"text": "$1 again"}),
+        };
+        let result = executor.execute_tool_call(&call).await;
+        assert_eq!(result.content, "disk full again");
+
+        // Shell 类工具展开为文件路径，引号内的 $1 保持原样
+        let call = ToolCallRequest {
+            id: "call_2".to_string(),
+            name: "echo_command".to_string(),
+            arguments: json!({"command": "awk '{print $1}' $1"}),
+        };
+        let result = executor.execute_tool_call(&call).await;
+        assert_eq!(result.content, format!("awk '{{print $1}}' {}", spool.path().join("1.txt").display()));
+
+        // 未声明展开方式的工具不展开
+        let call = ToolCallRequest {
+            id: "call_3".to_string(),
+            name: "echo_plain".to_string(),
+            arguments: json!({"text": "$1"}),
+        };
+        assert_eq!(executor.execute_tool_call(&call).await.content, "$1");
+
+        let store = store.read().await;
+        let last = store.last().unwrap();
+        assert_eq!(last.id, 4);
+        assert_eq!(last.kind, OutputKind::Tool);
+        assert!(last.source.starts_with("echo_plain("));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_execute_tool_call_error() {
         let registry = create_test_registry();